@@ -0,0 +1,69 @@
+//! To run the example with logs printed to stdout, run the following command:
+//!
+//! ```bash
+//! RUST_LOG=info cargo run --example event_processor
+//! ```
+//!
+//! Running multiple instances of this example at the same time will distribute the partitions
+//! across the instances.
+
+use std::time::Duration;
+
+use azeventhubs::primitives::{
+    CheckpointStore, EventProcessor, EventProcessorCheckpoint, EventProcessorOptions,
+    FileCheckpointStore,
+};
+use tokio_util::sync::CancellationToken;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    dotenv::from_filename(".env")?;
+
+    let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING")?;
+    let event_hub_name = std::env::var("EVENT_HUB_NAME")?;
+
+    // The checkpoints and partition ownership are shared by all instances through the file system
+    let store = FileCheckpointStore::new("./checkpoints");
+    let mut processor = EventProcessor::new_from_connection_string(
+        "$Default",
+        connection_string,
+        event_hub_name,
+        store.clone(),
+        EventProcessorOptions::default(),
+    )
+    .await?;
+    log::info!("Processor {} started", processor.identifier());
+
+    // Stop the processor after 60 seconds
+    let cancel = CancellationToken::new();
+    let child = cancel.child_token();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        cancel.cancel();
+    });
+
+    processor
+        .run(
+            |event| {
+                let store = store.clone();
+                async move {
+                    let partition_id = event.partition_context().partition_id();
+                    match event.body() {
+                        Ok(body) => log::info!("{}: {:?}", partition_id, std::str::from_utf8(body)),
+                        Err(err) => log::error!("{}: {:?}", partition_id, err),
+                    }
+
+                    let checkpoint = EventProcessorCheckpoint::from(&event);
+                    if let Err(err) = store.update_checkpoint(checkpoint).await {
+                        log::error!("Failed to update checkpoint: {:?}", err);
+                    }
+                }
+            },
+            child.cancelled(),
+        )
+        .await?;
+
+    processor.close().await?;
+    Ok(())
+}
@@ -6,35 +6,21 @@
 
 use std::time::Duration;
 
-use azeventhubs::consumer::{
-    EventHubConsumerClient, EventHubConsumerClientOptions, EventPosition, ReadEventOptions,
+use azeventhubs::{
+    consumer::{
+        EventHubConsumerClient, EventHubConsumerClientOptions, EventPosition, EventStream,
+        ReadEventOptions,
+    },
+    BasicRetryPolicy,
 };
 use futures_util::StreamExt;
 use tokio_util::sync::CancellationToken;
 
 async fn consumer_main(
-    index: usize,
-    connection_string: impl Into<String>,
-    event_hub_name: impl Into<String>,
-    client_options: EventHubConsumerClientOptions,
+    partition_id: String,
+    mut stream: EventStream<'static, BasicRetryPolicy>,
     cancel: CancellationToken,
 ) -> Result<(), azure_core::Error> {
-    let mut consumer = EventHubConsumerClient::new_from_connection_string(
-        EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
-        connection_string,
-        event_hub_name.into(),
-        client_options,
-    )
-    .await?;
-    let partition_ids = consumer.get_partition_ids().await?;
-    let partition_id = &partition_ids[index];
-    let starting_position = EventPosition::earliest();
-    let read_event_options = ReadEventOptions::default();
-
-    let mut stream = consumer
-        .read_events_from_partition(partition_id, starting_position, read_event_options)
-        .await?;
-
     loop {
         tokio::select! {
             _ = cancel.cancelled() => {
@@ -60,6 +46,7 @@ async fn consumer_main(
         }
     }
 
+    stream.close().await?;
     Ok(())
 }
 
@@ -72,18 +59,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let event_hub_name = std::env::var("EVENT_HUB_NAME")?;
     let client_options = EventHubConsumerClientOptions::default();
 
+    // A single client is shared by all the streams. Owned streams do not borrow the client, so
+    // they can be moved into the spawned tasks.
+    let mut consumer = EventHubConsumerClient::new_from_connection_string(
+        EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+        connection_string,
+        event_hub_name,
+        client_options,
+    )
+    .await?;
+    let partition_ids = consumer.get_partition_ids().await?;
+
     // We are going to use a cancellation token to stop the spawned tasks.
     let cancel = CancellationToken::new();
-    // Assuming that there are three partitions, and we will create one consumer for each partition.
+    // One stream is spawned for each partition.
     let mut handles = Vec::new();
-    for i in 0..3 {
-        let handle = tokio::spawn(consumer_main(
-            i,
-            connection_string.clone(),
-            event_hub_name.clone(),
-            client_options.clone(),
-            cancel.child_token(),
-        ));
+    for partition_id in partition_ids {
+        let stream = consumer
+            .read_owned_events_from_partition(
+                &partition_id,
+                EventPosition::earliest(),
+                ReadEventOptions::default(),
+            )
+            .await?;
+        let handle = tokio::spawn(consumer_main(partition_id, stream, cancel.child_token()));
         handles.push(handle);
     }
 
@@ -93,6 +92,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for handle in handles {
         handle.await??;
     }
+    consumer.close().await?;
 
     Ok(())
 }
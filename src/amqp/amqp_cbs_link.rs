@@ -321,7 +321,13 @@ impl AmqpCbsLink {
                                 let key = self
                                     .delay_queue
                                     .insert_at(Refresher::Authorization(auth), expires_at);
-                                self.active_link_identifiers.insert(link_identifier, key);
+                                // A link that is being recovered keeps its identifier, so the
+                                // previously scheduled refresh is replaced
+                                if let Some(old_key) =
+                                    self.active_link_identifiers.insert(link_identifier, key)
+                                {
+                                    self.delay_queue.try_remove(&old_key);
+                                }
                             }
                         }
                         let _ = result_sender.send(Ok(()));
@@ -350,6 +356,8 @@ impl AmqpCbsLink {
             }
             Refresher::Authorization(auth) => {
                 let link_identifier = auth.link_identifier;
+                // The key is no longer valid once the refresher is taken out of the queue
+                self.active_link_identifiers.remove(&link_identifier);
                 let result = self
                     .request_authorization_using_cbs(
                         &auth.endpoint,
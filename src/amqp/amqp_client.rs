@@ -1,5 +1,6 @@
-use std::sync::{atomic::Ordering, Arc};
+use std::sync::atomic::Ordering;
 
+use azure_core::auth::AccessToken;
 use url::Url;
 
 use crate::{
@@ -10,13 +11,16 @@ use crate::{
     event_hubs_connection_option::EventHubConnectionOptions,
     event_hubs_properties::EventHubProperties,
     event_hubs_retry_policy::EventHubsRetryPolicy,
+    event_hubs_transport_type::EventHubsTransportType,
     producer::PartitionPublishingOptions,
     util::sharable::Sharable,
     PartitionProperties,
 };
 
 use super::{
+    amqp_connection::{AmqpConnectionSettings, SessionWindows},
     amqp_connection_scope::AmqpConnectionScope,
+    amqp_connection_stream::ConnectionStreamOptions,
     amqp_consumer::AmqpConsumer,
     amqp_management::partition_properties::PartitionPropertiesRequest,
     amqp_management_link::AmqpManagementLink,
@@ -48,12 +52,22 @@ impl AmqpClient {
             management_link: shared_mgmt_link,
         }
     }
+
+    /// Closes the AMQP connection and opens a replacement, which is shared by every handle to the
+    /// connection. The links of the other handles are recovered on the replacement the next time
+    /// they are used.
+    pub(crate) async fn replace_connection(&mut self) -> Result<(), RecoverTransportClientError> {
+        if let Err(err) = self.connection_scope.connection.close().await {
+            log::error!("Error closing connection during replacement: {:?}", err);
+        }
+        self.recover().await
+    }
 }
 
 impl AmqpClient {
     pub(crate) async fn new(
         host: &str,
-        event_hub_name: Arc<String>,
+        event_hub_name: &str,
         credential: EventHubTokenCredential,
         options: EventHubConnectionOptions,
     ) -> Result<Self, AmqpClientError> {
@@ -62,6 +76,14 @@ impl AmqpClient {
         let service_endpoint = Url::parse(&service_endpoint)?;
 
         let connection_endpoint = match options.custom_endpoint_address {
+            // A custom endpoint with the plain `amqp` scheme is connected to without TLS, which is
+            // what local emulators expect
+            Some(url)
+                if url.scheme() == EventHubsTransportType::PLAIN_AMQP_SCHEME
+                    && options.transport_type == EventHubsTransportType::AmqpTcp =>
+            {
+                url
+            }
             Some(mut url) => {
                 url.set_scheme(options.transport_type.url_scheme())
                     .map_err(|_| AmqpClientError::SetUrlScheme)?;
@@ -78,6 +100,20 @@ impl AmqpClient {
             credential,
             options.transport_type,
             options.connection_idle_timeout,
+            AmqpConnectionSettings {
+                max_frame_size: options.max_frame_size,
+                channel_max: options.channel_max,
+                session_windows: SessionWindows {
+                    incoming_window: options.session_incoming_window,
+                    outgoing_window: options.session_outgoing_window,
+                },
+            },
+            ConnectionStreamOptions {
+                tls_connector: options.tls_connector,
+                proxy: options.proxy,
+                send_buffer_size: options.send_buffer_size_in_bytes,
+                receive_buffer_size: options.receive_buffer_size_in_bytes,
+            },
             None,
         )
         .await?;
@@ -90,6 +126,23 @@ impl AmqpClient {
             management_link,
         })
     }
+
+    /// The token that authorizes the management requests for the Event Hub
+    async fn management_token(
+        &self,
+        event_hub_name: &str,
+    ) -> Result<AccessToken, RequestResponseError> {
+        let resource = self
+            .connection_scope
+            .service_endpoint
+            .join(event_hub_name)
+            .map_err(azure_core::Error::from)?;
+        self.connection_scope
+            .credential
+            .get_token_for_resource(resource.as_str())
+            .await
+            .map_err(Into::into)
+    }
 }
 
 impl TransportClient for AmqpClient {
@@ -109,16 +162,12 @@ impl TransportClient for AmqpClient {
 
     async fn get_properties(
         &mut self,
+        event_hub_name: &str,
     ) -> Result<EventHubProperties, Self::RequestResponseError> {
-        let access_token = self
-            .connection_scope
-            .credential
-            .get_token_using_default_resource()
-            .await?;
+        let access_token = self.management_token(event_hub_name).await?;
         let token_value = access_token.token.secret();
 
-        let request =
-            EventHubPropertiesRequest::new(&*self.connection_scope.event_hub_name, token_value);
+        let request = EventHubPropertiesRequest::new(event_hub_name, token_value);
 
         self.management_link.call(request).await
             .map_err(Into::into)
@@ -126,20 +175,13 @@ impl TransportClient for AmqpClient {
 
     async fn get_partition_properties(
         &mut self,
+        event_hub_name: &str,
         partition_id: &str,
     ) -> Result<PartitionProperties, Self::RequestResponseError> {
-        let access_token = self
-            .connection_scope
-            .credential
-            .get_token_using_default_resource()
-            .await?;
+        let access_token = self.management_token(event_hub_name).await?;
         let token_value = access_token.token.secret();
-        
-        let request = PartitionPropertiesRequest::new(
-            &*self.connection_scope.event_hub_name,
-            partition_id,
-            token_value,
-        );
+
+        let request = PartitionPropertiesRequest::new(event_hub_name, partition_id, token_value);
 
         self.management_link.call(request).await
             .map_err(Into::into)
@@ -147,6 +189,7 @@ impl TransportClient for AmqpClient {
 
     async fn create_producer<RP>(
         &mut self,
+        event_hub_name: &str,
         partition_id: Option<String>,
         producer_identifier: Option<String>,
         requested_features: TransportProducerFeatures,
@@ -157,6 +200,7 @@ impl TransportClient for AmqpClient {
         RP: EventHubsRetryPolicy + Send,
     {
         self.connection_scope.open_producer_link(
+            event_hub_name,
             partition_id,
             requested_features,
             partition_options,
@@ -207,6 +251,7 @@ impl TransportClient for AmqpClient {
 
     async fn create_consumer<RP>(
         &mut self,
+        event_hub_name: &str,
         consumer_group: &str,
         partition_id: &str,
         consumer_identifier: Option<String>,
@@ -220,6 +265,7 @@ impl TransportClient for AmqpClient {
         RP: EventHubsRetryPolicy + Send,
     {
         self.connection_scope.open_consumer_link(
+            event_hub_name,
             consumer_group,
             partition_id,
             event_position,
@@ -231,98 +277,24 @@ impl TransportClient for AmqpClient {
         ).await
     }
 
-    // async fn recover_consumer<RP>(
-    //     &mut self,
-    //     consumer: &mut Self::Consumer<RP>,
-    // ) -> Result<(), Self::RecoverConsumerError>
-    // where
-    //     RP: EventHubsRetryPolicy + Send,
-    // {
-    //     log::debug!("Recovering consumer");
-
-    //     let endpoint = consumer.endpoint.to_string();
-    //     let resource = endpoint.clone();
-    //     let required_claims = vec![event_hub_claim::LISTEN.to_string()];
-    //     self.connection_scope
-    //         .request_refreshable_authorization_using_cbs(
-    //             consumer.link_identifier,
-    //             endpoint,
-    //             resource,
-    //             required_claims,
-    //         )
-    //         .await?;
-
-    //     if let Some(Ok(event_position)) = consumer
-    //         .current_event_position
-    //         .clone()
-    //         .map(|p| amqp_filter::build_filter_expression(&p))
-    //     {
-    //         let consumer_filter = Described::<Value>::from(ConsumerFilter(event_position));
-    //         let source = consumer.receiver.source_mut().get_or_insert(
-    //             Source::builder()
-    //                 .address(consumer.endpoint.to_string())
-    //                 .build(),
-    //         );
-    //         let source_filter = source.filter.get_or_insert(FilterSet::new());
-    //         source_filter.insert(
-    //             amqp_filter::CONSUMER_FILTER_NAME.into(),
-    //             consumer_filter.into(),
-    //         );
-    //     }
-
-    //     let mut exchange = if consumer.session_handle.is_ended() {
-    //         let new_session = self.connection_scope.connection.begin_session().await?;
-    //         let exchange = consumer
-    //             .receiver
-    //             .detach_then_resume_on_session(&new_session)
-    //             .await?;
-    //         let mut old_session = std::mem::replace(&mut consumer.session_handle, new_session);
-    //         let _ = old_session.end().await;
-    //         exchange
-    //     } else {
-    //         consumer
-    //             .receiver
-    //             .detach_then_resume_on_session(&consumer.session_handle)
-    //             .await?
-    //     };
-
-    //     // `ReceiverAttachExchange::Complete` => Resume is complete
-    //     //
-    //     // `ReceiverAttachExchange::IncompleteUnsettled` => There are unsettled messages, multiple
-    //     // detach and re-attach may happen in order to reduce the number of unsettled messages.
-    //     //
-    //     // `ReceiverAttachExchange::Resume` => There is one message that is partially transferred,
-    //     // so it would be OK to let the user use the receiver to receive the message
-    //     while let ReceiverAttachExchange::IncompleteUnsettled = exchange {
-    //         match consumer.receiver.recv::<Body<Value>>().await {
-    //             Ok(delivery) => {
-    //                 let modified = Modified {
-    //                     delivery_failed: None,
-    //                     undeliverable_here: None,
-    //                     message_annotations: None,
-    //                 };
-    //                 if let Err(err) = consumer.receiver.modify(delivery, modified).await {
-    //                     log::error!("Failed to abandon message: {}", err);
-    //                     exchange = consumer
-    //                         .receiver
-    //                         .detach_then_resume_on_session(&consumer.session_handle)
-    //                         .await?;
-    //                 }
-    //             }
-    //             Err(err) => {
-    //                 log::error!("Failed to receive message while trying to settle (abandon) the unsettled: {}", err);
-    //                 exchange = consumer
-    //                     .receiver
-    //                     .detach_then_resume_on_session(&consumer.session_handle)
-    //                     .await?;
-    //             }
-    //         }
-    //     }
-
-    //     log::debug!("Consumer recovered");
-
-    //     Ok(())
-    // }
+    async fn recover_consumer<RP>(
+        &mut self,
+        consumer: &mut Self::Consumer<RP>,
+    ) -> Result<(), Self::RecoverConsumerError>
+    where
+        RP: EventHubsRetryPolicy + Send,
+    {
+        log::debug!("Recovering consumer");
+
+        let event_position = consumer.resume_event_position();
+        self.connection_scope
+            .reopen_consumer_link(consumer, &event_position)
+            .await?;
+
+        log::debug!("Consumer recovered");
+
+        Ok(())
+    }
 
     async fn close(&mut self) -> Result<(), Self::DisposeError> {
         self.connection_scope.close().await
@@ -6,20 +6,47 @@ use crate::util::sharable::Sharable;
 
 use super::CONNECTION_IDENTIFIER;
 
+/// The settings that a connection is opened with, which are kept for recovery
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AmqpConnectionSettings {
+    pub max_frame_size: u32,
+    pub channel_max: u16,
+    pub session_windows: SessionWindows,
+}
+
+/// The flow control windows of the sessions that are begun on a connection
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SessionWindows {
+    pub incoming_window: u32,
+    pub outgoing_window: u32,
+}
+
 /// A wrapper around the AMQP connection handle that also tracks the identifier for the connection.
 #[derive(Debug)]
 pub(crate) struct AmqpConnection {
     pub identifier: u32,
     pub handle: fe2o3_amqp::connection::ConnectionHandle<()>,
+    pub session_windows: SessionWindows,
 }
 
 impl AmqpConnection {
-    pub(crate) fn new(handle: fe2o3_amqp::connection::ConnectionHandle<()>) -> Self {
+    pub(crate) fn new(
+        handle: fe2o3_amqp::connection::ConnectionHandle<()>,
+        session_windows: SessionWindows,
+    ) -> Self {
         Self {
             identifier: CONNECTION_IDENTIFIER.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
             handle,
+            session_windows,
         }
     }
+
+    pub(crate) async fn begin_session(&mut self) -> Result<SessionHandle<()>, BeginError> {
+        let mut builder = Session::builder();
+        builder.incoming_window = self.session_windows.incoming_window;
+        builder.outgoing_window = self.session_windows.outgoing_window;
+        builder.begin(&mut self.handle).await
+    }
 }
 
 impl Sharable<AmqpConnection> {
@@ -56,10 +83,10 @@ impl Sharable<AmqpConnection> {
 
     pub(crate) async fn begin_session(&mut self) -> Result<SessionHandle<()>, BeginError> {
         match self {
-            Sharable::Owned(c) => Session::begin(&mut c.handle).await,
+            Sharable::Owned(c) => c.begin_session().await,
             Sharable::Shared(lock) => {
                 let mut guard = lock.write().await;
-                Session::begin(&mut guard.handle).await
+                guard.begin_session().await
             }
             Sharable::None => unreachable!(),
         }
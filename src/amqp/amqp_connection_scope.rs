@@ -7,8 +7,9 @@ use std::{
 };
 
 use fe2o3_amqp::{
-    connection::ConnectionHandle, link::receiver::CreditMode, sasl_profile::SaslProfile,
-    session::SessionHandle, Connection, Receiver, Sender, Session,
+    connection::{ConnectionHandle, OpenError},
+    link::receiver::CreditMode, sasl_profile::SaslProfile,
+    session::SessionHandle, Connection, Receiver, Sender,
 };
 use fe2o3_amqp_cbs::client::CbsClient;
 use fe2o3_amqp_management::MgmtClient;
@@ -20,13 +21,11 @@ use fe2o3_amqp_types::{
 use fe2o3_amqp_ws::WebSocketStream;
 
 use serde_amqp::Value;
-use time::Duration as TimeSpan;
 use url::Url;
 
 use crate::{
     amqp::{
         amqp_cbs_link::AmqpCbsLink,
-        amqp_constants,
         amqp_filter::{self, ConsumerFilter},
         LINK_IDENTIFIER, SESSION_IDENTIFIER, amqp_producer::ProducerOptions,
     },
@@ -40,10 +39,11 @@ use crate::{
 
 use super::{
     amqp_cbs_link::AmqpCbsLinkHandle,
-    amqp_connection::AmqpConnection,
+    amqp_connection::{AmqpConnection, AmqpConnectionSettings},
+    amqp_connection_stream::{self, ConnectionStreamOptions},
     amqp_consumer::{AmqpConsumer, ConsumerOptions},
     amqp_management_link::AmqpManagementLink,
-    amqp_producer::AmqpProducer,
+    amqp_producer::{AmqpProducer, IdempotentPublishingState},
     amqp_property,
     cbs_token_provider::CbsTokenProvider,
     error::{
@@ -52,7 +52,6 @@ use super::{
     },
 };
 
-const AUTHORIZATION_REFRESH_BUFFER_SECONDS: u64 = 7 * 60;
 const WEBSOCKETS_PATH_SUFFIX: &str = "/$servicebus/websocket/";
 
 #[derive(Debug)]
@@ -69,9 +68,6 @@ pub(crate) struct AmqpConnectionScope {
     /// The endpoint to used establishing a connection to the Event Hubs service to which the scope is associated.
     pub(crate) connection_endpoint: Arc<Url>,
 
-    /// The name of the Event Hub to which the scope is associated.
-    pub(crate) event_hub_name: Arc<String>,
-
     /// The type of transport to use for communication.
     pub(crate) transport: EventHubsTransportType,
 
@@ -81,6 +77,12 @@ pub(crate) struct AmqpConnectionScope {
     // Keep a copy of connection_idle_timeout for recovery
     pub(crate) connection_idle_timeout: StdDuration,
 
+    // Keep a copy of the frame, channel and session settings for recovery
+    pub(crate) connection_settings: AmqpConnectionSettings,
+
+    // Keep a copy of the TLS connector, proxy and socket buffer sizes for recovery
+    pub(crate) stream_options: ConnectionStreamOptions,
+
     /// The AMQP connection to the Event Hubs service.
     pub(crate) connection: Sharable<AmqpConnection>,
 
@@ -92,12 +94,6 @@ pub(crate) struct AmqpConnectionScope {
 }
 
 impl AmqpConnectionScope {
-    /// The amount of buffer to apply when considering an authorization token
-    /// to be expired.  The token's actual expiration will be decreased by this
-    /// amount, ensuring that it is renewed before it has expired.
-    const AUTHORIZATION_TOKEN_EXPIRATION_BUFFER: TimeSpan =
-        TimeSpan::seconds(AUTHORIZATION_REFRESH_BUFFER_SECONDS as i64 + 2 * 60);
-
     pub(crate) fn is_owned(&self) -> bool {
         matches!(self.connection, Sharable::Owned(_))
     }
@@ -130,31 +126,29 @@ impl AmqpConnectionScope {
             id: self.id.clone(),
             service_endpoint: self.service_endpoint.clone(),
             connection_endpoint: self.connection_endpoint.clone(),
-            event_hub_name: self.event_hub_name.clone(),
             transport: self.transport,
             credential: self.credential.clone(),
             connection_idle_timeout: self.connection_idle_timeout,
+            connection_settings: self.connection_settings,
+            stream_options: self.stream_options.clone(),
             connection: shared_connection,
             cbs_session_handle: shared_cbs_session_handle,
             cbs_link_handle: shared_cbs_link_handle,
         }
     }
 
+    #[allow(clippy::too_many_arguments)] // TODO: how to reduce the number of arguments?
     pub(crate) async fn new(
         service_endpoint: Url,
         connection_endpoint: Url,
-        event_hub_name: Arc<String>,
+        event_hub_name: &str,
         credential: EventHubTokenCredential,
         transport_type: EventHubsTransportType,
         connection_idle_timeout: StdDuration,
+        connection_settings: AmqpConnectionSettings,
+        stream_options: ConnectionStreamOptions,
         identifier: Option<String>,
     ) -> Result<Self, AmqpConnectionScopeError> {
-        // sendBufferSizeInBytes and receiveBufferSizeInBytes are not used for now. They probably
-        // translate to `tokio::net::TcpSocket::set_send_buffer_size` and
-        // `tokio::net::TcpSocket::set_recv_buffer_size` for the TCP transport, and to
-        // `tungstenite::WebSocketConfig::max_message_size` or `max_frame_size` for the WebSocket
-        // transport.
-
         // Id = identifier ?? $"{ eventHubName }-{ Guid.NewGuid().ToString("D", CultureInfo.InvariantCulture).Substring(0, 8) }";
         let id = identifier.unwrap_or_else(|| {
             let uuid = uuid::Uuid::new_v4();
@@ -168,17 +162,16 @@ impl AmqpConnectionScope {
             transport_type,
             &id,
             connection_idle_timeout,
+            &connection_settings,
+            &stream_options,
         )
         .await?;
-        let mut connection = AmqpConnection::new(connection_handle);
+        let mut connection = AmqpConnection::new(connection_handle, connection_settings.session_windows);
 
-        let mut cbs_session_handle = Session::begin(&mut connection.handle).await?;
+        let mut cbs_session_handle = connection.begin_session().await?;
 
         let cbs_client = attach_cbs_client(&mut cbs_session_handle).await?;
-        let cbs_token_provider = CbsTokenProvider::new(
-            credential.clone(),
-            Self::AUTHORIZATION_TOKEN_EXPIRATION_BUFFER,
-        );
+        let cbs_token_provider = CbsTokenProvider::new(credential.clone());
         let cbs_link_handle = AmqpCbsLink::spawn(cbs_token_provider, cbs_client);
 
         let id = Arc::new(id);
@@ -192,24 +185,27 @@ impl AmqpConnectionScope {
             id,
             service_endpoint,
             connection_endpoint,
-            event_hub_name,
             transport: transport_type,
             credential,
             connection_idle_timeout,
+            connection_settings,
+            stream_options,
             connection,
             cbs_session_handle,
             cbs_link_handle,
         })
     }
 
+    #[allow(clippy::too_many_arguments)] // TODO: how to reduce the number of arguments?
     async fn open_connection(
         service_endpoint: &Url,
         connection_endpoint: &Url,
         transport_type: EventHubsTransportType,
         id: &str,
         idle_timeout: StdDuration,
+        connection_settings: &AmqpConnectionSettings,
+        stream_options: &ConnectionStreamOptions,
     ) -> Result<ConnectionHandle<()>, AmqpConnectionScopeError> {
-        let max_frame_size = amqp_constants::DEFAULT_MAX_FRAME_SIZE;
         let container_id = id;
 
         let connection_builder = Connection::builder()
@@ -217,17 +213,101 @@ impl AmqpConnectionScope {
             .hostname(service_endpoint.host_str())
             .alt_tls_establishment(true)
             .sasl_profile(SaslProfile::Anonymous)
-            .max_frame_size(max_frame_size)
+            .max_frame_size(connection_settings.max_frame_size)
+            .channel_max(connection_settings.channel_max)
             .idle_time_out(idle_timeout.as_millis() as u32);
 
         match transport_type {
             #[cfg(not(target_arch = "wasm32"))]
-            EventHubsTransportType::AmqpTcp => connection_builder
-                .open(connection_endpoint.clone())
-                .await
-                .map_err(Into::into),
+            EventHubsTransportType::AmqpTcp => {
+                if stream_options.proxy.is_some() {
+                    return Err(OpenError::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "a proxy can only be used with the AmqpWebSockets transport",
+                    ))
+                    .into());
+                }
+                if stream_options.is_default() {
+                    return connection_builder
+                        .open(connection_endpoint.clone())
+                        .await
+                        .map_err(Into::into);
+                }
+
+                let stream = amqp_connection_stream::connect_tcp(connection_endpoint, stream_options)
+                    .await
+                    .map_err(OpenError::Io)?;
+                // A plain `amqp` endpoint is connected to without TLS
+                if connection_endpoint.scheme() != EventHubsTransportType::AMQP_SCHEME {
+                    return connection_builder
+                        .scheme(connection_endpoint.scheme())
+                        .open_with_stream(stream)
+                        .await
+                        .map_err(Into::into);
+                }
+
+                #[cfg(any(feature = "native-tls", feature = "rustls"))]
+                {
+                    let connector = match &stream_options.tls_connector {
+                        Some(connector) => connector.clone(),
+                        None => crate::EventHubsTlsConnector::default_connector()
+                            .map_err(OpenError::Io)?,
+                    };
+                    let domain = connection_endpoint.host_str().unwrap_or_default();
+                    let stream = amqp_connection_stream::connect_tls(&connector, domain, stream)
+                        .await
+                        .map_err(OpenError::Io)?;
+                    // TLS is already established, so the TLS protocol header must not be
+                    // exchanged
+                    connection_builder
+                        .scheme(EventHubsTransportType::PLAIN_AMQP_SCHEME)
+                        .open_with_stream(stream)
+                        .await
+                        .map_err(Into::into)
+                }
+                #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+                {
+                    Err(OpenError::Io(std::io::Error::new(
+                        std::io::ErrorKind::Unsupported,
+                        "socket buffer sizes require either the \"native-tls\" or the \"rustls\" \
+                         feature with an amqps endpoint",
+                    ))
+                    .into())
+                }
+            }
             EventHubsTransportType::AmqpWebSockets => {
                 let addr = connection_endpoint.join(WEBSOCKETS_PATH_SUFFIX)?;
+
+                #[cfg(not(target_arch = "wasm32"))]
+                let ws_stream = match stream_options {
+                    options if options.is_default() => WebSocketStream::connect(addr).await?,
+                    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+                    options => {
+                        let stream = amqp_connection_stream::connect_tcp(&addr, options)
+                            .await
+                            .map_err(OpenError::Io)?;
+                        WebSocketStream::connect_tls_with_stream_and_config(
+                            addr,
+                            stream,
+                            None,
+                            options
+                                .tls_connector
+                                .as_ref()
+                                .map(amqp_connection_stream::websocket_connector),
+                        )
+                        .await?
+                    }
+                    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+                    _ => {
+                        return Err(OpenError::Io(std::io::Error::new(
+                            std::io::ErrorKind::Unsupported,
+                            "a proxy or socket buffer sizes require either the \"native-tls\" or \
+                             the \"rustls\" feature with the AmqpWebSockets transport",
+                        ))
+                        .into())
+                    }
+                };
+                #[cfg(target_arch = "wasm32")]
                 let ws_stream = WebSocketStream::connect(addr).await?;
 
                 #[cfg(not(target_arch = "wasm32"))]
@@ -285,8 +365,11 @@ impl AmqpConnectionScope {
         Ok((session_handle, mgmt_link))
     }
 
+    /// Opens a producer link to the Event Hub, which may be any Event Hub in the namespace of the
+    /// connection. The link is authorized for its own entity.
     pub(crate) async fn open_producer_link<RP>(
         &mut self,
+        event_hub_name: &str,
         partition_id: Option<String>,
         features: TransportProducerFeatures,
         options: PartitionPublishingOptions,
@@ -299,6 +382,7 @@ impl AmqpConnectionScope {
         use std::borrow::Cow;
 
         let initial_options = ProducerOptions {
+            event_hub_name: event_hub_name.to_string(),
             partition_id: partition_id.clone(),
             identifier: identifier.clone(),
             requested_features: features,
@@ -306,12 +390,11 @@ impl AmqpConnectionScope {
         };
 
         let path: Cow<str> = match &partition_id {
-            None => Cow::Borrowed(&self.event_hub_name),
-            Some(partition_id) if partition_id.is_empty() => Cow::Borrowed(&self.event_hub_name),
-            Some(partition_id) => Cow::Owned(format!(
-                "{}/Partitions/{}",
-                self.event_hub_name, partition_id
-            )),
+            None => Cow::Borrowed(event_hub_name),
+            Some(partition_id) if partition_id.is_empty() => Cow::Borrowed(event_hub_name),
+            Some(partition_id) => {
+                Cow::Owned(format!("{}/Partitions/{}", event_hub_name, partition_id))
+            }
         };
         let producer_endpoint = self.service_endpoint.join(&path)?;
 
@@ -329,6 +412,16 @@ impl AmqpConnectionScope {
             )
             .await?;
 
+        let idempotent_state = match features {
+            TransportProducerFeatures::IdempotentPublishing => {
+                Some(sender.properties(IdempotentPublishingState::from_link_properties))
+            }
+            TransportProducerFeatures::None => None,
+        };
+        let initialized_partition_properties = idempotent_state
+            .map(IdempotentPublishingState::to_partition_properties)
+            .unwrap_or_default();
+
         Ok(AmqpProducer {
             session_handle,
             _session_identifier: session_identifier,
@@ -338,7 +431,9 @@ impl AmqpConnectionScope {
             endpoint: producer_endpoint,
             cbs_command_sender: self.cbs_link_handle.command_sender().await,
 
+            initialized_partition_properties,
             initial_options,
+            idempotent_state,
         })
     }
 
@@ -356,7 +451,7 @@ impl AmqpConnectionScope {
     async fn create_sending_session_and_link(
         &mut self,
         endpoint: &Url,
-        _features: TransportProducerFeatures,
+        features: TransportProducerFeatures,
         options: PartitionPublishingOptions,
         session_identifier: u32,
         link_identifier: u32,
@@ -393,10 +488,16 @@ impl AmqpConnectionScope {
             .source(identifier)
             .target(endpoint.to_string());
 
+        let is_idempotent = features == TransportProducerFeatures::IdempotentPublishing;
+        if is_idempotent {
+            builder = builder.add_desired_capabilities(amqp_property::ENABLE_IDEMPOTENT_PUBLISHING);
+        }
+
         // If any of the options have a value, the entire set must be specified for the link
         // settings.  For any options that did not have a value, specifying null will signal the
         // service to generate the value.
-        if options.producer_group_id.is_some()
+        if is_idempotent
+            || options.producer_group_id.is_some()
             || options.owner_level.is_some()
             || options.starting_sequence_number.is_some()
         {
@@ -425,9 +526,12 @@ impl AmqpConnectionScope {
         Ok((session_handle, sender))
     }
 
+    /// Opens a consumer link to a partition of the Event Hub, which may be any Event Hub in the
+    /// namespace of the connection. The link is authorized for its own entity.
     #[allow(clippy::too_many_arguments)] // TODO: how to reduce the number of arguments?
     pub(crate) async fn open_consumer_link<RP>(
         &mut self,
+        event_hub_name: &str,
         consumer_group: &str,
         partition_id: &str,
         event_position: &EventPosition,
@@ -441,6 +545,7 @@ impl AmqpConnectionScope {
         RP: Clone,
     {
         let initial_options = ConsumerOptions {
+            event_hub_name: event_hub_name.to_string(),
             consumer_group: consumer_group.to_string(),
             partition_id: partition_id.to_string(),
             identifier: identifier.clone(),
@@ -450,11 +555,8 @@ impl AmqpConnectionScope {
             prefetch_count,
         };
 
-        let path = format!(
-            "{}/ConsumerGroups/{}/Partitions/{}",
-            self.event_hub_name, consumer_group, partition_id
-        );
-        let consumer_endpoint = self.service_endpoint.join(&path)?;
+        let consumer_endpoint =
+            self.consumer_endpoint(event_hub_name, consumer_group, partition_id)?;
         let identifier = identifier.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
         let session_identifier = SESSION_IDENTIFIER.fetch_add(1, Ordering::Relaxed);
         let link_identifier = LINK_IDENTIFIER.fetch_add(1, Ordering::Relaxed);
@@ -477,9 +579,7 @@ impl AmqpConnectionScope {
             _session_identifier: session_identifier,
             receiver,
             link_identifier,
-            track_last_enqueued_event_properties,
             current_event_position: None,
-            last_received_event: None,
             retry_policy,
             prefetch_count,
             cbs_command_sender: self.cbs_link_handle.command_sender().await,
@@ -487,6 +587,62 @@ impl AmqpConnectionScope {
         })
     }
 
+    /// Opens a new session and link for an existing consumer, starting from `event_position`.
+    ///
+    /// The link identifier is kept so that the authorization refresher registered for the
+    /// consumer is replaced instead of duplicated.
+    pub(crate) async fn reopen_consumer_link<RP>(
+        &mut self,
+        consumer: &mut AmqpConsumer<RP>,
+        event_position: &EventPosition,
+    ) -> Result<(), OpenConsumerError> {
+        let options = &consumer.initial_options;
+        let consumer_endpoint = self.consumer_endpoint(
+            &options.event_hub_name,
+            &options.consumer_group,
+            &options.partition_id,
+        )?;
+        let identifier = options
+            .identifier
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let session_identifier = SESSION_IDENTIFIER.fetch_add(1, Ordering::Relaxed);
+
+        let (session_handle, receiver) = self
+            .create_receiving_session_and_link(
+                &consumer_endpoint,
+                event_position,
+                options.prefetch_count,
+                options.owner_level,
+                options.track_last_enqueued_event_properties,
+                session_identifier,
+                consumer.link_identifier,
+                identifier,
+            )
+            .await?;
+
+        // Errors are ignored because the old link is most likely already broken
+        let old_receiver = std::mem::replace(&mut consumer.receiver, receiver);
+        let _ = old_receiver.close().await;
+        let mut old_session = std::mem::replace(&mut consumer.session_handle, session_handle);
+        let _ = old_session.close().await;
+        consumer._session_identifier = session_identifier;
+        Ok(())
+    }
+
+    fn consumer_endpoint(
+        &self,
+        event_hub_name: &str,
+        consumer_group: &str,
+        partition_id: &str,
+    ) -> Result<Url, url::ParseError> {
+        let path = format!(
+            "{}/ConsumerGroups/{}/Partitions/{}",
+            event_hub_name, consumer_group, partition_id
+        );
+        self.service_endpoint.join(&path)
+    }
+
     #[allow(clippy::too_many_arguments)] // TODO: how to reduce the number of arguments?
     async fn create_receiving_session_and_link(
         &mut self,
@@ -675,6 +831,7 @@ async fn attach_cbs_client(
     })
 }
 
+#[allow(clippy::too_many_arguments)] // TODO: how to reduce the number of arguments?
 async fn recover_connection(
     connection: &mut AmqpConnection,
     service_endpoint: &Url,
@@ -682,6 +839,8 @@ async fn recover_connection(
     transport_type: EventHubsTransportType,
     id: &str,
     idle_timeout: StdDuration,
+    connection_settings: &AmqpConnectionSettings,
+    stream_options: &ConnectionStreamOptions,
 ) -> Result<(), AmqpConnectionScopeError> {
     log::debug!("Recovering connection");
     if let Err(err) = connection.handle.close().await {
@@ -694,9 +853,11 @@ async fn recover_connection(
         transport_type,
         id,
         idle_timeout,
+        connection_settings,
+        stream_options,
     )
     .await?;
-    *connection = AmqpConnection::new(connection_handle);
+    *connection = AmqpConnection::new(connection_handle, connection_settings.session_windows);
     Ok(())
 }
 
@@ -723,6 +884,8 @@ impl RecoverableTransport for AmqpConnectionScope {
                         self.transport,
                         &self.id,
                         self.connection_idle_timeout,
+                        &self.connection_settings,
+                        &self.stream_options,
                     )
                     .await?
                 }
@@ -735,6 +898,8 @@ impl RecoverableTransport for AmqpConnectionScope {
                         self.transport,
                         &self.id,
                         self.connection_idle_timeout,
+                        &self.connection_settings,
+                        &self.stream_options,
                     )
                     .await?
                 }
@@ -751,10 +916,7 @@ impl RecoverableTransport for AmqpConnectionScope {
                     }
                     let mut new_cbs_session_handle = self.connection.begin_session().await?;
                     let cbs_client = attach_cbs_client(&mut new_cbs_session_handle).await?;
-                    let cbs_token_provider = CbsTokenProvider::new(
-                        self.credential.clone(),
-                        Self::AUTHORIZATION_TOKEN_EXPIRATION_BUFFER,
-                    );
+                    let cbs_token_provider = CbsTokenProvider::new(self.credential.clone());
                     let new_cbs_link_handle = AmqpCbsLink::spawn(cbs_token_provider, cbs_client);
                     self.cbs_session_handle = Sharable::Owned(new_cbs_session_handle);
                     self.cbs_link_handle = Sharable::Owned(new_cbs_link_handle);
@@ -766,12 +928,15 @@ impl RecoverableTransport for AmqpConnectionScope {
                     }
                     *session_guard = self.connection.begin_session().await?;
                     let cbs_client = attach_cbs_client(&mut session_guard).await?;
-                    let cbs_token_provider = CbsTokenProvider::new(
-                        self.credential.clone(),
-                        Self::AUTHORIZATION_TOKEN_EXPIRATION_BUFFER,
-                    );
+                    let cbs_token_provider = CbsTokenProvider::new(self.credential.clone());
                     let new_cbs_link_handle = AmqpCbsLink::spawn(cbs_token_provider, cbs_client);
-                    self.cbs_link_handle = Sharable::Owned(new_cbs_link_handle);
+                    // The other handles that share the session must use the new link as well
+                    match &mut self.cbs_link_handle {
+                        Sharable::Shared(link_lock) => {
+                            *link_lock.write().await = new_cbs_link_handle;
+                        }
+                        _ => self.cbs_link_handle = Sharable::Owned(new_cbs_link_handle),
+                    }
                 }
                 _ => {}
             }
@@ -782,3 +947,63 @@ impl RecoverableTransport for AmqpConnectionScope {
         Ok(())
     }
 }
+
+#[cfg(all(test, any(feature = "native-tls", feature = "rustls")))]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use tokio::{io::AsyncReadExt, net::TcpListener};
+    use url::Url;
+
+    use crate::{
+        amqp::{
+            amqp_connection::{AmqpConnectionSettings, SessionWindows},
+            amqp_connection_stream::ConnectionStreamOptions,
+        },
+        event_hubs_transport_type::EventHubsTransportType,
+    };
+
+    use super::AmqpConnectionScope;
+
+    /// The content type of a TLS handshake record
+    const TLS_HANDSHAKE: u8 = 0x16;
+
+    #[tokio::test]
+    async fn tls_is_established_when_only_socket_buffer_sizes_are_set() {
+        // An IP literal host, eg. of a local emulator
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = Url::parse(&format!("amqps://{}", listener.local_addr().unwrap())).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.read_u8().await.unwrap()
+        });
+
+        let settings = AmqpConnectionSettings {
+            max_frame_size: 256 * 1024,
+            channel_max: 255,
+            session_windows: SessionWindows {
+                incoming_window: 5000,
+                outgoing_window: 5000,
+            },
+        };
+        let stream_options = ConnectionStreamOptions {
+            send_buffer_size: Some(64 * 1024),
+            ..Default::default()
+        };
+        // The listener closes the stream after the first byte, so the handshake fails
+        let result = AmqpConnectionScope::open_connection(
+            &endpoint,
+            &endpoint,
+            EventHubsTransportType::AmqpTcp,
+            "test",
+            StdDuration::from_secs(60),
+            &settings,
+            &stream_options,
+        )
+        .await;
+        assert!(result.is_err());
+
+        // The client hello is sent rather than the TLS protocol header of AMQP
+        assert_eq!(server.await.unwrap(), TLS_HANDSHAKE);
+    }
+}
@@ -0,0 +1,321 @@
+//! Opens the streams that the AMQP connections are established on when a custom TLS connector, a
+//! proxy or socket buffer sizes are configured.
+
+use std::io;
+
+use base64::Engine;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpSocket, TcpStream},
+};
+use url::Url;
+
+use crate::{event_hubs_proxy_options::EventHubsProxyOptions, EventHubsTlsConnector};
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::event_hubs_tls_connector::TlsConnectorKind;
+
+/// The maximum size of the response to a `CONNECT` request
+const MAX_PROXY_RESPONSE_SIZE: usize = 8 * 1024;
+
+/// The settings that change how the stream of a connection is opened
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectionStreamOptions {
+    pub(crate) tls_connector: Option<EventHubsTlsConnector>,
+    pub(crate) proxy: Option<EventHubsProxyOptions>,
+    pub(crate) send_buffer_size: Option<u32>,
+    pub(crate) receive_buffer_size: Option<u32>,
+}
+
+impl ConnectionStreamOptions {
+    /// Whether the stream can be opened by the transport itself
+    pub(crate) fn is_default(&self) -> bool {
+        self.tls_connector.is_none()
+            && self.proxy.is_none()
+            && self.send_buffer_size.is_none()
+            && self.receive_buffer_size.is_none()
+    }
+}
+
+fn default_port(scheme: &str) -> u16 {
+    match scheme {
+        "amqp" => fe2o3_amqp_types::definitions::PORT,
+        "amqps" => fe2o3_amqp_types::definitions::SECURE_PORT,
+        "ws" => 80,
+        _ => 443,
+    }
+}
+
+/// Opens a TCP stream to the host of the url, or a tunnel to it through the proxy
+pub(crate) async fn connect_tcp(
+    url: &Url,
+    options: &ConnectionStreamOptions,
+) -> io::Result<TcpStream> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the url has no host"))?;
+    let port = url.port().unwrap_or_else(|| default_port(url.scheme()));
+    match &options.proxy {
+        Some(proxy) => {
+            let (proxy_host, proxy_port) = proxy_host_and_port(proxy)?;
+            let stream = connect_socket(proxy_host, proxy_port, options).await?;
+            open_tunnel(stream, proxy, host, port).await
+        }
+        None => connect_socket(host, port, options).await,
+    }
+}
+
+/// Connects a socket with the buffer sizes of the options to the first address of the host that
+/// accepts the connection
+async fn connect_socket(
+    host: &str,
+    port: u16,
+    options: &ConnectionStreamOptions,
+) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for addr in tokio::net::lookup_host((host, port)).await? {
+        let socket = match addr {
+            std::net::SocketAddr::V4(_) => TcpSocket::new_v4()?,
+            std::net::SocketAddr::V6(_) => TcpSocket::new_v6()?,
+        };
+        if let Some(size) = options.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = options.receive_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        match socket.connect(addr).await {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_error = Some(err),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no address was found for {}", host),
+        )
+    }))
+}
+
+fn proxy_host_and_port(proxy: &EventHubsProxyOptions) -> io::Result<(&str, u16)> {
+    if proxy.address.scheme() != "http" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only the http scheme is supported for the proxy",
+        ));
+    }
+    let host = proxy.address.host_str().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "the proxy address has no host")
+    })?;
+    Ok((host, proxy.address.port_or_known_default().unwrap_or(80)))
+}
+
+/// Opens a tunnel to `host:port` with `HTTP CONNECT` on a stream that is connected to the proxy
+async fn open_tunnel(
+    mut stream: TcpStream,
+    proxy: &EventHubsProxyOptions,
+    host: &str,
+    port: u16,
+) -> io::Result<TcpStream> {
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if let Some((username, password)) = proxy.credentials() {
+        let credentials =
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // The response is read one byte at a time so that nothing past its end is consumed from the
+    // tunnel
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_PROXY_RESPONSE_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the response of the proxy is too large",
+            ));
+        }
+        let byte = stream.read_u8().await?;
+        response.push(byte);
+    }
+
+    let status_line = response
+        .split(|byte| *byte == b'\n')
+        .next()
+        .map(String::from_utf8_lossy)
+        .unwrap_or_default();
+    let status_code = status_line.split_whitespace().nth(1);
+    match status_code {
+        Some(code) if code.starts_with('2') => Ok(stream),
+        _ => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!(
+                "the proxy refused to open a tunnel: {}",
+                status_line.trim_end()
+            ),
+        )),
+    }
+}
+
+/// A stream that TLS is established on
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) trait TlsStream:
+    tokio::io::AsyncRead + tokio::io::AsyncWrite + std::fmt::Debug + Send + Unpin
+{
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+impl<T> TlsStream for T where
+    T: tokio::io::AsyncRead + tokio::io::AsyncWrite + std::fmt::Debug + Send + Unpin
+{
+}
+
+/// Establishes TLS on the stream with the connector
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) async fn connect_tls(
+    connector: &EventHubsTlsConnector,
+    domain: &str,
+    stream: TcpStream,
+) -> io::Result<Box<dyn TlsStream>> {
+    match *connector.kind {
+        #[cfg(feature = "native-tls")]
+        TlsConnectorKind::NativeTls(ref connector) => {
+            let connector = tokio_native_tls::TlsConnector::from(connector.clone());
+            let stream = connector
+                .connect(domain, stream)
+                .await
+                .map_err(io::Error::other)?;
+            Ok(Box::new(stream))
+        }
+        #[cfg(feature = "rustls")]
+        TlsConnectorKind::Rustls(ref config) => {
+            let connector = tokio_rustls::TlsConnector::from(config.clone());
+            let domain = librustls::pki_types::ServerName::try_from(domain.to_string())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+            let stream = connector.connect(domain, stream).await?;
+            Ok(Box::new(stream))
+        }
+    }
+}
+
+/// The connector used by the WebSocket transport
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) fn websocket_connector(
+    connector: &EventHubsTlsConnector,
+) -> tokio_tungstenite::Connector {
+    match &*connector.kind {
+        #[cfg(feature = "native-tls")]
+        TlsConnectorKind::NativeTls(connector) => {
+            tokio_tungstenite::Connector::NativeTls(connector.clone())
+        }
+        #[cfg(feature = "rustls")]
+        TlsConnectorKind::Rustls(config) => tokio_tungstenite::Connector::Rustls(config.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+    use url::Url;
+
+    use crate::event_hubs_proxy_options::EventHubsProxyOptions;
+
+    use super::{connect_tcp, ConnectionStreamOptions};
+
+    fn with_proxy(proxy: EventHubsProxyOptions) -> ConnectionStreamOptions {
+        ConnectionStreamOptions {
+            proxy: Some(proxy),
+            ..Default::default()
+        }
+    }
+
+    fn websocket_url() -> Url {
+        Url::parse("wss://example.servicebus.windows.net/$servicebus/websocket/").unwrap()
+    }
+
+    /// Accepts one connection and answers its `CONNECT` request with the response. Returns the
+    /// request, and what was received through the tunnel afterwards.
+    async fn start_proxy(
+        response: &'static str,
+    ) -> (Url, tokio::task::JoinHandle<(Vec<String>, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+            let mut request = Vec::new();
+            loop {
+                let mut line = String::new();
+                stream.read_line(&mut line).await.unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                request.push(line.trim_end().to_string());
+            }
+            stream.write_all(response.as_bytes()).await.unwrap();
+            let mut tunneled = Vec::new();
+            stream.read_to_end(&mut tunneled).await.unwrap();
+            (request, tunneled)
+        });
+        (address, handle)
+    }
+
+    #[tokio::test]
+    async fn tunnel_is_opened_through_proxy() {
+        let (address, proxy) = start_proxy("HTTP/1.1 200 Connection established\r\n\r\n").await;
+        let options =
+            with_proxy(EventHubsProxyOptions::new(address).with_credentials("user", "pass"));
+
+        let mut stream = connect_tcp(&websocket_url(), &options).await.unwrap();
+        stream.write_all(b"AMQP").await.unwrap();
+        drop(stream);
+
+        let (request, tunneled) = proxy.await.unwrap();
+        assert_eq!(
+            request,
+            [
+                "CONNECT example.servicebus.windows.net:443 HTTP/1.1",
+                "Host: example.servicebus.windows.net:443",
+                // base64 of "user:pass"
+                "Proxy-Authorization: Basic dXNlcjpwYXNz",
+            ]
+        );
+        assert_eq!(tunneled, b"AMQP");
+    }
+
+    #[tokio::test]
+    async fn tunnel_refused_by_proxy_is_an_error() {
+        let (address, proxy) =
+            start_proxy("HTTP/1.1 407 Proxy Authentication Required\r\n\r\n").await;
+        let options = with_proxy(EventHubsProxyOptions::new(address));
+
+        let err = connect_tcp(&websocket_url(), &options).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+        assert!(err.to_string().contains("407"));
+
+        let (request, _) = proxy.await.unwrap();
+        assert_eq!(request.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn socket_buffer_sizes_are_applied() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("amqp://{}", listener.local_addr().unwrap())).unwrap();
+        let options = ConnectionStreamOptions {
+            send_buffer_size: Some(64 * 1024),
+            receive_buffer_size: Some(128 * 1024),
+            ..Default::default()
+        };
+
+        let stream = connect_tcp(&url, &options).await.unwrap();
+        let socket = tokio::net::TcpSocket::from_std_stream(stream.into_std().unwrap());
+
+        // The operating system may round the sizes up, eg. Linux doubles them
+        assert!(socket.send_buffer_size().unwrap() >= 64 * 1024);
+        assert!(socket.recv_buffer_size().unwrap() >= 128 * 1024);
+    }
+}
@@ -0,0 +1,366 @@
+//! A stream of batches of events.
+
+use std::{
+    collections::VecDeque,
+    future::poll_fn,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration as StdDuration,
+};
+
+use futures_util::{ready, Future, FutureExt, Stream};
+
+use crate::{
+    amqp::error::DisposeConsumerError,
+    consumer::{ConsumerPositionTracker, LastEnqueuedEventProperties},
+    event_hubs_retry_policy::EventHubsRetryPolicy,
+    transport_kind::{StreamClient, TransportConsumerKind},
+    ReceivedEventData,
+};
+
+use super::{EventStream, LastEnqueuedEventPropertiesMap, PartitionTaggedEvent};
+
+type BatchBoxedFuture<'a, RP> = Pin<
+    Box<
+        dyn Future<
+                Output = (
+                    Option<Result<Vec<PartitionTaggedEvent>, azure_core::Error>>,
+                    BatchConsumer<'a, RP>,
+                ),
+            > + Send
+            + 'a,
+    >,
+>;
+type BatchClosingBoxedFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<(), DisposeConsumerError>> + Send + 'a>>;
+
+enum BatchConsumer<'a, RP> {
+    Single {
+        client: StreamClient<'a>,
+        consumer: TransportConsumerKind<RP>,
+        partition_id: Arc<String>,
+    },
+    Multiple(EventStream<'a, RP>),
+}
+
+pin_project_lite::pin_project! {
+    #[project = BatchStreamStateProj]
+    #[project_replace = BatchStreamStateProjReplace]
+    enum BatchStreamState<'a, RP> {
+        Value {
+            value: BatchConsumer<'a, RP>,
+        },
+        Future {
+            #[pin]
+            future: BatchBoxedFuture<'a, RP>,
+        },
+        Closing {
+            #[pin]
+            future: BatchClosingBoxedFuture<'a>,
+        },
+        Empty,
+    }
+}
+
+impl<'a, RP> BatchStreamState<'a, RP>
+where
+    RP: Send + 'static,
+{
+    fn take_value(self: Pin<&mut Self>) -> Option<BatchConsumer<'a, RP>> {
+        match &*self {
+            BatchStreamState::Value { .. } => match self.project_replace(BatchStreamState::Empty) {
+                BatchStreamStateProjReplace::Value { value } => Some(value),
+                _ => unreachable!(),
+            },
+            _ => None,
+        }
+    }
+
+    fn project_future(self: Pin<&mut Self>) -> Option<Pin<&mut BatchBoxedFuture<'a, RP>>> {
+        match self.project() {
+            BatchStreamStateProj::Future { future } => Some(future),
+            _ => None,
+        }
+    }
+
+    fn project_closing(self: Pin<&mut Self>) -> Option<Pin<&mut BatchClosingBoxedFuture<'a>>> {
+        match self.project() {
+            BatchStreamStateProj::Closing { future } => Some(future),
+            _ => None,
+        }
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), DisposeConsumerError>> {
+        if let Some(value) = self.as_mut().take_value() {
+            self.set(BatchStreamState::Closing {
+                future: close_consumer(value).boxed(),
+            });
+        }
+
+        if self.as_mut().project_future().is_some() {
+            // Same as `EventStream`, a pending receive is dropped instead of being awaited
+            self.set(BatchStreamState::Empty);
+            return Poll::Ready(Ok(()));
+        }
+
+        let result = match self.as_mut().project_closing() {
+            Some(fut) => ready!(fut.poll(cx)),
+            // The stream has already ended and the consumers are closed
+            None => Ok(()),
+        };
+
+        self.set(BatchStreamState::Empty);
+        Poll::Ready(result)
+    }
+
+    async fn close(mut self) -> Result<(), DisposeConsumerError> {
+        poll_fn(|cx| Pin::new(&mut self).poll_close(cx)).await
+    }
+}
+
+/// Receives until the batch is full or until the wait time has elapsed. `None` is returned if
+/// there is no consumer left.
+async fn next_batch<RP>(
+    value: BatchConsumer<'_, RP>,
+    max_batch_size: usize,
+    max_wait_time: StdDuration,
+) -> (
+    Option<Result<Vec<PartitionTaggedEvent>, azure_core::Error>>,
+    BatchConsumer<'_, RP>,
+)
+where
+    RP: EventHubsRetryPolicy + Send + Unpin + 'static,
+{
+    match value {
+        BatchConsumer::Single {
+            mut client,
+            mut consumer,
+            partition_id,
+        } => {
+            let mut buffer = VecDeque::with_capacity(max_batch_size);
+            let outcome = consumer
+                .receive_event_batch(&mut client, &mut buffer, Some(max_wait_time))
+                .await
+                .map(|_| {
+                    buffer
+                        .into_iter()
+                        .map(|event| (partition_id.clone(), event))
+                        .collect()
+                });
+            let value = BatchConsumer::Single {
+                client,
+                consumer,
+                partition_id,
+            };
+            (Some(outcome), value)
+        }
+        BatchConsumer::Multiple(mut stream) => {
+            let mut batch = Vec::with_capacity(max_batch_size);
+            let deadline = crate::util::time::sleep(max_wait_time).fuse();
+            futures_util::pin_mut!(deadline);
+
+            // The event stream keeps the pending receive in its own state, so no event is lost
+            // when the deadline wins the race
+            while batch.len() < max_batch_size {
+                futures_util::select_biased! {
+                    item = stream.next_with_partition_id().fuse() => match item {
+                        Some(Ok(event)) => batch.push(event),
+                        Some(Err(err)) => return (Some(Err(err)), BatchConsumer::Multiple(stream)),
+                        None if batch.is_empty() => return (None, BatchConsumer::Multiple(stream)),
+                        None => break,
+                    },
+                    _ = deadline => break,
+                }
+            }
+            (Some(Ok(batch)), BatchConsumer::Multiple(stream))
+        }
+    }
+}
+
+async fn close_consumer<RP>(value: BatchConsumer<'_, RP>) -> Result<(), DisposeConsumerError>
+where
+    RP: Send + 'static,
+{
+    match value {
+        BatchConsumer::Single { consumer, .. } => consumer.close().await,
+        BatchConsumer::Multiple(stream) => stream.close().await,
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A stream of batches of events.
+    ///
+    /// Each batch holds up to `max_batch_size` events and is yielded as soon as it is full or once
+    /// `max_wait_time` has elapsed since the batch was started, in which case it may be empty.
+    ///
+    /// This is created by a `EventHubConsumerClient`. It takes the lifetime of the
+    /// `EventHubConsumerClient` that created it, and thus the stream must be closed/dropped before
+    /// the `EventHubConsumerClient` is dropped.
+    ///
+    /// # Generic Parameters:
+    ///
+    /// * `'a` - The lifetime of the `EventHubConsumerClient` that created this stream.
+    /// * `RP` - The retry policy to use for recovering from errors.
+    pub struct EventBatchStream<'a, RP> {
+        #[pin]
+        state: BatchStreamState<'a, RP>,
+        max_batch_size: usize,
+        max_wait_time: StdDuration,
+        last_enqueued_event_properties: LastEnqueuedEventPropertiesMap,
+        position_tracker: Option<ConsumerPositionTracker>,
+    }
+}
+
+impl<'a, RP> std::fmt::Debug for EventBatchStream<'a, RP> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBatchStream")
+            .field("max_batch_size", &self.max_batch_size)
+            .field("max_wait_time", &self.max_wait_time)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, RP> EventBatchStream<'a, RP> {
+    fn new(
+        value: BatchConsumer<'a, RP>,
+        max_batch_size: usize,
+        max_wait_time: StdDuration,
+    ) -> Self {
+        Self {
+            state: BatchStreamState::Value { value },
+            // At least one event is needed to make progress
+            max_batch_size: max_batch_size.max(1),
+            max_wait_time,
+            last_enqueued_event_properties: LastEnqueuedEventPropertiesMap::default(),
+            position_tracker: None,
+        }
+    }
+
+    pub(crate) fn with_consumer(
+        client: impl Into<StreamClient<'a>>,
+        consumer: TransportConsumerKind<RP>,
+        max_batch_size: usize,
+        max_wait_time: StdDuration,
+    ) -> Self {
+        let value = BatchConsumer::Single {
+            client: client.into(),
+            partition_id: consumer.partition_id(),
+            consumer,
+        };
+        Self::new(value, max_batch_size, max_wait_time)
+    }
+
+    pub(crate) fn with_event_stream(
+        stream: EventStream<'a, RP>,
+        max_batch_size: usize,
+        max_wait_time: StdDuration,
+    ) -> Self {
+        Self::new(
+            BatchConsumer::Multiple(stream),
+            max_batch_size,
+            max_wait_time,
+        )
+    }
+
+    /// The maximum number of events in a batch
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    /// The maximum time to wait for a batch to fill up
+    pub fn max_wait_time(&self) -> StdDuration {
+        self.max_wait_time
+    }
+
+    /// A set of information about the last event enqueued in the partition, as observed from the
+    /// events of the partition received by this stream. This is only available if the stream was
+    /// created with `track_last_enqueued_event_properties` set, and is empty until an event of
+    /// the partition is received.
+    pub fn read_last_enqueued_event_properties(
+        &self,
+        partition_id: &str,
+    ) -> LastEnqueuedEventProperties {
+        self.last_enqueued_event_properties.get(partition_id)
+    }
+
+    /// Records the position of every event that the stream yields in the tracker, whose clones
+    /// can then be given to `EventHubConsumerClient::get_consumer_lag`.
+    pub fn with_position_tracker(mut self, tracker: ConsumerPositionTracker) -> Self {
+        self.position_tracker = Some(tracker);
+        self
+    }
+}
+
+impl<'a, RP> EventBatchStream<'a, RP>
+where
+    RP: Send + 'static,
+{
+    /// Closes the [`EventBatchStream`].
+    pub async fn close(self) -> Result<(), DisposeConsumerError> {
+        self.state.close().await
+    }
+}
+
+impl<'a, RP> Stream for EventBatchStream<'a, RP>
+where
+    RP: EventHubsRetryPolicy + Send + Unpin + 'static,
+{
+    type Item = Result<Vec<ReceivedEventData>, azure_core::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if let Some(value) = this.state.as_mut().take_value() {
+            this.state.set(BatchStreamState::Future {
+                future: next_batch(value, *this.max_batch_size, *this.max_wait_time).boxed(),
+            });
+        }
+
+        let (item, next_state) = match this.state.as_mut().project_future() {
+            Some(fut) => ready!(fut.poll(cx)),
+            None => {
+                let result = match this.state.as_mut().project_closing() {
+                    Some(fut) => ready!(fut.poll(cx)),
+                    None => panic!("Stream must not be polled after completion"),
+                };
+
+                this.state.set(BatchStreamState::Empty);
+                match result {
+                    Ok(_) => return Poll::Ready(None),
+                    Err(err) => return Poll::Ready(Some(Err(err.into()))),
+                }
+            }
+        };
+
+        match item {
+            Some(item) => {
+                let item = item.map(|batch| {
+                    batch
+                        .into_iter()
+                        .map(|(partition_id, event)| {
+                            this.last_enqueued_event_properties
+                                .record(&partition_id, &event);
+                            if let Some(tracker) = this.position_tracker.as_ref() {
+                                tracker.record(partition_id.as_str(), &event);
+                            }
+                            event
+                        })
+                        .collect()
+                });
+                this.state
+                    .set(BatchStreamState::Value { value: next_state });
+                Poll::Ready(Some(item))
+            }
+            None => {
+                this.state.set(BatchStreamState::Closing {
+                    future: close_consumer(next_state).boxed(),
+                });
+                Poll::Ready(None)
+            }
+        }
+    }
+}
@@ -1,27 +1,67 @@
 use std::{
+    collections::HashMap,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll}, future::poll_fn,
 };
 
 use futures_util::{Future, Stream, FutureExt, ready};
 
-use crate::{EventHubsRetryPolicy, ReceivedEventData, consumer::EventPosition};
+use crate::{
+    consumer::{ConsumerPositionTracker, EventPosition, LastEnqueuedEventProperties},
+    EventHubsRetryPolicy, ReceivedEventData,
+};
+
+#[cfg(feature = "in-memory-transport")]
+use crate::in_memory::in_memory_consumer::{self, MultipleInMemoryConsumers};
 
 use self::single::EventStreamStateValue;
 
-use super::error::{DisposeConsumerError, RecoverAndReceiveError};
+use super::error::DisposeConsumerError;
 
+pub(crate) mod batch;
 pub(crate) mod multiple;
+pub(crate) mod partition_event_stream;
 pub(crate) mod single;
 
+pub use batch::EventBatchStream;
+pub use partition_event_stream::PartitionEventStream;
 pub(crate) use multiple::MultipleAmqpConsumers;
 pub(crate) use single::AmqpConsumer;
 
+/// An event together with the ID of the partition that it was received from
+pub(crate) type PartitionTaggedEvent = (Arc<String>, ReceivedEventData);
+
+/// The last enqueued event properties of each partition that a stream has received events from
+#[derive(Debug, Default)]
+pub(crate) struct LastEnqueuedEventPropertiesMap(HashMap<String, LastEnqueuedEventProperties>);
+
+impl LastEnqueuedEventPropertiesMap {
+    /// Records the properties carried by an event of the partition, if any
+    pub(crate) fn record(&mut self, partition_id: &str, event: &ReceivedEventData) {
+        let properties = LastEnqueuedEventProperties::from_event(event);
+        if properties.is_empty() {
+            return;
+        }
+        match self.0.get_mut(partition_id) {
+            Some(last) => *last = properties,
+            None => {
+                self.0.insert(partition_id.to_string(), properties);
+            }
+        }
+    }
+
+    /// The properties of the partition, which are empty if no event carried them
+    pub(crate) fn get(&self, partition_id: &str) -> LastEnqueuedEventProperties {
+        self.0.get(partition_id).copied().unwrap_or_default()
+    }
+}
+
 type StreamBoxedFuture<'a, C> = Pin<
     Box<
         dyn Future<
                 Output = (
-                    Option<Result<ReceivedEventData, RecoverAndReceiveError>>,
+                    Option<Result<PartitionTaggedEvent, azure_core::Error>>,
                     EventStreamStateValue<'a, C>,
                 ),
             > + Send
@@ -32,12 +72,16 @@ type ClosingBoxedFuture<'a> =
     Pin<Box<dyn Future<Output = Result<(), DisposeConsumerError>> + Send + 'a>>;
 
 enum Consumer<RP> {
-    Single(AmqpConsumer<RP>),
+    /// A consumer of a single partition, whose ID is kept alongside
+    Single(AmqpConsumer<RP>, Arc<String>),
     Multiple(MultipleAmqpConsumers<RP>),
+    #[cfg(feature = "in-memory-transport")]
+    InMemory(MultipleInMemoryConsumers<RP>),
 }
 
 #[derive(Debug)]
 pub(crate) struct ConsumerOptions {
+    pub(crate) event_hub_name: String,
     pub(crate) consumer_group: String,
     pub(crate) partition_id: String,
     pub(crate) identifier: Option<String>,
@@ -113,11 +157,13 @@ where
             });
         }
 
-        if let Some(future) = self.as_mut().project_future() {
-            let (_, value) = ready!(future.poll(cx));
-            self.set(EventStreamState::Closing {
-                future: close_consumer(value).boxed(),
-            });
+        if self.as_mut().project_future().is_some() {
+            // A receive that is still pending may never complete, so it is dropped together with
+            // the consumers instead of being awaited. Both Link and Session have internal states
+            // that implement the Drop trait and will exchange messages with the server to close
+            // the link/session.
+            self.set(EventStreamState::Empty);
+            return Poll::Ready(Ok(()));
         }
 
         let result = match self.as_mut().project_closing() {
@@ -136,36 +182,50 @@ where
     }
 }
 
+/// Receives the next event together with the ID of its partition
 async fn next_event<RP>(
     value: EventStreamStateValue<'_, Consumer<RP>>,
 ) -> (
-    Option<Result<ReceivedEventData, RecoverAndReceiveError>>,
+    Option<Result<PartitionTaggedEvent, azure_core::Error>>,
     EventStreamStateValue<'_, Consumer<RP>>,
 )
 where
     RP: EventHubsRetryPolicy + Send + Unpin + 'static,
 {
     let EventStreamStateValue {
-        client, consumer, ..
+        mut client, consumer, ..
     } = value;
 
     match consumer {
-        Consumer::Single(mut consumer) => {
-            let outcome = single::receive_event(client, &mut consumer).await;
+        Consumer::Single(mut consumer, partition_id) => {
+            let outcome = single::receive_event(client.as_amqp_mut(), &mut consumer)
+                .await
+                .map(|event| (partition_id.clone(), event));
             let value = EventStreamStateValue {
                 client,
-                consumer: Consumer::Single(consumer),
+                consumer: Consumer::Single(consumer, partition_id),
             };
-            (Some(outcome), value)
+            (Some(outcome.map_err(Into::into)), value)
         }
         Consumer::Multiple(mut consumer) => {
-            let outcome = multiple::receive_event(client, &mut consumer).await;
+            let outcome = multiple::receive_event(client.as_amqp_mut(), &mut consumer).await;
             let value = EventStreamStateValue {
                 client,
                 consumer: Consumer::Multiple(consumer),
             };
-            (outcome, value)
+            (outcome.map(|result| result.map_err(Into::into)), value)
         },
+        #[cfg(feature = "in-memory-transport")]
+        Consumer::InMemory(mut consumer) => {
+            let outcome =
+                in_memory_consumer::receive_event_from_any(client.as_in_memory_mut(), &mut consumer)
+                    .await;
+            let value = EventStreamStateValue {
+                client,
+                consumer: Consumer::InMemory(consumer),
+            };
+            (outcome.map(|result| result.map_err(Into::into)), value)
+        }
     }
 }
 
@@ -180,7 +240,7 @@ where
     } = value;
 
     match consumer {
-        Consumer::Single(consumer) => {
+        Consumer::Single(consumer, _) => {
             consumer.close().await
         }
         Consumer::Multiple(consumer) => {
@@ -192,6 +252,9 @@ where
                 .into_iter()
                 .fold(Ok(()), |acc, result| acc.and(result))
         },
+        // Nothing is attached for the in-memory consumers
+        #[cfg(feature = "in-memory-transport")]
+        Consumer::InMemory(_) => Ok(()),
     }
 }
 
@@ -202,6 +265,10 @@ pin_project_lite::pin_project! {
     /// `EventHubConsumerClient` that created it, and thus the stream must be closed/dropped before
     /// the `EventHubConsumerClient` is dropped.
     ///
+    /// A stream created by `EventHubConsumerClient::read_owned_events_from_partition` holds a
+    /// shared handle to the connection instead and is `'static`, so it can be moved into a
+    /// spawned task.
+    ///
     /// # Generic Parameters:
     ///
     /// * `'a` - The lifetime of the `EventHubConsumerClient` that created this stream.
@@ -209,6 +276,28 @@ pin_project_lite::pin_project! {
     pub struct EventStream<'a, RP> {
         #[pin]
         state: EventStreamState<'a, Consumer<RP>>,
+        last_enqueued_event_properties: LastEnqueuedEventPropertiesMap,
+        position_tracker: Option<ConsumerPositionTracker>,
+    }
+}
+
+impl<'a, RP> EventStream<'a, RP> {
+    /// A set of information about the last event enqueued in the partition, as observed from the
+    /// events of the partition received by this stream. This is only available if the stream was
+    /// created with `track_last_enqueued_event_properties` set, and is empty until an event of
+    /// the partition is received.
+    pub fn read_last_enqueued_event_properties(
+        &self,
+        partition_id: &str,
+    ) -> LastEnqueuedEventProperties {
+        self.last_enqueued_event_properties.get(partition_id)
+    }
+
+    /// Records the position of every event that the stream yields in the tracker, whose clones
+    /// can then be given to `EventHubConsumerClient::get_consumer_lag`.
+    pub fn with_position_tracker(mut self, tracker: ConsumerPositionTracker) -> Self {
+        self.position_tracker = Some(tracker);
+        self
     }
 }
 
@@ -222,14 +311,22 @@ where
     }
 }
 
-impl<'a, RP> Stream for EventStream<'a, RP>
+impl<'a, RP> EventStream<'a, RP>
 where
     RP: EventHubsRetryPolicy + Send + Unpin + 'static,
-    Consumer<RP>: Send + 'a,
 {
-    type Item = Result<ReceivedEventData, azure_core::Error>;
+    /// Receives the next event together with the ID of the partition that it was received from
+    pub(crate) async fn next_with_partition_id(
+        &mut self,
+    ) -> Option<Result<PartitionTaggedEvent, azure_core::Error>> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next_with_partition_id(cx)).await
+    }
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    /// Polls for the next event together with the ID of the partition that it was received from
+    pub(crate) fn poll_next_with_partition_id(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<PartitionTaggedEvent, azure_core::Error>>> {
         let mut this = self.project();
 
         if let Some(state) = this.state.as_mut().take_value() {
@@ -254,9 +351,16 @@ where
         };
 
         if let Some(item) = item {
+            if let Ok((partition_id, event)) = &item {
+                this.last_enqueued_event_properties
+                    .record(partition_id, event);
+                if let Some(tracker) = this.position_tracker.as_ref() {
+                    tracker.record(partition_id.as_str(), event);
+                }
+            }
             this.state
                 .set(EventStreamState::Value { value: next_state });
-            Poll::Ready(Some(item.map_err(Into::into)))
+            Poll::Ready(Some(item))
         } else {
             this.state.set(EventStreamState::Closing {
                 future: close_consumer(next_state).boxed(),
@@ -265,3 +369,16 @@ where
         }
     }
 }
+
+impl<'a, RP> Stream for EventStream<'a, RP>
+where
+    RP: EventHubsRetryPolicy + Send + Unpin + 'static,
+    Consumer<RP>: Send + 'a,
+{
+    type Item = Result<ReceivedEventData, azure_core::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_next_with_partition_id(cx)
+            .map(|item| item.map(|result| result.map(|(_, event)| event)))
+    }
+}
@@ -4,6 +4,7 @@ use std::{
     fmt::Debug,
     ops::DerefMut,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
@@ -14,15 +15,20 @@ use tokio_util::sync::CancellationToken;
 use crate::{
     amqp::{
         amqp_client::AmqpClient,
+        amqp_consumer::{LastEnqueuedEventPropertiesMap, PartitionTaggedEvent},
         error::{DisposeConsumerError, RecoverAndReceiveError},
     },
-    core::{RecoverableError, RecoverableTransport},
+    core::{RecoverableError, RecoverableTransport, TransportClient},
     event_hubs_retry_policy::EventHubsRetryPolicy,
+    transport_kind::{StreamClient, TransportClientKind, TransportConsumerKind},
     util::{self, time::timeout},
     ReceivedEventData,
 };
 
-use super::{AmqpConsumer, EventStream, EventStreamState, EventStreamStateValue, single::recover_consumer_by_creating_new_consumer};
+#[cfg(feature = "in-memory-transport")]
+use crate::in_memory::in_memory_consumer::MultipleInMemoryConsumers;
+
+use super::{AmqpConsumer, EventStream, EventStreamState, EventStreamStateValue};
 
 type ConsumerBoxedFuture<RP> =
     Pin<Box<dyn Future<Output = (Result<ReceivedEventData, RecvError>, AmqpConsumer<RP>)> + Send>>;
@@ -166,6 +172,11 @@ where
 #[derive(Debug)]
 pub(crate) struct MultipleAmqpConsumers<RP> {
     pub(crate) inner: Vec<ConsumerState<RP>>,
+    /// The partition of each consumer in `inner`
+    partition_ids: Vec<Arc<String>>,
+    /// The index of the consumer that is polled first, which rotates so that no partition is
+    /// starved
+    next: usize,
     retry_policy: RP,
 }
 
@@ -180,7 +191,7 @@ impl<'a, RP> Future for MultiAmqpConsumerRecv<'a, RP>
 where
     RP: Send + Unpin + 'static,
 {
-    type Output = Option<Result<ReceivedEventData, RecvError>>;
+    type Output = Option<Result<PartitionTaggedEvent, RecvError>>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
@@ -196,14 +207,18 @@ where
     fn poll_recv(
         mut self: Pin<&mut Self>,
         cx: &mut Context,
-    ) -> Poll<Option<Result<ReceivedEventData, RecvError>>> {
+    ) -> Poll<Option<Result<PartitionTaggedEvent, RecvError>>> {
         if self.inner.is_empty() {
             // Only return None if all consumers are dead
             return Poll::Ready(None);
         }
 
-        let item = self.inner.iter_mut().enumerate().find_map(|(i, consumer)| {
-            let pinned = Pin::new(consumer);
+        // Start from the consumer after the one that yielded last, so that a busy partition
+        // cannot starve the others
+        let count = self.inner.len();
+        let start = self.next % count;
+        let item = (0..count).map(|i| (start + i) % count).find_map(|i| {
+            let pinned = Pin::new(&mut self.inner[i]);
             match pinned.poll_recv_and_accept(cx) {
                 Poll::Ready(item) => Some((i, item)),
                 Poll::Pending => None,
@@ -211,10 +226,15 @@ where
         });
 
         match item {
-            Some((_, Some(item))) => Poll::Ready(Some(item)),
+            Some((i, Some(item))) => {
+                self.next = (i + 1) % count;
+                let partition_id = self.partition_ids[i].clone();
+                Poll::Ready(Some(item.map(|event| (partition_id, event))))
+            }
             Some((i, None)) => {
                 // Consumer is dead, remove it
                 self.inner.swap_remove(i);
+                self.partition_ids.swap_remove(i);
                 Poll::Pending
             }
             None => Poll::Pending,
@@ -226,36 +246,15 @@ where
     }
 }
 
-// async fn recover_consumers<RP>(
-//     client: &mut AmqpClient,
-//     consumers: &mut MultipleAmqpConsumers<RP>,
-// ) -> Result<(), RecoverAndReceiveError>
-// where
-//     RP: EventHubsRetryPolicy + Send,
-// {
-//     log::debug!("Recovering consumers");
-
-//     // Client should be already recovered
-//     let mut result = Ok(());
-
-//     for c in consumers.inner.iter_mut() {
-//         // Consumers that are polling would probably encounter an error
-//         // when they poll again, and it will enter another recovery
-//         if let ConsumerState::Value { value } = c {
-//             result = result.and(client.recover_consumer(value).await);
-//         }
-//     }
-
-//     result.map_err(Into::into)
-// }
-
-async fn recover_consumers_by_creating_new_consumers<RP>(
+async fn recover_consumers<RP>(
     client: &mut AmqpClient,
     consumers: &mut MultipleAmqpConsumers<RP>,
 ) -> Result<(), RecoverAndReceiveError>
 where
     RP: EventHubsRetryPolicy + Send,
 {
+    log::debug!("Recovering consumers");
+
     // Client should be already recovered
     let mut result = Ok(());
 
@@ -263,20 +262,19 @@ where
         // Consumers that are polling would probably encounter an error
         // when they poll again, and it will enter another recovery
         if let ConsumerState::Value { value } = c {
-            result = result.and(recover_consumer_by_creating_new_consumer(client, value).await);
+            result = result.and(client.recover_consumer(value).await);
         }
     }
 
     result.map_err(Into::into)
 }
 
-
 // There is a possibility that no child consumer exists, so a None is returned.
 async fn recover_and_recv<RP>(
     client: &mut AmqpClient,
     consumers: &mut MultipleAmqpConsumers<RP>,
     should_try_recover: bool,
-) -> Result<Option<ReceivedEventData>, RecoverAndReceiveError>
+) -> Result<Option<PartitionTaggedEvent>, RecoverAndReceiveError>
 where
     RP: EventHubsRetryPolicy + Send + Unpin + 'static,
 {
@@ -288,16 +286,17 @@ where
             }
         }
 
-        recover_consumers_by_creating_new_consumers(client, consumers).await?;
+        recover_consumers(client, consumers).await?;
     }
 
     consumers.recv().await.transpose().map_err(Into::into)
 }
 
+/// Receives the next event from any of the consumers, together with the ID of its partition
 pub(crate) async fn receive_event<RP>(
     client: &mut AmqpClient,
     consumers: &mut MultipleAmqpConsumers<RP>,
-) -> Option<Result<ReceivedEventData, RecoverAndReceiveError>>
+) -> Option<Result<PartitionTaggedEvent, RecoverAndReceiveError>>
 where
     RP: EventHubsRetryPolicy + Send + Unpin + 'static,
 {
@@ -348,24 +347,49 @@ where
     RP: Send + 'static,
 {
     pub(crate) fn with_multiple_consumers(
-        client: &'a mut AmqpClient,
-        consumers: Vec<AmqpConsumer<RP>>,
+        client: impl Into<StreamClient<'a>>,
+        consumers: Vec<TransportConsumerKind<RP>>,
         retry_policy: RP,
     ) -> Self {
         let cancel_source = CancellationToken::new();
         let _cancellation_token = cancel_source.child_token();
-        let consumers = consumers
-            .into_iter()
-            .map(|value| ConsumerState::Value { value })
-            .collect();
-        let consumers = MultipleAmqpConsumers {
-            inner: consumers,
-            retry_policy,
+
+        // All the consumers are created by the same client
+        let client = client.into();
+        let consumers = match &*client {
+            TransportClientKind::Amqp(_) => {
+                let (partition_ids, consumers) = consumers
+                    .into_iter()
+                    .map(|consumer| {
+                        let consumer = consumer.into_amqp();
+                        let partition_id =
+                            Arc::new(consumer.initial_options.partition_id.clone());
+                        (partition_id, ConsumerState::Value { value: consumer })
+                    })
+                    .unzip();
+                super::Consumer::Multiple(MultipleAmqpConsumers {
+                    inner: consumers,
+                    partition_ids,
+                    next: 0,
+                    retry_policy,
+                })
+            }
+            #[cfg(feature = "in-memory-transport")]
+            TransportClientKind::InMemory(_) => {
+                let consumers = consumers
+                    .into_iter()
+                    .map(TransportConsumerKind::into_in_memory)
+                    .collect();
+                super::Consumer::InMemory(MultipleInMemoryConsumers::new(consumers, retry_policy))
+            }
         };
-        let consumers = super::Consumer::Multiple(consumers);
         let value = EventStreamStateValue::new(client, consumers);
         let state = EventStreamState::Value { value };
 
-        Self { state }
+        Self {
+            state,
+            last_enqueued_event_properties: LastEnqueuedEventPropertiesMap::default(),
+            position_tracker: None,
+        }
     }
 }
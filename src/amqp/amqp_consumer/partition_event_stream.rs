@@ -0,0 +1,192 @@
+//! A stream of events that carry the context of their partition.
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_util::{ready, Stream};
+use tokio::sync::watch;
+
+use crate::{
+    amqp::error::DisposeConsumerError,
+    consumer::{
+        ConsumerPositionTracker, LastEnqueuedEventProperties, PartitionContext, PartitionEvent,
+    },
+    event_hubs_retry_policy::EventHubsRetryPolicy,
+    ReceivedEventData,
+};
+
+use super::EventStream;
+
+#[derive(Debug)]
+struct PartitionState {
+    context: PartitionContext,
+    last_received_event: watch::Sender<Option<ReceivedEventData>>,
+}
+
+pin_project_lite::pin_project! {
+    /// A stream of events read from multiple partitions.
+    ///
+    /// Each event is yielded as a [`PartitionEvent`], whose [`PartitionContext`] identifies the
+    /// partition that it was read from. The partitions are polled in a round robin fashion so that
+    /// a busy partition cannot starve the others.
+    ///
+    /// This is created by a `EventHubConsumerClient`. It takes the lifetime of the
+    /// `EventHubConsumerClient` that created it, and thus the stream must be closed/dropped before
+    /// the `EventHubConsumerClient` is dropped, unless it was created by
+    /// `EventHubConsumerClient::read_owned_events`, which makes a `'static` stream.
+    ///
+    /// # Generic Parameters:
+    ///
+    /// * `'a` - The lifetime of the `EventHubConsumerClient` that created this stream.
+    /// * `RP` - The retry policy to use for recovering from errors.
+    pub struct PartitionEventStream<'a, RP> {
+        #[pin]
+        inner: EventStream<'a, RP>,
+        fully_qualified_namespace: Arc<String>,
+        event_hub_name: Arc<String>,
+        consumer_group: Arc<String>,
+        track_last_enqueued_event_properties: bool,
+        partitions: HashMap<Arc<String>, PartitionState>,
+    }
+}
+
+impl<'a, RP> std::fmt::Debug for PartitionEventStream<'a, RP> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PartitionEventStream")
+            .field("fully_qualified_namespace", &self.fully_qualified_namespace)
+            .field("event_hub_name", &self.event_hub_name)
+            .field("consumer_group", &self.consumer_group)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, RP> PartitionEventStream<'a, RP> {
+    pub(crate) fn new(
+        inner: EventStream<'a, RP>,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+        track_last_enqueued_event_properties: bool,
+    ) -> Self {
+        Self {
+            inner,
+            fully_qualified_namespace: Arc::new(fully_qualified_namespace.to_string()),
+            event_hub_name: Arc::new(event_hub_name.to_string()),
+            consumer_group: Arc::new(consumer_group.to_string()),
+            track_last_enqueued_event_properties,
+            partitions: HashMap::new(),
+        }
+    }
+
+    /// A set of information about the last event enqueued in the partition, as observed from the
+    /// events of the partition received by this stream. This is only available if the stream was
+    /// created with `track_last_enqueued_event_properties` set.
+    ///
+    /// The same properties can be read from the [`PartitionContext`] of the events.
+    pub fn read_last_enqueued_event_properties(
+        &self,
+        partition_id: &str,
+    ) -> LastEnqueuedEventProperties {
+        self.inner.read_last_enqueued_event_properties(partition_id)
+    }
+
+    /// Records the position of every event that the stream yields in the tracker, whose clones
+    /// can then be given to `EventHubConsumerClient::get_consumer_lag`.
+    pub fn with_position_tracker(self, tracker: ConsumerPositionTracker) -> Self {
+        Self {
+            inner: self.inner.with_position_tracker(tracker),
+            ..self
+        }
+    }
+}
+
+impl<'a, RP> PartitionEventStream<'a, RP>
+where
+    RP: Send + 'static,
+{
+    /// Closes the [`PartitionEventStream`].
+    pub async fn close(self) -> Result<(), DisposeConsumerError> {
+        self.inner.close().await
+    }
+}
+
+impl<'a, RP> Stream for PartitionEventStream<'a, RP>
+where
+    RP: EventHubsRetryPolicy + Send + Unpin + 'static,
+{
+    type Item = Result<PartitionEvent, azure_core::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let (partition_id, event) = match ready!(this.inner.poll_next_with_partition_id(cx)) {
+            Some(Ok(item)) => item,
+            Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+            None => return Poll::Ready(None),
+        };
+
+        let partition = this
+            .partitions
+            .entry(partition_id.clone())
+            .or_insert_with(|| {
+                let (sender, receiver) = watch::channel(None);
+                let context = PartitionContext::new(
+                    this.fully_qualified_namespace.clone(),
+                    this.event_hub_name.clone(),
+                    this.consumer_group.clone(),
+                    partition_id,
+                    receiver,
+                );
+                PartitionState {
+                    context,
+                    last_received_event: sender,
+                }
+            });
+        if *this.track_last_enqueued_event_properties {
+            partition
+                .last_received_event
+                .send_replace(Some(event.clone()));
+        }
+
+        let event = PartitionEvent::new(partition.context.clone(), event.into_raw_amqp_message());
+        Poll::Ready(Some(Ok(event)))
+    }
+}
+
+#[cfg(all(test, feature = "in-memory-transport"))]
+mod tests {
+    use futures_util::StreamExt;
+
+    use crate::{consumer::EventHubConsumerClient, in_memory::fixture::TestEventHub};
+
+    #[tokio::test]
+    async fn busy_partition_does_not_starve_others() {
+        let mut test_hub = TestEventHub::new(3);
+        let mut consumer = test_hub.consumer();
+        test_hub.publish("0", &["0"; 10]).await;
+        for partition_id in ["1", "2"] {
+            test_hub.publish(partition_id, &[partition_id]).await;
+        }
+
+        let mut stream = consumer.read_events(true, Default::default()).await.unwrap();
+        let mut partition_ids = Vec::new();
+        for _ in 0..3 {
+            let event = stream.next().await.unwrap().unwrap();
+            let context = event.partition_context();
+            assert_eq!(context.event_hub_name(), TestEventHub::NAME);
+            assert_eq!(
+                context.consumer_group(),
+                EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME
+            );
+            assert_eq!(context.partition_id().as_bytes(), event.body().unwrap());
+            partition_ids.push(context.partition_id().to_string());
+        }
+        partition_ids.sort();
+        assert_eq!(partition_ids, ["0", "1", "2"]);
+
+        stream.close().await.unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::VecDeque, time::Duration as StdDuration};
+use std::{collections::VecDeque, sync::Arc, time::Duration as StdDuration};
 
 use fe2o3_amqp::{link::RecvError, session::SessionHandle, Receiver};
 use futures_util::FutureExt;
@@ -8,17 +8,23 @@ use crate::{
     consumer::EventPosition,
     core::{RecoverableError, RecoverableTransport, TransportClient},
     event_hubs_retry_policy::EventHubsRetryPolicy,
+    transport_kind::{StreamClient, TransportConsumerKind},
     util::{self, time::timeout},
     ReceivedEventData,
 };
 
+#[cfg(feature = "in-memory-transport")]
+use crate::in_memory::in_memory_consumer::MultipleInMemoryConsumers;
+
 use crate::amqp::{
     amqp_cbs_link::Command,
     amqp_client::AmqpClient,
     error::{DisposeConsumerError, RecoverAndReceiveError},
 };
 
-use super::{Consumer, ConsumerOptions, EventStream, EventStreamState};
+use super::{
+    Consumer, ConsumerOptions, EventStream, EventStreamState, LastEnqueuedEventPropertiesMap,
+};
 
 #[derive(Debug)]
 pub struct AmqpConsumer<RP> {
@@ -26,8 +32,6 @@ pub struct AmqpConsumer<RP> {
     pub(crate) _session_identifier: u32,
     pub(crate) receiver: Receiver,
     pub(crate) link_identifier: u32,
-    pub(crate) track_last_enqueued_event_properties: bool,
-    pub(crate) last_received_event: Option<ReceivedEventData>,
     pub(crate) current_event_position: Option<EventPosition>,
     pub(crate) retry_policy: RP,
     pub(crate) prefetch_count: u32,
@@ -65,13 +69,17 @@ impl<RP> AmqpConsumer<RP> {
             self.current_event_position = Some(EventPosition::from_offset(event_offset, false));
         }
 
-        if self.track_last_enqueued_event_properties {
-            self.last_received_event = Some(event.clone());
-        }
-
         Ok(event)
     }
 
+    /// The position to resume receiving from when the link is re-opened.
+    pub(crate) fn resume_event_position(&self) -> EventPosition {
+        resume_event_position(
+            self.current_event_position.as_ref(),
+            &self.initial_options.event_position,
+        )
+    }
+
     #[inline]
     async fn fill_buf(
         &mut self,
@@ -119,6 +127,26 @@ impl<RP> AmqpConsumer<RP> {
     }
 }
 
+/// The current event position is only empty when the consumer has not received any event yet, in
+/// which case the initial event position is used. Otherwise the position is made exclusive so that
+/// the last received event is not delivered again.
+pub(crate) fn resume_event_position(
+    current_event_position: Option<&EventPosition>,
+    initial_event_position: &EventPosition,
+) -> EventPosition {
+    match current_event_position.cloned() {
+        Some(mut position) => {
+            match &mut position {
+                EventPosition::Offset { is_inclusive, .. } => *is_inclusive = false,
+                EventPosition::SequenceNumber { is_inclusive, .. } => *is_inclusive = false,
+                EventPosition::EnqueuedTime(_) => {}
+            }
+            position
+        }
+        None => initial_event_position.clone(),
+    }
+}
+
 async fn recover_and_recv_batch<RP>(
     client: &mut AmqpClient,
     consumer: &mut AmqpConsumer<RP>,
@@ -137,17 +165,12 @@ where
             }
         }
 
-        recover_consumer_by_creating_new_consumer(client, consumer).await?;
+        client.recover_consumer(consumer).await?;
     }
 
     consumer
         .fill_buf_with_timeout(buffer, max_wait_time)
         .await?;
-    if consumer.track_last_enqueued_event_properties {
-        if let Some(event) = buffer.back().cloned() {
-            consumer.last_received_event = Some(event);
-        }
-    }
     Ok(())
 }
 
@@ -167,60 +190,12 @@ where
             }
         }
 
-        recover_consumer_by_creating_new_consumer(client, consumer).await?;
+        client.recover_consumer(consumer).await?;
     }
 
     consumer.recv_and_accept().await.map_err(Into::into)
 }
 
-pub(crate) async fn recover_consumer_by_creating_new_consumer(
-    client: &mut AmqpClient,
-    consumer: &mut AmqpConsumer<impl EventHubsRetryPolicy + Send>,
-) -> Result<(), RecoverAndReceiveError> {
-    log::debug!("Recovering consumer by creating new consumer");
-
-    let consumer_group = &consumer.initial_options.consumer_group;
-    let partition_id = &consumer.initial_options.partition_id;
-
-    // The current_event_position is only empty when the consumer is created for the first time
-    // and has not received any events yet. In this case, we should use the initial event position
-    let event_position = match consumer.current_event_position.clone() {
-        Some(mut p) => {
-            match &mut p {
-                EventPosition::Offset { is_inclusive, .. } => *is_inclusive = false,
-                EventPosition::SequenceNumber { is_inclusive, .. } => *is_inclusive = false,
-                EventPosition::EnqueuedTime(_) => {}
-            }
-            p
-        },
-        None => consumer.initial_options.event_position.clone(),
-    };
-    let prefetch_count = consumer.initial_options.prefetch_count;
-    let owner_level = consumer.initial_options.owner_level;
-    let track_last_enqueued_event_properties =
-        consumer.initial_options.track_last_enqueued_event_properties;
-    let identifier = consumer.initial_options.identifier.clone();
-    let retry_policy = consumer.retry_policy.clone();
-
-    let new_consumer = client
-        .create_consumer(
-            consumer_group,
-            partition_id,
-            identifier,
-            &event_position,
-            retry_policy,
-            track_last_enqueued_event_properties,
-            owner_level,
-            Some(prefetch_count),
-        )
-        .await?;
-
-    let old_consumer = std::mem::replace(consumer, new_consumer);
-    let _ = old_consumer.close().await;
-
-    Ok(())
-}
-
 pub(crate) async fn receive_event_batch<RP>(
     client: &mut AmqpClient,
     consumer: &mut AmqpConsumer<RP>,
@@ -322,26 +297,116 @@ where
 }
 
 pub(crate) struct EventStreamStateValue<'a, C> {
-    pub(crate) client: &'a mut AmqpClient,
+    pub(crate) client: StreamClient<'a>,
     pub(crate) consumer: C,
 }
 
 impl<'a, C> EventStreamStateValue<'a, C> {
-    pub(crate) fn new(client: &'a mut AmqpClient, consumer: C) -> Self {
+    pub(crate) fn new(client: StreamClient<'a>, consumer: C) -> Self {
         Self { client, consumer }
     }
 }
 
 impl<'a, RP> EventStream<'a, RP>
 where
-    RP: Send + 'a,
+    RP: EventHubsRetryPolicy + Send + 'a,
     AmqpConsumer<RP>: Send + 'a,
 {
-    pub(crate) fn with_consumer(client: &'a mut AmqpClient, consumer: AmqpConsumer<RP>) -> Self {
-        let consumer = Consumer::Single(consumer);
-        let value = EventStreamStateValue::new(client, consumer);
+    pub(crate) fn with_consumer(
+        client: impl Into<StreamClient<'a>>,
+        consumer: TransportConsumerKind<RP>,
+    ) -> Self {
+        let consumer = match consumer {
+            TransportConsumerKind::Amqp(consumer) => {
+                let partition_id = Arc::new(consumer.initial_options.partition_id.clone());
+                Consumer::Single(consumer, partition_id)
+            }
+            #[cfg(feature = "in-memory-transport")]
+            TransportConsumerKind::InMemory(consumer) => {
+                let retry_policy = consumer.retry_policy.clone();
+                Consumer::InMemory(MultipleInMemoryConsumers::new(vec![consumer], retry_policy))
+            }
+        };
+        let value = EventStreamStateValue::new(client.into(), consumer);
         let state = EventStreamState::Value { value };
 
-        Self { state }
+        Self {
+            state,
+            last_enqueued_event_properties: LastEnqueuedEventPropertiesMap::default(),
+            position_tracker: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "in-memory-transport")]
+    use futures_util::StreamExt;
+    use time::macros::datetime;
+
+    use crate::consumer::EventPosition;
+    #[cfg(feature = "in-memory-transport")]
+    use crate::{in_memory::fixture::TestEventHub, producer::SendEventOptions};
+
+    use super::resume_event_position;
+
+    #[test]
+    fn resume_from_initial_position_if_no_event_is_received() {
+        let initial = EventPosition::from_offset(100, true);
+        let position = resume_event_position(None, &initial);
+        assert_eq!(position, initial);
+    }
+
+    #[test]
+    fn resume_after_last_received_event() {
+        let initial = EventPosition::earliest();
+
+        let current = EventPosition::from_offset(100, true);
+        let position = resume_event_position(Some(&current), &initial);
+        assert_eq!(position, EventPosition::from_offset(100, false));
+
+        let current = EventPosition::from_sequence_number(7, true);
+        let position = resume_event_position(Some(&current), &initial);
+        assert_eq!(position, EventPosition::from_sequence_number(7, false));
+    }
+
+    #[test]
+    fn resume_from_enqueued_time_is_unchanged() {
+        let initial = EventPosition::earliest();
+        let current = EventPosition::from_enqueued_time(datetime!(2023-01-01 0:00 UTC));
+        let position = resume_event_position(Some(&current), &initial);
+        assert_eq!(position, current);
+    }
+
+    #[cfg(feature = "in-memory-transport")]
+    #[tokio::test]
+    async fn consumer_resumes_without_duplicates_after_links_are_detached() {
+        let mut test_hub = TestEventHub::new(1);
+        let mut producer = test_hub.producer();
+        let mut consumer = test_hub.consumer();
+
+        let options = SendEventOptions::new().with_partition_id("0");
+        for body in ["a", "b", "c"] {
+            producer.send_event(body, options.clone()).await.unwrap();
+        }
+
+        let mut stream = consumer
+            .read_events_from_partition("0", EventPosition::earliest(), Default::default())
+            .await
+            .unwrap();
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!(event.body().unwrap(), b"a");
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!(event.body().unwrap(), b"b");
+
+        test_hub.event_hub.detach_links();
+
+        // Both the producer and the consumer recover their links
+        producer.send_event("d", options).await.unwrap();
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!(event.body().unwrap(), b"c");
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!(event.body().unwrap(), b"d");
+        assert_eq!(test_hub.event_hub.events("0").len(), 4);
     }
 }
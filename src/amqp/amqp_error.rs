@@ -0,0 +1,134 @@
+//! Error conditions returned by the Event Hubs service and their classification
+
+use fe2o3_amqp_types::definitions::{
+    AmqpError, ConnectionError, ErrorCondition, LinkError, SessionError,
+};
+
+/// Indicates that the service is busy and the client should back off
+pub(crate) const SERVER_BUSY_ERROR: &str = "com.microsoft:server-busy";
+
+/// Indicates that the operation timed out on the service
+pub(crate) const TIMEOUT_ERROR: &str = "com.microsoft:timeout";
+
+/// Indicates that an argument provided to the service was invalid
+pub(crate) const ARGUMENT_ERROR: &str = "com.microsoft:argument-error";
+
+/// Indicates that an argument provided to the service was out of range
+pub(crate) const ARGUMENT_OUT_OF_RANGE_ERROR: &str = "com.microsoft:argument-out-of-range";
+
+/// Indicates that the entity is disabled
+pub(crate) const ENTITY_DISABLED_ERROR: &str = "com.microsoft:entity-disabled";
+
+/// Indicates that the partition is owned by another receiver with a higher owner level
+pub(crate) const PARTITION_NOT_OWNED_ERROR: &str = "com.microsoft:partition-not-owned";
+
+/// Indicates that the lock on the store was lost
+pub(crate) const STORE_LOCK_LOST_ERROR: &str = "com.microsoft:store-lock-lost";
+
+/// Indicates that the publisher was revoked
+pub(crate) const PUBLISHER_REVOKED_ERROR: &str = "com.microsoft:publisher-revoked";
+
+/// Indicates that the producer was disconnected by another producer with a higher owner level
+pub(crate) const PRODUCER_EPOCH_STOLEN_ERROR: &str = "com.microsoft:producer-epoch-stolen";
+
+/// Indicates that the idempotent sequence number is out of order
+pub(crate) const SEQUENCE_OUT_OF_ORDER_ERROR: &str = "com.microsoft:out-of-order-sequence";
+
+/// Returns true if the service may succeed when the operation that failed with the given condition
+/// is retried.
+///
+/// Conditions that indicate a problem with the request itself (eg. unauthorized access, an entity
+/// that does not exist or an invalid argument) are terminal. Unknown conditions are considered
+/// transient.
+pub(crate) fn is_transient_condition(condition: &ErrorCondition) -> bool {
+    match condition {
+        ErrorCondition::AmqpError(error) => match error {
+            AmqpError::InternalError | AmqpError::IllegalState | AmqpError::ResourceLocked => true,
+            AmqpError::NotFound
+            | AmqpError::UnauthorizedAccess
+            | AmqpError::DecodeError
+            | AmqpError::ResourceLimitExceeded
+            | AmqpError::NotAllowed
+            | AmqpError::InvalidField
+            | AmqpError::NotImplemented
+            | AmqpError::PreconditionFailed
+            | AmqpError::ResourceDeleted
+            | AmqpError::FrameSizeTooSmall => false,
+        },
+        ErrorCondition::ConnectionError(error) => match error {
+            ConnectionError::ConnectionForced
+            | ConnectionError::FramingError
+            | ConnectionError::Redirect => true,
+        },
+        ErrorCondition::SessionError(error) => match error {
+            SessionError::WindowViolation
+            | SessionError::ErrantLink
+            | SessionError::HandleInUse
+            | SessionError::UnattachedHandle => true,
+        },
+        ErrorCondition::LinkError(error) => match error {
+            LinkError::DetachForced | LinkError::TransferLimitExceeded | LinkError::Redirect => {
+                true
+            }
+            // The link is stolen by another consumer or producer with a higher owner level
+            LinkError::MessageSizeExceeded | LinkError::Stolen => false,
+        },
+        ErrorCondition::Custom(symbol) => match symbol.as_str() {
+            SERVER_BUSY_ERROR | TIMEOUT_ERROR | STORE_LOCK_LOST_ERROR => true,
+            ARGUMENT_ERROR
+            | ARGUMENT_OUT_OF_RANGE_ERROR
+            | ENTITY_DISABLED_ERROR
+            | PARTITION_NOT_OWNED_ERROR
+            | PUBLISHER_REVOKED_ERROR
+            | PRODUCER_EPOCH_STOLEN_ERROR
+            | SEQUENCE_OUT_OF_ORDER_ERROR => false,
+            _ => true,
+        },
+        // Transaction errors are only possible if the `transaction` feature of `fe2o3-amqp` is
+        // enabled by another crate
+        #[allow(unreachable_patterns)]
+        _ => true,
+    }
+}
+
+/// Returns true if the service reported that it is busy and the client should throttle its requests
+pub(crate) fn is_server_busy_condition(condition: &ErrorCondition) -> bool {
+    matches!(condition, ErrorCondition::Custom(symbol) if symbol.as_str() == SERVER_BUSY_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        definitions::{AmqpError, ErrorCondition, LinkError},
+        primitives::Symbol,
+    };
+
+    use super::{is_transient_condition, ENTITY_DISABLED_ERROR, SERVER_BUSY_ERROR, TIMEOUT_ERROR};
+
+    #[test]
+    fn terminal_conditions_are_not_transient() {
+        let conditions = [
+            ErrorCondition::AmqpError(AmqpError::UnauthorizedAccess),
+            ErrorCondition::AmqpError(AmqpError::NotFound),
+            ErrorCondition::LinkError(LinkError::Stolen),
+            ErrorCondition::Custom(Symbol::from(ENTITY_DISABLED_ERROR)),
+        ];
+        for condition in conditions.iter() {
+            assert!(!is_transient_condition(condition), "{:?}", condition);
+        }
+    }
+
+    #[test]
+    fn transient_conditions_are_transient() {
+        let conditions = [
+            ErrorCondition::AmqpError(AmqpError::InternalError),
+            ErrorCondition::LinkError(LinkError::DetachForced),
+            ErrorCondition::Custom(Symbol::from(SERVER_BUSY_ERROR)),
+            ErrorCondition::Custom(Symbol::from(TIMEOUT_ERROR)),
+            ErrorCondition::Custom(Symbol::from("com.example:unknown")),
+        ];
+        for condition in conditions.iter() {
+            assert!(is_transient_condition(condition), "{:?}", condition);
+        }
+    }
+}
@@ -1,11 +1,18 @@
-use fe2o3_amqp_types::messaging::{Batch, Data, Message};
+use fe2o3_amqp_types::{
+    messaging::{Batch, Body, Data, Message},
+    primitives::Value,
+};
 
-use crate::{core::TransportEventBatch, EventData};
+use crate::{
+    core::TransportEventBatch,
+    producer::{CreateBatchOptions, MINIMUM_BATCH_SIZE_LIMIT_IN_BYTES},
+    EventData,
+};
 
 use super::{
-    amqp_message_converter::create_empty_phantom_envelope,
+    amqp_message_converter::{create_empty_phantom_envelope, reserve_idempotent_annotations_in},
     amqp_phantom_message::{Phantom, PhantomMessage},
-    error::TryAddError,
+    error::{CreateBatchError, TryAddError},
 };
 
 /// A set of [`EventData`] with size constraints known up-front.
@@ -15,30 +22,63 @@ pub struct AmqpEventBatch {
     /// The maximum size of the batch, in bytes.
     pub(crate) max_size_in_bytes: u64,
     /// The list of events that will be sent as a batch.
-    pub(crate) events: Vec<Message<Data>>,
+    pub(crate) events: Vec<Message<Body<Value>>>,
 
     pub(crate) phantom_envelope: PhantomMessage<Batch<Data>>,
+
+    /// Whether space is reserved for the annotations that are stamped on each event when
+    /// publishing with idempotent publishing enabled.
+    pub(crate) reserve_idempotent_annotations: bool,
 }
 
 impl AmqpEventBatch {
     pub(crate) fn new(
         max_size_in_bytes: u64,
         partition_key: Option<String>,
+        reserve_idempotent_annotations: bool,
     ) -> Result<Self, serde_amqp::Error> {
-        let phantom_envelope = create_empty_phantom_envelope(partition_key)?;
+        let phantom_envelope =
+            create_empty_phantom_envelope(partition_key, reserve_idempotent_annotations)?;
 
         Ok(Self {
             max_size_in_bytes,
             events: Vec::new(),
             phantom_envelope,
+            reserve_idempotent_annotations,
         })
     }
+
+    /// Creates a batch that is limited to the requested size, which must fit in the maximum
+    /// message size of the link that the batch is sent on
+    pub(crate) fn with_link_max_message_size(
+        options: CreateBatchOptions,
+        link_max_message_size: u64,
+        reserve_idempotent_annotations: bool,
+    ) -> Result<Self, CreateBatchError> {
+        let max_size_in_bytes: u64 = match options.max_size_in_bytes {
+            Some(max_size_in_bytes) => {
+                if max_size_in_bytes < MINIMUM_BATCH_SIZE_LIMIT_IN_BYTES
+                    || max_size_in_bytes > link_max_message_size
+                {
+                    return Err(CreateBatchError::RequestedSizeOutOfRange);
+                }
+
+                max_size_in_bytes
+            }
+            None => link_max_message_size,
+        };
+        Ok(Self::new(
+            max_size_in_bytes,
+            options.partition_key,
+            reserve_idempotent_annotations,
+        )?)
+    }
 }
 
 impl TransportEventBatch for AmqpEventBatch {
     type TryAddError = TryAddError;
 
-    type Iter<'a> = std::slice::Iter<'a, Message<Data>>;
+    type Iter<'a> = std::slice::Iter<'a, Message<Body<Value>>>;
 
     fn max_size_in_bytes(&self) -> u64 {
         self.max_size_in_bytes
@@ -57,8 +97,20 @@ impl TransportEventBatch for AmqpEventBatch {
         self.events.is_empty()
     }
 
-    fn try_add(&mut self, event: EventData) -> Result<(), Self::TryAddError> {
-        let phantom_event = match Phantom::try_from(&event.amqp_message) {
+    fn try_add(&mut self, mut event: EventData) -> Result<(), Self::TryAddError> {
+        // The placeholders only account for the size of the annotations that are stamped when the
+        // batch is sent, so they are removed from the event once it is measured
+        let original_annotations = self.reserve_idempotent_annotations.then(|| {
+            let original = event.amqp_message.message.message_annotations.clone();
+            reserve_idempotent_annotations_in(&mut event.amqp_message.message.message_annotations);
+            original
+        });
+        let phantom_event = Phantom::try_from(&event.amqp_message.message);
+        if let Some(original) = original_annotations {
+            event.amqp_message.message.message_annotations = original;
+        }
+
+        let phantom_event = match phantom_event {
             Ok(phantom_event) => phantom_event,
             Err(err) => return Err(TryAddError::Codec { source: err, event }),
         };
@@ -69,7 +121,7 @@ impl TransportEventBatch for AmqpEventBatch {
             self.phantom_envelope.body.pop(phantom_event);
             Err(Self::TryAddError::BatchFull(event))
         } else {
-            self.events.push(event.amqp_message);
+            self.events.push(event.amqp_message.message);
             Ok(())
         }
     }
@@ -104,7 +156,7 @@ mod tests {
     #[test]
     fn new_sets_max_size_in_bytes() {
         let options = CreateBatchOptions::default();
-        let batch = AmqpEventBatch::new(1024, options.partition_key).unwrap();
+        let batch = AmqpEventBatch::new(1024, options.partition_key, false).unwrap();
         assert_eq!(batch.max_size_in_bytes, 1024);
     }
 
@@ -112,10 +164,10 @@ mod tests {
     fn try_add_sets_batch_size_in_bytes() {
         let options = CreateBatchOptions::default();
         let overhead = OVERHEAD_BYTES_SMALL_MESSAGE; // The events added are small
-        let mut batch = AmqpEventBatch::new(1024, options.partition_key).unwrap();
+        let mut batch = AmqpEventBatch::new(1024, options.partition_key, false).unwrap();
         let message = EventData::new("hello world");
 
-        let serializable = Serializable(&message.amqp_message);
+        let serializable = Serializable(&message.amqp_message.message);
         let size = serialized_size(&serializable).unwrap();
 
         assert!(size * 2 < 1024);
@@ -130,7 +182,7 @@ mod tests {
     #[test]
     fn serialized_size_matches() {
         let options = CreateBatchOptions::default();
-        let mut batch = AmqpEventBatch::new(262144, options.partition_key).unwrap();
+        let mut batch = AmqpEventBatch::new(262144, options.partition_key, false).unwrap();
         let event = "abcdefg";
 
         while batch.try_add(EventData::from(event)).is_ok() {}
@@ -168,13 +220,66 @@ mod tests {
         assert_eq!(ssize as u64, batch_size_in_bytes);
     }
 
+    #[test]
+    fn serialized_size_matches_with_value_and_sequence_bodies() {
+        use crate::AmqpAnnotatedMessage;
+
+        let mut batch = AmqpEventBatch::new(262144, None, false).unwrap();
+        let mut with_footer = AmqpAnnotatedMessage::from_data("data");
+        with_footer
+            .footer_mut()
+            .get_or_insert(Default::default())
+            .insert("x-opt-footer".into(), "footer".into());
+        let events = [
+            AmqpAnnotatedMessage::from_value(Value::Int(42)),
+            AmqpAnnotatedMessage::from_sequence(vec![Value::Bool(true), Value::from("item")]),
+            with_footer,
+        ];
+        for event in events {
+            batch.try_add(EventData::from(event)).unwrap();
+        }
+        let batch_size_in_bytes = batch.size_in_bytes();
+
+        let envelope = build_amqp_batch_from_messages(batch.events.into_iter(), None).unwrap();
+        let ssize = match envelope.sendable {
+            SendableEnvelope::Batch(sendable) => {
+                serialized_size(&Serializable(sendable.message)).unwrap()
+            }
+            SendableEnvelope::Single(_) => unreachable!(),
+        };
+        assert_eq!(ssize as u64, batch_size_in_bytes);
+    }
+
+    #[test]
+    fn serialized_size_matches_with_application_properties() {
+        let mut batch = AmqpEventBatch::new(262144, None, false).unwrap();
+        let mut event = EventData::from("abcdefg");
+        event.insert_property("trace-id", "00-0af7651916cd43dd8448eb211c80319c");
+        event.insert_property("count", 1u64);
+        event.insert_property("removed", true);
+        event.remove_property("removed");
+        *event.property_mut("count").unwrap() = u64::MAX.into();
+        batch.try_add(event.clone()).unwrap();
+        batch.try_add(event).unwrap();
+        let batch_size_in_bytes = batch.size_in_bytes();
+
+        let envelope = build_amqp_batch_from_messages(batch.events.into_iter(), None).unwrap();
+        let ssize = match envelope.sendable {
+            SendableEnvelope::Batch(sendable) => {
+                serialized_size(&Serializable(sendable.message)).unwrap()
+            }
+            SendableEnvelope::Single(_) => unreachable!(),
+        };
+        assert_eq!(ssize as u64, batch_size_in_bytes);
+    }
+
     #[test]
     fn try_add_accepts_message_smaller_than_max_size() {
-        let mut batch = AmqpEventBatch::new(1024, None).unwrap();
+        let mut batch = AmqpEventBatch::new(1024, None, false).unwrap();
         let message = EventData::new("hello world");
 
         // Make sure the message is smaller than the max size
-        let serializable = Serializable(message.amqp_message.clone());
+        let serializable = Serializable(message.amqp_message.message.clone());
         let message_size = serialized_size(&serializable).unwrap();
         assert!(message_size < 1024);
 
@@ -183,11 +288,11 @@ mod tests {
 
     #[test]
     fn try_add_does_not_accept_message_larger_than_max_size() {
-        let mut batch = AmqpEventBatch::new(1024, None).unwrap();
+        let mut batch = AmqpEventBatch::new(1024, None, false).unwrap();
         let message = EventData::new(vec![0u8; 1025]);
 
         // Make sure the message is larger than the max size
-        let serializable = Serializable(message.amqp_message.clone());
+        let serializable = Serializable(message.amqp_message.message.clone());
         let message_size = serialized_size(&serializable).unwrap();
         assert!(message_size > 1024);
 
@@ -199,7 +304,7 @@ mod tests {
         let max_size_in_bytes = 1024;
         let data = "abcdefg";
         let event = EventData::from(data);
-        let mut batch = AmqpEventBatch::new(max_size_in_bytes, None).unwrap();
+        let mut batch = AmqpEventBatch::new(max_size_in_bytes, None, false).unwrap();
 
         while batch.try_add(event.clone()).is_ok() {}
 
@@ -208,7 +313,7 @@ mod tests {
 
     #[test]
     fn iter_returns_iterator_over_added_messages() {
-        let mut batch = AmqpEventBatch::new(1024, None).unwrap();
+        let mut batch = AmqpEventBatch::new(1024, None, false).unwrap();
 
         let events: Vec<_> = (0..5)
             .map(|i| EventData::new(format!("message {}", i)))
@@ -219,13 +324,13 @@ mod tests {
 
         let iter = batch.iter();
         for (original, added) in events.into_iter().zip(iter) {
-            assert_eq!(original.amqp_message, *added);
+            assert_eq!(original.amqp_message.message, *added);
         }
     }
 
     #[test]
     fn clear_resets_batch_len_and_size_in_bytes() {
-        let mut batch = AmqpEventBatch::new(1024, None).unwrap();
+        let mut batch = AmqpEventBatch::new(1024, None, false).unwrap();
         let message = EventData::new("hello world");
 
         assert!(batch.try_add(message).is_ok());
@@ -236,4 +341,51 @@ mod tests {
         assert_eq!(batch.len(), 0);
         assert_eq!(batch.size_in_bytes(), 0);
     }
+
+    #[test]
+    fn size_reserved_for_idempotent_annotations_covers_stamped_batch() {
+        use crate::amqp::amqp_message_converter::stamp_idempotent_annotations;
+
+        let mut batch = AmqpEventBatch::new(262144, None, true).unwrap();
+        let without_reservation = {
+            let mut batch = AmqpEventBatch::new(262144, None, false).unwrap();
+            batch.try_add(EventData::from("abcdefg")).unwrap();
+            batch.size_in_bytes()
+        };
+        batch.try_add(EventData::from("abcdefg")).unwrap();
+        batch.try_add(EventData::from("abcdefg")).unwrap();
+        assert!(batch.size_in_bytes() > without_reservation);
+        let batch_size_in_bytes = batch.size_in_bytes();
+
+        let mut events = batch.events;
+        stamp_idempotent_annotations(events.iter_mut(), Some(1), Some(2), 3);
+        let envelope = build_amqp_batch_from_messages(events.into_iter(), None).unwrap();
+        let ssize = match envelope.sendable {
+            SendableEnvelope::Batch(sendable) => {
+                serialized_size(&Serializable(sendable.message)).unwrap()
+            }
+            SendableEnvelope::Single(_) => unreachable!(),
+        };
+        assert!(ssize as u64 <= batch_size_in_bytes);
+    }
+
+    #[test]
+    fn reserved_idempotent_annotations_are_not_sent() {
+        use fe2o3_amqp_types::messaging::annotations::AnnotationKey;
+
+        use crate::amqp::{amqp_message_converter::stamp_idempotent_annotations, amqp_property};
+
+        let mut batch = AmqpEventBatch::new(262144, None, true).unwrap();
+        batch.try_add(EventData::from("abcdefg")).unwrap();
+        assert!(batch.events[0].message_annotations.is_none());
+
+        // The service did not assign a producer group id nor an owner level
+        let mut events = batch.events;
+        stamp_idempotent_annotations(events.iter_mut(), None, None, 3);
+        let annotations = events[0].message_annotations.as_ref().unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert!(annotations
+            .get(&amqp_property::PRODUCER_SEQUENCE_NUMBER as &dyn AnnotationKey)
+            .is_some());
+    }
 }
@@ -82,3 +82,67 @@ pub fn build_filter_expression(event_position: &EventPosition) -> Result<String,
         }
     }
 }
+
+/// Parses an expression built by [`build_filter_expression`] back into an [`EventPosition`].
+/// Returns `None` if the expression is not understood.
+#[cfg(feature = "amqp-emulator")]
+pub(crate) fn parse_filter_expression(expression: &str) -> Option<EventPosition> {
+    let mut parts = expression.split_whitespace();
+    let (name, operator, value) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let is_inclusive = match operator {
+        ">=" => true,
+        ">" => false,
+        _ => return None,
+    };
+
+    match name {
+        OFFSET_NAME => Some(EventPosition::Offset {
+            offset: value.to_string(),
+            is_inclusive,
+        }),
+        SEQUENCE_NUMBER_NAME => Some(EventPosition::SequenceNumber {
+            sequence_number: value.parse().ok()?,
+            is_inclusive,
+        }),
+        ENQUEUED_TIME_NAME if !is_inclusive => {
+            let nanos = value.parse::<i128>().ok()? * 1_000_000;
+            time::OffsetDateTime::from_unix_timestamp_nanos(nanos)
+                .ok()
+                .map(EventPosition::EnqueuedTime)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "amqp-emulator"))]
+mod tests {
+    use time::macros::datetime;
+
+    use crate::consumer::EventPosition;
+
+    use super::{build_filter_expression, parse_filter_expression};
+
+    #[test]
+    fn filter_expression_round_trips() {
+        let positions = [
+            EventPosition::earliest(),
+            EventPosition::latest(),
+            EventPosition::from_offset(42, true),
+            EventPosition::from_sequence_number(7, false),
+            EventPosition::from_enqueued_time(datetime!(2024-01-02 03:04:05.678 UTC)),
+        ];
+        for position in positions {
+            let expression = build_filter_expression(&position).unwrap();
+            assert_eq!(parse_filter_expression(&expression), Some(position));
+        }
+    }
+
+    #[test]
+    fn unknown_filter_expression_is_rejected() {
+        assert_eq!(parse_filter_expression("amqp.annotation.x-opt-offset = 1"), None);
+        assert_eq!(parse_filter_expression("user.property > 1"), None);
+    }
+}
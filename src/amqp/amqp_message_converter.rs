@@ -1,4 +1,7 @@
-use fe2o3_amqp_types::messaging::{Data, Message, MessageAnnotations};
+use fe2o3_amqp_types::{
+    messaging::{annotations::OwnedKey, Body, Data, Message, MessageAnnotations},
+    primitives::Value,
+};
 
 use crate::EventData;
 
@@ -44,7 +47,7 @@ impl std::fmt::Debug for BatchEnvelopeState {
 
 #[derive(Debug)]
 pub(crate) enum SendableEnvelope {
-    Single(Sendable<Data>),
+    Single(Sendable<Body<Value>>),
     Batch(Sendable<Batch<Data>>),
 }
 
@@ -62,8 +65,11 @@ pub(crate) fn create_envelope_from_events(
 }
 
 #[inline]
-fn build_amqp_message_from_event(event: EventData, partition_key: Option<String>) -> Message<Data> {
-    let mut message = event.amqp_message;
+fn build_amqp_message_from_event(
+    event: EventData,
+    partition_key: Option<String>,
+) -> Message<Body<Value>> {
+    let mut message = event.amqp_message.message;
 
     // add partition key to message annotation
     if let Some(partition_key) = partition_key {
@@ -78,6 +84,76 @@ fn build_amqp_message_from_event(event: EventData, partition_key: Option<String>
     message
 }
 
+/// Stamps the annotations used by the service to detect duplicates when publishing with
+/// idempotent publishing enabled. Consecutive sequence numbers starting from
+/// `first_sequence_number` are assigned to the messages. The producer group id and the owner level
+/// are only stamped if the service assigned them.
+pub(crate) fn stamp_idempotent_annotations<'a>(
+    messages: impl Iterator<Item = &'a mut Message<Body<Value>>>,
+    producer_group_id: Option<i64>,
+    owner_level: Option<i16>,
+    first_sequence_number: i32,
+) {
+    let mut sequence_number = first_sequence_number;
+    for message in messages {
+        let annotations = message
+            .message_annotations
+            .get_or_insert(Default::default());
+        annotations.insert(
+            amqp_property::PRODUCER_SEQUENCE_NUMBER.into(),
+            sequence_number.into(),
+        );
+        if let Some(producer_group_id) = producer_group_id {
+            annotations.insert(
+                amqp_property::PRODUCER_GROUP_ID.into(),
+                producer_group_id.into(),
+            );
+        }
+        if let Some(owner_level) = owner_level {
+            annotations.insert(
+                amqp_property::PRODUCER_OWNER_LEVEL.into(),
+                owner_level.into(),
+            );
+        }
+        sequence_number = next_sequence_number(sequence_number);
+    }
+}
+
+/// The sequence numbers assigned by a producer are in the range of 0 to [`i32::MAX`] and roll
+/// over to 0.
+pub(crate) fn next_sequence_number(sequence_number: i32) -> i32 {
+    if sequence_number == i32::MAX {
+        0
+    } else {
+        sequence_number + 1
+    }
+}
+
+/// Copies the idempotent publishing annotations of the first message of a batch to the envelope,
+/// which is what the service uses to detect duplicated batches.
+fn copy_idempotent_annotations(
+    source: &Option<MessageAnnotations>,
+    envelope: &mut Message<Batch<Data>>,
+) {
+    let source = match source {
+        Some(source) => source,
+        None => return,
+    };
+    for key in [
+        amqp_property::PRODUCER_SEQUENCE_NUMBER,
+        amqp_property::PRODUCER_GROUP_ID,
+        amqp_property::PRODUCER_OWNER_LEVEL,
+    ] {
+        let key = OwnedKey::from(key);
+        if let Some(value) = source.get(&key) {
+            envelope
+                .message_annotations
+                .get_or_insert(Default::default())
+                .insert(key, value.clone());
+        }
+    }
+}
+
 #[inline]
 fn build_amqp_batch_from_events(
     events: impl ExactSizeIterator<Item = EventData>,
@@ -92,7 +168,7 @@ fn build_amqp_batch_from_events(
 ///
 /// If `force_batch` is set to true, then a batch will be created even if there is only one message.
 pub(crate) fn build_amqp_batch_from_messages(
-    mut source: impl ExactSizeIterator<Item = Message<Data>>,
+    mut source: impl ExactSizeIterator<Item = Message<Body<Value>>>,
     partition_key: Option<String>,
 ) -> Option<BatchEnvelope> {
     let total = source.len();
@@ -113,8 +189,12 @@ pub(crate) fn build_amqp_batch_from_messages(
         }
         _ => {
             let mut batch_data: Batch<Data> = Batch::from(Vec::with_capacity(total));
+            let mut first_annotations = None;
 
             for message in source {
+                if first_annotations.is_none() {
+                    first_annotations = Some(message.message_annotations.clone());
+                }
                 // No need to set each message as batchable, as the field batchable is in the Transfer
                 // performative
                 let data = Data::from(to_vec(&Serializable(message)).ok()?);
@@ -122,6 +202,9 @@ pub(crate) fn build_amqp_batch_from_messages(
             }
 
             let mut envelope = Message::builder().body(batch_data).build();
+            if let Some(first_annotations) = &first_annotations {
+                copy_idempotent_annotations(first_annotations, &mut envelope);
+            }
 
             if let Some(partition_key) = partition_key {
                 if !partition_key.is_empty() {
@@ -147,10 +230,15 @@ pub(crate) fn build_amqp_batch_from_messages(
 
 pub(crate) fn create_empty_phantom_envelope(
     partition_key: Option<String>,
+    reserve_idempotent_annotations: bool,
 ) -> Result<PhantomMessage<Batch<Data>>, serde_amqp::Error> {
     let header = None;
     let mut message_annotations: Option<MessageAnnotations> = None;
 
+    if reserve_idempotent_annotations {
+        reserve_idempotent_annotations_in(&mut message_annotations);
+    }
+
     if let Some(partition_key) = partition_key {
         if !partition_key.is_empty() {
             message_annotations
@@ -175,3 +263,89 @@ pub(crate) fn create_empty_phantom_envelope(
         footer: Phantom::try_from(&footer)?,
     })
 }
+
+/// Inserts placeholders for the idempotent publishing annotations so that the size of a batch
+/// accounts for the annotations that are stamped when the batch is sent. The placeholders use the
+/// largest possible values and are only used to measure the size, they are never sent.
+pub(crate) fn reserve_idempotent_annotations_in(annotations: &mut Option<MessageAnnotations>) {
+    let annotations = annotations.get_or_insert(Default::default());
+    annotations.insert(
+        amqp_property::PRODUCER_SEQUENCE_NUMBER.into(),
+        i32::MAX.into(),
+    );
+    annotations.insert(amqp_property::PRODUCER_GROUP_ID.into(), i64::MAX.into());
+    annotations.insert(amqp_property::PRODUCER_OWNER_LEVEL.into(), i16::MAX.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{messaging::annotations::OwnedKey, primitives::Value};
+
+    use crate::{amqp::amqp_property, EventData};
+
+    use super::{
+        build_amqp_batch_from_messages, next_sequence_number, stamp_idempotent_annotations,
+        SendableEnvelope,
+    };
+
+    fn annotation(message_annotations: &Option<super::MessageAnnotations>, key: &str) -> Value {
+        message_annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(&OwnedKey::from(key)))
+            .cloned()
+            .unwrap_or(Value::Null)
+    }
+
+    #[test]
+    fn sequence_number_rolls_over_to_zero() {
+        assert_eq!(next_sequence_number(0), 1);
+        assert_eq!(next_sequence_number(i32::MAX), 0);
+    }
+
+    #[test]
+    fn stamped_sequence_numbers_are_consecutive_and_copied_to_envelope() {
+        let mut messages: Vec<_> = (0..3)
+            .map(|i| EventData::from(format!("event {}", i)).amqp_message.message)
+            .collect();
+        stamp_idempotent_annotations(messages.iter_mut(), Some(7), Some(1), i32::MAX - 1);
+
+        let sequence_numbers: Vec<_> = messages
+            .iter()
+            .map(|m| {
+                annotation(
+                    &m.message_annotations,
+                    amqp_property::PRODUCER_SEQUENCE_NUMBER.0,
+                )
+            })
+            .collect();
+        assert_eq!(
+            sequence_numbers,
+            vec![
+                Value::Int(i32::MAX - 1),
+                Value::Int(i32::MAX),
+                Value::Int(0)
+            ]
+        );
+
+        let envelope = build_amqp_batch_from_messages(messages.into_iter(), None).unwrap();
+        let envelope_annotations = match envelope.sendable {
+            SendableEnvelope::Batch(sendable) => sendable.message.message_annotations,
+            SendableEnvelope::Single(_) => unreachable!(),
+        };
+        assert_eq!(
+            annotation(
+                &envelope_annotations,
+                amqp_property::PRODUCER_SEQUENCE_NUMBER.0
+            ),
+            Value::Int(i32::MAX - 1)
+        );
+        assert_eq!(
+            annotation(&envelope_annotations, amqp_property::PRODUCER_GROUP_ID.0),
+            Value::Long(7)
+        );
+        assert_eq!(
+            annotation(&envelope_annotations, amqp_property::PRODUCER_OWNER_LEVEL.0),
+            Value::Short(1)
+        );
+    }
+}
@@ -1,10 +1,12 @@
 use std::borrow::Cow;
 use fe2o3_amqp_types::{
     messaging::{
-        annotations::AnnotationKey, Message, MessageId, Properties,
+        annotations::AnnotationKey, Annotations, Message, MessageId, Properties,
     },
     primitives::{BinaryRef, Symbol, Value},
 };
+use serde_amqp::primitives::SymbolRef;
+use time::OffsetDateTime;
 
 use crate::constants::MAX_MESSAGE_ID_LENGTH;
 
@@ -21,6 +23,20 @@ pub(crate) trait AmqpMessageExt {
     fn correlation_id(&self) -> Option<Cow<'_, str>>;
 
     fn content_type(&self) -> Option<&str>;
+
+    fn sequence_number(&self) -> Option<i64>;
+
+    fn offset(&self) -> Option<i64>;
+
+    fn enqueued_time(&self) -> Option<OffsetDateTime>;
+
+    fn last_partition_sequence_number(&self) -> Option<i64>;
+
+    fn last_partition_offset(&self) -> Option<i64>;
+
+    fn last_partition_enqueued_time(&self) -> Option<OffsetDateTime>;
+
+    fn last_partition_properties_retrieval_time(&self) -> Option<OffsetDateTime>;
 }
 
 pub(crate) trait AmqpMessageMutExt {
@@ -78,6 +94,71 @@ impl<B> AmqpMessageExt for Message<B> {
             .map(|s| s.as_str())
     }
 
+    #[inline]
+    fn sequence_number(&self) -> Option<i64> {
+        let annotations = self.message_annotations.as_ref()?;
+        get_i64(annotations, &amqp_property::SEQUENCE_NUMBER)
+    }
+
+    #[inline]
+    fn offset(&self) -> Option<i64> {
+        let annotations = self.message_annotations.as_ref()?;
+        get_i64(annotations, &amqp_property::OFFSET)
+    }
+
+    #[inline]
+    fn enqueued_time(&self) -> Option<OffsetDateTime> {
+        let annotations = self.message_annotations.as_ref()?;
+        get_timestamp(annotations, &amqp_property::ENQUEUED_TIME)
+    }
+
+    #[inline]
+    fn last_partition_sequence_number(&self) -> Option<i64> {
+        let annotations = self.delivery_annotations.as_ref()?;
+        get_i64(annotations, &amqp_property::PARTITION_LAST_ENQUEUED_SEQUENCE_NUMBER)
+    }
+
+    #[inline]
+    fn last_partition_offset(&self) -> Option<i64> {
+        let annotations = self.delivery_annotations.as_ref()?;
+        get_i64(annotations, &amqp_property::PARTITION_LAST_ENQUEUED_OFFSET)
+    }
+
+    #[inline]
+    fn last_partition_enqueued_time(&self) -> Option<OffsetDateTime> {
+        let annotations = self.delivery_annotations.as_ref()?;
+        get_timestamp(annotations, &amqp_property::PARTITION_LAST_ENQUEUED_TIME_UTC)
+    }
+
+    #[inline]
+    fn last_partition_properties_retrieval_time(&self) -> Option<OffsetDateTime> {
+        let annotations = self.delivery_annotations.as_ref()?;
+        get_timestamp(
+            annotations,
+            &amqp_property::LAST_PARTITION_PROPERTIES_RETRIEVAL_TIME_UTC,
+        )
+    }
+}
+
+/// The service may encode numeric values either as a `long` or as a `string` (ie. offset)
+fn get_i64(annotations: &Annotations, key: &SymbolRef<'static>) -> Option<i64> {
+    match annotations.get(key as &dyn AnnotationKey)? {
+        Value::Long(val) => Some(*val),
+        Value::Int(val) => Some(*val as i64),
+        Value::String(val) => val.parse().ok(),
+        _ => None,
+    }
+}
+
+fn get_timestamp(annotations: &Annotations, key: &SymbolRef<'static>) -> Option<OffsetDateTime> {
+    match annotations.get(key as &dyn AnnotationKey)? {
+        Value::Timestamp(val) => Some(OffsetDateTime::from(val.clone())),
+        // Milliseconds since unix epoch
+        Value::Long(millis) => {
+            OffsetDateTime::from_unix_timestamp_nanos(*millis as i128 * 1_000_000).ok()
+        }
+        _ => None,
+    }
 }
 
 impl<B> AmqpMessageMutExt for Message<B> {
@@ -1,9 +1,10 @@
 use std::marker::PhantomData;
 
 use fe2o3_amqp_types::messaging::{
-    message::__private::Serializable, ApplicationProperties, Batch, Data, DeliveryAnnotations,
-    Footer, Header, Message, MessageAnnotations, Properties,
+    message::__private::Serializable, ApplicationProperties, Batch, Body, Data,
+    DeliveryAnnotations, Footer, Header, Message, MessageAnnotations, Properties,
 };
+use fe2o3_amqp_types::primitives::Value;
 use serde_amqp::serialized_size;
 
 pub(crate) const U8_MAX: usize = u8::MAX as usize;
@@ -24,7 +25,7 @@ pub(crate) const U32_MAX_MINUS_4: usize = u32::MAX as usize - 4;
 ///
 /// This is a simple case where the serialized size is copied over.
 ///
-/// 2. `&Message<Data>`
+/// 2. `&Message<Body<Value>>`
 ///
 /// This is a slightly more complex case because the message is serialized
 /// entirely into a buffer and then the buffer is wrapped in a `Data` type.
@@ -78,6 +79,7 @@ impl_try_from_type_for_phantom! {
     ApplicationProperties,
     Data,
     Batch<Data>,
+    Body<Value>,
     Footer
 }
 
@@ -132,10 +134,10 @@ impl Phantom<Data> {
     }
 }
 
-impl<'a> TryFrom<&'a Message<Data>> for Phantom<Data> {
+impl<'a> TryFrom<&'a Message<Body<Value>>> for Phantom<Data> {
     type Error = serde_amqp::Error;
 
-    fn try_from(value: &'a Message<Data>) -> Result<Self, Self::Error> {
+    fn try_from(value: &'a Message<Body<Value>>) -> Result<Self, Self::Error> {
         // In the batched format, each message is serialized to bytes and wrapped in a Data section.
         // So there will be repeated descriptors, format code, and length byte(s) for the Data
         // section. The length bytes might be different.
@@ -198,10 +200,10 @@ impl<T> PhantomMessage<T> {
     }
 }
 
-impl<'a> TryFrom<&'a Message<Data>> for PhantomMessage<Data> {
+impl<'a> TryFrom<&'a Message<Body<Value>>> for PhantomMessage<Body<Value>> {
     type Error = serde_amqp::Error;
 
-    fn try_from(value: &'a Message<Data>) -> Result<Self, Self::Error> {
+    fn try_from(value: &'a Message<Body<Value>>) -> Result<Self, Self::Error> {
         let header = Phantom::try_from(&value.header)?;
         let delivery_annotations = Phantom::try_from(&value.delivery_annotations)?;
         let message_annotations = Phantom::try_from(&value.message_annotations)?;
@@ -315,7 +317,7 @@ mod tests {
     fn phantom_message_size_with_one_event() {
         let data = "abcdefghij";
         let event = EventData::from(data);
-        let message_iter = std::iter::once(event.amqp_message);
+        let message_iter = std::iter::once(event.amqp_message.message);
 
         let batch = build_amqp_batch_from_messages(message_iter.clone(), None).unwrap();
         let _serialized_value = serialized_value_of_sendable(batch.sendable);
@@ -333,7 +335,7 @@ mod tests {
     fn phantom_message_size_with_multiple_events() {
         let data = "abcdefghij";
         let event = EventData::from(data);
-        let messages = vec![event.amqp_message.clone(); 2];
+        let messages = vec![event.amqp_message.message.clone(); 2];
 
         let batch = build_amqp_batch_from_messages(messages.clone().into_iter(), None).unwrap();
         let serialized_bytes = serialized_bytes_of_sendable(batch.sendable);
@@ -353,12 +355,23 @@ mod tests {
             body: Phantom::new(0),
             footer: Phantom::try_from(&None).unwrap(),
         };
-        let phantom_event_body = Phantom::try_from(&event.amqp_message).unwrap();
+        let phantom_event_body = Phantom::try_from(&event.amqp_message.message).unwrap();
         phantom_envelope.body.push(phantom_event_body);
-        let phantom_event_body = Phantom::try_from(&event.amqp_message).unwrap();
+        let phantom_event_body = Phantom::try_from(&event.amqp_message.message).unwrap();
         phantom_envelope.body.push(phantom_event_body);
         let phantom_size = phantom_envelope.serialized_size();
 
         assert_eq!(phantom_size, serialized_size)
     }
+
+    #[test]
+    fn phantom_message_size_with_one_value_event() {
+        let event = crate::AmqpAnnotatedMessage::from_value(Value::from("abcdefghij"));
+        let message_iter = std::iter::once(event.message);
+
+        let batch = build_amqp_batch_from_messages(message_iter, None).unwrap();
+        let (phantom_size, ssize) =
+            phantom_size_and_serialized_size_of_sendable_envelope(batch.sendable);
+        assert_eq!(phantom_size, ssize)
+    }
 }
@@ -1,13 +1,18 @@
 use azure_core::Url;
 use fe2o3_amqp::{session::SessionHandle, Sender};
-use fe2o3_amqp_types::messaging::Outcome;
+use fe2o3_amqp_types::{
+    definitions::Fields,
+    messaging::{Body, Message, Outcome},
+    primitives::{Symbol, Value},
+};
+use serde_amqp::primitives::SymbolRef;
 use tokio::sync::mpsc;
 
 use crate::{
     amqp::amqp_message_converter::create_envelope_from_events,
     core::{RecoverableError, RecoverableTransport, TransportClient, TransportProducer, TransportProducerFeatures},
     event_hubs_retry_policy::EventHubsRetryPolicy,
-    producer::{CreateBatchOptions, SendEventOptions, MINIMUM_BATCH_SIZE_LIMIT_IN_BYTES, PartitionPublishingOptions},
+    producer::{CreateBatchOptions, SendEventOptions, PartitionPublishingOptions, PartitionPublishingProperties},
     util::{self},
     EventData,
 };
@@ -17,8 +22,10 @@ use super::{
     amqp_client::AmqpClient,
     amqp_event_batch::AmqpEventBatch,
     amqp_message_converter::{
-        build_amqp_batch_from_messages, BatchEnvelope, BatchEnvelopeState, SendableEnvelope,
+        build_amqp_batch_from_messages, next_sequence_number, stamp_idempotent_annotations,
+        BatchEnvelope, BatchEnvelopeState, SendableEnvelope,
     },
+    amqp_property,
     error::{
         AmqpSendError, CreateBatchError, DisposeProducerError, NotAcceptedError,
         RecoverAndSendError,
@@ -27,12 +34,84 @@ use super::{
 
 #[derive(Debug)]
 pub(crate) struct ProducerOptions {
+    pub(crate) event_hub_name: String,
     pub(crate) partition_id: Option<String>,
     pub(crate) identifier: Option<String>,
     pub(crate) requested_features: TransportProducerFeatures,
     pub(crate) partition_options: PartitionPublishingOptions,
 }
 
+/// The state of idempotent publishing for a partition. The initial state is assigned by the service
+/// when the link is attached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct IdempotentPublishingState {
+    pub(crate) producer_group_id: Option<i64>,
+    pub(crate) owner_level: Option<i16>,
+    pub(crate) last_published_sequence_number: Option<i32>,
+}
+
+impl IdempotentPublishingState {
+    /// Reads the state from the properties of an attached link
+    pub(crate) fn from_link_properties(properties: &Option<Fields>) -> Self {
+        let get = |key: SymbolRef<'static>| {
+            properties
+                .as_ref()
+                .and_then(|properties| properties.get(&Symbol::from(key)))
+        };
+
+        let producer_group_id =
+            get(amqp_property::PRODUCER_GROUP_ID).and_then(|value| match value {
+                Value::Long(val) => Some(*val),
+                Value::Int(val) => Some(*val as i64),
+                _ => None,
+            });
+        let owner_level = get(amqp_property::PRODUCER_OWNER_LEVEL).and_then(|value| match value {
+            Value::Short(val) => Some(*val),
+            Value::Int(val) => i16::try_from(*val).ok(),
+            Value::Long(val) => i16::try_from(*val).ok(),
+            _ => None,
+        });
+        let last_published_sequence_number =
+            get(amqp_property::PRODUCER_SEQUENCE_NUMBER).and_then(|value| match value {
+                Value::Int(val) => Some(*val),
+                Value::Long(val) => i32::try_from(*val).ok(),
+                _ => None,
+            });
+
+        Self {
+            producer_group_id,
+            owner_level,
+            last_published_sequence_number,
+        }
+    }
+
+    /// The sequence number to assign to the next event that is published
+    pub(crate) fn next_sequence_number(&self) -> i32 {
+        self.last_published_sequence_number
+            .map(next_sequence_number)
+            .unwrap_or(0)
+    }
+
+    /// The properties reported to the user for a producer that has idempotent publishing enabled
+    pub(crate) fn to_partition_properties(self) -> PartitionPublishingProperties {
+        PartitionPublishingProperties {
+            is_idempotent_publishing_enabled: true,
+            producer_group_id: self.producer_group_id,
+            owner_level: self.owner_level,
+            last_published_sequence_number: self.last_published_sequence_number,
+        }
+    }
+
+    /// The options needed to continue publishing with the same state on a new link
+    pub(crate) fn to_partition_options(self) -> PartitionPublishingOptions {
+        PartitionPublishingOptions {
+            producer_group_id: self.producer_group_id,
+            owner_level: self.owner_level,
+            starting_sequence_number: self.last_published_sequence_number,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AmqpProducer<RP> {
     pub(crate) session_handle: SessionHandle<()>,
@@ -40,13 +119,16 @@ pub struct AmqpProducer<RP> {
     pub(crate) sender: Sender,
     pub(crate) link_identifier: u32,
 
-    // /// TODO: this is only useful for idempotent sending, which won't be implemented in the current plan
-    // pub(crate) initialized_partition_properties: PartitionPublishingProperties,
+    /// The publishing properties assigned by the service when the link was attached
+    pub(crate) initialized_partition_properties: PartitionPublishingProperties,
     pub(crate) retry_policy: RP,
     pub(crate) endpoint: Url,
     pub(crate) cbs_command_sender: mpsc::Sender<Command>,
 
     pub(crate) initial_options: ProducerOptions,
+
+    /// This is `Some(_)` only if idempotent publishing is enabled
+    pub(crate) idempotent_state: Option<IdempotentPublishingState>,
 }
 
 impl<RP> AmqpProducer<RP> {
@@ -91,28 +173,20 @@ impl<RP> AmqpProducer<RP> {
         }
     }
 
+    /// Creates a batch that reserves space for the idempotent publishing annotations if
+    /// `reserve_idempotent_annotations` is true
     pub(crate) fn create_batch(
         &self,
         options: CreateBatchOptions,
+        reserve_idempotent_annotations: bool,
     ) -> Result<AmqpEventBatch, CreateBatchError> {
+        // If this field is zero or unset, there is no maximum size imposed by the link endpoint.
         let link_max_message_size = self.sender.max_message_size().unwrap_or(u64::MAX);
-        let max_size_in_bytes: u64 = match options.max_size_in_bytes {
-            Some(max_size_in_bytes) => {
-                if max_size_in_bytes < MINIMUM_BATCH_SIZE_LIMIT_IN_BYTES
-                    || max_size_in_bytes > link_max_message_size
-                {
-                    return Err(CreateBatchError::RequestedSizeOutOfRange);
-                }
-
-                max_size_in_bytes
-            }
-            // If this field is zero or unset, there is no maximum size imposed by the link endpoint.
-            None => link_max_message_size,
-        };
-        Ok(AmqpEventBatch::new(
-            max_size_in_bytes,
-            options.partition_key,
-        )?)
+        AmqpEventBatch::with_link_max_message_size(
+            options,
+            link_max_message_size,
+            reserve_idempotent_annotations,
+        )
     }
 
     pub(crate) async fn close(mut self) -> Result<(), DisposeProducerError> {
@@ -143,10 +217,16 @@ where
         let partition_id = self.producer.initial_options.partition_id.clone();
         let identifier = self.producer.initial_options.identifier.clone();
         let requested_features = self.producer.initial_options.requested_features;
-        let partition_options = self.producer.initial_options.partition_options.clone();
+        // Continue from the current state so that the service keeps detecting duplicates
+        let partition_options = match self.producer.idempotent_state {
+            Some(state) => state.to_partition_options(),
+            None => self.producer.initial_options.partition_options.clone(),
+        };
         let retry_policy = self.producer.retry_policy.clone();
 
+        let event_hub_name = self.producer.initial_options.event_hub_name.clone();
         let new_producer = self.client.create_producer(
+            &event_hub_name,
             partition_id,
             identifier,
             requested_features,
@@ -196,6 +276,44 @@ where
         Ok(())
     }
 
+    /// Stamps the next sequence numbers on the messages before sending them. The sequence numbers
+    /// are kept if the send is retried, so the service can discard the events that were already
+    /// published. The state only advances once the service has accepted the events.
+    async fn send_idempotent(
+        &mut self,
+        mut messages: Vec<Message<Body<Value>>>,
+    ) -> Result<(), RecoverAndSendError> {
+        let state = match self.producer.idempotent_state {
+            Some(state) => state,
+            None => unreachable!("idempotent publishing is not enabled"),
+        };
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let first_sequence_number = state.next_sequence_number();
+        stamp_idempotent_annotations(
+            messages.iter_mut(),
+            state.producer_group_id,
+            state.owner_level,
+            first_sequence_number,
+        );
+        let count = messages.len();
+        let batch = match build_amqp_batch_from_messages(messages.into_iter(), None) {
+            Some(batch) => batch,
+            None => return Ok(()),
+        };
+        self.send_batch_envelope(batch).await?;
+
+        // Sequence numbers roll over to 0 after i32::MAX
+        let last_published_sequence_number =
+            ((first_sequence_number as i64 + count as i64 - 1) % (i32::MAX as i64 + 1)) as i32;
+        if let Some(state) = self.producer.idempotent_state.as_mut() {
+            state.last_published_sequence_number = Some(last_published_sequence_number);
+        }
+        Ok(())
+    }
+
     async fn send_batch_envelope(
         &mut self,
         mut batch: BatchEnvelope,
@@ -257,8 +375,10 @@ where
     fn create_batch(
         &self,
         options: CreateBatchOptions,
+        reserve_idempotent_annotations: bool,
     ) -> Result<Self::MessageBatch, Self::CreateBatchError> {
-        self.producer.create_batch(options)
+        self.producer
+            .create_batch(options, reserve_idempotent_annotations)
     }
 
     async fn send(
@@ -266,6 +386,11 @@ where
         events: impl ExactSizeIterator<Item = EventData> + Send,
         options: SendEventOptions,
     ) -> Result<(), Self::SendError> {
+        if self.producer.idempotent_state.is_some() {
+            let messages = events.map(|event| event.amqp_message.message).collect();
+            return self.send_idempotent(messages).await;
+        }
+
         // TODO: check size of envelope and make sure it's not too big
         match create_envelope_from_events(events, options.into_partition_key()) {
             Some(batch) => self.send_batch_envelope(batch).await,
@@ -278,6 +403,10 @@ where
         batch: Self::MessageBatch,
         options: SendEventOptions,
     ) -> Result<(), Self::SendError> {
+        if self.producer.idempotent_state.is_some() {
+            return self.send_idempotent(batch.events).await;
+        }
+
         match build_amqp_batch_from_messages(batch.events.into_iter(), options.into_partition_key())
         {
             Some(batch) => self.send_batch_envelope(batch).await,
@@ -18,4 +18,15 @@ impl<'a, B> From<&'a Message<B>> for AmqpSystemProperties<'a> {
     }
 }
 
-impl<'a> AmqpSystemProperties<'a> {}
+impl<'a> AmqpSystemProperties<'a> {
+    /// The AMQP properties of the message
+    pub fn properties(&self) -> Option<&Properties> {
+        self.amqp_properties.as_deref()
+    }
+
+    /// The message annotations of the message, which carry the system properties set by the Event
+    /// Hubs service (ie. sequence number, offset and enqueued time)
+    pub fn message_annotations(&self) -> Option<&MessageAnnotations> {
+        self.amqp_message_annotations.as_deref()
+    }
+}
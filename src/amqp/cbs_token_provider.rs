@@ -1,47 +1,49 @@
 use azure_core::auth::AccessToken;
 use fe2o3_amqp_cbs::{token::CbsToken, AsyncCbsTokenProvider};
 use fe2o3_amqp_types::primitives::Timestamp;
-use std::{future::Future, sync::Arc};
-use time::Duration as TimeSpan;
+use std::{collections::HashMap, future::Future, sync::Arc};
 
 use crate::authorization::event_hub_token_credential::EventHubTokenCredential;
 
-use super::token_type::TokenType;
-
 #[derive(Debug)]
 pub(crate) struct CbsTokenProvider {
-    /// The token type
-    token_type: TokenType,
+    /// The credential that provides the tokens
+    credential: Arc<EventHubTokenCredential>,
 
-    /// The amount of buffer to when evaluating token expiration; the token's expiration date will
-    /// be adjusted earlier by this amount.
-    token_expiration_buffer: TimeSpan,
+    /// The tokens that are cached for the resources, if the provider of the credential has a
+    /// token expiration buffer. A provider whose tokens are not scoped to a resource has a single
+    /// token, which is cached under an empty key
+    cached_tokens: HashMap<String, AccessToken>,
 }
 
 impl CbsTokenProvider {
     /// Initializes a new instance of the [`CbsTokenProvider`] class.
-    pub fn new(
-        credential: Arc<EventHubTokenCredential>,
-        token_expiration_buffer: TimeSpan,
-    ) -> Self {
-        let token_type = if credential.is_shared_access_credential() {
-            TokenType::SharedAccessToken { credential }
-        } else {
-            TokenType::JsonWebToken {
-                credential,
-                cached_token: None,
-            }
-        };
-
+    pub fn new(credential: Arc<EventHubTokenCredential>) -> Self {
         Self {
-            token_type,
-            token_expiration_buffer,
+            credential,
+            cached_tokens: HashMap::new(),
         }
     }
-}
 
-fn is_nearing_expiration(token: &AccessToken, token_expiration_buffer: TimeSpan) -> bool {
-    token.expires_on - token_expiration_buffer <= crate::util::time::now_utc()
+    async fn get_token(&mut self, resource: String) -> azure_core::Result<AccessToken> {
+        let provider = self.credential.provider();
+        if provider.token_expiration_buffer().is_none() {
+            return provider.get_token(&resource).await;
+        }
+
+        let key = match provider.is_resource_scoped() {
+            true => resource.as_str(),
+            false => "",
+        };
+        match self.cached_tokens.get(key) {
+            Some(cached) if !provider.should_refresh(cached) => Ok(cached.clone()),
+            _ => {
+                let token = provider.get_token(&resource).await?;
+                self.cached_tokens.insert(key.to_string(), token.clone());
+                Ok(token)
+            }
+        }
+    }
 }
 
 impl AsyncCbsTokenProvider for CbsTokenProvider {
@@ -53,44 +55,20 @@ impl AsyncCbsTokenProvider for CbsTokenProvider {
     fn get_token_async(
         &mut self,
         _container_id: impl AsRef<str>,
-        _resource_id: impl AsRef<str>,
+        resource_id: impl AsRef<str>,
         _claims: impl IntoIterator<Item = impl AsRef<str>>,
     ) -> impl Future<Output = Result<CbsToken<'_>, azure_core::error::Error>> + Send {
+        let resource_id = resource_id.as_ref().to_string();
         async move {
-            // CbsTokenFut { provider: self }
-            let expiration_buffer = self.token_expiration_buffer;
-            let entity_type = self.token_type.entity_type().to_string();
-
-            let result = match &mut self.token_type {
-                TokenType::SharedAccessToken { credential } => {
-                    let token = credential.get_token_using_default_resource().await?;
-                    Ok(token)
-                }
-                TokenType::JsonWebToken {
-                    credential,
-                    cached_token,
-                } => match cached_token {
-                    Some(cached) => {
-                        if is_nearing_expiration(cached, expiration_buffer) {
-                            let token = credential.get_token_using_default_resource().await?;
-                            *cached = token.clone();
-                        }
-                        Ok(cached.clone())
-                    }
-                    None => {
-                        let token = credential.get_token_using_default_resource().await?;
-                        *cached_token = Some(token.clone());
-                        Ok(token)
-                    }
-                },
-            };
-            result.map(|token| {
-                CbsToken::new(
-                    token.token.secret().to_owned(),
-                    entity_type,
-                    Some(Timestamp::from(token.expires_on)),
-                )
-            })
+            let entity_type = self.credential.token_type().to_string();
+            // A shared access signature is scoped to the entity, so that a connection can
+            // authorize the links of any Event Hub in the namespace
+            let token = self.get_token(resource_id).await?;
+            Ok(CbsToken::new(
+                token.token.secret().to_owned(),
+                entity_type,
+                Some(Timestamp::from(token.expires_on)),
+            ))
         }
     }
 }
@@ -99,7 +77,11 @@ impl AsyncCbsTokenProvider for CbsTokenProvider {
 mod tests {
     cfg_not_wasm32! {
         use fe2o3_amqp_cbs::AsyncCbsTokenProvider;
-        use std::sync::Arc;
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+        use std::time::Duration as StdDuration;
         use time::Duration as TimeSpan;
 
         use azure_core::auth::{Secret, AccessToken};
@@ -108,10 +90,59 @@ mod tests {
         use crate::{
             authorization::{
                 event_hub_token_credential::EventHubTokenCredential, tests::MockTokenCredential,
+                EventHubTokenProvider, EventHubTokenType, GetTokenFuture, TokenCredentialProvider,
             },
-            constants::JSON_WEB_TOKEN_TYPE,
+            constants::{JSON_WEB_TOKEN_TYPE, SAS_TOKEN_TYPE},
         };
 
+        /// Signs a token for each resource that is requested, and counts the requests
+        #[derive(Debug)]
+        struct SigningProvider {
+            token_expiration_buffer: Option<StdDuration>,
+            validity: TimeSpan,
+            requests: Arc<AtomicUsize>,
+        }
+
+        impl EventHubTokenProvider for SigningProvider {
+            fn token_type(&self) -> EventHubTokenType {
+                EventHubTokenType::SharedAccessSignature
+            }
+
+            fn get_token<'a>(&'a self, resource: &'a str) -> GetTokenFuture<'a> {
+                self.requests.fetch_add(1, Ordering::Relaxed);
+                let expires_on = crate::util::time::now_utc() + self.validity;
+                let token = AccessToken::new(format!("signed {}", resource), expires_on);
+                Box::pin(async move { Ok(token) })
+            }
+
+            fn token_expiration_buffer(&self) -> Option<StdDuration> {
+                self.token_expiration_buffer
+            }
+        }
+
+        async fn count_requests(
+            token_expiration_buffer: Option<StdDuration>,
+            validity: TimeSpan,
+            resources: &[&str],
+        ) -> usize {
+            let requests = Arc::new(AtomicUsize::new(0));
+            let credential = EventHubTokenCredential::from_provider(SigningProvider {
+                token_expiration_buffer,
+                validity,
+                requests: requests.clone(),
+            });
+            let mut provider = super::CbsTokenProvider::new(Arc::new(credential));
+            for resource in resources {
+                let token = provider
+                    .get_token_async("http://www.here.com", resource, Vec::<String>::new())
+                    .await
+                    .unwrap();
+                assert_eq!(token.token_type(), SAS_TOKEN_TYPE);
+                assert_eq!(token.token_value(), format!("signed {}", resource));
+            }
+            requests.load(Ordering::Relaxed)
+        }
+
         #[tokio::test]
         async fn get_token() {
             let token_value = "ValuE_oF_tHE_tokEn";
@@ -130,7 +161,7 @@ mod tests {
                 });
 
             let credential = EventHubTokenCredential::from(mock_credential);
-            let mut provider = super::CbsTokenProvider::new(Arc::new(credential), TimeSpan::seconds(0));
+            let mut provider = super::CbsTokenProvider::new(Arc::new(credential));
 
             let token = provider
                 .get_token_async("http://www.here.com", "nobody", Vec::<String>::new())
@@ -164,7 +195,7 @@ mod tests {
                 });
 
             let credential = EventHubTokenCredential::from(mock_credential);
-            let mut provider = super::CbsTokenProvider::new(Arc::new(credential), TimeSpan::seconds(0));
+            let mut provider = super::CbsTokenProvider::new(Arc::new(credential));
 
             let (first_token_value, first_token_type, first_token_expires_at) = {
                 let token = provider
@@ -194,49 +225,87 @@ mod tests {
 
         #[tokio::test]
         async fn get_token_respects_expiration_buffer_for_jwt() {
-            let token_value = "ValuE_oF_tHE_tokEn";
-            let buffer = TimeSpan::minutes(5);
-            let expires_on: OffsetDateTime =
-                crate::util::time::now_utc() - buffer + TimeSpan::seconds(-10);
+            let buffer = StdDuration::from_secs(5 * 60);
+            // The token has not expired yet, but it expires within the buffer
+            let expires_on: OffsetDateTime = crate::util::time::now_utc() + TimeSpan::minutes(2);
+            let requests = Arc::new(AtomicUsize::new(0));
             let mut mock_credential = MockTokenCredential::new();
 
+            let counter = requests.clone();
             mock_credential
                 .expect_get_token()
                 .times(2)
                 .returning(move |_resource| {
+                    let count = counter.fetch_add(1, Ordering::Relaxed);
                     Box::pin(async move {
-                        Ok(AccessToken {
-                            token: Secret::new(token_value),
-                            expires_on,
-                        })
+                        Ok(AccessToken::new(format!("token {}", count), expires_on))
                     })
                 });
 
-            let credential = EventHubTokenCredential::from(mock_credential);
-            let mut provider = super::CbsTokenProvider::new(Arc::new(credential), buffer);
+            let token_provider = TokenCredentialProvider::new(mock_credential)
+                .with_token_expiration_buffer(buffer);
+            assert_eq!(token_provider.token_expiration_buffer(), Some(buffer));
+            let credential = EventHubTokenCredential::from_provider(token_provider);
+            let mut provider = super::CbsTokenProvider::new(Arc::new(credential));
 
-            let (first_token_value, first_token_type, first_token_expires_at) = {
-                let token = provider
-                    .get_token_async("http://www.here.com", "nobody", Vec::<String>::new())
-                    .await
-                    .unwrap();
+            let first_token = provider
+                .get_token_async("http://www.here.com", "nobody", Vec::<String>::new())
+                .await
+                .unwrap();
+            assert_eq!(first_token.token_value(), "token 0");
+            assert_eq!(first_token.token_type(), JSON_WEB_TOKEN_TYPE);
 
-                (
-                    token.token_value().to_owned(),
-                    token.token_type().to_owned(),
-                    token.expires_at_utc().clone(),
-                )
-            };
             let second_token = provider
                 .get_token_async("http://www.here.com", "nobody", Vec::<String>::new())
                 .await
                 .unwrap();
-            assert_eq!(first_token_value, second_token.token_value());
-            assert_eq!(first_token_type, second_token.token_type());
-            assert_eq!(
-                first_token_expires_at,
-                second_token.expires_at_utc().clone()
-            );
+            assert_eq!(second_token.token_value(), "token 1");
+            assert_eq!(requests.load(Ordering::Relaxed), 2);
+        }
+
+        #[tokio::test]
+        async fn get_token_caches_jwt_tokens_once_for_all_resources() {
+            let expires_on: OffsetDateTime = crate::util::time::now_utc() + TimeSpan::days(60);
+            let mut mock_credential = MockTokenCredential::new();
+            mock_credential
+                .expect_get_token()
+                .times(1)
+                .returning(move |_resource| {
+                    Box::pin(async move { Ok(AccessToken::new("token", expires_on)) })
+                });
+
+            let credential = EventHubTokenCredential::from(mock_credential);
+            let mut provider = super::CbsTokenProvider::new(Arc::new(credential));
+            for resource in ["first", "second", "third"] {
+                let token = provider
+                    .get_token_async("http://www.here.com", resource, Vec::<String>::new())
+                    .await
+                    .unwrap();
+                assert_eq!(token.token_value(), "token");
+            }
+        }
+
+        #[tokio::test]
+        async fn get_token_caches_custom_provider_tokens_per_resource() {
+            let buffer = Some(StdDuration::from_secs(60));
+            let resources = ["first", "second", "first", "second"];
+            let requests = count_requests(buffer, TimeSpan::hours(1), &resources).await;
+            assert_eq!(requests, 2);
+        }
+
+        #[tokio::test]
+        async fn get_token_refreshes_custom_provider_tokens_within_expiration_buffer() {
+            let buffer = Some(StdDuration::from_secs(60));
+            let resources = ["first", "first"];
+            let requests = count_requests(buffer, TimeSpan::seconds(30), &resources).await;
+            assert_eq!(requests, 2);
+        }
+
+        #[tokio::test]
+        async fn get_token_does_not_cache_custom_provider_tokens_without_expiration_buffer() {
+            let resources = ["first", "first"];
+            let requests = count_requests(None, TimeSpan::hours(1), &resources).await;
+            assert_eq!(requests, 2);
         }
     }
 }
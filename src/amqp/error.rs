@@ -14,7 +14,7 @@ use fe2o3_amqp_types::messaging::{Modified, Rejected, Released};
 use timer_kit::error::Elapsed;
 
 use crate::{
-    consumer::error::OffsetIsEmpty, core::RecoverableError, util::{IntoAzureCoreError, error::ResumableLinkError}, EventData,
+    consumer::error::OffsetIsEmpty, core::RecoverableError, util::{IntoAzureCoreError, error::{ResumableLinkError, TransientError}}, EventData,
 };
 
 /// The value exceeds the maximum length allowed
@@ -780,6 +780,29 @@ pub enum RecoverConsumerError {
 
     #[error("Connection scope is disposed")]
     ConnectionScopeDisposed,
+
+    #[error(transparent)]
+    ParseEndpoint(#[from] url::ParseError),
+
+    #[error(transparent)]
+    ConsumerFilter(#[from] OffsetIsEmpty),
+
+    #[error(transparent)]
+    Elapsed(#[from] Elapsed),
+}
+
+impl From<OpenConsumerError> for RecoverConsumerError {
+    fn from(value: OpenConsumerError) -> Self {
+        match value {
+            OpenConsumerError::ParseEndpoint(err) => err.into(),
+            OpenConsumerError::ConnectionScopeDisposed => Self::ConnectionScopeDisposed,
+            OpenConsumerError::CbsAuth(err) => err.into(),
+            OpenConsumerError::Session(err) => err.into(),
+            OpenConsumerError::ReceiverLink(err) => ReceiverResumeErrorKind::AttachError(err).into(),
+            OpenConsumerError::ConsumerFilter(err) => err.into(),
+            OpenConsumerError::Elapsed(err) => err.into(),
+        }
+    }
 }
 
 impl From<DetachThenResumeReceiverError> for RecoverConsumerError {
@@ -1026,6 +1049,9 @@ impl From<RecoverConsumerError> for RecoverAndReceiveError {
             RecoverConsumerError::ReceiverDetach(err) => err.into(),
             RecoverConsumerError::ReceiverResume(err) => err.into(),
             RecoverConsumerError::CbsAuth(err) => err.into(),
+            RecoverConsumerError::ParseEndpoint(err) => err.into(),
+            RecoverConsumerError::ConsumerFilter(err) => err.into(),
+            RecoverConsumerError::Elapsed(err) => err.into(),
         }
     }
 }
@@ -1086,3 +1112,359 @@ impl RecoverableError for RecoverAndReceiveError {
         matches!(self, RecoverAndReceiveError::ConnectionScopeDisposed)
     }
 }
+
+impl TransientError for AmqpConnectionScopeError {
+    fn is_transient(&self) -> bool {
+        match self {
+            AmqpConnectionScopeError::Open(err) => err.is_transient(),
+            AmqpConnectionScopeError::WebSocket(err) => err.is_transient(),
+            AmqpConnectionScopeError::Begin(err) => err.is_transient(),
+            AmqpConnectionScopeError::SenderAttach(err) => err.is_transient(),
+            AmqpConnectionScopeError::ReceiverAttach(err) => err.is_transient(),
+            AmqpConnectionScopeError::ScopeDisposed => false,
+            AmqpConnectionScopeError::Parse(_) => false,
+        }
+    }
+}
+
+impl TransientError for CbsAuthError {
+    fn is_transient(&self) -> bool {
+        match self {
+            CbsAuthError::TokenCredential(err) => err.is_transient(),
+            CbsAuthError::Cbs(err) => err.is_transient(),
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            CbsAuthError::TokenCredential(err) => err.is_server_busy(),
+            CbsAuthError::Cbs(err) => err.is_server_busy(),
+        }
+    }
+}
+
+impl TransientError for OpenMgmtLinkError {
+    fn is_transient(&self) -> bool {
+        match self {
+            OpenMgmtLinkError::ConnectionScopeDisposed => false,
+            OpenMgmtLinkError::Session(err) => err.is_transient(),
+            OpenMgmtLinkError::Link(AttachError::Sender(err)) => err.is_transient(),
+            OpenMgmtLinkError::Link(AttachError::Receiver(err)) => err.is_transient(),
+        }
+    }
+}
+
+impl TransientError for AmqpClientError {
+    fn is_transient(&self) -> bool {
+        match self {
+            AmqpClientError::ConnectionScope(err) => err.is_transient(),
+            AmqpClientError::ParseUrl(_) => false,
+            AmqpClientError::SetUrlScheme => false,
+            AmqpClientError::ManagementLink(err) => err.is_transient(),
+        }
+    }
+}
+
+impl TransientError for OpenProducerError {
+    fn is_transient(&self) -> bool {
+        match self {
+            OpenProducerError::ParseEndpoint(_) => false,
+            OpenProducerError::ConnectionScopeDisposed => false,
+            OpenProducerError::CbsAuth(err) => err.is_transient(),
+            OpenProducerError::Session(err) => err.is_transient(),
+            OpenProducerError::SenderLink(err) => err.is_transient(),
+            OpenProducerError::Elapsed(err) => err.is_transient(),
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            OpenProducerError::CbsAuth(err) => err.is_server_busy(),
+            OpenProducerError::Session(err) => err.is_server_busy(),
+            OpenProducerError::SenderLink(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for OpenConsumerError {
+    fn is_transient(&self) -> bool {
+        match self {
+            OpenConsumerError::ParseEndpoint(_) => false,
+            OpenConsumerError::ConnectionScopeDisposed => false,
+            OpenConsumerError::CbsAuth(err) => err.is_transient(),
+            OpenConsumerError::Session(err) => err.is_transient(),
+            OpenConsumerError::ReceiverLink(err) => err.is_transient(),
+            OpenConsumerError::ConsumerFilter(_) => false,
+            OpenConsumerError::Elapsed(err) => err.is_transient(),
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            OpenConsumerError::CbsAuth(err) => err.is_server_busy(),
+            OpenConsumerError::Session(err) => err.is_server_busy(),
+            OpenConsumerError::ReceiverLink(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for NotAcceptedError {
+    fn is_transient(&self) -> bool {
+        match self {
+            // A rejected message will be rejected again unless the service reports a transient
+            // condition (eg. server busy)
+            NotAcceptedError::Rejected(rejected) => rejected
+                .error
+                .as_ref()
+                .map(|err| err.is_transient())
+                .unwrap_or(false),
+            NotAcceptedError::Released(_) => true,
+            NotAcceptedError::Modified(_) => true,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            NotAcceptedError::Rejected(rejected) => rejected
+                .error
+                .as_ref()
+                .map(|err| err.is_server_busy())
+                .unwrap_or(false),
+            NotAcceptedError::Released(_) | NotAcceptedError::Modified(_) => false,
+        }
+    }
+}
+
+impl TransientError for AmqpSendError {
+    fn is_transient(&self) -> bool {
+        match self {
+            AmqpSendError::Send(err) => err.is_transient(),
+            AmqpSendError::NotAccepted(err) => err.is_transient(),
+            AmqpSendError::Elapsed(err) => err.is_transient(),
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            AmqpSendError::Send(err) => err.is_server_busy(),
+            AmqpSendError::NotAccepted(err) => err.is_server_busy(),
+            AmqpSendError::Elapsed(_) => false,
+        }
+    }
+}
+
+impl TransientError for RecoverProducerError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RecoverProducerError::ParseEndpoint(_) => false,
+            RecoverProducerError::CbsAuth(err) => err.is_transient(),
+            RecoverProducerError::SessionBegin(err) => err.is_transient(),
+            RecoverProducerError::SenderAttach(err) => err.is_transient(),
+            RecoverProducerError::SenderDetach(err) => err.is_transient(),
+            RecoverProducerError::SenderResume(err) => err.is_transient(),
+            RecoverProducerError::ConnectionScopeDisposed => false,
+            RecoverProducerError::Elapsed(err) => err.is_transient(),
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            RecoverProducerError::CbsAuth(err) => err.is_server_busy(),
+            RecoverProducerError::SessionBegin(err) => err.is_server_busy(),
+            RecoverProducerError::SenderAttach(err) => err.is_server_busy(),
+            RecoverProducerError::SenderDetach(err) => err.is_server_busy(),
+            RecoverProducerError::SenderResume(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for RecoverAndSendError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RecoverAndSendError::ParseEndpoint(_) => false,
+            RecoverAndSendError::CbsAuth(err) => err.is_transient(),
+            RecoverAndSendError::SessionBegin(err) => err.is_transient(),
+            RecoverAndSendError::SenderAttach(err) => err.is_transient(),
+            RecoverAndSendError::SenderDetach(err) => err.is_transient(),
+            RecoverAndSendError::SenderResume(err) => err.is_transient(),
+            RecoverAndSendError::ConnectionScopeDisposed => false,
+            RecoverAndSendError::Send(err) => err.is_transient(),
+            RecoverAndSendError::NotAccepted(err) => err.is_transient(),
+            RecoverAndSendError::Elapsed(err) => err.is_transient(),
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            RecoverAndSendError::CbsAuth(err) => err.is_server_busy(),
+            RecoverAndSendError::SessionBegin(err) => err.is_server_busy(),
+            RecoverAndSendError::SenderAttach(err) => err.is_server_busy(),
+            RecoverAndSendError::SenderDetach(err) => err.is_server_busy(),
+            RecoverAndSendError::SenderResume(err) => err.is_server_busy(),
+            RecoverAndSendError::Send(err) => err.is_server_busy(),
+            RecoverAndSendError::NotAccepted(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for RecoverConsumerError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RecoverConsumerError::CbsAuth(err) => err.is_transient(),
+            RecoverConsumerError::SessionBegin(err) => err.is_transient(),
+            RecoverConsumerError::ReceiverDetach(err) => err.is_transient(),
+            RecoverConsumerError::ReceiverResume(err) => err.is_transient(),
+            RecoverConsumerError::ConnectionScopeDisposed => false,
+            RecoverConsumerError::ParseEndpoint(_) => false,
+            RecoverConsumerError::ConsumerFilter(_) => false,
+            RecoverConsumerError::Elapsed(err) => err.is_transient(),
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            RecoverConsumerError::CbsAuth(err) => err.is_server_busy(),
+            RecoverConsumerError::SessionBegin(err) => err.is_server_busy(),
+            RecoverConsumerError::ReceiverDetach(err) => err.is_server_busy(),
+            RecoverConsumerError::ReceiverResume(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for RecoverTransportClientError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RecoverTransportClientError::Parse(_) => false,
+            RecoverTransportClientError::Open(err) => err.is_transient(),
+            RecoverTransportClientError::WebSocket(err) => err.is_transient(),
+            RecoverTransportClientError::SessionBegin(err) => err.is_transient(),
+            RecoverTransportClientError::LinkDetach(err) => err.is_transient(),
+            RecoverTransportClientError::SenderResume(err) => err.is_transient(),
+            RecoverTransportClientError::ReceiverResume(err) => err.is_transient(),
+            RecoverTransportClientError::ConnectionScopeDisposed => false,
+        }
+    }
+}
+
+impl TransientError for RequestResponseError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RequestResponseError::Token(err) => err.is_transient(),
+            RequestResponseError::Management(err) => err.is_transient(),
+            RequestResponseError::Elapsed(err) => err.is_transient(),
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            RequestResponseError::Token(err) => err.is_server_busy(),
+            RequestResponseError::Management(err) => err.is_server_busy(),
+            RequestResponseError::Elapsed(_) => false,
+        }
+    }
+}
+
+impl TransientError for RecoverAndReceiveError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RecoverAndReceiveError::CbsAuth(err) => err.is_transient(),
+            RecoverAndReceiveError::Receive(err) => err.is_transient(),
+            RecoverAndReceiveError::SessionBegin(err) => err.is_transient(),
+            RecoverAndReceiveError::ReceiverResume(err) => err.is_transient(),
+            RecoverAndReceiveError::ConnectionScopeDisposed => false,
+            RecoverAndReceiveError::Parse(_) => false,
+            RecoverAndReceiveError::Open(err) => err.is_transient(),
+            RecoverAndReceiveError::WebSocket(err) => err.is_transient(),
+            RecoverAndReceiveError::LinkDetach(err) => err.is_transient(),
+            RecoverAndReceiveError::SenderResume(err) => err.is_transient(),
+            RecoverAndReceiveError::Disposition(err) => err.is_transient(),
+            RecoverAndReceiveError::SessionEnd(err) => err.is_transient(),
+            RecoverAndReceiveError::Elapsed(err) => err.is_transient(),
+            RecoverAndReceiveError::OffsetIsEmpty(_) => false,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            RecoverAndReceiveError::CbsAuth(err) => err.is_server_busy(),
+            RecoverAndReceiveError::Receive(err) => err.is_server_busy(),
+            RecoverAndReceiveError::SessionBegin(err) => err.is_server_busy(),
+            RecoverAndReceiveError::ReceiverResume(err) => err.is_server_busy(),
+            RecoverAndReceiveError::LinkDetach(err) => err.is_server_busy(),
+            RecoverAndReceiveError::SenderResume(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+/// Applies `classify` to the error if it is one of the errors produced by this crate
+fn classify_error<T>(
+    error: &(dyn std::error::Error + 'static),
+    classify: &impl Fn(&dyn TransientError) -> T,
+) -> Option<T> {
+    macro_rules! classify_as {
+        ($($error_type:ty),* $(,)?) => {
+            $(
+                if let Some(err) = error.downcast_ref::<$error_type>() {
+                    return Some(classify(err));
+                }
+            )*
+        };
+    }
+
+    // `azure_core::Error` may wrap one of the errors below
+    if let Some(err) = error.downcast_ref::<azure_core::Error>() {
+        let inner = err
+            .get_ref()
+            .and_then(|inner| classify_error(inner, classify));
+        return Some(inner.unwrap_or_else(|| classify(err)));
+    }
+
+    classify_as!(
+        RecoverAndSendError,
+        RecoverAndReceiveError,
+        RequestResponseError,
+        AmqpSendError,
+        NotAcceptedError,
+        OpenProducerError,
+        OpenConsumerError,
+        RecoverProducerError,
+        RecoverConsumerError,
+        RecoverTransportClientError,
+        AmqpClientError,
+        AmqpConnectionScopeError,
+        OpenMgmtLinkError,
+        CbsAuthError,
+        Elapsed,
+        fe2o3_amqp::link::SendError,
+        RecvError,
+        DetachError,
+        SenderAttachError,
+        ReceiverAttachError,
+        SenderResumeErrorKind,
+        ReceiverResumeErrorKind,
+        BeginError,
+        OpenError,
+        ManagementError,
+    );
+    #[cfg(feature = "in-memory-transport")]
+    classify_as!(crate::in_memory::InMemoryTransportError);
+    None
+}
+
+/// Returns false if the error is known to be terminal, ie. retrying the operation will not
+/// succeed. Errors that are not produced by this crate are considered transient.
+pub(crate) fn is_transient_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    classify_error(error, &|err| err.is_transient()).unwrap_or(true)
+}
+
+/// Returns true if the service reported that it is busy and the client should throttle its
+/// requests
+pub(crate) fn is_server_busy_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    classify_error(error, &|err| err.is_server_busy()).unwrap_or(false)
+}
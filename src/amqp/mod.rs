@@ -2,8 +2,10 @@ pub(crate) mod amqp_cbs_link;
 pub(crate) mod amqp_client;
 pub(crate) mod amqp_connection;
 pub(crate) mod amqp_connection_scope;
+pub(crate) mod amqp_connection_stream;
 pub(crate) mod amqp_constants;
 pub(crate) mod amqp_consumer;
+pub(crate) mod amqp_error;
 pub(crate) mod amqp_event_batch;
 pub(crate) mod amqp_filter;
 pub(crate) mod amqp_management;
@@ -15,9 +17,8 @@ pub(crate) mod amqp_producer;
 pub(crate) mod amqp_property;
 pub(crate) mod cbs_token_provider;
 pub(crate) mod error;
-pub(crate) mod token_type;
 
-// pub(crate) mod amqp_system_properties; // TODO: implement processor
+pub(crate) mod amqp_system_properties;
 
 use std::sync::atomic::AtomicU32;
 
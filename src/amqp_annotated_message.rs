@@ -0,0 +1,167 @@
+use fe2o3_amqp_types::messaging::{
+    AmqpSequence, AmqpValue, ApplicationProperties, Batch, Body, Data, DeliveryAnnotations, Footer,
+    Header, Message, MessageAnnotations, Properties,
+};
+use serde_amqp::{primitives::Binary, Value};
+
+/// The body of an AMQP message, which is either one or more data sections, one or more
+/// sequence sections, a single value section or empty.
+pub type AmqpMessageBody = Body<Value>;
+
+/// An AMQP message with all its sections, which is what an event is sent and received as.
+///
+/// Unlike [`EventData::body`](crate::EventData::body), which only handles a single binary data
+/// section, this allows the body to be any of the kinds defined by the AMQP 1.0 specification.
+///
+/// # Example
+///
+/// ```rust
+/// use azeventhubs::{AmqpAnnotatedMessage, EventData};
+/// use fe2o3_amqp_types::primitives::Value;
+///
+/// let mut message = AmqpAnnotatedMessage::from_value(Value::Int(42));
+/// message
+///     .message_annotations_mut()
+///     .get_or_insert(Default::default())
+///     .insert("x-opt-custom".into(), "value".into());
+/// assert!(message.body().is_value());
+///
+/// let event = EventData::from(message);
+/// assert!(event.amqp_message().body().is_value());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AmqpAnnotatedMessage {
+    pub(crate) message: Message<Body<Value>>,
+}
+
+impl From<Message<Body<Value>>> for AmqpAnnotatedMessage {
+    fn from(message: Message<Body<Value>>) -> Self {
+        Self { message }
+    }
+}
+
+impl From<AmqpAnnotatedMessage> for Message<Body<Value>> {
+    fn from(message: AmqpAnnotatedMessage) -> Self {
+        message.message
+    }
+}
+
+impl AmqpAnnotatedMessage {
+    /// Creates a new message with the given body and no other section
+    pub fn new(body: impl Into<AmqpMessageBody>) -> Self {
+        Self {
+            message: Message::builder().body(body.into()).build(),
+        }
+    }
+
+    /// Creates a new message with a single data section
+    pub fn from_data(data: impl Into<Vec<u8>>) -> Self {
+        Self::new(Body::Data(Batch::from(vec![Data(Binary::from(data))])))
+    }
+
+    /// Creates a new message with a value section
+    pub fn from_value(value: impl Into<Value>) -> Self {
+        Self::new(Body::Value(AmqpValue(value.into())))
+    }
+
+    /// Creates a new message with a single sequence section
+    pub fn from_sequence(sequence: impl Into<Vec<Value>>) -> Self {
+        Self::new(Body::Sequence(Batch::from(vec![AmqpSequence(
+            sequence.into(),
+        )])))
+    }
+
+    /// The body of the message
+    pub fn body(&self) -> &AmqpMessageBody {
+        &self.message.body
+    }
+
+    /// A mutable reference to the body of the message
+    pub fn body_mut(&mut self) -> &mut AmqpMessageBody {
+        &mut self.message.body
+    }
+
+    /// Sets the body of the message
+    pub fn set_body(&mut self, body: impl Into<AmqpMessageBody>) {
+        self.message.body = body.into();
+    }
+
+    /// The transport headers of the message
+    pub fn header(&self) -> Option<&Header> {
+        self.message.header.as_ref()
+    }
+
+    /// A mutable reference to the transport headers of the message
+    pub fn header_mut(&mut self) -> &mut Option<Header> {
+        &mut self.message.header
+    }
+
+    /// The delivery-specific non-standard properties of the message
+    pub fn delivery_annotations(&self) -> Option<&DeliveryAnnotations> {
+        self.message.delivery_annotations.as_ref()
+    }
+
+    /// A mutable reference to the delivery annotations of the message
+    pub fn delivery_annotations_mut(&mut self) -> &mut Option<DeliveryAnnotations> {
+        &mut self.message.delivery_annotations
+    }
+
+    /// The properties of the message which are aimed at the infrastructure, eg. the sequence
+    /// number and offset that the service assigns to an event
+    pub fn message_annotations(&self) -> Option<&MessageAnnotations> {
+        self.message.message_annotations.as_ref()
+    }
+
+    /// A mutable reference to the message annotations of the message
+    pub fn message_annotations_mut(&mut self) -> &mut Option<MessageAnnotations> {
+        &mut self.message.message_annotations
+    }
+
+    /// The immutable properties of the message, eg. the message id and content type
+    pub fn properties(&self) -> Option<&Properties> {
+        self.message.properties.as_ref()
+    }
+
+    /// A mutable reference to the properties of the message
+    pub fn properties_mut(&mut self) -> &mut Option<Properties> {
+        &mut self.message.properties
+    }
+
+    /// The application-defined properties of the message
+    pub fn application_properties(&self) -> Option<&ApplicationProperties> {
+        self.message.application_properties.as_ref()
+    }
+
+    /// A mutable reference to the application properties of the message
+    pub fn application_properties_mut(&mut self) -> &mut Option<ApplicationProperties> {
+        &mut self.message.application_properties
+    }
+
+    /// The transport footers of the message
+    pub fn footer(&self) -> Option<&Footer> {
+        self.message.footer.as_ref()
+    }
+
+    /// A mutable reference to the transport footers of the message
+    pub fn footer_mut(&mut self) -> &mut Option<Footer> {
+        &mut self.message.footer
+    }
+
+    /// Gets the raw AMQP message
+    pub fn raw_amqp_message(&self) -> &Message<Body<Value>> {
+        &self.message
+    }
+
+    /// Consumes the message and returns the raw AMQP message
+    pub fn into_raw_amqp_message(self) -> Message<Body<Value>> {
+        self.message
+    }
+
+    /// The binary data of the body if it consists of exactly one data section
+    pub(crate) fn single_data(&self) -> Option<&[u8]> {
+        match &self.message.body {
+            Body::Data(batch) if batch.len() == 1 => Some(batch[0].0.as_ref()),
+            _ => None,
+        }
+    }
+}
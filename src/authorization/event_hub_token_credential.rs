@@ -1,13 +1,18 @@
-use azure_core::auth::{TokenCredential, AccessToken};
+use std::{future::Future, pin::Pin};
 
-use super::shared_access_credential::SharedAccessCredential;
+use azure_core::auth::{AccessToken, TokenCredential};
+
+use super::{
+    event_hub_token_provider::{EventHubTokenProvider, EventHubTokenType, TokenCredentialProvider},
+    shared_access_credential::SharedAccessCredential,
+};
 
-// FIXME: This is an exact copy from the Service Bus crate. This should probably moved
-// to a common crate.
 /// Provides a generic token-based credential for a given Event Hub instance.
 ///
-/// This supports [`SharedAccessCredential`] and any other credential type that implements
-/// [`TokenCredential`], eg. [`azure_identity::DefaultAzureCredential`].
+/// This supports [`SharedAccessCredential`], any other credential type that implements
+/// [`TokenCredential`], eg. [`azure_identity::DefaultAzureCredential`], which are converted with
+/// [`From::from`], and any custom [`EventHubTokenProvider`], which is wrapped with
+/// [`EventHubTokenCredential::from_provider`].
 ///
 /// # Example
 ///
@@ -18,28 +23,21 @@ use super::shared_access_credential::SharedAccessCredential;
 /// let default_credential = DefaultAzureCredential::create(TokenCredentialOptions::default()).unwrap();
 /// let credential = EventHubTokenCredential::from(default_credential);
 /// ```
-pub enum EventHubTokenCredential {
-    // FIXME: This is a temporary workaround until specialization is stablized.
-    /// Shared Access Signature credential.
-    SharedAccessCredential(SharedAccessCredential),
-
-    // TODO: Is the use of trait object here justified?
-    /// Other credential types.
-    Other(Box<dyn TokenCredential>),
+pub struct EventHubTokenCredential {
+    provider: Box<dyn EventHubTokenProvider>,
 }
 
 impl std::fmt::Debug for EventHubTokenCredential {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::SharedAccessCredential(_arg0) => f.debug_tuple("SharedAccessCredential").finish(),
-            Self::Other(_arg0) => f.debug_tuple("Other").finish(),
-        }
+        f.debug_struct("EventHubTokenCredential")
+            .field("token_type", &self.provider.token_type())
+            .finish()
     }
 }
 
 impl From<SharedAccessCredential> for EventHubTokenCredential {
     fn from(source: SharedAccessCredential) -> Self {
-        Self::SharedAccessCredential(source)
+        Self::from_provider(source)
     }
 }
 
@@ -48,7 +46,36 @@ where
     TC: TokenCredential + 'static,
 {
     fn from(source: TC) -> Self {
-        Self::Other(Box::new(source) as Box<dyn TokenCredential>)
+        Self::from_provider(TokenCredentialProvider::new(source))
+    }
+}
+
+/// Lets a boxed credential be wrapped in a [`TokenCredentialProvider`]
+#[derive(Debug)]
+struct BoxedTokenCredential(Box<dyn TokenCredential>);
+
+impl TokenCredential for BoxedTokenCredential {
+    fn get_token<'life0, 'life1, 'life2, 'async_trait>(
+        &'life0 self,
+        scopes: &'life1 [&'life2 str],
+    ) -> Pin<Box<dyn Future<Output = azure_core::Result<AccessToken>> + Send + 'async_trait>>
+    where
+        Self: 'async_trait,
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        'life2: 'async_trait,
+    {
+        self.0.get_token(scopes)
+    }
+
+    fn clear_cache<'life0, 'async_trait>(
+        &'life0 self,
+    ) -> Pin<Box<dyn Future<Output = azure_core::Result<()>> + Send + 'async_trait>>
+    where
+        Self: 'async_trait,
+        'life0: 'async_trait,
+    {
+        self.0.clear_cache()
     }
 }
 
@@ -59,28 +86,43 @@ impl EventHubTokenCredential {
         source.into()
     }
 
+    /// Creates a credential from a boxed [`TokenCredential`], eg. the one of the former
+    /// `EventHubTokenCredential::Other` variant
+    pub fn from_boxed(credential: Box<dyn TokenCredential>) -> Self {
+        let credential = BoxedTokenCredential(credential);
+        Self::from_provider(TokenCredentialProvider::new(credential))
+    }
+
+    /// Creates a credential whose tokens are provided by a custom [`EventHubTokenProvider`]
+    pub fn from_provider(provider: impl EventHubTokenProvider + 'static) -> Self {
+        Self {
+            provider: Box::new(provider),
+        }
+    }
+
     /// Indicates whether the credential is based on an Event Hubs
     /// shared access policy.
     pub fn is_shared_access_credential(&self) -> bool {
-        matches!(self, EventHubTokenCredential::SharedAccessCredential(_))
+        self.token_type() == EventHubTokenType::SharedAccessSignature
     }
-}
 
-impl EventHubTokenCredential {
-    pub(crate) const DEFAULT_SCOPE: &'static str = "https://eventhubs.azure.net/.default";
-
-    /// Gets a `AccessToken` for the specified resource
-    pub(crate) async fn get_token(&self, scopes: &[&str]) -> azure_core::Result<AccessToken> {
-        match self {
-            EventHubTokenCredential::SharedAccessCredential(credential) => {
-                credential.get_token(scopes).await
-            }
-            EventHubTokenCredential::Other(credential) => credential.get_token(scopes).await,
-        }
+    /// The type of the tokens that are provided by the credential
+    pub fn token_type(&self) -> EventHubTokenType {
+        self.provider.token_type()
+    }
+
+    /// The provider of the tokens
+    pub(crate) fn provider(&self) -> &dyn EventHubTokenProvider {
+        self.provider.as_ref()
     }
 
-    pub(crate) async fn get_token_using_default_resource(&self) -> azure_core::Result<AccessToken> {
-        self.get_token(&[Self::DEFAULT_SCOPE]).await
+    /// Gets a `AccessToken` that authorizes the Event Hubs entity. Only a shared access signature
+    /// is scoped to the entity, the other credentials use the default scope.
+    pub(crate) async fn get_token_for_resource(
+        &self,
+        resource: &str,
+    ) -> azure_core::Result<AccessToken> {
+        self.provider.get_token(resource).await
     }
 }
 
@@ -115,7 +157,7 @@ cfg_not_wasm32! {
                 });
 
             let credential = EventHubTokenCredential::from(mock_credentials);
-            let token_result = credential.get_token(&[resource]).await;
+            let token_result = credential.get_token_for_resource(resource).await;
             assert_eq!(token_result.unwrap().token.secret(), token_value);
         }
 
@@ -140,7 +182,7 @@ cfg_not_wasm32! {
             let default_credential = DefaultAzureCredential::create(TokenCredentialOptions::default()).unwrap();
             let event_hub_token_credential = EventHubTokenCredential::from(default_credential);
             let token = event_hub_token_credential
-                .get_token_using_default_resource()
+                .get_token_for_resource("amqps://namespace.servicebus.windows.net/hub-name")
                 .await
                 .unwrap();
             assert!(!token.token.secret().is_empty())
@@ -0,0 +1,209 @@
+use std::{future::Future, pin::Pin, time::Duration as StdDuration};
+
+use azure_core::auth::{AccessToken, TokenCredential};
+
+use crate::constants::{JSON_WEB_TOKEN_TYPE, SAS_TOKEN_TYPE};
+
+use super::shared_access_credential::SharedAccessCredential;
+
+/// The future returned by [`EventHubTokenProvider::get_token`]
+pub type GetTokenFuture<'a> =
+    Pin<Box<dyn Future<Output = azure_core::Result<AccessToken>> + Send + 'a>>;
+
+/// The type of the tokens that are put to the CBS node of an Event Hubs namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventHubTokenType {
+    /// A token that is based on an Event Hubs shared access signature
+    SharedAccessSignature,
+
+    /// A JSON web token, eg. from Microsoft Entra ID
+    JsonWebToken,
+}
+
+impl EventHubTokenType {
+    /// The value that identifies the token type to the CBS node
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventHubTokenType::SharedAccessSignature => SAS_TOKEN_TYPE,
+            EventHubTokenType::JsonWebToken => JSON_WEB_TOKEN_TYPE,
+        }
+    }
+}
+
+impl std::fmt::Display for EventHubTokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A source of the tokens that authorize the links of a connection to an Event Hubs namespace.
+///
+/// This is implemented for [`SharedAccessCredential`] and for a [`TokenCredentialProvider`], which
+/// wraps any type that implements [`TokenCredential`], eg.
+/// [`azure_identity::DefaultAzureCredential`]. A custom provider, eg. one that issues shared access
+/// signatures from a secret store or that reads the tokens from a file, is turned into an
+/// [`EventHubTokenCredential`](super::EventHubTokenCredential) with
+/// [`EventHubTokenCredential::from_provider`](super::EventHubTokenCredential::from_provider).
+///
+/// The tokens are cached if [`token_expiration_buffer`] returns a buffer, for each resource unless
+/// [`is_resource_scoped`] returns false, and a cached token is replaced once [`should_refresh`]
+/// returns true. Otherwise a token is requested every time a link is authorized.
+///
+/// [`token_expiration_buffer`]: EventHubTokenProvider::token_expiration_buffer
+/// [`is_resource_scoped`]: EventHubTokenProvider::is_resource_scoped
+/// [`should_refresh`]: EventHubTokenProvider::should_refresh
+///
+/// # Example
+///
+/// ```rust
+/// use azure_core::auth::AccessToken;
+/// use azeventhubs::authorization::{
+///     EventHubTokenCredential, EventHubTokenProvider, EventHubTokenType, GetTokenFuture,
+/// };
+///
+/// #[derive(Debug)]
+/// struct TokenFile(std::path::PathBuf);
+///
+/// impl EventHubTokenProvider for TokenFile {
+///     fn token_type(&self) -> EventHubTokenType {
+///         EventHubTokenType::JsonWebToken
+///     }
+///
+///     fn get_token<'a>(&'a self, _resource: &'a str) -> GetTokenFuture<'a> {
+///         Box::pin(async move {
+///             let token = tokio::fs::read_to_string(&self.0).await?;
+///             let expires_on = time::OffsetDateTime::now_utc() + time::Duration::minutes(5);
+///             Ok(AccessToken::new(token.trim().to_string(), expires_on))
+///         })
+///     }
+/// }
+///
+/// let credential = EventHubTokenCredential::from_provider(TokenFile("/var/run/token".into()));
+/// ```
+pub trait EventHubTokenProvider: Send + Sync {
+    /// The type of the tokens that are provided
+    fn token_type(&self) -> EventHubTokenType;
+
+    /// Gets a token that authorizes the Event Hubs entity `resource`, eg.
+    /// `amqps://<namespace>.servicebus.windows.net/<event-hub>/Partitions/0`.
+    fn get_token<'a>(&'a self, resource: &'a str) -> GetTokenFuture<'a>;
+
+    /// Whether a token only authorizes the resource that it was requested for, in which case the
+    /// tokens are cached for each resource. Otherwise one cached token authorizes every resource.
+    ///
+    /// Defaults to `true`
+    fn is_resource_scoped(&self) -> bool {
+        true
+    }
+
+    /// The amount of time before the expiration of a cached token that it is considered expired,
+    /// or `None` if the tokens should not be cached.
+    ///
+    /// Defaults to `None`
+    fn token_expiration_buffer(&self) -> Option<StdDuration> {
+        None
+    }
+
+    /// Whether a cached token should be replaced with a new one.
+    ///
+    /// Defaults to whether the token expires within the
+    /// [`token_expiration_buffer`](EventHubTokenProvider::token_expiration_buffer)
+    fn should_refresh(&self, token: &AccessToken) -> bool {
+        match self.token_expiration_buffer() {
+            Some(buffer) => token.expires_on - buffer <= crate::util::time::now_utc(),
+            None => true,
+        }
+    }
+}
+
+impl EventHubTokenProvider for SharedAccessCredential {
+    fn token_type(&self) -> EventHubTokenType {
+        EventHubTokenType::SharedAccessSignature
+    }
+
+    fn get_token<'a>(&'a self, resource: &'a str) -> GetTokenFuture<'a> {
+        Box::pin(self.get_token_for_resource(resource))
+    }
+}
+
+/// The amount of time before the expiration of a JSON web token that it is considered expired,
+/// so that it is renewed before the service rejects it
+const JSON_WEB_TOKEN_EXPIRATION_BUFFER: StdDuration = StdDuration::from_secs(9 * 60);
+
+/// An [`EventHubTokenProvider`] that gets JSON web tokens from a [`TokenCredential`], eg.
+/// [`azure_identity::DefaultAzureCredential`].
+///
+/// The tokens are requested for the Event Hubs scope, which authorizes any entity, and cached until
+/// they expire within the token expiration buffer, which defaults to 9 minutes. A
+/// [`TokenCredential`] is wrapped in this provider when it is converted into an
+/// [`EventHubTokenCredential`](super::EventHubTokenCredential) with [`From::from`].
+///
+/// # Example
+///
+/// ```rust, no_run
+/// use std::time::Duration;
+///
+/// use azure_identity::{DefaultAzureCredential, TokenCredentialOptions};
+/// use azeventhubs::authorization::{EventHubTokenCredential, TokenCredentialProvider};
+///
+/// let default_credential =
+///     DefaultAzureCredential::create(TokenCredentialOptions::default()).unwrap();
+/// let provider = TokenCredentialProvider::new(default_credential)
+///     .with_token_expiration_buffer(Duration::from_secs(5 * 60));
+/// let credential = EventHubTokenCredential::from_provider(provider);
+/// ```
+#[derive(Debug)]
+pub struct TokenCredentialProvider<TC> {
+    credential: TC,
+    token_expiration_buffer: StdDuration,
+}
+
+impl<TC> TokenCredentialProvider<TC>
+where
+    TC: TokenCredential,
+{
+    /// Creates a provider of the tokens of the credential
+    pub fn new(credential: TC) -> Self {
+        Self {
+            credential,
+            token_expiration_buffer: JSON_WEB_TOKEN_EXPIRATION_BUFFER,
+        }
+    }
+
+    /// Sets the amount of time before the expiration of a cached token that it is considered
+    /// expired
+    pub fn with_token_expiration_buffer(mut self, buffer: StdDuration) -> Self {
+        self.token_expiration_buffer = buffer;
+        self
+    }
+
+    /// The credential that the tokens are requested from
+    pub fn credential(&self) -> &TC {
+        &self.credential
+    }
+}
+
+impl<TC> EventHubTokenProvider for TokenCredentialProvider<TC>
+where
+    TC: TokenCredential,
+{
+    fn token_type(&self) -> EventHubTokenType {
+        EventHubTokenType::JsonWebToken
+    }
+
+    /// The token is requested for the Event Hubs scope, which authorizes any entity
+    fn get_token<'a>(&'a self, _resource: &'a str) -> GetTokenFuture<'a> {
+        Box::pin(self.credential.get_token(&[DEFAULT_SCOPE]))
+    }
+
+    fn is_resource_scoped(&self) -> bool {
+        false
+    }
+
+    fn token_expiration_buffer(&self) -> Option<StdDuration> {
+        Some(self.token_expiration_buffer)
+    }
+}
+
+/// The scope of the tokens that authorize the Event Hubs entities
+pub(crate) const DEFAULT_SCOPE: &str = "https://eventhubs.azure.net/.default";
@@ -7,6 +7,9 @@
 pub use azure_named_key_credential::AzureNamedKeyCredential;
 pub use azure_sas_credential::AzureSasCredential;
 pub use event_hub_token_credential::EventHubTokenCredential;
+pub use event_hub_token_provider::{
+    EventHubTokenProvider, EventHubTokenType, GetTokenFuture, TokenCredentialProvider,
+};
 pub use shared_access_credential::SharedAccessCredential;
 pub use signautre_authorization_resource::*;
 
@@ -15,6 +18,7 @@ mod azure_sas_credential;
 mod signautre_authorization_resource;
 pub(crate) mod event_hub_claim;
 pub(crate) mod event_hub_token_credential;
+pub(crate) mod event_hub_token_provider;
 pub(crate) mod shared_access_credential;
 pub(crate) mod shared_access_signature;
 
@@ -138,6 +138,73 @@ impl SharedAccessCredential {
             expires_on: *signature.signature_expiration(),
         })
     }
+
+    /// Retrieves a token that authorizes the `resource`, eg. an entity of another Event Hub in the
+    /// same namespace.
+    ///
+    /// The signature of the credential is used if it covers the resource or if it cannot be
+    /// regenerated without the key. Otherwise a signature is generated for the resource itself.
+    pub(crate) async fn get_token_for_resource(
+        &self,
+        resource: &str,
+    ) -> azure_core::Result<AccessToken> {
+        let parts = {
+            let signature = self.shared_access_signature.lock().map_err(|error| {
+                azure_core::Error::new(azure_core::error::ErrorKind::Other, error.to_string())
+            })?;
+            if signature.shared_access_key().is_empty()
+                || covers_resource(signature.resource(), resource)
+            {
+                None
+            } else {
+                match &self.source_key_credential {
+                    Some(key_credential) => Some((
+                        key_credential.name().to_string(),
+                        key_credential.key().to_string(),
+                    )),
+                    None => Some((
+                        signature.shared_access_key_name().to_string(),
+                        signature.shared_access_key().to_string(),
+                    )),
+                }
+            }
+        };
+
+        match parts {
+            Some((name, key)) => {
+                let signature = SharedAccessSignature::try_from_parts(
+                    resource.to_lowercase(),
+                    name,
+                    key,
+                    Some(Self::SIGNATURE_EXTENSION_DURATION),
+                )?;
+                Ok(AccessToken {
+                    token: Secret::new(signature.value().to_string()),
+                    expires_on: *signature.signature_expiration(),
+                })
+            }
+            None => self.get_token(&[resource]).await,
+        }
+    }
+}
+
+/// Whether a signature for `signature_resource` also authorizes `resource`, ie. the resource is
+/// the same as or under the signature resource. The schemes are not compared.
+fn covers_resource(signature_resource: &str, resource: &str) -> bool {
+    fn without_scheme(resource: &str) -> String {
+        let resource = resource
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .unwrap_or(resource);
+        resource.trim_end_matches('/').to_lowercase()
+    }
+
+    let signature_resource = without_scheme(signature_resource);
+    let resource = without_scheme(resource);
+    match resource.strip_prefix(&signature_resource) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
 }
 
 #[cfg(test)]
@@ -205,6 +272,55 @@ mod tests {
         assert!(token.expires_on - expected_expiration < TimeSpan::seconds(1));
     }
 
+    #[tokio::test]
+    async fn get_token_for_resource_covered_by_signature_returns_signature_value() {
+        let signature = SharedAccessSignature::try_from_parts(
+            "amqps://namespace.servicebus.windows.net/hub-name",
+            "keyName",
+            "key",
+            None,
+        )
+        .unwrap();
+        let credential = SharedAccessCredential::from_signature(signature.clone());
+
+        let resource = "amqps://namespace.servicebus.windows.net/Hub-Name/Partitions/0";
+        let token = credential.get_token_for_resource(resource).await.unwrap();
+        assert_eq!(token.token.secret(), signature.value());
+    }
+
+    #[tokio::test]
+    async fn get_token_for_resource_of_another_event_hub_is_signed_for_the_resource() {
+        let source_credential = AzureNamedKeyCredential::new("keyName", "key");
+        let credential = SharedAccessCredential::try_from_named_key_credential(
+            source_credential,
+            "amqps://namespace.servicebus.windows.net/hub-name",
+        )
+        .unwrap();
+
+        // The name of another Event Hub that starts with the name of the signed one
+        let resource = "amqps://namespace.servicebus.windows.net/hub-name-2/Partitions/0";
+        let token = credential.get_token_for_resource(resource).await.unwrap();
+        let value = token.token.secret();
+        let encoded_resource: String =
+            url::form_urlencoded::byte_serialize(resource.to_lowercase().as_bytes()).collect();
+        assert!(value.ends_with(&format!("&sr={}", encoded_resource)));
+        assert!(value.contains("&skn=keyName&"));
+    }
+
+    #[tokio::test]
+    async fn get_token_for_resource_without_the_key_returns_signature_value() {
+        let expires_on = crate::util::time::now_utc() + TimeSpan::hours(2);
+        let value = format!("SharedAccessSignature sr=https%3A%2F%2Ffake-test.servicebus.windows.net%2Fhub-name&sig=nNBNavJfBiHuXUzWOLhSvI3bVgqbQUzA7Po8%2F4wQQng%3D&se={}&skn=fakeKey", expires_on.unix_timestamp());
+        let signature = SharedAccessSignature::try_from_signature(&value).unwrap();
+        let credential = SharedAccessCredential::from_signature(signature);
+
+        let token = credential
+            .get_token_for_resource("amqps://fake-test.servicebus.windows.net/other-hub")
+            .await
+            .unwrap();
+        assert_eq!(token.token.secret(), value);
+    }
+
     #[tokio::test]
     async fn get_token_does_not_extend_an_expired_token_when_created_without_the_key() {
         let expires_on = crate::util::time::now_utc() - TimeSpan::hours(2);
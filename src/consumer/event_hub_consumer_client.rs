@@ -1,15 +1,21 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, time::Duration as StdDuration};
+
+use futures_util::Stream;
 
 use crate::{
-    amqp::amqp_consumer::EventStream,
+    amqp::amqp_consumer::{EventBatchStream, EventStream, PartitionEventStream},
     authorization::{event_hub_token_credential::EventHubTokenCredential, AzureNamedKeyCredential, AzureSasCredential},
     core::BasicRetryPolicy,
     event_hubs_properties::EventHubProperties,
     event_hubs_retry_policy::EventHubsRetryPolicy,
+    transport_kind::TransportConsumerKind,
     EventHubConnection, EventHubsRetryOptions,
 };
 
-use super::{EventHubConsumerClientOptions, EventPosition, ReadEventOptions};
+use super::{
+    ConsumerPositionSource, EventHubConsumerClientOptions, EventPosition, PartitionLag,
+    ReadEventOptions,
+};
 
 /// A client responsible for reading [`crate::EventData`] from a specific Event Hub
 /// as a member of a specific consumer group.
@@ -288,6 +294,72 @@ where
             .await
     }
 
+    /// Computes how far the consumers of the consumer group are behind the last event enqueued in
+    /// each partition, from the positions that they have reached.
+    ///
+    /// A partition without a position is reported as lagging by all the events that it retains.
+    /// The positions of the events that a stream of the client yields are recorded in a
+    /// [`ConsumerPositionTracker`](crate::consumer::ConsumerPositionTracker) that is attached to
+    /// the stream, see its documentation.
+    pub async fn get_consumer_lag<S>(
+        &mut self,
+        positions: &S,
+    ) -> Result<Vec<PartitionLag>, azure_core::Error>
+    where
+        S: ConsumerPositionSource,
+    {
+        get_consumer_lag::<RP, S>(
+            &mut self.connection,
+            &self.consumer_group,
+            &self.options.retry_options,
+            positions,
+        )
+        .await
+    }
+
+    /// Returns a `Stream` that yields the lag of the consumer group in every partition, as
+    /// computed by [`get_consumer_lag`](Self::get_consumer_lag), once immediately and then every
+    /// `refresh_interval`.
+    ///
+    /// The stream holds a shared handle to the connection of the client instead of borrowing the
+    /// client, so the client can keep reading events while the lag is watched. The stream should be
+    /// dropped before the client is closed, as the client does not close a connection that is
+    /// still shared.
+    ///
+    /// The stream never ends on its own. An error is yielded in place of the refresh that failed,
+    /// and the following refreshes are still attempted. The stream is not `Unpin`, so it must be
+    /// pinned, for example with `Box::pin`, before `.next()` can be called on it.
+    pub fn watch_consumer_lag<'a, S>(
+        &mut self,
+        positions: &'a S,
+        refresh_interval: StdDuration,
+    ) -> impl Stream<Item = Result<Vec<PartitionLag>, azure_core::Error>> + 'a
+    where
+        S: ConsumerPositionSource + Sync,
+        RP: 'a,
+    {
+        let state = (
+            self.connection.clone_as_shared(),
+            self.consumer_group.clone(),
+            self.options.retry_options.clone(),
+            true,
+        );
+        futures_util::stream::unfold(state, move |state| async move {
+            let (mut connection, consumer_group, retry_options, is_first) = state;
+            if !is_first {
+                crate::util::time::sleep(refresh_interval).await;
+            }
+            let lags = get_consumer_lag::<RP, S>(
+                &mut connection,
+                &consumer_group,
+                &retry_options,
+                positions,
+            )
+            .await;
+            Some((lags, (connection, consumer_group, retry_options, false)))
+        })
+    }
+
     /// Reads events from the requested partition as an `Stream`, allowing events to be `.await`ed
     /// as they become available on the partition.
     pub async fn read_events_from_partition(
@@ -297,17 +369,7 @@ where
         read_event_options: ReadEventOptions,
     ) -> Result<EventStream<'_, RP>, azure_core::Error> {
         let consumer = self
-            .connection
-            .create_transport_consumer(
-                &self.consumer_group,
-                partition_id,
-                self.options.identifier.clone(),
-                starting_position,
-                RP::from(self.options.retry_options.clone()),
-                read_event_options.track_last_enqueued_event_properties,
-                read_event_options.owner_level,
-                Some(read_event_options.prefetch_count),
-            )
+            .create_consumer(partition_id, starting_position, &read_event_options)
             .await?;
 
         let event_stream = EventStream::with_consumer(
@@ -319,14 +381,192 @@ where
 
     /// Reads events from all partitions as an `Stream`, allowing events to be `.await`ed
     /// as they become available on the partition.
+    ///
+    /// The partitions are read in a round robin fashion so that a busy partition does not starve
+    /// the others. Each event is yielded as a [`PartitionEvent`](crate::consumer::PartitionEvent)
+    /// whose [`PartitionContext`](crate::consumer::PartitionContext) identifies its partition.
     pub async fn read_events(
         &mut self,
         start_reading_at_earliest_event: bool,
         read_event_options: ReadEventOptions,
+    ) -> Result<PartitionEventStream<'_, RP>, azure_core::Error>
+    where
+        RP: 'static,
+    {
+        let track_last_enqueued_event_properties =
+            read_event_options.track_last_enqueued_event_properties;
+        let fully_qualified_namespace = self.connection.fully_qualified_namespace().to_string();
+        let event_hub_name = self.connection.event_hub_name().to_string();
+        let consumer_group = self.consumer_group.clone();
+
+        let event_stream = self
+            .read_events_from_all_partitions(start_reading_at_earliest_event, &read_event_options)
+            .await?;
+        Ok(PartitionEventStream::new(
+            event_stream,
+            &fully_qualified_namespace,
+            &event_hub_name,
+            &consumer_group,
+            track_last_enqueued_event_properties,
+        ))
+    }
+
+    /// Reads events from the requested partition as an owned `Stream`.
+    ///
+    /// Unlike [`read_events_from_partition`](Self::read_events_from_partition), the stream does
+    /// not borrow the client. It holds a shared handle to the connection of the client instead, so
+    /// it can be moved into a spawned task, and several streams created by the same client can be
+    /// read concurrently.
+    pub async fn read_owned_events_from_partition(
+        &mut self,
+        partition_id: &str,
+        starting_position: EventPosition,
+        read_event_options: ReadEventOptions,
+    ) -> Result<EventStream<'static, RP>, azure_core::Error>
+    where
+        RP: 'static,
+    {
+        let consumer = self
+            .create_consumer(partition_id, starting_position, &read_event_options)
+            .await?;
+
+        let client = self.connection.inner.clone_as_shared();
+        Ok(EventStream::with_consumer(client, consumer))
+    }
+
+    /// Reads events from all partitions as an owned `Stream`.
+    ///
+    /// This is the same as [`read_events`](Self::read_events) except that the stream holds a
+    /// shared handle to the connection of the client instead of borrowing the client. See
+    /// [`read_owned_events_from_partition`](Self::read_owned_events_from_partition).
+    pub async fn read_owned_events(
+        &mut self,
+        start_reading_at_earliest_event: bool,
+        read_event_options: ReadEventOptions,
+    ) -> Result<PartitionEventStream<'static, RP>, azure_core::Error>
+    where
+        RP: 'static,
+    {
+        let consumers = self
+            .create_consumers_for_all_partitions(
+                start_reading_at_earliest_event,
+                &read_event_options,
+            )
+            .await?;
+
+        let retry_policy = RP::from(self.options.retry_options.clone());
+        let client = self.connection.inner.clone_as_shared();
+        let event_stream = EventStream::with_multiple_consumers(client, consumers, retry_policy);
+        Ok(PartitionEventStream::new(
+            event_stream,
+            self.connection.fully_qualified_namespace(),
+            self.connection.event_hub_name(),
+            &self.consumer_group,
+            read_event_options.track_last_enqueued_event_properties,
+        ))
+    }
+
+    /// Reads batches of events from the requested partition as an `Stream`.
+    ///
+    /// Each batch holds up to `max_batch_size` events and is yielded as soon as it is full or once
+    /// `max_wait_time` has elapsed, in which case it may be empty. A `max_batch_size` of zero is
+    /// treated as one.
+    pub async fn read_event_batches_from_partition(
+        &mut self,
+        partition_id: &str,
+        starting_position: EventPosition,
+        max_batch_size: usize,
+        max_wait_time: StdDuration,
+        read_event_options: ReadEventOptions,
+    ) -> Result<EventBatchStream<'_, RP>, azure_core::Error> {
+        let consumer = self
+            .create_consumer(partition_id, starting_position, &read_event_options)
+            .await?;
+
+        let batch_stream = EventBatchStream::with_consumer(
+            &mut self.connection.inner,
+            consumer,
+            max_batch_size,
+            max_wait_time,
+        );
+        Ok(batch_stream)
+    }
+
+    /// Reads batches of events from all partitions as an `Stream`.
+    ///
+    /// A batch may hold events from different partitions. Each batch holds up to `max_batch_size`
+    /// events and is yielded as soon as it is full or once `max_wait_time` has elapsed, in which
+    /// case it may be empty. A `max_batch_size` of zero is treated as one.
+    pub async fn read_event_batches(
+        &mut self,
+        start_reading_at_earliest_event: bool,
+        max_batch_size: usize,
+        max_wait_time: StdDuration,
+        read_event_options: ReadEventOptions,
+    ) -> Result<EventBatchStream<'_, RP>, azure_core::Error>
+    where
+        RP: 'static,
+    {
+        let event_stream = self
+            .read_events_from_all_partitions(start_reading_at_earliest_event, &read_event_options)
+            .await?;
+        Ok(EventBatchStream::with_event_stream(
+            event_stream,
+            max_batch_size,
+            max_wait_time,
+        ))
+    }
+
+    async fn read_events_from_all_partitions(
+        &mut self,
+        start_reading_at_earliest_event: bool,
+        read_event_options: &ReadEventOptions,
     ) -> Result<EventStream<'_, RP>, azure_core::Error>
     where
         RP: 'static,
     {
+        let consumers = self
+            .create_consumers_for_all_partitions(
+                start_reading_at_earliest_event,
+                read_event_options,
+            )
+            .await?;
+
+        // Create an event stream that will read from all consumers
+        let retry_policy = RP::from(self.options.retry_options.clone());
+        let event_stream = EventStream::with_multiple_consumers(
+            &mut self.connection.inner,
+            consumers,
+            retry_policy,
+        );
+        Ok(event_stream)
+    }
+
+    async fn create_consumer(
+        &mut self,
+        partition_id: &str,
+        starting_position: EventPosition,
+        read_event_options: &ReadEventOptions,
+    ) -> Result<TransportConsumerKind<RP>, azure_core::Error> {
+        self.connection
+            .create_transport_consumer(
+                &self.consumer_group,
+                partition_id,
+                self.options.identifier.clone(),
+                starting_position,
+                RP::from(self.options.retry_options.clone()),
+                read_event_options.track_last_enqueued_event_properties,
+                read_event_options.owner_level,
+                Some(read_event_options.prefetch_count),
+            )
+            .await
+    }
+
+    async fn create_consumers_for_all_partitions(
+        &mut self,
+        start_reading_at_earliest_event: bool,
+        read_event_options: &ReadEventOptions,
+    ) -> Result<Vec<TransportConsumerKind<RP>>, azure_core::Error> {
         let starting_position = match start_reading_at_earliest_event {
             true => EventPosition::earliest(),
             false => EventPosition::latest(),
@@ -338,31 +578,12 @@ where
         // Create one consumer per partition
         let mut consumers = Vec::with_capacity(partitions.len());
         for partition in partitions {
-            let retry_policy = RP::from(self.options.retry_options.clone());
             let consumer = self
-                .connection
-                .create_transport_consumer(
-                    &self.consumer_group,
-                    &partition,
-                    self.options.identifier.clone(),
-                    starting_position.clone(),
-                    retry_policy,
-                    read_event_options.track_last_enqueued_event_properties,
-                    read_event_options.owner_level,
-                    Some(read_event_options.prefetch_count),
-                )
+                .create_consumer(&partition, starting_position.clone(), read_event_options)
                 .await?;
             consumers.push(consumer);
         }
-
-        // Create an event stream that will read from all consumers
-        let retry_policy = RP::from(self.options.retry_options.clone());
-        let event_stream = EventStream::with_multiple_consumers(
-            &mut self.connection.inner,
-            consumers,
-            retry_policy,
-        );
-        Ok(event_stream)
+        Ok(consumers)
     }
 
     /// Closes the consumer.
@@ -372,3 +593,222 @@ where
         self.connection.close_if_owned().await
     }
 }
+
+async fn get_consumer_lag<RP, S>(
+    connection: &mut EventHubConnection,
+    consumer_group: &str,
+    retry_options: &EventHubsRetryOptions,
+    positions: &S,
+) -> Result<Vec<PartitionLag>, azure_core::Error>
+where
+    RP: EventHubsRetryPolicy + From<EventHubsRetryOptions> + Send,
+    S: ConsumerPositionSource,
+{
+    let mut positions = positions
+        .consumer_positions(
+            connection.fully_qualified_namespace(),
+            connection.event_hub_name(),
+            consumer_group,
+        )
+        .await?;
+
+    let partition_ids = connection
+        .get_partition_ids(RP::from(retry_options.clone()))
+        .await?;
+    let mut lags = Vec::with_capacity(partition_ids.len());
+    for partition_id in partition_ids {
+        let properties = connection
+            .get_partition_properties(&partition_id, RP::from(retry_options.clone()))
+            .await?;
+        lags.push(PartitionLag::new(
+            &properties,
+            positions.remove(&partition_id),
+        ));
+    }
+    Ok(lags)
+}
+
+#[cfg(all(test, feature = "in-memory-transport"))]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use futures_util::StreamExt;
+
+    use crate::{
+        consumer::{ConsumerPositionTracker, EventPosition},
+        in_memory::fixture::TestEventHub,
+        primitives::{CheckpointStore, EventProcessorCheckpoint, InMemoryCheckpointStore},
+    };
+
+    use super::EventHubConsumerClient;
+
+    #[tokio::test]
+    async fn owned_streams_of_one_consumer_are_read_in_spawned_tasks() {
+        let mut test_hub = TestEventHub::new(2);
+        let mut consumer = test_hub.consumer();
+        for partition_id in ["0", "1"] {
+            test_hub.publish(partition_id, &[partition_id]).await;
+        }
+
+        let mut handles = Vec::new();
+        for partition_id in ["0", "1"] {
+            let mut stream = consumer
+                .read_owned_events_from_partition(
+                    partition_id,
+                    EventPosition::earliest(),
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+            handles.push(tokio::spawn(async move {
+                let event = stream.next().await.unwrap().unwrap();
+                stream.close().await.unwrap();
+                event.body().unwrap().to_vec()
+            }));
+        }
+        let mut stream = consumer
+            .read_owned_events(true, Default::default())
+            .await
+            .unwrap();
+        let all_partitions = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for _ in 0..2 {
+                let event = stream.next().await.unwrap().unwrap();
+                bodies.push(event.body().unwrap().to_vec());
+            }
+            stream.close().await.unwrap();
+            bodies.sort();
+            bodies
+        });
+
+        // The client is no longer borrowed by the streams
+        consumer.close().await.unwrap();
+        let mut bodies = Vec::new();
+        for handle in handles {
+            bodies.push(handle.await.unwrap());
+        }
+        assert_eq!(bodies, [b"0".to_vec(), b"1".to_vec()]);
+        assert_eq!(
+            all_partitions.await.unwrap(),
+            [b"0".to_vec(), b"1".to_vec()]
+        );
+    }
+
+    #[tokio::test]
+    async fn consumer_lag_is_computed_from_tracker_and_checkpoints() {
+        let mut test_hub = TestEventHub::new(2);
+        let mut consumer = test_hub.consumer();
+        test_hub.publish("0", &["a", "b", "c"]).await;
+
+        let tracker = ConsumerPositionTracker::new();
+        let mut stream = consumer
+            .read_events_from_partition("0", EventPosition::earliest(), Default::default())
+            .await
+            .unwrap()
+            .with_position_tracker(tracker.clone());
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            tracker.position("0").unwrap().sequence_number(),
+            first.sequence_number()
+        );
+        stream.close().await.unwrap();
+
+        let lags = consumer.get_consumer_lag(&tracker).await.unwrap();
+        assert_eq!(lags.len(), 2);
+        assert_eq!(lags[0].partition_id(), "0");
+        assert_eq!(lags[0].event_lag(), 2);
+        assert_eq!(lags[0].last_enqueued_sequence_number(), 2);
+        assert!(lags[0].time_lag().is_some());
+        assert_eq!(lags[1].event_lag(), 0);
+        assert_eq!(lags[1].consumer_position(), None);
+
+        let checkpoint_store = InMemoryCheckpointStore::new();
+        checkpoint_store
+            .update_checkpoint(EventProcessorCheckpoint {
+                fully_qualified_namespace: test_hub.connection.fully_qualified_namespace().to_string(),
+                event_hub_name: TestEventHub::NAME.to_string(),
+                consumer_group: EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME.to_string(),
+                partition_id: "0".to_string(),
+                offset: None,
+                sequence_number: Some(2),
+            })
+            .await
+            .unwrap();
+        let mut lag_stream =
+            Box::pin(consumer.watch_consumer_lag(&checkpoint_store, StdDuration::from_millis(10)));
+        for _ in 0..2 {
+            let lags = lag_stream.next().await.unwrap().unwrap();
+            assert_eq!(lags[0].event_lag(), 0);
+            assert_eq!(lags[0].time_lag(), Some(StdDuration::ZERO));
+
+            // The client is not borrowed by the stream
+            assert_eq!(consumer.get_partition_ids().await.unwrap(), ["0", "1"]);
+        }
+
+        let share_count = test_hub.connection.share_count();
+        drop(lag_stream);
+        assert_eq!(test_hub.connection.share_count(), share_count - 1);
+        consumer.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn consumer_reads_event_batches() {
+        let mut test_hub = TestEventHub::new(2);
+        let mut consumer = test_hub.consumer();
+        test_hub.publish("0", &["a", "b", "c", "d", "e"]).await;
+        test_hub.publish("1", &["f"]).await;
+
+        let max_wait_time = StdDuration::from_millis(20);
+        let mut stream = consumer
+            .read_event_batches_from_partition(
+                "0",
+                EventPosition::earliest(),
+                2,
+                max_wait_time,
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        let mut sizes = Vec::new();
+        for _ in 0..4 {
+            let batch = stream.next().await.unwrap().unwrap();
+            sizes.push(batch.len());
+        }
+        // The last batch is empty because the partition has no more events
+        assert_eq!(sizes, [2, 2, 1, 0]);
+        assert_eq!(
+            stream
+                .read_last_enqueued_event_properties("0")
+                .sequence_number(),
+            Some(4)
+        );
+        stream.close().await.unwrap();
+
+        let mut stream = consumer
+            .read_event_batches(true, 10, max_wait_time, Default::default())
+            .await
+            .unwrap();
+        let batch = stream.next().await.unwrap().unwrap();
+        let mut bodies: Vec<_> = batch
+            .iter()
+            .map(|event| event.body().unwrap().to_vec())
+            .collect();
+        bodies.sort();
+        assert_eq!(
+            bodies,
+            [b"a", b"b", b"c", b"d", b"e", b"f"].map(|b| b.to_vec())
+        );
+        // The properties are kept for each partition
+        for (partition_id, sequence_number) in [("0", 4), ("1", 0)] {
+            let properties = stream.read_last_enqueued_event_properties(partition_id);
+            assert_eq!(properties.sequence_number(), Some(sequence_number));
+        }
+        assert!(stream
+            .read_last_enqueued_event_properties("2")
+            .sequence_number()
+            .is_none());
+        stream.close().await.unwrap();
+
+        consumer.close().await.unwrap();
+    }
+}
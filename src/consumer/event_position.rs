@@ -1,7 +1,7 @@
 use time::OffsetDateTime;
 
 const START_OF_STREAM_OFFSET: &str = "-1";
-const END_OF_STREAM_OFFSET: &str = "@latest";
+pub(crate) const END_OF_STREAM_OFFSET: &str = "@latest";
 
 /// The position of events in an Event Hub partition, typically used in the creation of
 /// an [`crate::consumer::EventHubConsumerClient`].
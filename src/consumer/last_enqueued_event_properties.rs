@@ -1,10 +1,128 @@
 use time::OffsetDateTime;
 
+use crate::{amqp::amqp_message_extension::AmqpMessageExt, ReceivedEventData};
+
 /// A set of information about the enqueued state of a partition, as observed by the consumer.
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub struct LastEnqueuedEventProperties {
     pub(crate) sequence_number: Option<i64>,
     pub(crate) offset: Option<i64>,
     pub(crate) enqueued_time: Option<OffsetDateTime>,
     pub(crate) last_received_time: Option<OffsetDateTime>,
 }
+
+impl LastEnqueuedEventProperties {
+    /// Reads the properties from the delivery annotations that the service adds to an event when
+    /// the consumer tracks the last enqueued event
+    pub(crate) fn from_event(event: &ReceivedEventData) -> Self {
+        let message = event.raw_amqp_message();
+        Self {
+            sequence_number: message.last_partition_sequence_number(),
+            offset: message.last_partition_offset(),
+            enqueued_time: message.last_partition_enqueued_time(),
+            last_received_time: message.last_partition_properties_retrieval_time(),
+        }
+    }
+
+    /// Whether none of the properties is available
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The sequence number of the last event enqueued in the partition
+    pub fn sequence_number(&self) -> Option<i64> {
+        self.sequence_number
+    }
+
+    /// The offset of the last event enqueued in the partition
+    pub fn offset(&self) -> Option<i64> {
+        self.offset
+    }
+
+    /// The date and time, in UTC, that the last event was enqueued in the partition
+    pub fn enqueued_time(&self) -> Option<OffsetDateTime> {
+        self.enqueued_time
+    }
+
+    /// The date and time, in UTC, that the information about the last enqueued event was
+    /// retrieved by the service
+    pub fn last_received_time(&self) -> Option<OffsetDateTime> {
+        self.last_received_time
+    }
+}
+
+#[cfg(all(test, feature = "in-memory-transport"))]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use futures_util::StreamExt;
+
+    use crate::{
+        consumer::{EventHubConsumerClient, EventPosition, ReadEventOptions},
+        in_memory::fixture::TestEventHub,
+        primitives::{PartitionReceiver, PartitionReceiverOptions},
+    };
+
+    #[tokio::test]
+    async fn last_enqueued_event_properties_are_tracked_by_stream_and_receiver() {
+        let mut test_hub = TestEventHub::new(1);
+        let mut consumer = test_hub.consumer();
+        test_hub.publish("0", &["a", "b", "c"]).await;
+        let last_enqueued = test_hub.event_hub.partition_properties("0").unwrap();
+
+        let mut stream = consumer
+            .read_events_from_partition("0", EventPosition::earliest(), Default::default())
+            .await
+            .unwrap();
+        assert!(stream
+            .read_last_enqueued_event_properties("0")
+            .sequence_number()
+            .is_none());
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.sequence_number(), 0);
+        let properties = stream.read_last_enqueued_event_properties("0");
+        assert_eq!(properties.sequence_number(), Some(2));
+        assert_eq!(
+            properties.offset(),
+            Some(last_enqueued.last_enqueued_offset())
+        );
+        assert_eq!(
+            properties.enqueued_time(),
+            Some(*last_enqueued.last_enqueued_time())
+        );
+        assert!(properties.last_received_time().is_some());
+        stream.close().await.unwrap();
+
+        let options = ReadEventOptions::default().with_track_last_enqueued_event_properties(false);
+        let mut stream = consumer
+            .read_events_from_partition("0", EventPosition::earliest(), options)
+            .await
+            .unwrap();
+        stream.next().await.unwrap().unwrap();
+        assert!(stream
+            .read_last_enqueued_event_properties("0")
+            .sequence_number()
+            .is_none());
+        stream.close().await.unwrap();
+        consumer.close().await.unwrap();
+
+        let mut receiver = PartitionReceiver::with_conneciton(
+            EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+            "0",
+            EventPosition::earliest(),
+            test_hub.connection,
+            PartitionReceiverOptions::default(),
+        )
+        .await
+        .unwrap();
+        let events = receiver
+            .recv_batch(1, StdDuration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        let properties = receiver.read_last_enqueued_event_properties();
+        assert_eq!(properties.sequence_number(), Some(2));
+
+        receiver.close().await.unwrap();
+    }
+}
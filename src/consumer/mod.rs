@@ -4,17 +4,20 @@ mod event_hub_consumer_client;
 mod event_hub_consumer_client_options;
 mod event_position;
 mod last_enqueued_event_properties;
-// mod partition_context; // TODO: implement processor
-// mod partition_event; // TODO: implement processor
+mod partition_context;
+mod partition_event;
+mod partition_lag;
 mod read_event_options;
 
 pub mod error;
 
-pub use crate::amqp::amqp_consumer::EventStream;
+pub use crate::amqp::amqp_consumer::{EventBatchStream, EventStream, PartitionEventStream};
+pub use crate::amqp::amqp_system_properties::AmqpSystemProperties;
 pub use event_hub_consumer_client::*;
 pub use event_hub_consumer_client_options::*;
 pub use event_position::*;
 pub use last_enqueued_event_properties::*;
-// pub use partition_context::PartitionContext; // TODO: implement processor
-// pub use partition_event::PartitionEvent; // TODO: implement processor
+pub use partition_context::PartitionContext;
+pub use partition_event::PartitionEvent;
+pub use partition_lag::*;
 pub use read_event_options::*;
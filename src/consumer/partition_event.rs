@@ -8,7 +8,10 @@ use serde_amqp::Value;
 use time::OffsetDateTime;
 
 use crate::{
-    amqp::{amqp_message_extension::AmqpMessageExt, error::RawAmqpMessageError, amqp_system_properties::AmqpSystemProperties},
+    amqp::{
+        amqp_message_extension::AmqpMessageExt, amqp_system_properties::AmqpSystemProperties,
+        error::RawAmqpMessageError,
+    },
     constants::DEFAULT_OFFSET_DATE_TIME,
 };
 
@@ -18,15 +21,15 @@ use super::PartitionContext;
 /// Event Hub service, as well as the received event, if any.
 #[derive(Debug)]
 pub struct PartitionEvent {
-    /// The Event Hub partition that the <see cref="PartitionEvent.Data" /> is associated with.
+    /// The Event Hub partition that the event is associated with.
     pub(crate) partition_context: PartitionContext,
 
-    /// An event that was read from the associated <see cref="PartitionEvent.Partition" />.
+    /// An event that was read from the associated partition.
     pub(crate) raw_amqp_message: Message<Body<Value>>,
 }
 
 impl PartitionEvent {
-    /// Initializes a new instance of the <see cref="PartitionEvent"/> structure.
+    /// Initializes a new instance of the [`PartitionEvent`] structure.
     pub(crate) fn new(
         partition_context: PartitionContext,
         raw_amqp_message: Message<Body<Value>>,
@@ -37,7 +40,7 @@ impl PartitionEvent {
         }
     }
 
-    /// The Event Hub partition that the <see cref="PartitionEvent.Data" /> is associated with.
+    /// The Event Hub partition that the event is associated with.
     pub fn partition_context(&self) -> &PartitionContext {
         &self.partition_context
     }
@@ -119,34 +122,17 @@ impl PartitionEvent {
     /// The date and time, in UTC, of when the event was enqueued in the Event Hub partition.
     ///
     /// This value is read-only and will only be populated for events that have been read from Event
-    /// Hubs. The default value when not populated is [`DEFAULT_OFFSET_DATE_TIME`].
+    /// Hubs. The default value when not populated is `0001-01-01 12:00:00 AM +00:00`.
     pub fn enqueued_time(&self) -> OffsetDateTime {
         self.raw_amqp_message
             .enqueued_time()
             .unwrap_or(DEFAULT_OFFSET_DATE_TIME)
     }
 
-    /// The partition hashing key applied to the batch that the associated [`Event`], was published with.
+    /// The partition hashing key applied to the batch that the associated event was published with.
     ///
     /// This value is read-only and will only be populated for events that have been read from Event Hubs.
     pub fn partition_key(&self) -> Option<&str> {
         self.raw_amqp_message.partition_key()
     }
-
-    pub(crate) fn last_partition_sequence_number(&self) -> Option<i64> {
-        self.raw_amqp_message.last_partition_sequence_number()
-    }
-
-    pub(crate) fn last_partition_offset(&self) -> Option<i64> {
-        self.raw_amqp_message.last_partition_offset()
-    }
-
-    pub(crate) fn last_partition_enqueued_time(&self) -> Option<OffsetDateTime> {
-        self.raw_amqp_message.last_partition_enqueued_time()
-    }
-
-    pub(crate) fn last_partition_properties_retrieval_time(&self) -> Option<OffsetDateTime> {
-        self.raw_amqp_message
-            .last_partition_properties_retrieval_time()
-    }
 }
@@ -0,0 +1,357 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration as StdDuration,
+};
+
+use time::OffsetDateTime;
+
+use crate::{
+    primitives::{CheckpointStore, EventProcessorCheckpoint},
+    PartitionProperties, ReceivedEventData,
+};
+
+/// The position that a consumer has reached in a partition, which is the last event that it has
+/// received or processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConsumerPosition {
+    sequence_number: i64,
+    enqueued_time: Option<OffsetDateTime>,
+}
+
+impl ConsumerPosition {
+    /// Creates a position from the sequence number of the last event and, if known, the date and
+    /// time that it was enqueued
+    pub fn new(sequence_number: i64, enqueued_time: Option<OffsetDateTime>) -> Self {
+        Self {
+            sequence_number,
+            enqueued_time,
+        }
+    }
+
+    /// The position of a consumer that has received the event
+    pub fn from_event(event: &ReceivedEventData) -> Self {
+        Self::new(event.sequence_number(), Some(event.enqueued_time()))
+    }
+
+    /// The position recorded by a checkpoint. Returns `None` if the checkpoint does not record a
+    /// sequence number.
+    ///
+    /// A checkpoint does not record when the event was enqueued, so the lag in time cannot be
+    /// computed from it unless the consumer has caught up.
+    pub fn from_checkpoint(checkpoint: &EventProcessorCheckpoint) -> Option<Self> {
+        checkpoint
+            .sequence_number
+            .map(|sequence_number| Self::new(sequence_number, None))
+    }
+
+    /// The sequence number of the last event
+    pub fn sequence_number(&self) -> i64 {
+        self.sequence_number
+    }
+
+    /// The date and time, in UTC, that the last event was enqueued, if known
+    pub fn enqueued_time(&self) -> Option<OffsetDateTime> {
+        self.enqueued_time
+    }
+}
+
+/// A source of the positions that the consumers of a consumer group have reached, keyed by the
+/// partition identifier.
+///
+/// This is implemented for every [`CheckpointStore`], which reads the positions from the
+/// checkpoints, and for [`ConsumerPositionTracker`], which records the events as the streams
+/// receive them.
+pub trait ConsumerPositionSource {
+    /// Retrieves the positions of the consumers of the given Event Hub and consumer group
+    fn consumer_positions(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> impl Future<Output = Result<HashMap<String, ConsumerPosition>, azure_core::Error>> + Send;
+}
+
+impl<S> ConsumerPositionSource for S
+where
+    S: CheckpointStore + Sync,
+{
+    async fn consumer_positions(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<HashMap<String, ConsumerPosition>, azure_core::Error> {
+        let checkpoints = self
+            .list_checkpoints(fully_qualified_namespace, event_hub_name, consumer_group)
+            .await?;
+        let positions = checkpoints
+            .iter()
+            .filter_map(|checkpoint| {
+                ConsumerPosition::from_checkpoint(checkpoint)
+                    .map(|position| (checkpoint.partition_id.clone(), position))
+            })
+            .collect();
+        Ok(positions)
+    }
+}
+
+/// Records the positions of consumers as they receive events, so that their lag can be
+/// monitored while they are running.
+///
+/// A tracker is attached to an event stream with `with_position_tracker`, eg.
+/// [`EventStream::with_position_tracker`](crate::consumer::EventStream::with_position_tracker),
+/// after which the stream records every event that it yields. Cloning the tracker returns a handle
+/// to the same positions, so several streams can record into one tracker, and the events can
+/// also be given to [`record`](Self::record) by hand, eg. once they have been processed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use azeventhubs::consumer::{
+///     ConsumerPositionTracker, EventHubConsumerClient, EventHubConsumerClientOptions,
+///     EventPosition,
+/// };
+/// use futures_util::StreamExt;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut consumer = EventHubConsumerClient::new_from_connection_string(
+///     EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+///     "<CONNECTION_STRING>",
+///     "<EVENT_HUB_NAME>".to_string(),
+///     EventHubConsumerClientOptions::default(),
+/// )
+/// .await?;
+/// let tracker = ConsumerPositionTracker::new();
+///
+/// let mut events = consumer
+///     .read_owned_events_from_partition("0", EventPosition::earliest(), Default::default())
+///     .await?
+///     .with_position_tracker(tracker.clone());
+/// tokio::spawn(async move {
+///     while let Some(Ok(event)) = events.next().await {
+///         println!("{:?}", event.body());
+///     }
+/// });
+///
+/// let mut lags = Box::pin(consumer.watch_consumer_lag(&tracker, Duration::from_secs(30)));
+/// while let Some(lags) = lags.next().await {
+///     for lag in lags? {
+///         println!("{}: {} events behind", lag.partition_id(), lag.event_lag());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConsumerPositionTracker {
+    positions: Arc<Mutex<HashMap<String, ConsumerPosition>>>,
+}
+
+impl ConsumerPositionTracker {
+    /// Creates a tracker without any position
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the event has been received from the partition
+    pub fn record(&self, partition_id: impl Into<String>, event: &ReceivedEventData) {
+        self.lock()
+            .insert(partition_id.into(), ConsumerPosition::from_event(event));
+    }
+
+    /// The position recorded for the partition
+    pub fn position(&self, partition_id: &str) -> Option<ConsumerPosition> {
+        self.lock().get(partition_id).copied()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ConsumerPosition>> {
+        // The state is always left consistent, so it is safe to ignore poisoning
+        self.positions.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl ConsumerPositionSource for ConsumerPositionTracker {
+    async fn consumer_positions(
+        &self,
+        _fully_qualified_namespace: &str,
+        _event_hub_name: &str,
+        _consumer_group: &str,
+    ) -> Result<HashMap<String, ConsumerPosition>, azure_core::Error> {
+        Ok(self.lock().clone())
+    }
+}
+
+/// How far a consumer is behind the last event enqueued in a partition.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PartitionLag {
+    partition_id: String,
+    last_enqueued_sequence_number: i64,
+    last_enqueued_time: OffsetDateTime,
+    consumer_position: Option<ConsumerPosition>,
+    event_lag: i64,
+    time_lag: Option<StdDuration>,
+}
+
+impl PartitionLag {
+    /// Computes the lag of a consumer at the position, or of a consumer that has not received
+    /// any event if the position is `None`
+    pub fn new(
+        properties: &PartitionProperties,
+        consumer_position: Option<ConsumerPosition>,
+    ) -> Self {
+        let last_enqueued_sequence_number = properties.last_enqueued_sequence_number();
+        let last_enqueued_time = *properties.last_enqueued_time();
+
+        // Events that have expired from the partition can no longer be read, so they do not
+        // count towards the lag
+        let retained_events = match properties.is_empty() {
+            true => 0,
+            false => last_enqueued_sequence_number - properties.beginning_sequence_number() + 1,
+        };
+        let event_lag = match consumer_position {
+            Some(position) => {
+                (last_enqueued_sequence_number - position.sequence_number).clamp(0, retained_events)
+            }
+            None => retained_events,
+        };
+        let time_lag = match (event_lag, consumer_position.and_then(|p| p.enqueued_time)) {
+            (0, _) => Some(StdDuration::ZERO),
+            (_, Some(enqueued_time)) => {
+                let lag = last_enqueued_time - enqueued_time;
+                match lag.is_positive() {
+                    true => Some(lag.unsigned_abs()),
+                    false => Some(StdDuration::ZERO),
+                }
+            }
+            (_, None) => None,
+        };
+
+        Self {
+            partition_id: properties.id().to_string(),
+            last_enqueued_sequence_number,
+            last_enqueued_time,
+            consumer_position,
+            event_lag,
+            time_lag,
+        }
+    }
+
+    /// The identifier of the partition
+    pub fn partition_id(&self) -> &str {
+        &self.partition_id
+    }
+
+    /// The sequence number of the last event enqueued in the partition
+    pub fn last_enqueued_sequence_number(&self) -> i64 {
+        self.last_enqueued_sequence_number
+    }
+
+    /// The date and time, in UTC, that the last event was enqueued in the partition
+    pub fn last_enqueued_time(&self) -> OffsetDateTime {
+        self.last_enqueued_time
+    }
+
+    /// The position that the consumer has reached, or `None` if it has not received any event
+    pub fn consumer_position(&self) -> Option<ConsumerPosition> {
+        self.consumer_position
+    }
+
+    /// The number of events in the partition that the consumer has not received yet
+    pub fn event_lag(&self) -> i64 {
+        self.event_lag
+    }
+
+    /// The time between when the last event received by the consumer and the last event in the
+    /// partition were enqueued.
+    ///
+    /// This is `None` if the consumer is behind but it is not known when its last event was
+    /// enqueued, which is the case for a position read from a checkpoint.
+    pub fn time_lag(&self) -> Option<StdDuration> {
+        self.time_lag
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use time::{macros::datetime, OffsetDateTime};
+
+    use crate::{primitives::EventProcessorCheckpoint, PartitionProperties};
+
+    use super::{ConsumerPosition, PartitionLag};
+
+    const LAST_ENQUEUED_TIME: OffsetDateTime = datetime!(2024-01-01 12:00:00 UTC);
+
+    fn properties(
+        beginning_sequence_number: i64,
+        last_enqueued_sequence_number: i64,
+    ) -> PartitionProperties {
+        PartitionProperties {
+            event_hub_name: "eventhub".to_string(),
+            id: "0".to_string(),
+            beginning_sequence_number,
+            last_enqueued_sequence_number,
+            last_enqueued_offset: last_enqueued_sequence_number * 100,
+            last_enqueued_time: LAST_ENQUEUED_TIME,
+            is_empty: false,
+        }
+    }
+
+    #[test]
+    fn lag_is_measured_from_consumer_position() {
+        let position =
+            ConsumerPosition::new(15, Some(LAST_ENQUEUED_TIME - time::Duration::seconds(30)));
+        let lag = PartitionLag::new(&properties(10, 20), Some(position));
+        assert_eq!(lag.partition_id(), "0");
+        assert_eq!(lag.event_lag(), 5);
+        assert_eq!(lag.time_lag(), Some(StdDuration::from_secs(30)));
+    }
+
+    #[test]
+    fn consumer_without_position_lags_by_all_retained_events() {
+        let lag = PartitionLag::new(&properties(10, 20), None);
+        assert_eq!(lag.event_lag(), 11);
+        assert_eq!(lag.time_lag(), None);
+
+        // Expired events are not counted either
+        let lag = PartitionLag::new(&properties(10, 20), Some(ConsumerPosition::new(2, None)));
+        assert_eq!(lag.event_lag(), 11);
+    }
+
+    #[test]
+    fn caught_up_consumer_has_no_lag() {
+        let lag = PartitionLag::new(&properties(10, 20), Some(ConsumerPosition::new(20, None)));
+        assert_eq!(lag.event_lag(), 0);
+        assert_eq!(lag.time_lag(), Some(StdDuration::ZERO));
+
+        let mut empty = properties(0, -1);
+        empty.is_empty = true;
+        let lag = PartitionLag::new(&empty, None);
+        assert_eq!(lag.event_lag(), 0);
+        assert_eq!(lag.time_lag(), Some(StdDuration::ZERO));
+    }
+
+    #[test]
+    fn position_from_checkpoint_requires_sequence_number() {
+        let mut checkpoint = EventProcessorCheckpoint {
+            fully_qualified_namespace: "namespace".to_string(),
+            event_hub_name: "eventhub".to_string(),
+            consumer_group: "$Default".to_string(),
+            partition_id: "0".to_string(),
+            offset: Some(1500),
+            sequence_number: Some(15),
+        };
+        assert_eq!(
+            ConsumerPosition::from_checkpoint(&checkpoint),
+            Some(ConsumerPosition::new(15, None))
+        );
+
+        checkpoint.sequence_number = None;
+        assert_eq!(ConsumerPosition::from_checkpoint(&checkpoint), None);
+    }
+}
@@ -3,30 +3,21 @@ use std::time::Duration;
 use rand::Rng;
 
 use crate::{
-    event_hubs_retry_mode::EventHubsRetryMode, event_hubs_retry_options::EventHubsRetryOptions,
-    event_hubs_retry_policy::EventHubsRetryPolicy,
+    amqp::error::is_server_busy_error, event_hubs_retry_mode::EventHubsRetryMode,
+    event_hubs_retry_options::EventHubsRetryOptions, event_hubs_retry_policy::EventHubsRetryPolicy,
 };
 
 const DEFAULT_JITTER_FACTOR: f64 = 0.08;
-// const DEFAULT_MINIMUM_THROTTLE_SECONDS: u32 = 4;
-// const DEFAULT_MAXIMUM_THROTTLE_SECONDS: u32 = 8;
 
 /// Default retry policy used by the client.
+///
+/// When the service indicates that it is busy, an additional delay between
+/// [`EventHubsRetryOptions::minimum_throttle_delay`] and
+/// [`EventHubsRetryOptions::maximum_throttle_delay`] is applied before retrying.
 #[derive(Debug, Clone)]
 pub struct BasicRetryPolicy {
     options: EventHubsRetryOptions,
     jitter_factor: f64,
-    // /// The minimum number of seconds to wait before retrying when the service
-    // /// indicates that the client should throttle its requests.
-    // ///
-    // /// TODO: This is not being used right now
-    // minimum_throttle_seconds: u32,
-
-    // /// The maximum number of seconds to wait before retrying when the service
-    // /// indicates that the client should throttle its requests.
-    // ///
-    // /// TODO: This is not being used right now
-    // maximum_throttle_seconds: u32,
 }
 
 impl BasicRetryPolicy {
@@ -46,8 +37,6 @@ impl From<EventHubsRetryOptions> for BasicRetryPolicy {
         Self {
             options,
             jitter_factor: DEFAULT_JITTER_FACTOR,
-            // minimum_throttle_seconds: DEFAULT_MINIMUM_THROTTLE_SECONDS,
-            // maximum_throttle_seconds: DEFAULT_MAXIMUM_THROTTLE_SECONDS,
         }
     }
 }
@@ -59,19 +48,19 @@ impl EventHubsRetryPolicy for BasicRetryPolicy {
 
     fn calculate_retry_delay(
         &self,
-        _last_error: &dyn std::error::Error,
+        last_error: &(dyn std::error::Error + 'static),
         attempt_count: u32,
     ) -> Option<std::time::Duration> {
         if self.options.delay == std::time::Duration::ZERO
             || self.options.maximum_delay == std::time::Duration::ZERO
             || attempt_count > self.options.max_retries.0
-        // || !self.should_retry_last_error(last_error) // TODO:
+            || !self.should_retry(last_error)
         {
             return None;
         }
 
         let base_jitter_seconds = self.options.delay.as_secs_f64() * self.jitter_factor;
-        let retry_delay = match &self.options.mode {
+        let mut retry_delay = match &self.options.mode {
             EventHubsRetryMode::Fixed => {
                 calculate_fixed_delay(self.options.delay.as_secs_f64(), base_jitter_seconds)
             }
@@ -82,7 +71,12 @@ impl EventHubsRetryPolicy for BasicRetryPolicy {
             ),
         };
 
-        // TODO: determine if the error represents a request to throttle
+        if is_server_busy_error(last_error) {
+            retry_delay = retry_delay.saturating_add(calculate_throttle_delay(
+                self.options.minimum_throttle_delay,
+                self.options.maximum_throttle_delay,
+            ));
+        }
 
         // Adjust the delay
         if retry_delay > self.options.maximum_delay {
@@ -103,6 +97,14 @@ fn calculate_fixed_delay(base_delay_seconds: f64, base_jitter_seconds: f64) -> D
     }
 }
 
+fn calculate_throttle_delay(minimum_delay: Duration, maximum_delay: Duration) -> Duration {
+    if maximum_delay <= minimum_delay {
+        return minimum_delay;
+    }
+    let mut rng = rand::thread_rng();
+    rng.gen_range(minimum_delay..=maximum_delay)
+}
+
 fn calculate_exponential_delay(
     base_delay_seconds: f64,
     base_jitter_seconds: f64,
@@ -117,3 +119,114 @@ fn calculate_exponential_delay(
         Duration::from_secs_f64(delay)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use fe2o3_amqp_types::{
+        definitions::{self, AmqpError, ErrorCondition},
+        messaging::Rejected,
+        primitives::Symbol,
+    };
+
+    use crate::{
+        amqp::{
+            amqp_error::SERVER_BUSY_ERROR,
+            error::{AmqpSendError, NotAcceptedError, RecoverAndSendError},
+        },
+        EventHubsRetryMode, EventHubsRetryOptions, EventHubsRetryPolicy,
+    };
+
+    use super::BasicRetryPolicy;
+
+    fn rejected(condition: impl Into<ErrorCondition>) -> RecoverAndSendError {
+        let error = definitions::Error::new(condition, None, None);
+        let rejected = Rejected { error: Some(error) };
+        RecoverAndSendError::from(AmqpSendError::from(NotAcceptedError::Rejected(rejected)))
+    }
+
+    #[test]
+    fn terminal_errors_are_not_retried() {
+        let policy = BasicRetryPolicy::from(EventHubsRetryOptions::default());
+
+        let err = rejected(AmqpError::UnauthorizedAccess);
+        assert!(!policy.should_retry(&err));
+        assert!(policy.calculate_retry_delay(&err, 1).is_none());
+
+        let err = rejected(AmqpError::NotFound);
+        assert!(policy.calculate_retry_delay(&err, 1).is_none());
+
+        let err = RecoverAndSendError::ConnectionScopeDisposed;
+        assert!(policy.calculate_retry_delay(&err, 1).is_none());
+    }
+
+    #[test]
+    fn transient_errors_are_retried() {
+        let policy = BasicRetryPolicy::from(EventHubsRetryOptions::default());
+
+        let err = rejected(AmqpError::InternalError);
+        assert!(policy.should_retry(&err));
+        assert!(policy.calculate_retry_delay(&err, 1).is_some());
+
+        // Errors that are not produced by this crate are assumed to be transient
+        let err = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        assert!(policy.calculate_retry_delay(&err, 1).is_some());
+    }
+
+    #[test]
+    fn server_busy_errors_are_throttled() {
+        let options = EventHubsRetryOptions {
+            mode: EventHubsRetryMode::Fixed,
+            delay: Duration::from_millis(100),
+            minimum_throttle_delay: Duration::from_secs(4),
+            maximum_throttle_delay: Duration::from_secs(8),
+            ..Default::default()
+        };
+        let policy = BasicRetryPolicy::from(options);
+
+        let err = rejected(ErrorCondition::Custom(Symbol::from(SERVER_BUSY_ERROR)));
+        assert!(policy.should_retry(&err));
+        let delay = policy.calculate_retry_delay(&err, 1).unwrap();
+        assert!(delay >= Duration::from_secs(4));
+        assert!(delay <= Duration::from_secs(9));
+
+        let err = rejected(AmqpError::InternalError);
+        let delay = policy.calculate_retry_delay(&err, 1).unwrap();
+        assert!(delay < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn classification_is_preserved_in_azure_core_error() {
+        let policy = BasicRetryPolicy::from(EventHubsRetryOptions::default());
+
+        let err: azure_core::Error = rejected(AmqpError::UnauthorizedAccess).into();
+        assert!(!policy.should_retry(&err));
+    }
+
+    #[test]
+    fn custom_policy_can_query_classification() {
+        #[derive(Clone)]
+        struct CustomPolicy;
+
+        impl EventHubsRetryPolicy for CustomPolicy {
+            fn calculate_try_timeout(&self, _attempt_count: u32) -> Duration {
+                Duration::from_secs(1)
+            }
+
+            fn calculate_retry_delay(
+                &self,
+                last_error: &(dyn std::error::Error + 'static),
+                _attempt_count: u32,
+            ) -> Option<Duration> {
+                self.should_retry(last_error)
+                    .then_some(Duration::from_millis(10))
+            }
+        }
+
+        let err = rejected(AmqpError::UnauthorizedAccess);
+        assert!(CustomPolicy.calculate_retry_delay(&err, 1).is_none());
+        let err = rejected(AmqpError::InternalError);
+        assert!(CustomPolicy.calculate_retry_delay(&err, 1).is_some());
+    }
+}
@@ -25,15 +25,18 @@ pub trait TransportClient: Sized {
 
     async fn get_properties(
         &mut self,
+        event_hub_name: &str,
     ) -> Result<EventHubProperties, Self::RequestResponseError>;
 
     async fn get_partition_properties(
         &mut self,
+        event_hub_name: &str,
         partition_id: &str,
     ) -> Result<PartitionProperties, Self::RequestResponseError>;
 
     async fn create_producer<RP>(
         &mut self,
+        event_hub_name: &str,
         partition_id: Option<String>,
         producer_identifier: Option<String>,
         requested_features: TransportProducerFeatures,
@@ -53,6 +56,7 @@ pub trait TransportClient: Sized {
     #[allow(clippy::too_many_arguments)] // TODO: how to reduce the number of arguments?
     async fn create_consumer<RP>(
         &mut self,
+        event_hub_name: &str,
         consumer_group: &str,
         partition_id: &str,
         consumer_identifier: Option<String>,
@@ -65,12 +69,12 @@ pub trait TransportClient: Sized {
     where
         RP: EventHubsRetryPolicy + Send;
 
-    // async fn recover_consumer<RP>(
-    //     &mut self,
-    //     consumer: &mut Self::Consumer<RP>,
-    // ) -> Result<(), Self::RecoverConsumerError>
-    // where
-    //     RP: EventHubsRetryPolicy + Send;
+    async fn recover_consumer<RP>(
+        &mut self,
+        consumer: &mut Self::Consumer<RP>,
+    ) -> Result<(), Self::RecoverConsumerError>
+    where
+        RP: EventHubsRetryPolicy + Send;
 
     /// Closes the connection to the transport client instance.
     async fn close(
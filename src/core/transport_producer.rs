@@ -14,6 +14,7 @@ pub(crate) trait TransportProducer {
     fn create_batch(
         &self,
         options: CreateBatchOptions,
+        reserve_idempotent_annotations: bool,
     ) -> Result<Self::MessageBatch, Self::CreateBatchError>;
 
     async fn send(
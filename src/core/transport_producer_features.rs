@@ -1,7 +1,9 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum TransportProducerFeatures {
     None = 0u8,
-    // TODO: is this needed given that AMQP protocol should already take care of duplicated message upon recovery?
-    // IdempotentPublishing = 1,
+
+    /// The producer stamps a sequence number on each event so that the service can detect and
+    /// discard duplicates, which may be published when a send is retried after a failure.
+    IdempotentPublishing = 1,
 }
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use fe2o3_amqp::{
+    acceptor::{
+        ConnectionAcceptor, LinkAcceptor, LinkEndpoint, ListenerSessionHandle,
+        SaslAnonymousMechanism, SessionAcceptor,
+    },
+    Sender,
+};
+use fe2o3_amqp_types::definitions::{self, AmqpError};
+use tokio::{net::TcpStream, sync::mpsc, task::JoinSet};
+
+use crate::in_memory::InMemoryEventHub;
+
+use super::{
+    management::{self, ReplyLinks, RequestNode},
+    node::{self, Node},
+    partition_link,
+};
+
+/// Serves the sessions of a connection until the connection is closed
+pub(super) async fn serve(
+    acceptor: Arc<ConnectionAcceptor<(), SaslAnonymousMechanism>>,
+    stream: TcpStream,
+    event_hubs: Arc<[InMemoryEventHub]>,
+) {
+    let mut connection = match acceptor.accept(stream).await {
+        Ok(connection) => connection,
+        Err(err) => {
+            log::debug!("Failed to accept connection: {:?}", err);
+            return;
+        }
+    };
+
+    let session_acceptor = SessionAcceptor::new();
+    let mut sessions = JoinSet::new();
+    while let Some(incoming) = connection.next_incoming_session().await {
+        match session_acceptor
+            .accept_incoming_session(incoming, &mut connection)
+            .await
+        {
+            Ok(session) => {
+                sessions.spawn(serve_session(session, event_hubs.clone()));
+            }
+            Err(err) => log::debug!("Failed to accept session: {:?}", err),
+        }
+    }
+    // The sessions and their links are aborted when the set is dropped
+}
+
+async fn serve_session(mut session: ListenerSessionHandle, event_hubs: Arc<[InMemoryEventHub]>) {
+    // The links accept the messages up to the largest size that any of the Event Hubs accepts
+    let max_message_size = event_hubs
+        .iter()
+        .map(InMemoryEventHub::max_message_size)
+        .max()
+        .unwrap_or_default();
+    let link_acceptor = LinkAcceptor::builder()
+        .max_message_size(max_message_size)
+        .build();
+    // The client nodes of the request-response links are only unique within a session
+    let reply_links = ReplyLinks::default();
+    let mut links = JoinSet::new();
+
+    while let Some(attach) = session.next_incoming_attach().await {
+        let node = Node::from_attach(&attach, &event_hubs);
+        let link = match link_acceptor
+            .accept_incoming_attach(attach, &mut session)
+            .await
+        {
+            Ok(link) => link,
+            Err(err) => {
+                log::debug!("Failed to accept link: {:?}", err);
+                continue;
+            }
+        };
+
+        match (node, link) {
+            (Node::Cbs, LinkEndpoint::Receiver(receiver)) => {
+                let serve = management::serve_requests(
+                    receiver,
+                    RequestNode::Cbs,
+                    reply_links.clone(),
+                    event_hubs.clone(),
+                );
+                links.spawn(serve);
+            }
+            (Node::Management, LinkEndpoint::Receiver(receiver)) => {
+                let serve = management::serve_requests(
+                    receiver,
+                    RequestNode::Management,
+                    reply_links.clone(),
+                    event_hubs.clone(),
+                );
+                links.spawn(serve);
+            }
+            (Node::Cbs | Node::Management, LinkEndpoint::Sender(sender)) => {
+                let (responses_tx, responses_rx) = mpsc::unbounded_channel();
+                let address = client_node_address(&sender);
+                reply_links.lock().await.insert(address, responses_tx);
+                links.spawn(management::serve_responses(sender, responses_rx));
+            }
+            (
+                Node::Publisher {
+                    event_hub_name,
+                    partition_id,
+                },
+                LinkEndpoint::Receiver(receiver),
+            ) => {
+                let event_hub = event_hub(&event_hubs, &event_hub_name);
+                let serve = partition_link::serve_publisher(receiver, partition_id, event_hub);
+                links.spawn(serve);
+            }
+            (
+                Node::Consumer {
+                    event_hub_name,
+                    partition_id,
+                    event_position,
+                    track_last_enqueued_event_properties,
+                },
+                LinkEndpoint::Sender(sender),
+            ) => {
+                let event_hub = event_hub(&event_hubs, &event_hub_name);
+                let serve = partition_link::serve_consumer(
+                    sender,
+                    partition_id,
+                    event_position,
+                    track_last_enqueued_event_properties,
+                    event_hub,
+                );
+                links.spawn(serve);
+            }
+            (node, link) => {
+                let description = format!("The node {:?} cannot be attached to", node);
+                let error = definitions::Error::new(AmqpError::NotFound, description, None);
+                let result = match link {
+                    LinkEndpoint::Sender(sender) => sender.close_with_error(error).await,
+                    LinkEndpoint::Receiver(receiver) => receiver.close_with_error(error).await,
+                };
+                if let Err(err) = result {
+                    log::debug!("Failed to close link: {:?}", err);
+                }
+            }
+        }
+    }
+}
+
+/// The Event Hub of a node, which was found when the node was resolved
+fn event_hub(event_hubs: &[InMemoryEventHub], event_hub_name: &str) -> InMemoryEventHub {
+    node::find_event_hub(event_hubs, event_hub_name)
+        .cloned()
+        .expect("the Event Hub of the node exists")
+}
+
+/// The address of the client node, which is the target of the link that receives the responses
+fn client_node_address(sender: &Sender) -> String {
+    sender
+        .target()
+        .as_ref()
+        .and_then(|target| target.address.clone())
+        .unwrap_or_default()
+}
+
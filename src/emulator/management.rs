@@ -0,0 +1,236 @@
+use std::{collections::HashMap, sync::Arc};
+
+use fe2o3_amqp::{Delivery, Receiver, Sender};
+use fe2o3_amqp_types::{
+    messaging::{AmqpValue, ApplicationProperties, Body, Message, MessageId, Properties},
+    primitives::{Array, OrderedMap, SimpleValue, Timestamp, Value},
+};
+use time::OffsetDateTime;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::{
+    amqp::amqp_management::{
+        response_map, EVENT_HUB_RESOURCE_TYPE_VALUE, PARTITION_NAME_KEY,
+        PARTITION_RESOURCE_TYPE_VALUE, READ_OPERATION_VALUE, RESOURCE_NAME_KEY, RESOURCE_TYPE_KEY,
+    },
+    in_memory::InMemoryEventHub,
+};
+
+use super::node::find_event_hub;
+
+const OPERATION_KEY: &str = "operation";
+const PUT_TOKEN_OPERATION_VALUE: &str = "put-token";
+const STATUS_CODE_KEY: &str = "status-code";
+const STATUS_DESCRIPTION_KEY: &str = "status-description";
+
+type ResponseMessage = Message<AmqpValue<Value>>;
+
+/// The links that the responses are sent on, keyed by the address of the client node, which the
+/// requests carry in their `reply-to` property
+pub(super) type ReplyLinks = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<ResponseMessage>>>>;
+
+/// The nodes that follow the request-response pattern of the AMQP management specification
+#[derive(Debug, Clone, Copy)]
+pub(super) enum RequestNode {
+    Cbs,
+    Management,
+}
+
+struct Response {
+    status_code: u16,
+    description: String,
+    body: Value,
+}
+
+impl Response {
+    fn new(status_code: u16, description: impl Into<String>) -> Self {
+        Self {
+            status_code,
+            description: description.into(),
+            body: Value::Null,
+        }
+    }
+
+    fn ok(body: OrderedMap<Value, Value>) -> Self {
+        Self {
+            status_code: 200,
+            description: "OK".to_string(),
+            body: Value::Map(body),
+        }
+    }
+
+    fn into_message(self, correlation_id: Option<MessageId>) -> ResponseMessage {
+        let application_properties = ApplicationProperties::builder()
+            .insert(STATUS_CODE_KEY, SimpleValue::Int(self.status_code as i32))
+            .insert(STATUS_DESCRIPTION_KEY, self.description)
+            .build();
+        let properties = Properties {
+            correlation_id,
+            ..Default::default()
+        };
+        Message::builder()
+            .properties(properties)
+            .application_properties(application_properties)
+            .value(self.body)
+            .build()
+    }
+}
+
+/// Answers the requests that are received on the link until the link is closed
+pub(super) async fn serve_requests(
+    mut receiver: Receiver,
+    node: RequestNode,
+    reply_links: ReplyLinks,
+    event_hubs: Arc<[InMemoryEventHub]>,
+) {
+    loop {
+        let delivery: Delivery<Body<Value>> = match receiver.recv().await {
+            Ok(delivery) => delivery,
+            Err(err) => {
+                log::debug!("Request link of {:?} is closed: {:?}", node, err);
+                return;
+            }
+        };
+        if let Err(err) = receiver.accept(&delivery).await {
+            log::debug!("Failed to accept request: {:?}", err);
+            return;
+        }
+
+        let request = delivery.into_message();
+        let response = match node {
+            RequestNode::Cbs => put_token(&request),
+            RequestNode::Management => read(&request, &event_hubs),
+        };
+        let properties = request.properties.unwrap_or_default();
+        let message = response.into_message(properties.message_id);
+
+        let reply_links = reply_links.lock().await;
+        match properties
+            .reply_to
+            .and_then(|reply_to| reply_links.get(&reply_to))
+        {
+            Some(responses) => {
+                if responses.send(message).is_err() {
+                    log::debug!("The link to reply to the request is closed");
+                }
+            }
+            None => log::debug!("No link to reply to the request"),
+        }
+    }
+}
+
+/// Sends the responses on the link until the link is closed. The link is served by its own task
+/// so that it answers the detach of the client even if there is no response to send.
+pub(super) async fn serve_responses(
+    mut sender: Sender,
+    mut responses: mpsc::UnboundedReceiver<ResponseMessage>,
+) {
+    loop {
+        let response = tokio::select! {
+            response = responses.recv() => match response {
+                Some(response) => response,
+                None => return,
+            },
+            err = sender.on_detach() => {
+                log::debug!("Response link is closed: {:?}", err);
+                return;
+            }
+        };
+        if let Err(err) = sender.send(response).await {
+            log::debug!("Failed to send response: {:?}", err);
+            return;
+        }
+    }
+}
+
+fn application_property<'a>(request: &'a Message<Body<Value>>, key: &str) -> Option<&'a str> {
+    match request.application_properties.as_ref()?.get(key)? {
+        SimpleValue::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// The tokens are accepted without being validated
+fn put_token(request: &Message<Body<Value>>) -> Response {
+    match application_property(request, OPERATION_KEY) {
+        Some(PUT_TOKEN_OPERATION_VALUE) => Response::new(202, "Accepted"),
+        _ => Response::new(501, "Only put-token is supported"),
+    }
+}
+
+fn read(request: &Message<Body<Value>>, event_hubs: &[InMemoryEventHub]) -> Response {
+    if application_property(request, OPERATION_KEY) != Some(READ_OPERATION_VALUE) {
+        return Response::new(501, "Only READ is supported");
+    }
+    let name = application_property(request, RESOURCE_NAME_KEY).unwrap_or_default();
+    let event_hub = match find_event_hub(event_hubs, name) {
+        Some(event_hub) => event_hub,
+        None => return Response::new(404, format!("The Event Hub '{}' does not exist", name)),
+    };
+
+    match application_property(request, RESOURCE_TYPE_KEY) {
+        Some(EVENT_HUB_RESOURCE_TYPE_VALUE) => {
+            let properties = event_hub.properties();
+            let partition_ids = properties
+                .partition_ids
+                .into_iter()
+                .map(Value::String)
+                .collect();
+
+            let mut body = OrderedMap::new();
+            body.insert(response_map::NAME.into(), Value::String(properties.name));
+            body.insert(
+                response_map::CREATED_AT.into(),
+                Value::Timestamp(to_timestamp(properties.created_on)),
+            );
+            body.insert(
+                response_map::PARTITION_IDENTIFIERS.into(),
+                Value::Array(Array(partition_ids)),
+            );
+            Response::ok(body)
+        }
+        Some(PARTITION_RESOURCE_TYPE_VALUE) => {
+            let partition_id = application_property(request, PARTITION_NAME_KEY).unwrap_or_default();
+            let properties = match event_hub.partition_properties(partition_id) {
+                Ok(properties) => properties,
+                Err(err) => return Response::new(404, err.to_string()),
+            };
+
+            let mut body = OrderedMap::new();
+            body.insert(
+                response_map::NAME.into(),
+                Value::String(properties.event_hub_name),
+            );
+            body.insert(
+                response_map::PARTITION_IDENTIFIER.into(),
+                Value::String(properties.id),
+            );
+            body.insert(
+                response_map::PARTITION_BEGIN_SEQUENCE_NUMBER.into(),
+                Value::Long(properties.beginning_sequence_number),
+            );
+            body.insert(
+                response_map::PARTITION_LAST_ENQUEUED_SEQUENCE_NUMBER.into(),
+                Value::Long(properties.last_enqueued_sequence_number),
+            );
+            body.insert(
+                response_map::PARTITION_LAST_ENQUEUED_OFFSET.into(),
+                Value::String(properties.last_enqueued_offset.to_string()),
+            );
+            body.insert(
+                response_map::PARTITION_LAST_ENQUEUED_TIME_UTC.into(),
+                Value::Timestamp(to_timestamp(properties.last_enqueued_time)),
+            );
+            body.insert(
+                response_map::PARTITION_RUNTIME_INFO_PARTITION_IS_EMPTY.into(),
+                Value::Bool(properties.is_empty),
+            );
+            Response::ok(body)
+        }
+        _ => Response::new(400, "Unknown entity type"),
+    }
+}
+
+fn to_timestamp(time: OffsetDateTime) -> Timestamp {
+    Timestamp::from_milliseconds((time.unix_timestamp_nanos() / 1_000_000) as i64)
+}
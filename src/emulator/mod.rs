@@ -0,0 +1,211 @@
+//! A local AMQP 1.0 server that emulates an Event Hub, which allows the clients to be tested over
+//! a real AMQP connection without an Event Hubs namespace.
+//!
+//! This module is only available with the `amqp-emulator` feature.
+
+use std::{io, net::SocketAddr, sync::Arc};
+
+use fe2o3_amqp::acceptor::{ConnectionAcceptor, SaslAnonymousMechanism};
+use tokio::{
+    net::{TcpListener, ToSocketAddrs},
+    task::{JoinHandle, JoinSet},
+};
+use url::Url;
+
+use crate::{in_memory::InMemoryEventHub, EventHubConnectionOptions};
+
+mod connection;
+mod management;
+mod node;
+mod partition_link;
+
+/// The container id of the emulator
+const CONTAINER_ID: &str = "azeventhubs-emulator";
+
+/// The host name that is used in the connection string of the emulator. The connection itself is
+/// made to the address that the emulator listens on.
+const EMULATOR_HOST: &str = "localhost";
+
+/// The emulator accepts any key, because the tokens are not validated
+const EMULATOR_KEY_NAME: &str = "emulator";
+const EMULATOR_KEY: &str = "emulator";
+
+/// A local AMQP 1.0 server that serves one or more [`InMemoryEventHub`]s to the clients of this
+/// crate.
+///
+/// The emulator speaks enough of the Event Hubs protocol for the clients to connect to it through
+/// [`EventHubConnectionOptions::custom_endpoint_address`]:
+///
+/// - Tokens are put on the `$cbs` node, but they are not validated
+/// - The `$management` node answers `READ` requests for the `com.microsoft:eventhub` and
+///   `com.microsoft:partition` entities
+/// - Producers attach sender links to `<event-hub>` or `<event-hub>/Partitions/<id>`
+/// - Consumers attach receiver links to `<event-hub>/ConsumerGroups/<group>/Partitions/<id>` with
+///   an `apache.org:selector-filter:string` filter
+/// - [`InMemoryEventHub::detach_links`] detaches the links of the consumers with a
+///   `amqp:link:detach-forced` error, so that their recovery can be tested
+///
+/// All the Event Hubs are served in the same namespace, so a connection can reach any of them. The
+/// connection does not use TLS. Idempotent publishing, owner levels and consumer groups are
+/// not emulated, ie. all consumer groups read the same events.
+///
+/// The emulator stops when it is dropped or shut down, which closes all its connections.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use azeventhubs::{
+///     emulator::EventHubsEmulator,
+///     in_memory::InMemoryEventHub,
+///     producer::{EventHubProducerClient, EventHubProducerClientOptions, SendEventOptions},
+/// };
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let emulator = EventHubsEmulator::start(InMemoryEventHub::new("my-event-hub", 2)).await?;
+///
+/// let options = EventHubProducerClientOptions {
+///     connection_options: emulator.connection_options(),
+///     ..Default::default()
+/// };
+/// let mut producer =
+///     EventHubProducerClient::new_from_connection_string(emulator.connection_string(), None, options)
+///         .await?;
+/// producer.send_event("hello", SendEventOptions::new().with_partition_id("0")).await?;
+/// assert_eq!(emulator.event_hub().events("0").len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct EventHubsEmulator {
+    event_hubs: Arc<[InMemoryEventHub]>,
+    local_addr: SocketAddr,
+    accept_loop: JoinHandle<()>,
+}
+
+impl EventHubsEmulator {
+    /// Starts an emulator that listens on a random port of the loopback interface.
+    ///
+    /// This must be called within a tokio runtime.
+    pub async fn start(event_hub: InMemoryEventHub) -> io::Result<Self> {
+        Self::bind("127.0.0.1:0", event_hub).await
+    }
+
+    /// Starts an emulator that listens on the given address.
+    ///
+    /// This must be called within a tokio runtime.
+    pub async fn bind(addr: impl ToSocketAddrs, event_hub: InMemoryEventHub) -> io::Result<Self> {
+        Self::bind_with_event_hubs(addr, [event_hub]).await
+    }
+
+    /// Starts an emulator that serves all the Event Hubs and listens on a random port of the
+    /// loopback interface. The connection string of the emulator names the first Event Hub.
+    ///
+    /// This must be called within a tokio runtime.
+    pub async fn start_with_event_hubs(
+        event_hubs: impl IntoIterator<Item = InMemoryEventHub>,
+    ) -> io::Result<Self> {
+        Self::bind_with_event_hubs("127.0.0.1:0", event_hubs).await
+    }
+
+    /// Starts an emulator that serves all the Event Hubs and listens on the given address. The
+    /// connection string of the emulator names the first Event Hub.
+    ///
+    /// Returns an error if there is no Event Hub. This must be called within a tokio runtime.
+    pub async fn bind_with_event_hubs(
+        addr: impl ToSocketAddrs,
+        event_hubs: impl IntoIterator<Item = InMemoryEventHub>,
+    ) -> io::Result<Self> {
+        let event_hubs: Arc<[InMemoryEventHub]> = event_hubs.into_iter().collect();
+        if event_hubs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the emulator must serve at least one Event Hub",
+            ));
+        }
+
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let accept_loop = tokio::spawn(accept_connections(listener, event_hubs.clone()));
+        Ok(Self {
+            event_hubs,
+            local_addr,
+            accept_loop,
+        })
+    }
+
+    /// The first Event Hub that is served by the emulator
+    pub fn event_hub(&self) -> &InMemoryEventHub {
+        &self.event_hubs[0]
+    }
+
+    /// All the Event Hubs that are served by the emulator
+    pub fn event_hubs(&self) -> &[InMemoryEventHub] {
+        &self.event_hubs
+    }
+
+    /// The address that the emulator listens on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// The `amqp://` address to use as [`EventHubConnectionOptions::custom_endpoint_address`]
+    pub fn endpoint(&self) -> Url {
+        // A socket address is always a valid host and port
+        Url::parse(&format!("amqp://{}", self.local_addr)).expect("valid url")
+    }
+
+    /// A connection string that includes the name of the first Event Hub. It must be used together
+    /// with [`EventHubsEmulator::connection_options`].
+    pub fn connection_string(&self) -> String {
+        format!(
+            "Endpoint=sb://{}/;SharedAccessKeyName={};SharedAccessKey={};EntityPath={}",
+            EMULATOR_HOST,
+            EMULATOR_KEY_NAME,
+            EMULATOR_KEY,
+            self.event_hub().event_hub_name()
+        )
+    }
+
+    /// Connection options that connect to the emulator
+    pub fn connection_options(&self) -> EventHubConnectionOptions {
+        EventHubConnectionOptions {
+            custom_endpoint_address: Some(self.endpoint()),
+            ..Default::default()
+        }
+    }
+
+    /// Stops the emulator and closes all its connections
+    pub fn shutdown(self) {
+        // The accept loop is aborted when the emulator is dropped
+    }
+}
+
+impl Drop for EventHubsEmulator {
+    fn drop(&mut self) {
+        // The connections are owned by the accept loop, so they are dropped together with it
+        self.accept_loop.abort();
+    }
+}
+
+async fn accept_connections(listener: TcpListener, event_hubs: Arc<[InMemoryEventHub]>) {
+    let acceptor = ConnectionAcceptor::builder()
+        .container_id(CONTAINER_ID)
+        .sasl_acceptor(SaslAnonymousMechanism::new())
+        .build();
+    let acceptor = Arc::new(acceptor);
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            result = listener.accept() => match result {
+                Ok((stream, _)) => {
+                    let serve = connection::serve(acceptor.clone(), stream, event_hubs.clone());
+                    connections.spawn(serve);
+                }
+                Err(err) => log::debug!("Failed to accept TCP stream: {:?}", err),
+            },
+            // Reap the connections that are closed
+            Some(_) = connections.join_next() => {}
+        }
+    }
+}
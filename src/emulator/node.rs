@@ -0,0 +1,147 @@
+use fe2o3_amqp_types::{
+    definitions::Role,
+    messaging::{Source, TargetArchetype},
+    performatives::Attach,
+    primitives::Value,
+};
+use url::Url;
+
+use crate::{
+    amqp::{
+        amqp_filter::{parse_filter_expression, CONSUMER_FILTER_NAME},
+        amqp_property,
+    },
+    consumer::EventPosition,
+    in_memory::InMemoryEventHub,
+};
+
+/// The address of the claims-based security node
+const CBS_NODE_ADDRESS: &str = "$cbs";
+
+/// The address of the management node
+const MANAGEMENT_NODE_ADDRESS: &str = "$management";
+
+/// The node of the Event Hub that a link is attached to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Node {
+    Cbs,
+    Management,
+    Publisher {
+        event_hub_name: String,
+        partition_id: Option<String>,
+    },
+    Consumer {
+        event_hub_name: String,
+        partition_id: String,
+        event_position: EventPosition,
+        track_last_enqueued_event_properties: bool,
+    },
+    /// The node does not exist or the link cannot be attached to it
+    NotFound(String),
+}
+
+impl Node {
+    /// Finds the node from the address of the remote terminus, ie. the target of a remote sender or
+    /// the source of a remote receiver
+    pub(super) fn from_attach(attach: &Attach, event_hubs: &[InMemoryEventHub]) -> Self {
+        let source = attach.source.as_deref();
+        let address = match attach.role {
+            Role::Sender => target_address(attach),
+            Role::Receiver => source.and_then(|source| source.address.as_deref()),
+        };
+        let address = match address {
+            Some(CBS_NODE_ADDRESS) => return Self::Cbs,
+            Some(MANAGEMENT_NODE_ADDRESS) => return Self::Management,
+            Some(address) => address,
+            None => return Self::NotFound(String::new()),
+        };
+
+        let (event_hub, path) = match entity_path(address, event_hubs) {
+            Some(entity) => entity,
+            None => return Self::NotFound(address.to_string()),
+        };
+        let segments: Vec<&str> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        match (&attach.role, segments.as_slice()) {
+            (Role::Sender, []) => Self::Publisher {
+                event_hub_name: event_hub.event_hub_name().to_string(),
+                partition_id: None,
+            },
+            (Role::Sender, ["Partitions", partition_id]) => Self::Publisher {
+                event_hub_name: event_hub.event_hub_name().to_string(),
+                partition_id: Some(partition_id.to_string()),
+            },
+            (Role::Receiver, ["ConsumerGroups", _, "Partitions", partition_id]) => {
+                match source.and_then(event_position) {
+                    Some(event_position) => Self::Consumer {
+                        event_hub_name: event_hub.event_hub_name().to_string(),
+                        partition_id: partition_id.to_string(),
+                        event_position,
+                        track_last_enqueued_event_properties: desires_capability(
+                            attach,
+                            amqp_property::TRACK_LAST_ENQUEUED_EVENT_PROPERTIES.0,
+                        ),
+                    },
+                    None => Self::NotFound(address.to_string()),
+                }
+            }
+            _ => Self::NotFound(address.to_string()),
+        }
+    }
+}
+
+fn desires_capability(attach: &Attach, capability: &str) -> bool {
+    attach
+        .desired_capabilities
+        .as_ref()
+        .is_some_and(|capabilities| capabilities.iter().any(|c| c.as_str() == capability))
+}
+
+fn target_address(attach: &Attach) -> Option<&str> {
+    match attach.target.as_deref()? {
+        TargetArchetype::Target(target) => target.address.as_deref(),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// The Event Hub of the address and the path after its name. The address is either a path or a
+/// URL, whose host is ignored. Returns `None` if the address is not in any of the Event Hubs.
+fn entity_path<'a>(
+    address: &str,
+    event_hubs: &'a [InMemoryEventHub],
+) -> Option<(&'a InMemoryEventHub, String)> {
+    let path = match Url::parse(address) {
+        Ok(url) => url.path().to_string(),
+        Err(_) => address.to_string(),
+    };
+    let path = path.trim_start_matches('/');
+    let (name, rest) = path.split_once('/').unwrap_or((path, ""));
+    find_event_hub(event_hubs, name).map(|event_hub| (event_hub, rest.to_string()))
+}
+
+/// Finds the Event Hub by its name, which is not case sensitive
+pub(super) fn find_event_hub<'a>(
+    event_hubs: &'a [InMemoryEventHub],
+    event_hub_name: &str,
+) -> Option<&'a InMemoryEventHub> {
+    event_hubs
+        .iter()
+        .find(|event_hub| event_hub.event_hub_name().eq_ignore_ascii_case(event_hub_name))
+}
+
+/// The position that the consumer starts from, which is given by the selector filter of the source
+fn event_position(source: &Source) -> Option<EventPosition> {
+    let filter = source.filter.as_ref()?.get(CONSUMER_FILTER_NAME)?;
+    let expression = match filter {
+        Value::Described(described) => match &described.value {
+            Value::String(expression) => expression,
+            _ => return None,
+        },
+        Value::String(expression) => expression,
+        _ => return None,
+    };
+    parse_filter_expression(expression)
+}
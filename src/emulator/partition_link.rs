@@ -0,0 +1,177 @@
+use fe2o3_amqp::{Delivery, Receiver, Sendable, Sender};
+use fe2o3_amqp_types::{
+    definitions::{self, AmqpError, LinkError},
+    messaging::{
+        annotations::AnnotationKey, message::__private::Deserializable, Body, Message,
+    },
+    primitives::Value,
+};
+use serde_amqp::from_slice;
+
+use crate::{
+    amqp::{amqp_constants::AMQP_BATCHED_MESSAGE_FORMAT, amqp_property},
+    consumer::EventPosition,
+    in_memory::InMemoryEventHub,
+};
+
+type Event = Message<Body<Value>>;
+
+/// Enqueues the events that are received on the link until the link is closed
+pub(super) async fn serve_publisher(
+    mut receiver: Receiver,
+    partition_id: Option<String>,
+    event_hub: InMemoryEventHub,
+) {
+    loop {
+        let delivery: Delivery<Body<Value>> = match receiver.recv().await {
+            Ok(delivery) => delivery,
+            Err(err) => {
+                log::debug!("Publisher link is closed: {:?}", err);
+                return;
+            }
+        };
+
+        let result = decode_events(&delivery).and_then(|(partition_key, messages)| {
+            event_hub
+                .enqueue(
+                    partition_id.as_deref(),
+                    partition_key.as_deref(),
+                    messages,
+                    None,
+                )
+                .map_err(|err| err.to_string())
+        });
+        let result = match result {
+            Ok(_) => receiver.accept(&delivery).await,
+            Err(description) => {
+                let error = definitions::Error::new(AmqpError::InvalidField, description, None);
+                receiver.reject(&delivery, error).await
+            }
+        };
+        if let Err(err) = result {
+            log::debug!("Failed to settle delivery: {:?}", err);
+            return;
+        }
+    }
+}
+
+/// Returns the partition key and the events of a delivery, which is either a single event or a
+/// batch whose data sections are the serialized events
+fn decode_events(
+    delivery: &Delivery<Body<Value>>,
+) -> Result<(Option<String>, Vec<Event>), String> {
+    let message = delivery.message();
+    let partition_key = message
+        .message_annotations
+        .as_ref()
+        .and_then(|annotations| {
+            annotations.get(&amqp_property::PARTITION_KEY as &dyn AnnotationKey)
+        })
+        .and_then(|value| match value {
+            Value::String(key) => Some(key.clone()),
+            _ => None,
+        });
+
+    let messages = match (delivery.message_format(), &message.body) {
+        (Some(AMQP_BATCHED_MESSAGE_FORMAT), Body::Data(batch)) => batch
+            .iter()
+            .map(|data| {
+                from_slice::<Deserializable<Event>>(&data.0)
+                    .map(|event| event.0)
+                    .map_err(|err| err.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        (Some(AMQP_BATCHED_MESSAGE_FORMAT), _) => {
+            return Err("The body of a batch must be data sections".to_string())
+        }
+        _ => vec![message.clone()],
+    };
+    Ok((partition_key, messages))
+}
+
+/// The error that a consumer link is detached with after [`InMemoryEventHub::detach_links`], which
+/// the consumer recovers from
+fn detach_forced() -> definitions::Error {
+    let description = "The link is detached".to_string();
+    definitions::Error::new(LinkError::DetachForced, description, None)
+}
+
+/// Sends the events of the partition, starting from the given position, until the link is closed,
+/// or detached by [`InMemoryEventHub::detach_links`]
+pub(super) async fn serve_consumer(
+    mut sender: Sender,
+    partition_id: String,
+    event_position: EventPosition,
+    track_last_enqueued_event_properties: bool,
+    event_hub: InMemoryEventHub,
+) {
+    let link_generation = event_hub.lock().link_generation;
+    let start_index = event_hub
+        .lock()
+        .partition(&partition_id)
+        .and_then(|partition| partition.index_of(&event_position));
+    let mut next_index = match start_index {
+        Ok(index) => index,
+        Err(err) => {
+            let description = err.to_string();
+            let error = definitions::Error::new(AmqpError::NotFound, description, None);
+            if let Err(err) = sender.close_with_error(error).await {
+                log::debug!("Failed to close consumer link: {:?}", err);
+            }
+            return;
+        }
+    };
+
+    loop {
+        // The notification must be registered before checking for new events, otherwise an event
+        // that is enqueued in between would be missed
+        let notified = event_hub.notify().notified();
+
+        if event_hub.lock().link_generation != link_generation {
+            if let Err(err) = sender.detach_with_error(detach_forced()).await {
+                log::debug!("Failed to detach consumer link: {:?}", err);
+            }
+            return;
+        }
+
+        let events: Vec<_> = match event_hub.lock().partition(&partition_id) {
+            Ok(partition) => {
+                let delivery_annotations = track_last_enqueued_event_properties
+                    .then(|| partition.last_enqueued_event_annotations())
+                    .flatten();
+                partition.events[next_index..]
+                    .iter()
+                    .map(|enqueued| Event {
+                        delivery_annotations: delivery_annotations.clone(),
+                        ..enqueued.event.raw_amqp_message().clone()
+                    })
+                    .collect()
+            }
+            Err(_) => return,
+        };
+
+        if events.is_empty() {
+            tokio::select! {
+                _ = notified => continue,
+                err = sender.on_detach() => {
+                    log::debug!("Consumer link is closed: {:?}", err);
+                    return;
+                }
+            }
+        }
+
+        for event in events {
+            // The events are sent settled, so the link does not wait for their dispositions
+            let sendable = Sendable::builder()
+                .message(event)
+                .settled(true)
+                .build();
+            if let Err(err) = sender.send(sendable).await {
+                log::debug!("Failed to send event: {:?}", err);
+                return;
+            }
+            next_index += 1;
+        }
+    }
+}
+
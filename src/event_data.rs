@@ -1,8 +1,8 @@
 use std::borrow::Cow;
 
 use fe2o3_amqp_types::messaging::annotations::AnnotationKey;
-use fe2o3_amqp_types::messaging::{ApplicationProperties, Body, Data, Message, Properties};
-use fe2o3_amqp_types::primitives::{OrderedMap, SimpleValue};
+use fe2o3_amqp_types::messaging::{ApplicationProperties, Batch, Body, Data, Message, Properties};
+use fe2o3_amqp_types::primitives::{OrderedMap, SimpleValue, Timestamp};
 use serde_amqp::primitives::Binary;
 use serde_amqp::Value;
 use time::OffsetDateTime;
@@ -13,12 +13,53 @@ use crate::amqp::{
     amqp_message_extension::{AmqpMessageExt, AmqpMessageMutExt},
     error::SetMessageIdError,
 };
+use crate::amqp_annotated_message::AmqpAnnotatedMessage;
 use crate::constants::DEFAULT_OFFSET_DATE_TIME;
 
+/// The value of an application property of an event, which can be created from the common Rust
+/// types that have an AMQP counterpart
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyValue(SimpleValue);
+
+impl From<PropertyValue> for SimpleValue {
+    fn from(value: PropertyValue) -> Self {
+        value.0
+    }
+}
+
+macro_rules! impl_from_for_property_value {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for PropertyValue {
+                fn from(value: $ty) -> Self {
+                    Self(SimpleValue::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_property_value! {
+    SimpleValue, bool, u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, char, String, &str,
+    Timestamp
+}
+
+impl From<OffsetDateTime> for PropertyValue {
+    fn from(value: OffsetDateTime) -> Self {
+        Self(SimpleValue::Timestamp(Timestamp::from(value)))
+    }
+}
+
+impl From<uuid::Uuid> for PropertyValue {
+    fn from(value: uuid::Uuid) -> Self {
+        Self(SimpleValue::Uuid(value.into_bytes().into()))
+    }
+}
+
 /// An Event Hubs event, encapsulating a set of data and its associated metadata.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EventData {
-    pub(crate) amqp_message: Message<Data>,
+    pub(crate) amqp_message: AmqpAnnotatedMessage,
 }
 
 impl<T> From<T> for EventData
@@ -27,42 +68,73 @@ where
 {
     fn from(value: T) -> Self {
         Self {
-            amqp_message: Message::builder().data(Binary::from(value)).build(),
+            amqp_message: AmqpAnnotatedMessage::from_data(value),
         }
     }
 }
 
+impl From<AmqpAnnotatedMessage> for EventData {
+    fn from(amqp_message: AmqpAnnotatedMessage) -> Self {
+        Self { amqp_message }
+    }
+}
+
 impl EventData {
     /// Creates a new event from the given data
     pub fn new(body: impl Into<Vec<u8>>) -> Self {
         Self::from(body)
     }
 
-    /// The data associated with the event
+    /// The data associated with the event.
+    ///
+    /// Returns an empty slice if the body is not a single data section, which
+    /// [`EventData::data_body`] tells apart from an empty body.
     pub fn body(&self) -> &[u8] {
-        &self.amqp_message.body.0
+        self.data_body().unwrap_or_default()
+    }
+
+    /// The data associated with the event, or `None` if the body is not a single data section, in
+    /// which case the body can be read from [`EventData::amqp_message`].
+    pub fn data_body(&self) -> Option<&[u8]> {
+        self.amqp_message.single_data()
     }
 
     /// Sets the body associated with the event
     pub fn set_body(&mut self, body: impl Into<Vec<u8>>) {
-        self.amqp_message.body = Data(Binary::from(body));
+        self.amqp_message.message.body = Body::Data(Batch::from(vec![Data(Binary::from(body))]));
+    }
+
+    /// The AMQP message that the event is sent as
+    pub fn amqp_message(&self) -> &AmqpAnnotatedMessage {
+        &self.amqp_message
+    }
+
+    /// A mutable reference to the AMQP message that the event is sent as, which allows the body
+    /// and all the sections of the message to be set
+    pub fn amqp_message_mut(&mut self) -> &mut AmqpAnnotatedMessage {
+        &mut self.amqp_message
+    }
+
+    /// Consumes the event and returns the AMQP message that the event is sent as
+    pub fn into_amqp_message(self) -> AmqpAnnotatedMessage {
+        self.amqp_message
     }
 
     /// The content type associated with the event
     pub fn content_type(&self) -> Option<&str> {
-        self.amqp_message.content_type()
+        self.amqp_message.message.content_type()
     }
 
     /// Sets the content type associated with the event
     pub fn set_content_type(&mut self, content_type: impl Into<Option<String>>) {
-        self.amqp_message.set_content_type(content_type)
+        self.amqp_message.message.set_content_type(content_type)
     }
 
     /// An application-defined value that uniquely identifies the event.  The identifier is
     /// a free-form value and can reflect a GUID or an identifier derived from the application
     /// context.
     pub fn message_id(&self) -> Option<Cow<'_, str>> {
-        self.amqp_message.message_id()
+        self.amqp_message.message.message_id()
     }
 
     /// Sets the message ID associated with the event
@@ -70,61 +142,112 @@ impl EventData {
         &mut self,
         message_id: impl Into<String>,
     ) -> Result<(), SetMessageIdError> {
-        self.amqp_message.set_message_id(message_id)
+        self.amqp_message.message.set_message_id(message_id)
     }
 
     /// An application-defined value that represents the context to use for correlation across
     /// one or more operations.  The identifier is a free-form value and may reflect a unique
     /// identity or a shared data element with significance to the application.
     pub fn correlation_id(&self) -> Option<Cow<'_, str>> {
-        self.amqp_message.correlation_id()
+        self.amqp_message.message.correlation_id()
     }
 
     /// Sets the correlation ID associated with the event
     pub fn set_correlation_id(&mut self, correlation_id: impl Into<Option<String>>) {
-        self.amqp_message.set_correlation_id(correlation_id)
+        self.amqp_message.message.set_correlation_id(correlation_id)
     }
 
     /// The set of free-form properties which may be used for associating metadata with the event that
     /// is meaningful within the application context.
     pub fn properties(&self) -> Option<&OrderedMap<String, SimpleValue>> {
+        self.amqp_message.application_properties().map(|p| &p.0)
+    }
+
+    /// A mutable reference to the application properties of the event, which are created if the
+    /// event has none
+    pub fn properties_mut(&mut self) -> &mut OrderedMap<String, SimpleValue> {
+        &mut self
+            .amqp_message
+            .message
+            .application_properties
+            .get_or_insert_with(Default::default)
+            .0
+    }
+
+    /// Sets an application property of the event. Returns the previous value of the property if
+    /// there was one.
+    pub fn insert_property(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<PropertyValue>,
+    ) -> Option<SimpleValue> {
+        self.properties_mut()
+            .insert(key.into(), SimpleValue::from(value.into()))
+    }
+
+    /// A mutable reference to the value of an application property of the event
+    pub fn property_mut(&mut self, key: &str) -> Option<&mut SimpleValue> {
         self.amqp_message
+            .message
             .application_properties
-            .as_ref()
-            .map(|p| &p.0)
+            .as_mut()?
+            .0
+            .get_mut(key)
+    }
+
+    /// Removes an application property of the event and returns its value. The order of the
+    /// other properties is preserved.
+    pub fn remove_property(&mut self, key: &str) -> Option<SimpleValue> {
+        self.amqp_message
+            .message
+            .application_properties
+            .as_mut()?
+            .0
+            .shift_remove(key)
     }
 }
 
 /// A received event.
 #[derive(Debug, Clone)]
 pub struct ReceivedEventData {
-    raw_amqp_message: Message<Body<Value>>,
+    pub(crate) amqp_message: AmqpAnnotatedMessage,
 }
 
 impl ReceivedEventData {
     pub(crate) fn from_raw_amqp_message(raw_amqp_message: Message<Body<Value>>) -> Self {
-        Self { raw_amqp_message }
+        Self {
+            amqp_message: AmqpAnnotatedMessage::from(raw_amqp_message),
+        }
     }
 
     /// Gets the raw AMQP message.
     pub fn raw_amqp_message(&self) -> &Message<Body<Value>> {
-        &self.raw_amqp_message
+        &self.amqp_message.message
     }
 
     /// Consumes the event and returns the raw AMQP message.
     pub fn into_raw_amqp_message(self) -> Message<Body<Value>> {
-        self.raw_amqp_message
+        self.amqp_message.message
+    }
+
+    /// Gets the AMQP message that the event was received as, whose body can be of any kind
+    pub fn amqp_message(&self) -> &AmqpAnnotatedMessage {
+        &self.amqp_message
+    }
+
+    /// Consumes the event and returns the AMQP message that the event was received as
+    pub fn into_amqp_message(self) -> AmqpAnnotatedMessage {
+        self.amqp_message
     }
 
     /// Gets the body of the message.
+    ///
+    /// Returns an error if the body is not a single data section, in which case the body can be
+    /// read from [`ReceivedEventData::amqp_message`].
     pub fn body(&self) -> Result<&[u8], RawAmqpMessageError> {
-        match &self.raw_amqp_message.body {
-            Body::Data(batch) => match batch.len() {
-                1 => Ok(batch[0].0.as_ref()),
-                _ => Err(RawAmqpMessageError {}),
-            },
-            _ => Err(RawAmqpMessageError {}),
-        }
+        self.amqp_message
+            .single_data()
+            .ok_or(RawAmqpMessageError {})
     }
 
     /// Gets the MessageId to identify the message.
@@ -136,7 +259,12 @@ impl ReceivedEventData {
     /// feature identifies and removes second and further submissions of messages with the same
     /// MessageId.
     pub fn message_id(&self) -> Option<Cow<'_, str>> {
-        self.raw_amqp_message.message_id()
+        self.amqp_message.message.message_id()
+    }
+
+    /// The content type of the event, which describes how the body is encoded
+    pub fn content_type(&self) -> Option<&str> {
+        self.amqp_message.message.content_type()
     }
 
     /// Gets the correlation identifier.
@@ -146,24 +274,25 @@ impl ReceivedEventData {
     /// Routing and
     /// Correlation](https://docs.microsoft.com/azure/service-bus-messaging/service-bus-messages-payloads?#message-routing-and-correlation").
     pub fn correlation_id(&self) -> Option<Cow<'_, str>> {
-        self.raw_amqp_message.correlation_id()
+        self.amqp_message.message.correlation_id()
     }
 
     /// The set of free-form properties which may be used for associating metadata with the event that
     /// is meaningful within the application context.
     pub fn properties(&self) -> Option<&ApplicationProperties> {
-        self.raw_amqp_message.application_properties.as_ref()
+        self.amqp_message.message.application_properties.as_ref()
     }
 
     /// The set of free-form event properties which were provided by the Event Hubs service to pass metadata associated with the
     /// event or associated Event Hubs operation.
     pub fn system_properties(&self) -> Option<&Properties> {
-        self.raw_amqp_message.properties.as_ref()
+        self.amqp_message.message.properties.as_ref()
     }
 
     /// The sequence number assigned to the event when it was enqueued in the associated Event Hub partition.
     pub fn sequence_number(&self) -> i64 {
-        self.raw_amqp_message
+        self.amqp_message
+            .message
             .message_annotations
             .as_ref()
             .and_then(|m| m.get(&amqp_property::SEQUENCE_NUMBER as &dyn AnnotationKey))
@@ -176,7 +305,8 @@ impl ReceivedEventData {
 
     /// The offset of the event when it was received from the associated Event Hub partition.
     pub fn offset(&self) -> Option<i64> {
-        self.raw_amqp_message
+        self.amqp_message
+            .message
             .message_annotations
             .as_ref()
             .and_then(|m| m.get(&amqp_property::OFFSET as &dyn AnnotationKey))
@@ -190,7 +320,8 @@ impl ReceivedEventData {
     /// The date and time, in UTC, that the event was enqueued in the associated Event Hub
     /// partition.
     pub fn enqueued_time(&self) -> OffsetDateTime {
-        self.raw_amqp_message
+        self.amqp_message
+            .message
             .message_annotations
             .as_ref()
             .and_then(|m| m.get(&amqp_property::ENQUEUED_TIME as &dyn AnnotationKey))
@@ -203,6 +334,72 @@ impl ReceivedEventData {
 
     /// The partition key set when the event was enqueued in the associated Event Hub partition.
     pub fn partition_key(&self) -> Option<&str> {
-        self.raw_amqp_message.partition_key()
+        self.amqp_message.message.partition_key()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::primitives::{SimpleValue, Timestamp};
+    use time::macros::datetime;
+
+    use super::EventData;
+    use crate::amqp_annotated_message::AmqpAnnotatedMessage;
+
+    #[test]
+    fn properties_are_inserted_updated_and_removed() {
+        let mut event = EventData::from("body");
+        assert!(event.properties().is_none());
+
+        let id = uuid::Uuid::new_v4();
+        assert_eq!(event.insert_property("count", 1i32), None);
+        event.insert_property("traced", true);
+        event.insert_property("route", "north");
+        event.insert_property("sent_at", datetime!(2024-01-02 03:04:05 UTC));
+        event.insert_property("id", id);
+
+        let properties = event.properties().unwrap();
+        let keys: Vec<_> = properties.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["count", "traced", "route", "sent_at", "id"]);
+        assert_eq!(
+            properties.get("sent_at"),
+            Some(&SimpleValue::Timestamp(Timestamp::from_milliseconds(
+                1_704_164_645_000
+            )))
+        );
+        assert_eq!(
+            properties.get("id"),
+            Some(&SimpleValue::Uuid(id.into_bytes().into()))
+        );
+
+        *event.property_mut("count").unwrap() = SimpleValue::Int(2);
+        let previous = event.insert_property("route", String::from("south"));
+        assert_eq!(previous, Some(SimpleValue::from("north")));
+        assert_eq!(
+            event.remove_property("traced"),
+            Some(SimpleValue::Bool(true))
+        );
+        assert_eq!(event.remove_property("traced"), None);
+
+        let properties = event.properties().unwrap();
+        let keys: Vec<_> = properties.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["count", "route", "sent_at", "id"]);
+        assert_eq!(properties.get("count"), Some(&SimpleValue::Int(2)));
+        assert_eq!(properties.get("route"), Some(&SimpleValue::from("south")));
+    }
+
+    #[test]
+    fn data_body_is_none_unless_it_is_a_single_data_section() {
+        let event = EventData::from("body");
+        assert_eq!(event.body(), b"body");
+        assert_eq!(event.data_body(), Some(&b"body"[..]));
+
+        let event = EventData::from(AmqpAnnotatedMessage::from_value(42i64));
+        assert!(event.body().is_empty());
+        assert_eq!(event.data_body(), None);
+
+        let event = EventData::from(AmqpAnnotatedMessage::from_sequence(vec![]));
+        assert!(event.body().is_empty());
+        assert_eq!(event.data_body(), None);
     }
 }
@@ -1,14 +1,10 @@
 use const_format::concatcp;
 use std::sync::Arc;
+use timer_kit::error::Elapsed;
 use url::Url;
 
 use crate::{
-    amqp::{
-        amqp_client::AmqpClient,
-        amqp_consumer::AmqpConsumer,
-        amqp_producer::AmqpProducer,
-        error::AmqpClientError,
-    },
+    amqp::{amqp_client::AmqpClient, error::AmqpClientError},
     authorization::{
         event_hub_token_credential::EventHubTokenCredential,
         shared_access_credential::SharedAccessCredential,
@@ -23,9 +19,13 @@ use crate::{
     event_hubs_retry_policy::EventHubsRetryPolicy,
     event_hubs_transport_type::EventHubsTransportType,
     producer::PartitionPublishingOptions,
+    transport_kind::{TransportClientKind, TransportConsumerKind, TransportProducerKind},
     PartitionProperties, util,
 };
 
+#[cfg(feature = "in-memory-transport")]
+use crate::in_memory::{in_memory_client::InMemoryClient, InMemoryEventHub, IN_MEMORY_NAMESPACE};
+
 /// Error with the `EventHubConnection`.
 #[derive(Debug, thiserror::Error)]
 pub enum EventHubConnectionError {
@@ -46,11 +46,14 @@ impl From<EventHubConnectionError> for azure_core::error::Error {
 /// Event Hub instance within an Event Hubs namespace.  A single connection may be shared among multiple
 /// Event Hub producers and/or consumers, or may be used as a dedicated connection for a single
 /// producer or consumer client.
+///
+/// The producers and consumers of other Event Hubs in the same namespace can share the connection
+/// through [`EventHubConnection::clone_for_event_hub`].
 #[derive(Debug)]
 pub struct EventHubConnection {
     fully_qualified_namespace: Arc<String>,
     event_hub_name: Arc<String>,
-    pub(crate) inner: AmqpClient,
+    pub(crate) inner: TransportClientKind,
 }
 
 impl EventHubConnection {
@@ -129,8 +132,7 @@ impl EventHubConnection {
         let shared_access_credential =
             SharedAccessCredential::from_signature(shared_access_signature);
 
-        let token_credential =
-            EventHubTokenCredential::SharedAccessCredential(shared_access_credential);
+        let token_credential = EventHubTokenCredential::from(shared_access_credential);
 
         Self::new_from_credential(
             fully_qualified_namespace.to_string(),
@@ -155,7 +157,7 @@ impl EventHubConnection {
 
         let inner_client = AmqpClient::new(
             &fully_qualified_namespace,
-            event_hub_name.clone(),
+            &event_hub_name,
             token_credential,
             options,
         )
@@ -166,7 +168,7 @@ impl EventHubConnection {
         Ok(Self {
             fully_qualified_namespace,
             event_hub_name,
-            inner: inner_client,
+            inner: TransportClientKind::Amqp(inner_client),
         })
     }
 
@@ -212,6 +214,29 @@ impl EventHubConnection {
         )
         .await
     }
+
+    /// Creates a new [`EventHubConnection`] to an [`InMemoryEventHub`].
+    ///
+    /// The clients that are created with this connection publish to and read from the Event Hub in
+    /// memory instead of the Event Hubs service, which is useful for testing.
+    #[cfg(feature = "in-memory-transport")]
+    pub fn new_from_in_memory_event_hub(event_hub: &InMemoryEventHub) -> Self {
+        Self::new_from_in_memory_event_hubs(event_hub.event_hub_name(), vec![event_hub.clone()])
+    }
+
+    /// Creates a connection to one of the in-memory Event Hubs, which other Event Hubs of the
+    /// same namespace can share through [`EventHubConnection::clone_for_event_hub`].
+    #[cfg(feature = "in-memory-transport")]
+    pub(crate) fn new_from_in_memory_event_hubs(
+        event_hub_name: &str,
+        event_hubs: Vec<InMemoryEventHub>,
+    ) -> Self {
+        Self {
+            fully_qualified_namespace: Arc::new(IN_MEMORY_NAMESPACE.to_string()),
+            event_hub_name: Arc::new(event_hub_name.to_string()),
+            inner: TransportClientKind::InMemory(InMemoryClient::new(event_hubs)),
+        }
+    }
 }
 
 impl EventHubConnection {
@@ -222,44 +247,13 @@ impl EventHubConnection {
     where
         RP: EventHubsRetryPolicy + Send,
     {
-        // // We don't need to explicitly check if the connection is closed here because
-        // // `self.inner.get_properties` will do that for us.
-        // self.inner.get_properties(retry_policy).await
-
-        let mut try_timeout = retry_policy.calculate_try_timeout(0);
-        let mut failed_attempt = 0;
-        let mut should_try_recover = false;
-
-        loop {
-            // The underlying AMQP client may get closed if idle for too long.  If that happens, we
-            // need to recreate it.
-            if should_try_recover {
-                self.inner.recover().await?;
+        match &mut self.inner {
+            TransportClientKind::Amqp(client) => {
+                get_properties_with_retry(client, &self.event_hub_name, retry_policy).await
             }
-
-            let fut = self.inner.get_properties();
-            let error = match util::time::timeout(try_timeout, fut).await {
-                Ok(Ok(response)) => return Ok(response),
-                Ok(Err(err)) => err,
-                Err(elapsed) => elapsed.into(),
-            };
-
-            log::debug!("get_properties failed: {:?}", error);
-
-            failed_attempt += 1;
-            let delay = retry_policy.calculate_retry_delay(&error, failed_attempt);
-            should_try_recover = error.should_try_recover();
-            match delay {
-                Some(delay) => {
-                    util::time::sleep(delay).await;
-                    try_timeout = retry_policy.calculate_try_timeout(failed_attempt);
-                }
-                // Stop retrying and close the client. The connection close error is often more
-                // useful
-                None => match self.inner.close_if_owned().await {
-                    Ok(_) => return Err(error.into()),
-                    Err(dispose_err) => return Err(dispose_err.into()),
-                },
+            #[cfg(feature = "in-memory-transport")]
+            TransportClientKind::InMemory(client) => {
+                get_properties_with_retry(client, &self.event_hub_name, retry_policy).await
             }
         }
     }
@@ -285,40 +279,25 @@ impl EventHubConnection {
     where
         RP: EventHubsRetryPolicy + Send,
     {
-        let mut try_timeout = retry_policy.calculate_try_timeout(0);
-        let mut failed_attempt = 0;
-        let mut should_try_recover = false;
-
-        loop {
-            // The underlying AMQP client may get closed if idle for too long.  If that happens, we
-            // need to recreate it.
-            if should_try_recover {
-                self.inner.recover().await?;
+        match &mut self.inner {
+            TransportClientKind::Amqp(client) => {
+                get_partition_properties_with_retry(
+                    client,
+                    &self.event_hub_name,
+                    partition_id,
+                    retry_policy,
+                )
+                .await
             }
-
-            let fut = self.inner.get_partition_properties(partition_id);
-            let error = match util::time::timeout(try_timeout, fut).await {
-                Ok(Ok(response)) => return Ok(response),
-                Ok(Err(err)) => err,
-                Err(elapsed) => elapsed.into(),
-            };
-
-            log::debug!("get_partition_properties failed: {:?}", error);
-
-            failed_attempt += 1;
-            let delay = retry_policy.calculate_retry_delay(&error, failed_attempt);
-            should_try_recover = error.should_try_recover();
-            match delay {
-                Some(delay) => {
-                    util::time::sleep(delay).await;
-                    try_timeout = retry_policy.calculate_try_timeout(failed_attempt);
-                }
-                // Stop retrying and close the client. The connection close error is often more
-                // useful
-                None => match self.inner.close_if_owned().await {
-                    Ok(_) => return Err(error.into()),
-                    Err(dispose_err) => return Err(dispose_err.into()),
-                },
+            #[cfg(feature = "in-memory-transport")]
+            TransportClientKind::InMemory(client) => {
+                get_partition_properties_with_retry(
+                    client,
+                    &self.event_hub_name,
+                    partition_id,
+                    retry_policy,
+                )
+                .await
             }
         }
     }
@@ -330,53 +309,34 @@ impl EventHubConnection {
         requested_features: TransportProducerFeatures,
         partition_options: PartitionPublishingOptions,
         retry_policy: RP,
-    ) -> Result<AmqpProducer<RP>, azure_core::Error>
+    ) -> Result<TransportProducerKind<RP>, azure_core::Error>
     where
         RP: EventHubsRetryPolicy + Send,
     {
-        let mut try_timeout = retry_policy.calculate_try_timeout(0);
-        let mut failed_attempt = 0;
-        let mut should_try_recover = false;
-
-        loop {
-            // The underlying AMQP client may get closed if idle for too long.  If that happens, we
-            // need to recreate it.
-            if should_try_recover {
-                self.inner.recover().await?;
-            }
-
-            // TODO: can we reduce clone() calls?
-            let fut = self.inner
-                .create_producer(
-                    partition_id.clone(),
-                    producer_identifier.clone(),
-                    requested_features,
-                    partition_options.clone(),
-                    retry_policy.clone(),
-                );
-            let error = match util::time::timeout(try_timeout, fut).await {
-                Ok(Ok(response)) => return Ok(response),
-                Ok(Err(err)) => err,
-                Err(elapsed) => elapsed.into(),
-            };
-
-            log::debug!("create producer failed: {:?}", error);
-
-            failed_attempt += 1;
-            let delay = retry_policy.calculate_retry_delay(&error, failed_attempt);
-            should_try_recover = error.should_try_recover();
-            match delay {
-                Some(delay) => {
-                    util::time::sleep(delay).await;
-                    try_timeout = retry_policy.calculate_try_timeout(failed_attempt);
-                }
-                // Stop retrying and close the client. The connection close error is often more
-                // useful
-                None => match self.inner.close_if_owned().await {
-                    Ok(_) => return Err(error.into()),
-                    Err(dispose_err) => return Err(dispose_err.into()),
-                },
-            }
+        match &mut self.inner {
+            TransportClientKind::Amqp(client) => create_producer_with_retry(
+                client,
+                &self.event_hub_name,
+                partition_id,
+                producer_identifier,
+                requested_features,
+                partition_options,
+                retry_policy,
+            )
+            .await
+            .map(TransportProducerKind::Amqp),
+            #[cfg(feature = "in-memory-transport")]
+            TransportClientKind::InMemory(client) => create_producer_with_retry(
+                client,
+                &self.event_hub_name,
+                partition_id,
+                producer_identifier,
+                requested_features,
+                partition_options,
+                retry_policy,
+            )
+            .await
+            .map(TransportProducerKind::InMemory),
         }
     }
 
@@ -391,68 +351,52 @@ impl EventHubConnection {
         track_last_enqueued_event_properties: bool,
         owner_level: Option<i64>,
         prefetch_count: Option<u32>,
-    ) -> Result<AmqpConsumer<RP>, azure_core::Error>
+    ) -> Result<TransportConsumerKind<RP>, azure_core::Error>
     where
         RP: EventHubsRetryPolicy + Send,
     {
-        let mut try_timeout = retry_policy.calculate_try_timeout(0);
-        let mut failed_attempt = 0;
-        let mut should_try_recover = false;
-
-        loop {
-            // The underlying AMQP client may get closed if idle for too long.  If that happens, we
-            // need to recreate it.
-            if should_try_recover {
-                self.inner.recover().await?;
-            }
-
-            let fut = self
-                .inner
-                .create_consumer(
-                    consumer_group,
-                    partition_id,
-                    consumer_identifier.clone(),
-                    &event_position,
-                    retry_policy.clone(),
-                    track_last_enqueued_event_properties,
-                    owner_level,
-                    prefetch_count,
-                );
-            let error = match util::time::timeout(try_timeout, fut).await {
-                Ok(Ok(response)) => return Ok(response),
-                Ok(Err(err)) => err,
-                Err(elapsed) => elapsed.into(),
-            };
-
-            log::debug!("create consumer failed: {:?}", error);
-
-            failed_attempt += 1;
-            let delay = retry_policy.calculate_retry_delay(&error, failed_attempt);
-            should_try_recover = error.should_try_recover();
-            match delay {
-                Some(delay) => {
-                    util::time::sleep(delay).await;
-                    try_timeout = retry_policy.calculate_try_timeout(failed_attempt);
-                }
-                // Stop retrying and close the client. The connection close error is often more
-                // useful
-                None => match self.inner.close_if_owned().await {
-                    Ok(_) => return Err(error.into()),
-                    Err(dispose_err) => return Err(dispose_err.into()),
-                },
-            }
+        match &mut self.inner {
+            TransportClientKind::Amqp(client) => create_consumer_with_retry(
+                client,
+                &self.event_hub_name,
+                consumer_group,
+                partition_id,
+                consumer_identifier,
+                event_position,
+                retry_policy,
+                track_last_enqueued_event_properties,
+                owner_level,
+                prefetch_count,
+            )
+            .await
+            .map(TransportConsumerKind::Amqp),
+            #[cfg(feature = "in-memory-transport")]
+            TransportClientKind::InMemory(client) => create_consumer_with_retry(
+                client,
+                &self.event_hub_name,
+                consumer_group,
+                partition_id,
+                consumer_identifier,
+                event_position,
+                retry_policy,
+                track_last_enqueued_event_properties,
+                owner_level,
+                prefetch_count,
+            )
+            .await
+            .map(TransportConsumerKind::InMemory),
         }
     }
 
     /// Closes the inner client regardless of whether it is owned or shared.
     pub async fn close(mut self) -> Result<(), azure_core::Error> {
-        self.inner.close().await.map_err(Into::into)
+        self.inner.close().await
     }
 
     /// Closes the inner client if it is owned or if it is shared and this is the last reference to
     /// it.
     pub async fn close_if_owned(mut self) -> Result<(), azure_core::Error> {
-        self.inner.close_if_owned().await.map_err(Into::into)
+        self.inner.close_if_owned().await
     }
 }
 
@@ -465,6 +409,52 @@ impl EventHubConnection {
         }
     }
 
+    /// Creates a handle that shares this connection, but is associated with another Event Hub in
+    /// the same namespace.
+    ///
+    /// The clients that are created with the handle use its Event Hub, so the producers and
+    /// consumers of any number of Event Hubs share a single connection. Each link is authorized
+    /// for its own entity. A shared access signature that does not cover the other Event Hub is
+    /// signed again for the entity if the shared access key is known, otherwise the credential must
+    /// grant access to the namespace.
+    ///
+    /// A connection to an in-memory Event Hub only serves that Event Hub.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use azeventhubs::{
+    ///     producer::{EventHubProducerClient, SendEventOptions},
+    ///     EventHubConnection,
+    /// };
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut connection = EventHubConnection::new_from_connection_string(
+    ///     "<CONNECTION_STRING>",
+    ///     "orders".to_string(),
+    ///     Default::default(),
+    /// )
+    /// .await?;
+    /// let mut orders =
+    ///     EventHubProducerClient::with_connection(&mut connection, Default::default());
+    /// let mut invoices = EventHubProducerClient::with_connection(
+    ///     &mut connection.clone_for_event_hub("invoices"),
+    ///     Default::default(),
+    /// );
+    ///
+    /// orders.send_event("order", SendEventOptions::default()).await?;
+    /// invoices.send_event("invoice", SendEventOptions::default()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn clone_for_event_hub(&mut self, event_hub_name: impl Into<String>) -> Self {
+        Self {
+            fully_qualified_namespace: self.fully_qualified_namespace.clone(),
+            event_hub_name: Arc::new(event_hub_name.into()),
+            inner: self.inner.clone_as_shared(),
+        }
+    }
+
     /// The fully qualified namespace that the connection is associated with.
     pub fn fully_qualified_namespace(&self) -> &str {
         &self.fully_qualified_namespace
@@ -475,6 +465,16 @@ impl EventHubConnection {
         &self.event_hub_name
     }
 
+    /// The number of handles, including this one, that share the underlying connection
+    pub(crate) fn share_count(&self) -> usize {
+        self.inner.share_count()
+    }
+
+    /// Replaces the underlying connection for every handle that shares it
+    pub(crate) async fn replace_connection(&mut self) -> Result<(), azure_core::Error> {
+        self.inner.replace_connection().await
+    }
+
     /// Returns true if the connection is closed.
     pub fn is_closed(&self) -> bool {
         self.inner.is_closed()
@@ -495,6 +495,243 @@ impl EventHubConnection {
     }
 }
 
+async fn get_properties_with_retry<C, RP>(
+    client: &mut C,
+    event_hub_name: &str,
+    retry_policy: RP,
+) -> Result<EventHubProperties, azure_core::Error>
+where
+    C: TransportClient + RecoverableTransport,
+    RP: EventHubsRetryPolicy + Send,
+    azure_core::Error:
+        From<C::RequestResponseError> + From<C::DisposeError> + From<C::RecoverError>,
+    C::RequestResponseError: RecoverableError + From<Elapsed> + 'static,
+{
+    // // We don't need to explicitly check if the connection is closed here because
+    // // `client.get_properties` will do that for us.
+    // client.get_properties(retry_policy).await
+
+    let mut try_timeout = retry_policy.calculate_try_timeout(0);
+    let mut failed_attempt = 0;
+    let mut should_try_recover = false;
+
+    loop {
+        // The underlying AMQP client may get closed if idle for too long.  If that happens, we
+        // need to recreate it.
+        if should_try_recover {
+            client.recover().await?;
+        }
+
+        let fut = client.get_properties(event_hub_name);
+        let error = match util::time::timeout(try_timeout, fut).await {
+            Ok(Ok(response)) => return Ok(response),
+            Ok(Err(err)) => err,
+            Err(elapsed) => elapsed.into(),
+        };
+
+        log::debug!("get_properties failed: {:?}", error);
+
+        failed_attempt += 1;
+        let delay = retry_policy.calculate_retry_delay(&error, failed_attempt);
+        should_try_recover = error.should_try_recover();
+        match delay {
+            Some(delay) => {
+                util::time::sleep(delay).await;
+                try_timeout = retry_policy.calculate_try_timeout(failed_attempt);
+            }
+            // Stop retrying and close the client. The connection close error is often more
+            // useful
+            None => match client.close_if_owned().await {
+                Ok(_) => return Err(error.into()),
+                Err(dispose_err) => return Err(dispose_err.into()),
+            },
+        }
+    }
+}
+
+async fn get_partition_properties_with_retry<C, RP>(
+    client: &mut C,
+    event_hub_name: &str,
+    partition_id: &str,
+    retry_policy: RP,
+) -> Result<PartitionProperties, azure_core::Error>
+where
+    C: TransportClient + RecoverableTransport,
+    RP: EventHubsRetryPolicy + Send,
+    azure_core::Error:
+        From<C::RequestResponseError> + From<C::DisposeError> + From<C::RecoverError>,
+    C::RequestResponseError: RecoverableError + From<Elapsed> + 'static,
+{
+    let mut try_timeout = retry_policy.calculate_try_timeout(0);
+    let mut failed_attempt = 0;
+    let mut should_try_recover = false;
+
+    loop {
+        // The underlying AMQP client may get closed if idle for too long.  If that happens, we
+        // need to recreate it.
+        if should_try_recover {
+            client.recover().await?;
+        }
+
+        let fut = client.get_partition_properties(event_hub_name, partition_id);
+        let error = match util::time::timeout(try_timeout, fut).await {
+            Ok(Ok(response)) => return Ok(response),
+            Ok(Err(err)) => err,
+            Err(elapsed) => elapsed.into(),
+        };
+
+        log::debug!("get_partition_properties failed: {:?}", error);
+
+        failed_attempt += 1;
+        let delay = retry_policy.calculate_retry_delay(&error, failed_attempt);
+        should_try_recover = error.should_try_recover();
+        match delay {
+            Some(delay) => {
+                util::time::sleep(delay).await;
+                try_timeout = retry_policy.calculate_try_timeout(failed_attempt);
+            }
+            // Stop retrying and close the client. The connection close error is often more
+            // useful
+            None => match client.close_if_owned().await {
+                Ok(_) => return Err(error.into()),
+                Err(dispose_err) => return Err(dispose_err.into()),
+            },
+        }
+    }
+}
+
+async fn create_producer_with_retry<C, RP>(
+    client: &mut C,
+    event_hub_name: &str,
+    partition_id: Option<String>,
+    producer_identifier: Option<String>,
+    requested_features: TransportProducerFeatures,
+    partition_options: PartitionPublishingOptions,
+    retry_policy: RP,
+) -> Result<C::Producer<RP>, azure_core::Error>
+where
+    C: TransportClient + RecoverableTransport,
+    RP: EventHubsRetryPolicy + Send,
+    azure_core::Error:
+        From<C::OpenProducerError> + From<C::DisposeError> + From<C::RecoverError>,
+    C::OpenProducerError: RecoverableError + From<Elapsed> + 'static,
+{
+    let mut try_timeout = retry_policy.calculate_try_timeout(0);
+    let mut failed_attempt = 0;
+    let mut should_try_recover = false;
+
+    loop {
+        // The underlying AMQP client may get closed if idle for too long.  If that happens, we
+        // need to recreate it.
+        if should_try_recover {
+            client.recover().await?;
+        }
+
+        // TODO: can we reduce clone() calls?
+        let fut = client
+            .create_producer(
+                event_hub_name,
+                partition_id.clone(),
+                producer_identifier.clone(),
+                requested_features,
+                partition_options.clone(),
+                retry_policy.clone(),
+            );
+        let error = match util::time::timeout(try_timeout, fut).await {
+            Ok(Ok(response)) => return Ok(response),
+            Ok(Err(err)) => err,
+            Err(elapsed) => elapsed.into(),
+        };
+
+        log::debug!("create producer failed: {:?}", error);
+
+        failed_attempt += 1;
+        let delay = retry_policy.calculate_retry_delay(&error, failed_attempt);
+        should_try_recover = error.should_try_recover();
+        match delay {
+            Some(delay) => {
+                util::time::sleep(delay).await;
+                try_timeout = retry_policy.calculate_try_timeout(failed_attempt);
+            }
+            // Stop retrying and close the client. The connection close error is often more
+            // useful
+            None => match client.close_if_owned().await {
+                Ok(_) => return Err(error.into()),
+                Err(dispose_err) => return Err(dispose_err.into()),
+            },
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)] // TODO: how to reduce the number of arguments?
+async fn create_consumer_with_retry<C, RP>(
+    client: &mut C,
+    event_hub_name: &str,
+    consumer_group: &str,
+    partition_id: &str,
+    consumer_identifier: Option<String>,
+    event_position: EventPosition,
+    retry_policy: RP,
+    track_last_enqueued_event_properties: bool,
+    owner_level: Option<i64>,
+    prefetch_count: Option<u32>,
+) -> Result<C::Consumer<RP>, azure_core::Error>
+where
+    C: TransportClient + RecoverableTransport,
+    RP: EventHubsRetryPolicy + Send,
+    azure_core::Error:
+        From<C::OpenConsumerError> + From<C::DisposeError> + From<C::RecoverError>,
+    C::OpenConsumerError: RecoverableError + From<Elapsed> + 'static,
+{
+    let mut try_timeout = retry_policy.calculate_try_timeout(0);
+    let mut failed_attempt = 0;
+    let mut should_try_recover = false;
+
+    loop {
+        // The underlying AMQP client may get closed if idle for too long.  If that happens, we
+        // need to recreate it.
+        if should_try_recover {
+            client.recover().await?;
+        }
+
+        let fut = client
+            .create_consumer(
+                event_hub_name,
+                consumer_group,
+                partition_id,
+                consumer_identifier.clone(),
+                &event_position,
+                retry_policy.clone(),
+                track_last_enqueued_event_properties,
+                owner_level,
+                prefetch_count,
+            );
+        let error = match util::time::timeout(try_timeout, fut).await {
+            Ok(Ok(response)) => return Ok(response),
+            Ok(Err(err)) => err,
+            Err(elapsed) => elapsed.into(),
+        };
+
+        log::debug!("create consumer failed: {:?}", error);
+
+        failed_attempt += 1;
+        let delay = retry_policy.calculate_retry_delay(&error, failed_attempt);
+        should_try_recover = error.should_try_recover();
+        match delay {
+            Some(delay) => {
+                util::time::sleep(delay).await;
+                try_timeout = retry_policy.calculate_try_timeout(failed_attempt);
+            }
+            // Stop retrying and close the client. The connection close error is often more
+            // useful
+            None => match client.close_if_owned().await {
+                Ok(_) => return Err(error.into()),
+                Err(dispose_err) => return Err(dispose_err.into()),
+            },
+        }
+    }
+}
+
 // internal static string BuildConnectionSignatureAuthorizationResource(EventHubsTransportType transportType,
 //     string fullyQualifiedNamespace,
 //     string eventHubName)
@@ -552,6 +789,31 @@ impl RecoverableTransport for EventHubConnection {
     type RecoverError = azure_core::Error;
 
     async fn recover(&mut self) -> Result<(), Self::RecoverError> {
-        self.inner.recover().await.map_err(Into::into)
+        self.inner.recover().await
+    }
+}
+
+#[cfg(all(test, feature = "in-memory-transport"))]
+mod tests {
+    use crate::{
+        in_memory::fixture::TestEventHub,
+        producer::{EventHubProducerClient, SendEventOptions},
+    };
+
+    #[tokio::test]
+    async fn connection_for_another_event_hub_is_rejected() {
+        let mut test_hub = TestEventHub::new(1);
+        let mut other_connection = test_hub.connection.clone_for_event_hub("unknown-hub");
+        let mut producer =
+            EventHubProducerClient::with_connection(&mut other_connection, Default::default());
+
+        assert!(producer.get_partition_ids().await.is_err());
+        let options = SendEventOptions::new().with_partition_id("0");
+        assert!(producer.send_event("a", options).await.is_err());
+        assert!(test_hub.event_hub.events("0").is_empty());
+
+        // The connection keeps serving its own Event Hub
+        let mut producer = test_hub.producer();
+        assert_eq!(producer.get_partition_ids().await.unwrap(), ["0"]);
     }
 }
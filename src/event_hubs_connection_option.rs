@@ -1,7 +1,12 @@
 use std::time::Duration;
 use url::Url;
 
-use crate::event_hubs_transport_type::EventHubsTransportType;
+use crate::{
+    amqp::amqp_constants,
+    event_hubs_proxy_options::EventHubsProxyOptions,
+    event_hubs_tls_connector::EventHubsTlsConnector,
+    event_hubs_transport_type::EventHubsTransportType,
+};
 
 /// The set of options that can be specified when creating [`crate::EventHubConnection`]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -13,10 +18,45 @@ pub struct EventHubConnectionOptions {
     /// service.
     pub transport_type: EventHubsTransportType,
 
-    // send_buffer_size_in_bytes: usize, // TODO: need upstream to support changing buffer size
-    // receive_buffer_size_in_bytes: usize, // TODO: need upstream to support changing buffer size
+    /// The size of the send buffer of the socket. If `None`, the default of the operating system
+    /// is used.
+    pub send_buffer_size_in_bytes: Option<u32>,
+
+    /// The size of the receive buffer of the socket. If `None`, the default of the operating
+    /// system is used.
+    pub receive_buffer_size_in_bytes: Option<u32>,
+
+    /// The largest AMQP frame that the connection accepts, in bytes. Values below the minimum of
+    /// 512 bytes that the AMQP protocol allows are raised to the minimum.
+    pub max_frame_size: u32,
+
+    /// The highest channel number that can be used on the connection, which limits the number of
+    /// sessions that the connection can have at once
+    pub channel_max: u16,
+
+    /// The number of transfer frames that each session can receive before the service has to wait
+    /// for the session to process them
+    pub session_incoming_window: u32,
+
+    /// The number of transfer frames that each session can send before it has to wait for the
+    /// service to process them
+    pub session_outgoing_window: u32,
+
     /// The custom address to use for establishing a connection to the Event Hubs service
+    ///
+    /// The scheme is replaced with the one of the transport type, except that an `amqp://`
+    /// address with [`EventHubsTransportType::AmqpTcp`] is connected to without TLS, eg. for a
+    /// local emulator.
     pub custom_endpoint_address: Option<Url>,
+
+    /// The TLS connector to use instead of the default one, eg. to trust a custom CA bundle or to
+    /// present a client certificate. It is applied to both transport types.
+    pub tls_connector: Option<EventHubsTlsConnector>,
+
+    /// The HTTP proxy to tunnel the connection through. A proxy can only be used with
+    /// [`EventHubsTransportType::AmqpWebSockets`], and opening a connection with
+    /// [`EventHubsTransportType::AmqpTcp`] and a proxy fails.
+    pub proxy: Option<EventHubsProxyOptions>,
 }
 
 impl Default for EventHubConnectionOptions {
@@ -24,7 +64,15 @@ impl Default for EventHubConnectionOptions {
         Self {
             connection_idle_timeout: Duration::from_secs(60),
             transport_type: Default::default(),
+            send_buffer_size_in_bytes: None,
+            receive_buffer_size_in_bytes: None,
+            max_frame_size: amqp_constants::DEFAULT_MAX_FRAME_SIZE,
+            channel_max: fe2o3_amqp::connection::DEFAULT_CHANNEL_MAX,
+            session_incoming_window: fe2o3_amqp::session::DEFAULT_WINDOW,
+            session_outgoing_window: fe2o3_amqp::session::DEFAULT_WINDOW,
             custom_endpoint_address: Default::default(),
+            tls_connector: Default::default(),
+            proxy: Default::default(),
         }
     }
 }
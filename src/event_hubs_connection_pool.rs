@@ -0,0 +1,733 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use azure_core::auth::{AccessToken, TokenCredential};
+
+use crate::{
+    authorization::{AzureNamedKeyCredential, EventHubTokenCredential},
+    consumer::{EventHubConsumerClient, EventHubConsumerClientOptions, EventPosition},
+    core::BasicRetryPolicy,
+    event_hubs_connection::EventHubConnection,
+    event_hubs_connection_option::EventHubConnectionOptions,
+    event_hubs_connection_string_properties::EventHubsConnectionStringProperties,
+    event_hubs_retry_options::EventHubsRetryOptions,
+    primitives::{PartitionReceiver, PartitionReceiverOptions},
+    producer::{EventHubProducerClient, EventHubProducerClientOptions},
+};
+
+#[cfg(feature = "in-memory-transport")]
+use crate::in_memory::InMemoryEventHub;
+
+/// Error with the [`EventHubConnectionPool`].
+#[derive(Debug, thiserror::Error)]
+pub enum EventHubConnectionPoolError {
+    /// The pool has no connection that it can share with the Event Hub, and no more connections
+    /// can be opened, ie. [`EventHubConnectionPoolOptions::max_connections`] is zero.
+    #[error("The connection pool has no connection available for the Event Hub")]
+    Exhausted,
+
+    /// The Event Hub is not one of the in-memory Event Hubs that the pool was created with.
+    #[error("The Event Hub is unknown to the connection pool")]
+    UnknownEventHub,
+}
+
+impl From<EventHubConnectionPoolError> for azure_core::error::Error {
+    fn from(error: EventHubConnectionPoolError) -> Self {
+        use azure_core::error::ErrorKind;
+
+        azure_core::Error::new(ErrorKind::Other, error)
+    }
+}
+
+/// The set of options that can be specified when creating an [`EventHubConnectionPool`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EventHubConnectionPoolOptions {
+    /// The options that every connection of the pool is opened with.
+    pub connection_options: EventHubConnectionOptions,
+
+    /// The maximum number of connections that the pool holds across all the Event Hubs.
+    pub max_connections: usize,
+
+    /// The number of clients that share a connection before the pool opens another connection to
+    /// the same Event Hub. Once `max_connections` is reached, the clients are added to the least
+    /// used connection of the Event Hub instead, or to the least used connection of the pool if
+    /// none serves the Event Hub yet.
+    pub max_clients_per_connection: usize,
+
+    /// The retry options of the requests that [`EventHubConnectionPool::check_health`] sends.
+    pub retry_options: EventHubsRetryOptions,
+}
+
+impl Default for EventHubConnectionPoolOptions {
+    fn default() -> Self {
+        Self {
+            connection_options: Default::default(),
+            max_connections: 4,
+            max_clients_per_connection: 16,
+            retry_options: Default::default(),
+        }
+    }
+}
+
+/// Shares one credential among all the connections of the pool
+#[derive(Debug, Clone)]
+struct SharedTokenCredential(Arc<dyn TokenCredential>);
+
+impl TokenCredential for SharedTokenCredential {
+    fn get_token<'life0, 'life1, 'life2, 'async_trait>(
+        &'life0 self,
+        scopes: &'life1 [&'life2 str],
+    ) -> Pin<Box<dyn Future<Output = azure_core::Result<AccessToken>> + Send + 'async_trait>>
+    where
+        Self: 'async_trait,
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        'life2: 'async_trait,
+    {
+        self.0.get_token(scopes)
+    }
+
+    fn clear_cache<'life0, 'async_trait>(
+        &'life0 self,
+    ) -> Pin<Box<dyn Future<Output = azure_core::Result<()>> + Send + 'async_trait>>
+    where
+        Self: 'async_trait,
+        'life0: 'async_trait,
+    {
+        self.0.clear_cache()
+    }
+}
+
+/// How the connections of the pool are opened
+enum ConnectionSource {
+    ConnectionString(String),
+    NamedKeyCredential {
+        fully_qualified_namespace: String,
+        name: String,
+        key: String,
+    },
+    TokenCredential {
+        fully_qualified_namespace: String,
+        credential: SharedTokenCredential,
+    },
+    #[cfg(feature = "in-memory-transport")]
+    InMemory(Vec<InMemoryEventHub>),
+}
+
+impl std::fmt::Debug for ConnectionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The connection string and the key are secrets
+        match self {
+            Self::ConnectionString(_) => f.debug_tuple("ConnectionString").finish(),
+            Self::NamedKeyCredential {
+                fully_qualified_namespace,
+                name,
+                ..
+            } => f
+                .debug_struct("NamedKeyCredential")
+                .field("fully_qualified_namespace", fully_qualified_namespace)
+                .field("name", name)
+                .finish_non_exhaustive(),
+            Self::TokenCredential {
+                fully_qualified_namespace,
+                ..
+            } => f
+                .debug_struct("TokenCredential")
+                .field("fully_qualified_namespace", fully_qualified_namespace)
+                .finish_non_exhaustive(),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(event_hubs) => f.debug_tuple("InMemory").field(event_hubs).finish(),
+        }
+    }
+}
+
+impl ConnectionSource {
+    async fn open(
+        &self,
+        event_hub_name: &str,
+        options: EventHubConnectionOptions,
+    ) -> Result<EventHubConnection, azure_core::Error> {
+        match self {
+            Self::ConnectionString(connection_string) => {
+                EventHubConnection::new_from_connection_string(
+                    connection_string,
+                    event_hub_name.to_string(),
+                    options,
+                )
+                .await
+            }
+            Self::NamedKeyCredential {
+                fully_qualified_namespace,
+                name,
+                key,
+            } => {
+                EventHubConnection::new_from_named_key_credential(
+                    fully_qualified_namespace.clone(),
+                    event_hub_name,
+                    AzureNamedKeyCredential::new(name, key),
+                    options,
+                )
+                .await
+            }
+            Self::TokenCredential {
+                fully_qualified_namespace,
+                credential,
+            } => {
+                EventHubConnection::new_from_credential(
+                    fully_qualified_namespace.clone(),
+                    event_hub_name,
+                    EventHubTokenCredential::from(credential.clone()),
+                    options,
+                )
+                .await
+            }
+            // The connection can be shared by the other Event Hubs of the pool, like an AMQP
+            // connection to the namespace
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(event_hubs) => {
+                match event_hubs
+                    .iter()
+                    .any(|event_hub| event_hub.event_hub_name() == event_hub_name)
+                {
+                    true => Ok(EventHubConnection::new_from_in_memory_event_hubs(
+                        event_hub_name,
+                        event_hubs.clone(),
+                    )),
+                    false => Err(EventHubConnectionPoolError::UnknownEventHub.into()),
+                }
+            }
+        }
+    }
+}
+
+/// A connection of the pool with a handle for each Event Hub that it serves
+#[derive(Debug)]
+struct PooledConnection {
+    /// The first handle is the one that the connection was opened for
+    handles: Vec<EventHubConnection>,
+}
+
+impl PooledConnection {
+    fn new(connection: EventHubConnection) -> Self {
+        Self {
+            handles: vec![connection],
+        }
+    }
+
+    /// The handles that the pool keeps are not clients
+    fn client_count(&self) -> usize {
+        self.handles[0].share_count() - self.handles.len()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.handles[0].is_closed()
+    }
+
+    fn serves(&self, event_hub_name: &str) -> bool {
+        self.handles
+            .iter()
+            .any(|handle| handle.event_hub_name() == event_hub_name)
+    }
+
+    /// The handle for the Event Hub, which is added if the connection does not serve it yet
+    fn handle(&mut self, event_hub_name: &str) -> &mut EventHubConnection {
+        let index = match self
+            .handles
+            .iter()
+            .position(|handle| handle.event_hub_name() == event_hub_name)
+        {
+            Some(index) => index,
+            None => {
+                let handle = self.handles[0].clone_for_event_hub(event_hub_name);
+                self.handles.push(handle);
+                self.handles.len() - 1
+            }
+        };
+        &mut self.handles[index]
+    }
+
+    fn event_hub_names(&self) -> Vec<&str> {
+        self.handles
+            .iter()
+            .map(|handle| handle.event_hub_name())
+            .collect()
+    }
+
+    async fn close(self) -> Result<(), azure_core::Error> {
+        let mut handles = self.handles.into_iter();
+        match handles.next() {
+            Some(connection) => connection.close().await,
+            None => Ok(()),
+        }
+    }
+
+    async fn close_if_owned(mut self) -> Result<(), azure_core::Error> {
+        // Only the first handle is left to tell whether clients still share the connection
+        self.handles.truncate(1);
+        match self.handles.pop() {
+            Some(connection) => connection.close_if_owned().await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// A pool of connections to the Event Hubs of one namespace, which hands out producer, consumer
+/// and receiver clients that share a bounded number of connections.
+///
+/// Connections are opened lazily when a client for an Event Hub is first requested. A new client
+/// is added to the connection of its Event Hub that is shared by the fewest clients, and another
+/// connection is opened once every connection of the Event Hub has
+/// [`EventHubConnectionPoolOptions::max_clients_per_connection`] clients. If the pool already
+/// holds [`EventHubConnectionPoolOptions::max_connections`] connections, a connection that is no
+/// longer used by any client is closed to make room for the new one.
+///
+/// Once the pool holds as many connections as it may, a client for an Event Hub that has no
+/// connection of its own is added to the least used connection of the pool, which then serves
+/// both Event Hubs like a connection from [`EventHubConnection::clone_for_event_hub`].
+///
+/// Links are not moved between connections: a client keeps the connection that it was created
+/// with, and the clients are only balanced as they are created. Closed connections are dropped
+/// when a client is requested. [`EventHubConnectionPool::check_health`] replaces a connection that
+/// can no longer reach the service but is still used by clients: a new AMQP connection is opened
+/// behind the handle that the clients share, and each client recovers its links on the
+/// replacement the next time it sends or receives. A connection that no client uses, or that
+/// cannot be replaced, is dropped.
+///
+/// The clients use the retry policy [`BasicRetryPolicy`]. A client with a custom retry policy can
+/// be created with a handle from [`EventHubConnectionPool::connection`]. The connection options of
+/// the client options are ignored, because the connections of the pool are opened with
+/// [`EventHubConnectionPoolOptions::connection_options`].
+#[derive(Debug)]
+pub struct EventHubConnectionPool {
+    source: ConnectionSource,
+    options: EventHubConnectionPoolOptions,
+    connections: Vec<PooledConnection>,
+}
+
+impl EventHubConnectionPool {
+    /// Creates a new [`EventHubConnectionPool`] from a connection string of the namespace. The
+    /// Event Hub of the connection string, if any, is ignored.
+    pub fn new_from_connection_string(
+        connection_string: impl Into<String>,
+        options: EventHubConnectionPoolOptions,
+    ) -> Result<Self, azure_core::Error> {
+        let connection_string = connection_string.into();
+        // Fail early on a malformed connection string instead of on the first client
+        EventHubsConnectionStringProperties::parse(&connection_string)?;
+        Ok(Self::new(
+            ConnectionSource::ConnectionString(connection_string),
+            options,
+        ))
+    }
+
+    /// Creates a new [`EventHubConnectionPool`] from a namespace and a
+    /// [`AzureNamedKeyCredential`].
+    pub fn new_from_named_key_credential(
+        fully_qualified_namespace: impl Into<String>,
+        credential: AzureNamedKeyCredential,
+        options: EventHubConnectionPoolOptions,
+    ) -> Self {
+        Self::new(
+            ConnectionSource::NamedKeyCredential {
+                fully_qualified_namespace: fully_qualified_namespace.into(),
+                name: credential.name().to_string(),
+                key: credential.key().to_string(),
+            },
+            options,
+        )
+    }
+
+    /// Creates a new [`EventHubConnectionPool`] from a namespace and a [`TokenCredential`], eg.
+    /// [`azure_identity::DefaultAzureCredential`], that is shared by all the connections.
+    pub fn new_from_token_credential(
+        fully_qualified_namespace: impl Into<String>,
+        credential: impl TokenCredential + 'static,
+        options: EventHubConnectionPoolOptions,
+    ) -> Self {
+        Self::new(
+            ConnectionSource::TokenCredential {
+                fully_qualified_namespace: fully_qualified_namespace.into(),
+                credential: SharedTokenCredential(Arc::new(credential)),
+            },
+            options,
+        )
+    }
+
+    /// Creates a new [`EventHubConnectionPool`] whose connections publish to and read from the
+    /// Event Hubs in memory, which is useful for testing.
+    #[cfg(feature = "in-memory-transport")]
+    pub fn new_from_in_memory_event_hubs(
+        event_hubs: impl IntoIterator<Item = InMemoryEventHub>,
+        options: EventHubConnectionPoolOptions,
+    ) -> Self {
+        Self::new(
+            ConnectionSource::InMemory(event_hubs.into_iter().collect()),
+            options,
+        )
+    }
+
+    fn new(source: ConnectionSource, options: EventHubConnectionPoolOptions) -> Self {
+        Self {
+            source,
+            options,
+            connections: Vec::new(),
+        }
+    }
+}
+
+impl EventHubConnectionPool {
+    /// Returns a handle to the least used connection to the Event Hub, opening a connection if
+    /// needed.
+    pub async fn connection(
+        &mut self,
+        event_hub_name: &str,
+    ) -> Result<EventHubConnection, azure_core::Error> {
+        let connection = self.select_connection(event_hub_name).await?;
+        Ok(connection.clone_as_shared())
+    }
+
+    /// Creates an [`EventHubProducerClient`] for the Event Hub.
+    pub async fn create_producer_client(
+        &mut self,
+        event_hub_name: &str,
+        client_options: EventHubProducerClientOptions,
+    ) -> Result<EventHubProducerClient<BasicRetryPolicy>, azure_core::Error> {
+        let connection = self.select_connection(event_hub_name).await?;
+        Ok(EventHubProducerClient::with_connection(
+            connection,
+            client_options,
+        ))
+    }
+
+    /// Creates an [`EventHubConsumerClient`] for the consumer group of the Event Hub.
+    pub async fn create_consumer_client(
+        &mut self,
+        event_hub_name: &str,
+        consumer_group: impl Into<String>,
+        client_options: EventHubConsumerClientOptions,
+    ) -> Result<EventHubConsumerClient<BasicRetryPolicy>, azure_core::Error> {
+        let connection = self.select_connection(event_hub_name).await?;
+        Ok(EventHubConsumerClient::with_connection(
+            consumer_group,
+            connection,
+            client_options,
+        ))
+    }
+
+    /// Creates a [`PartitionReceiver`] for a partition of the Event Hub.
+    pub async fn create_partition_receiver(
+        &mut self,
+        event_hub_name: &str,
+        consumer_group: &str,
+        partition_id: &str,
+        event_position: EventPosition,
+        options: PartitionReceiverOptions,
+    ) -> Result<PartitionReceiver<BasicRetryPolicy>, azure_core::Error> {
+        let connection = self.connection(event_hub_name).await?;
+        PartitionReceiver::with_policy()
+            .with_connection(
+                consumer_group,
+                partition_id,
+                event_position,
+                connection,
+                options,
+            )
+            .await
+    }
+
+    /// Sends a request to the service over every connection. A connection that cannot be recovered
+    /// within the retry options is replaced if clients still use it, whose links are then recovered
+    /// on the replacement, and is dropped otherwise. Returns the number of connections that were
+    /// dropped.
+    pub async fn check_health(&mut self) -> usize {
+        let retry_policy = || BasicRetryPolicy::from(self.options.retry_options.clone());
+        let mut healthy = Vec::with_capacity(self.connections.len());
+        let mut dropped = 0;
+        for mut pooled in self.connections.drain(..) {
+            let is_closed = pooled.is_closed();
+            let client_count = pooled.client_count();
+            let connection = &mut pooled.handles[0];
+            let mut is_healthy =
+                !is_closed && connection.get_properties(retry_policy()).await.is_ok();
+            // A connection that was closed by a client cannot be reopened
+            if !is_healthy && !is_closed && client_count > 0 {
+                log::warn!(
+                    "Replacing unhealthy connection to {:?}",
+                    pooled.event_hub_names()
+                );
+                let connection = &mut pooled.handles[0];
+                is_healthy = match connection.replace_connection().await {
+                    Ok(()) => connection.get_properties(retry_policy()).await.is_ok(),
+                    Err(err) => {
+                        log::error!("Error replacing unhealthy connection: {:?}", err);
+                        false
+                    }
+                };
+            }
+            if is_healthy {
+                healthy.push(pooled);
+            } else {
+                log::warn!(
+                    "Dropping unhealthy connection to {:?}",
+                    pooled.event_hub_names()
+                );
+                dropped += 1;
+                if let Err(err) = pooled.close_if_owned().await {
+                    log::error!("Error closing unhealthy connection: {:?}", err);
+                }
+            }
+        }
+        self.connections = healthy;
+        dropped
+    }
+
+    /// The number of connections that the pool holds
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// The number of clients that share each connection of the pool that serves the Event Hub,
+    /// including the clients of the other Event Hubs that the connection serves
+    pub fn client_counts(&self, event_hub_name: &str) -> Vec<usize> {
+        self.connections
+            .iter()
+            .filter(|connection| connection.serves(event_hub_name))
+            .map(PooledConnection::client_count)
+            .collect()
+    }
+
+    /// Closes every connection of the pool, including the ones that are still used by clients.
+    pub async fn close(self) -> Result<(), azure_core::Error> {
+        let mut result = Ok(());
+        for connection in self.connections {
+            if let Err(err) = connection.close().await {
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    async fn select_connection(
+        &mut self,
+        event_hub_name: &str,
+    ) -> Result<&mut EventHubConnection, azure_core::Error> {
+        self.connections.retain(|connection| !connection.is_closed());
+
+        let least_used = find_least_used(&self.connections, |connection| {
+            connection.serves(event_hub_name)
+        });
+        if let Some((index, count)) = least_used {
+            if count < self.options.max_clients_per_connection {
+                return Ok(self.connections[index].handle(event_hub_name));
+            }
+        }
+
+        if self.connections.len() >= self.options.max_connections {
+            let idle = self
+                .connections
+                .iter()
+                .position(|connection| connection.client_count() == 0);
+            match (idle, least_used) {
+                (Some(index), _) => {
+                    let idle = self.connections.swap_remove(index);
+                    if let Err(err) = idle.close().await {
+                        log::error!("Error closing idle connection: {:?}", err);
+                    }
+                }
+                // The connections of the Event Hub take more clients than the limit rather than
+                // exceeding the number of connections
+                (None, Some((index, _))) => {
+                    return Ok(self.connections[index].handle(event_hub_name))
+                }
+                // Every connection is in use by other Event Hubs, so the Event Hub shares the
+                // least used one
+                (None, None) => {
+                    return match find_least_used(&self.connections, |_| true) {
+                        Some((index, _)) => Ok(self.connections[index].handle(event_hub_name)),
+                        None => Err(EventHubConnectionPoolError::Exhausted.into()),
+                    }
+                }
+            }
+        }
+
+        let connection = self
+            .source
+            .open(event_hub_name, self.options.connection_options.clone())
+            .await?;
+        self.connections.push(PooledConnection::new(connection));
+        let last = self.connections.len() - 1;
+        Ok(self.connections[last].handle(event_hub_name))
+    }
+}
+
+/// The index and the client count of the least used connection that matches the predicate
+fn find_least_used(
+    connections: &[PooledConnection],
+    predicate: impl Fn(&PooledConnection) -> bool,
+) -> Option<(usize, usize)> {
+    connections
+        .iter()
+        .enumerate()
+        .filter(|(_, connection)| predicate(connection))
+        .min_by_key(|(_, connection)| connection.client_count())
+        .map(|(index, connection)| (index, connection.client_count()))
+}
+
+#[cfg(all(test, feature = "in-memory-transport"))]
+mod tests {
+    use futures_util::StreamExt;
+
+    use crate::{
+        consumer::{EventHubConsumerClient, EventPosition},
+        in_memory::{fixture::TestEventHub, InMemoryEventHub},
+        producer::SendEventOptions,
+    };
+
+    use super::{EventHubConnectionPool, EventHubConnectionPoolOptions};
+
+    fn pool_options(
+        max_connections: usize,
+        max_clients_per_connection: usize,
+    ) -> EventHubConnectionPoolOptions {
+        EventHubConnectionPoolOptions {
+            max_connections,
+            max_clients_per_connection,
+            retry_options: TestEventHub::retry_options(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn pool_balances_clients_over_bounded_connections() {
+        let event_hub = InMemoryEventHub::new("test-hub", 1);
+        let mut pool = EventHubConnectionPool::new_from_in_memory_event_hubs(
+            [event_hub.clone()],
+            pool_options(2, 2),
+        );
+
+        let mut producers = Vec::new();
+        for expected in [vec![1], vec![2], vec![2, 1], vec![2, 2], vec![3, 2]] {
+            let producer = pool
+                .create_producer_client("test-hub", Default::default())
+                .await
+                .unwrap();
+            producers.push(producer);
+            assert_eq!(pool.client_counts("test-hub"), expected);
+        }
+        assert_eq!(pool.connection_count(), 2);
+
+        // Dropped clients no longer count against their connection
+        producers.truncate(2);
+        let mut counts = pool.client_counts("test-hub");
+        counts.sort();
+        assert_eq!(counts, [0, 2]);
+
+        let mut consumer = pool
+            .create_consumer_client(
+                "test-hub",
+                EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(pool.client_counts("test-hub").iter().sum::<usize>(), 3);
+        let options = SendEventOptions::new().with_partition_id("0");
+        producers[0].send_event("a", options).await.unwrap();
+        let mut stream = consumer
+            .read_events_from_partition("0", EventPosition::earliest(), Default::default())
+            .await
+            .unwrap();
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!(event.body().unwrap(), b"a");
+        stream.close().await.unwrap();
+
+        pool.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pool_shares_busy_connection_with_another_event_hub() {
+        let first = InMemoryEventHub::new("first-hub", 1);
+        let second = InMemoryEventHub::new("second-hub", 1);
+        let mut pool = EventHubConnectionPool::new_from_in_memory_event_hubs(
+            [first.clone(), second.clone()],
+            pool_options(1, 4),
+        );
+
+        let mut first_producer = pool
+            .create_producer_client("first-hub", Default::default())
+            .await
+            .unwrap();
+        let mut second_producer = pool
+            .create_producer_client("second-hub", Default::default())
+            .await
+            .unwrap();
+        assert_eq!(pool.connection_count(), 1);
+        assert_eq!(pool.client_counts("first-hub"), [2]);
+        assert_eq!(pool.client_counts("second-hub"), [2]);
+
+        let options = SendEventOptions::new().with_partition_id("0");
+        first_producer
+            .send_event("first", options.clone())
+            .await
+            .unwrap();
+        second_producer.send_event("second", options).await.unwrap();
+        assert_eq!(first.events("0").len(), 1);
+        assert_eq!(second.events("0").len(), 1);
+
+        assert!(pool
+            .create_producer_client("unknown-hub", Default::default())
+            .await
+            .unwrap()
+            .send_event("unknown", SendEventOptions::default())
+            .await
+            .is_err());
+        pool.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pool_closes_idle_connection_for_another_event_hub() {
+        let first = InMemoryEventHub::new("first-hub", 1);
+        let second = InMemoryEventHub::new("second-hub", 1);
+        let mut pool = EventHubConnectionPool::new_from_in_memory_event_hubs(
+            [first, second],
+            pool_options(1, 4),
+        );
+
+        let producer = pool
+            .create_producer_client("first-hub", Default::default())
+            .await
+            .unwrap();
+        drop(producer);
+        let _producer = pool
+            .create_producer_client("second-hub", Default::default())
+            .await
+            .unwrap();
+        assert_eq!(pool.connection_count(), 1);
+        assert!(pool.client_counts("first-hub").is_empty());
+        assert_eq!(pool.client_counts("second-hub"), [1]);
+
+        assert!(EventHubConnectionPool::new_from_in_memory_event_hubs([], pool_options(1, 4))
+            .create_producer_client("unknown-hub", Default::default())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn pool_drops_dead_connections() {
+        let event_hub = InMemoryEventHub::new("test-hub", 1);
+        let mut pool =
+            EventHubConnectionPool::new_from_in_memory_event_hubs([event_hub], pool_options(2, 1));
+
+        let first = pool.connection("test-hub").await.unwrap();
+        let _second = pool.connection("test-hub").await.unwrap();
+        assert_eq!(pool.connection_count(), 2);
+        assert_eq!(pool.check_health().await, 0);
+
+        first.close().await.unwrap();
+        assert_eq!(pool.check_health().await, 1);
+        assert_eq!(pool.connection_count(), 1);
+
+        // A replacement is opened for the next client
+        let _third = pool.connection("test-hub").await.unwrap();
+        assert_eq!(pool.client_counts("test-hub"), [1, 1]);
+    }
+}
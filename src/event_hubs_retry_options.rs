@@ -7,6 +7,8 @@ const DEFAULT_MAX_RETRIES: u32 = 3;
 const DEFAULT_DELAY: Duration = Duration::from_millis(800);
 const DEFAULT_MAXIMUM_DELAY: Duration = Duration::from_secs(60);
 const DEFAULT_TRY_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_MINIMUM_THROTTLE_DELAY: Duration = Duration::from_secs(4);
+const DEFAULT_MAXIMUM_THROTTLE_DELAY: Duration = Duration::from_secs(8);
 
 /// The maximum number of retry attempts before considering the associated operation to have failed
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -63,6 +65,18 @@ pub struct EventHubsRetryOptions {
     ///
     /// The default retry mode is [`EventHubsRetryMode::Exponential`]
     pub mode: EventHubsRetryMode,
+
+    /// The minimum additional delay to apply when the service indicates that it is busy and the
+    /// client should throttle its requests
+    ///
+    /// The default value is 4 seconds
+    pub minimum_throttle_delay: Duration,
+
+    /// The maximum additional delay to apply when the service indicates that it is busy and the
+    /// client should throttle its requests
+    ///
+    /// The default value is 8 seconds
+    pub maximum_throttle_delay: Duration,
 }
 
 impl Default for EventHubsRetryOptions {
@@ -73,13 +87,15 @@ impl Default for EventHubsRetryOptions {
             maximum_delay: DEFAULT_MAXIMUM_DELAY,
             try_timeout: DEFAULT_TRY_TIMEOUT,
             mode: EventHubsRetryMode::default(),
+            minimum_throttle_delay: DEFAULT_MINIMUM_THROTTLE_DELAY,
+            maximum_throttle_delay: DEFAULT_MAXIMUM_THROTTLE_DELAY,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{EventHubsRetryOptions, MaxRetries, event_hubs_retry_options::{DEFAULT_MAXIMUM_DELAY, DEFAULT_DELAY, DEFAULT_TRY_TIMEOUT}, EventHubsRetryMode};
+    use crate::{EventHubsRetryOptions, MaxRetries, event_hubs_retry_options::{DEFAULT_MAXIMUM_DELAY, DEFAULT_DELAY, DEFAULT_TRY_TIMEOUT, DEFAULT_MINIMUM_THROTTLE_DELAY, DEFAULT_MAXIMUM_THROTTLE_DELAY}, EventHubsRetryMode};
 
     #[test]
     fn default_values() {
@@ -93,5 +109,7 @@ mod tests {
         assert_eq!(options.maximum_delay, DEFAULT_MAXIMUM_DELAY);
         assert_eq!(options.try_timeout, DEFAULT_TRY_TIMEOUT);
         assert_eq!(options.mode, EventHubsRetryMode::default());
+        assert_eq!(options.minimum_throttle_delay, DEFAULT_MINIMUM_THROTTLE_DELAY);
+        assert_eq!(options.maximum_throttle_delay, DEFAULT_MAXIMUM_THROTTLE_DELAY);
     }
 }
@@ -6,9 +6,21 @@ pub trait EventHubsRetryPolicy: Clone {
     fn calculate_try_timeout(&self, attempt_count: u32) -> Duration;
 
     /// Calculates the amount of time to wait before retrying an operation.
+    ///
+    /// Implementations should return `None` if [`EventHubsRetryPolicy::should_retry`] returns
+    /// `false` for the `last_error`.
     fn calculate_retry_delay(
         &self,
-        last_error: &dyn std::error::Error,
+        last_error: &(dyn std::error::Error + 'static),
         attempt_count: u32,
     ) -> Option<Duration>;
+
+    /// Determines whether an operation that failed with `last_error` should be retried.
+    ///
+    /// The default implementation returns `false` for the errors that are known to be terminal,
+    /// such as unauthorized access, an Event Hub or consumer group that does not exist, or an
+    /// invalid argument, and `true` for all other errors.
+    fn should_retry(&self, last_error: &(dyn std::error::Error + 'static)) -> bool {
+        crate::amqp::error::is_transient_error(last_error)
+    }
 }
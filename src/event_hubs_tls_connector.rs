@@ -0,0 +1,115 @@
+use std::{
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+#[derive(Clone)]
+pub(crate) enum TlsConnectorKind {
+    #[cfg(feature = "native-tls")]
+    NativeTls(native_tls::TlsConnector),
+    #[cfg(feature = "rustls")]
+    Rustls(Arc<librustls::ClientConfig>),
+}
+
+impl TlsConnectorKind {
+    fn backend(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "native-tls")]
+            TlsConnectorKind::NativeTls(_) => "native-tls",
+            #[cfg(feature = "rustls")]
+            TlsConnectorKind::Rustls(_) => "rustls",
+        }
+    }
+}
+
+/// A TLS connector that is used instead of the default one of the enabled TLS backend when
+/// establishing a connection to the Event Hubs service, eg. to trust a custom CA bundle or to
+/// present a client certificate.
+///
+/// The connector is applied to both [`crate::EventHubsTransportType::AmqpTcp`] and
+/// [`crate::EventHubsTransportType::AmqpWebSockets`]. It is created from a
+/// `native_tls::TlsConnector` (version 0.2) if the `"native-tls"` feature is enabled, or from a
+/// `rustls::ClientConfig` (version 0.22) if the `"rustls"` feature is enabled.
+///
+/// Two connectors are equal only if they are clones of each other.
+#[derive(Clone)]
+pub struct EventHubsTlsConnector {
+    pub(crate) kind: Arc<TlsConnectorKind>,
+}
+
+impl EventHubsTlsConnector {
+    /// Creates a connector that establishes TLS with `native-tls`
+    #[cfg(feature = "native-tls")]
+    pub fn from_native_tls(connector: native_tls::TlsConnector) -> Self {
+        Self {
+            kind: Arc::new(TlsConnectorKind::NativeTls(connector)),
+        }
+    }
+
+    /// Creates a connector that establishes TLS with `rustls`
+    #[cfg(feature = "rustls")]
+    pub fn from_rustls(config: Arc<librustls::ClientConfig>) -> Self {
+        Self {
+            kind: Arc::new(TlsConnectorKind::Rustls(config)),
+        }
+    }
+
+    /// The connector that is used when none is given, which trusts the system certificates with
+    /// `native-tls`, or the Mozilla root certificates with `rustls`. `native-tls` is preferred if
+    /// both features are enabled.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub(crate) fn default_connector() -> std::io::Result<Self> {
+        #[cfg(feature = "native-tls")]
+        {
+            native_tls::TlsConnector::new()
+                .map(Self::from_native_tls)
+                .map_err(std::io::Error::other)
+        }
+
+        #[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+        {
+            let mut root_cert_store = librustls::RootCertStore::empty();
+            root_cert_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            let config = librustls::ClientConfig::builder()
+                .with_root_certificates(root_cert_store)
+                .with_no_client_auth();
+            Ok(Self::from_rustls(Arc::new(config)))
+        }
+    }
+}
+
+#[cfg(feature = "native-tls")]
+impl From<native_tls::TlsConnector> for EventHubsTlsConnector {
+    fn from(connector: native_tls::TlsConnector) -> Self {
+        Self::from_native_tls(connector)
+    }
+}
+
+#[cfg(feature = "rustls")]
+impl From<Arc<librustls::ClientConfig>> for EventHubsTlsConnector {
+    fn from(config: Arc<librustls::ClientConfig>) -> Self {
+        Self::from_rustls(config)
+    }
+}
+
+impl std::fmt::Debug for EventHubsTlsConnector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventHubsTlsConnector")
+            .field("backend", &self.kind.backend())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for EventHubsTlsConnector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.kind, &other.kind)
+    }
+}
+
+impl Eq for EventHubsTlsConnector {}
+
+impl Hash for EventHubsTlsConnector {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.kind).hash(state);
+    }
+}
@@ -19,6 +19,7 @@ impl Default for EventHubsTransportType {
 
 impl EventHubsTransportType {
     pub(crate) const AMQP_SCHEME: &'static str = "amqps";
+    pub(crate) const PLAIN_AMQP_SCHEME: &'static str = "amqp";
     pub(crate) const WEBSOCKET_SCHEME: &'static str = "wss";
 
     /// Returns the URI scheme for the transport type.
@@ -0,0 +1,238 @@
+//! Encoding of typed payloads into the body of an event
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{amqp::error::RawAmqpMessageError, EventData, ReceivedEventData};
+
+/// The encoding of a typed payload in the body of an event.
+///
+/// The encoding is recorded in the content type of the event so that the consumer knows how to
+/// decode the body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PayloadEncoding {
+    /// JSON encoded with `serde_json`
+    Json,
+
+    /// The AMQP 1.0 type system encoded with `serde_amqp`
+    Amqp,
+}
+
+impl PayloadEncoding {
+    /// The content type of a JSON payload
+    pub const JSON_CONTENT_TYPE: &'static str = "application/json";
+
+    /// The content type of an AMQP encoded payload
+    pub const AMQP_CONTENT_TYPE: &'static str = "application/x-amqp";
+
+    /// The content type that is set on the events encoded with this encoding
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            PayloadEncoding::Json => Self::JSON_CONTENT_TYPE,
+            PayloadEncoding::Amqp => Self::AMQP_CONTENT_TYPE,
+        }
+    }
+
+    /// Returns the encoding of a content type. The parameters of the content type, eg.
+    /// `charset=utf-8`, are ignored.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        if media_type.eq_ignore_ascii_case(Self::JSON_CONTENT_TYPE) {
+            Some(PayloadEncoding::Json)
+        } else if media_type.eq_ignore_ascii_case(Self::AMQP_CONTENT_TYPE) {
+            Some(PayloadEncoding::Amqp)
+        } else {
+            None
+        }
+    }
+
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, PayloadError>
+    where
+        T: Serialize,
+    {
+        match self {
+            PayloadEncoding::Json => serde_json::to_vec(value).map_err(Into::into),
+            PayloadEncoding::Amqp => serde_amqp::to_vec(value).map_err(Into::into),
+        }
+    }
+
+    fn decode<T>(&self, body: &[u8]) -> Result<T, PayloadError>
+    where
+        T: DeserializeOwned,
+    {
+        match self {
+            PayloadEncoding::Json => serde_json::from_slice(body).map_err(Into::into),
+            PayloadEncoding::Amqp => serde_amqp::from_slice(body).map_err(Into::into),
+        }
+    }
+}
+
+/// Error encoding or decoding a typed payload
+#[derive(Debug, thiserror::Error)]
+pub enum PayloadError {
+    /// The content type of the event is missing or is not one of the supported encodings
+    #[error("Unsupported content type: {0:?}")]
+    UnsupportedContentType(Option<String>),
+
+    /// The body of the event is not a single data section
+    #[error(transparent)]
+    RawAmqpMessage(#[from] RawAmqpMessageError),
+
+    /// Error encoding or decoding JSON
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// Error encoding or decoding the AMQP type system
+    #[error(transparent)]
+    Amqp(#[from] serde_amqp::Error),
+}
+
+fn decode_body<T>(content_type: Option<&str>, body: &[u8]) -> Result<T, PayloadError>
+where
+    T: DeserializeOwned,
+{
+    let encoding = content_type
+        .and_then(PayloadEncoding::from_content_type)
+        .ok_or_else(|| PayloadError::UnsupportedContentType(content_type.map(String::from)))?;
+    encoding.decode(body)
+}
+
+impl EventData {
+    /// Creates a new event whose body is the value encoded as JSON. The content type is set to
+    /// `application/json`.
+    pub fn from_json<T>(value: &T) -> Result<Self, PayloadError>
+    where
+        T: Serialize,
+    {
+        Self::from_serde(value, PayloadEncoding::Json)
+    }
+
+    /// Creates a new event whose body is the value encoded with the given encoding. The content
+    /// type is set to that of the encoding.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use azeventhubs::{EventData, PayloadEncoding};
+    ///
+    /// #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    /// struct Reading {
+    ///     sensor: String,
+    ///     value: f64,
+    /// }
+    ///
+    /// let reading = Reading { sensor: "a".to_string(), value: 1.5 };
+    /// let event = EventData::from_serde(&reading, PayloadEncoding::Amqp).unwrap();
+    /// assert_eq!(event.content_type(), Some("application/x-amqp"));
+    /// assert_eq!(event.deserialize::<Reading>().unwrap(), reading);
+    /// ```
+    pub fn from_serde<T>(value: &T, encoding: PayloadEncoding) -> Result<Self, PayloadError>
+    where
+        T: Serialize,
+    {
+        let mut event = EventData::new(encoding.encode(value)?);
+        event.set_content_type(encoding.content_type().to_string());
+        Ok(event)
+    }
+
+    /// Decodes the body of the event with the encoding given by its content type
+    pub fn deserialize<T>(&self) -> Result<T, PayloadError>
+    where
+        T: DeserializeOwned,
+    {
+        let body = self.data_body().ok_or(RawAmqpMessageError {})?;
+        decode_body(self.content_type(), body)
+    }
+}
+
+impl ReceivedEventData {
+    /// Decodes the body of the event with the encoding given by its content type.
+    ///
+    /// Returns [`PayloadError::UnsupportedContentType`] if the content type is missing or is not
+    /// one of the encodings in [`PayloadEncoding`].
+    pub fn deserialize<T>(&self) -> Result<T, PayloadError>
+    where
+        T: DeserializeOwned,
+    {
+        decode_body(self.content_type(), self.body()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{EventData, ReceivedEventData};
+
+    use super::{PayloadEncoding, PayloadError};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Reading {
+        sensor: String,
+        value: f64,
+        tags: Vec<String>,
+    }
+
+    fn reading() -> Reading {
+        Reading {
+            sensor: "sensor-1".to_string(),
+            value: 21.5,
+            tags: vec!["indoor".to_string()],
+        }
+    }
+
+    fn receive(event: EventData) -> ReceivedEventData {
+        ReceivedEventData::from_raw_amqp_message(event.amqp_message.into_raw_amqp_message())
+    }
+
+    #[test]
+    fn json_payload_round_trips() {
+        let event = EventData::from_json(&reading()).unwrap();
+        assert_eq!(event.content_type(), Some("application/json"));
+        assert_eq!(
+            event.body(),
+            br#"{"sensor":"sensor-1","value":21.5,"tags":["indoor"]}"#
+        );
+
+        let received = receive(event);
+        assert_eq!(received.deserialize::<Reading>().unwrap(), reading());
+    }
+
+    #[test]
+    fn amqp_payload_round_trips() {
+        let event = EventData::from_serde(&reading(), PayloadEncoding::Amqp).unwrap();
+        assert_eq!(event.content_type(), Some("application/x-amqp"));
+
+        let received = receive(event);
+        assert_eq!(received.deserialize::<Reading>().unwrap(), reading());
+    }
+
+    #[test]
+    fn content_type_parameters_are_ignored() {
+        assert_eq!(
+            PayloadEncoding::from_content_type("Application/JSON; charset=utf-8"),
+            Some(PayloadEncoding::Json)
+        );
+
+        let mut event = EventData::new(r#"{"sensor":"s","value":1.0,"tags":[]}"#);
+        event.set_content_type("application/json; charset=utf-8".to_string());
+        assert_eq!(receive(event).deserialize::<Reading>().unwrap().sensor, "s");
+    }
+
+    #[test]
+    fn missing_or_unknown_content_type_is_rejected() {
+        let mut event = EventData::from_json(&reading()).unwrap();
+        event.set_content_type(None);
+        let result = event.deserialize::<Reading>();
+        assert!(matches!(
+            result,
+            Err(PayloadError::UnsupportedContentType(None))
+        ));
+
+        event.set_content_type("text/plain".to_string());
+        let result = receive(event).deserialize::<Reading>();
+        assert!(matches!(
+            result,
+            Err(PayloadError::UnsupportedContentType(Some(content_type))) if content_type == "text/plain"
+        ));
+    }
+}
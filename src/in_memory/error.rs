@@ -0,0 +1,83 @@
+//! Error types for the in-memory transport
+
+use timer_kit::error::Elapsed;
+
+use crate::{
+    amqp::error::CreateBatchError, consumer::error::OffsetIsEmpty, core::RecoverableError,
+    util::error::TransientError,
+};
+
+/// Error with an operation on the in-memory transport
+#[derive(Debug, thiserror::Error)]
+pub enum InMemoryTransportError {
+    /// The client is closed
+    #[error("The in-memory client is closed")]
+    ClientClosed,
+
+    /// The Event Hub is not the one that the client was created for
+    #[error("The Event Hub {0} does not exist")]
+    EventHubNotFound(String),
+
+    /// The partition does not exist in the Event Hub
+    #[error("The partition {0} does not exist")]
+    PartitionNotFound(String),
+
+    /// The link was detached by [`crate::in_memory::InMemoryEventHub::detach_links`]
+    #[error("The link is detached")]
+    LinkDetached,
+
+    /// The offset is not a number
+    #[error("The offset {0} is not valid")]
+    InvalidOffset(String),
+
+    /// The offset is empty
+    #[error(transparent)]
+    OffsetIsEmpty(#[from] OffsetIsEmpty),
+
+    /// The sequence number stamped by an idempotent producer skips some sequence numbers
+    #[error("The sequence number {actual} is out of order, {expected} is expected")]
+    SequenceOutOfOrder {
+        /// The sequence number expected by the partition
+        expected: i32,
+        /// The sequence number of the first event that was published
+        actual: i32,
+    },
+
+    /// Error creating a batch
+    #[error(transparent)]
+    CreateBatch(#[from] CreateBatchError),
+
+    /// The operation timed out
+    #[error(transparent)]
+    Elapsed(#[from] Elapsed),
+}
+
+impl From<InMemoryTransportError> for azure_core::Error {
+    fn from(error: InMemoryTransportError) -> Self {
+        use azure_core::error::ErrorKind;
+
+        match error {
+            InMemoryTransportError::CreateBatch(err) => err.into(),
+            _ => azure_core::Error::new(ErrorKind::Other, error),
+        }
+    }
+}
+
+impl RecoverableError for InMemoryTransportError {
+    fn should_try_recover(&self) -> bool {
+        matches!(self, InMemoryTransportError::LinkDetached)
+    }
+
+    fn is_scope_disposed(&self) -> bool {
+        matches!(self, InMemoryTransportError::ClientClosed)
+    }
+}
+
+impl TransientError for InMemoryTransportError {
+    fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            InMemoryTransportError::LinkDetached | InMemoryTransportError::Elapsed(_)
+        )
+    }
+}
@@ -0,0 +1,79 @@
+//! The in-memory Event Hub that the tests of the clients are run against
+
+use std::time::Duration as StdDuration;
+
+use crate::{
+    consumer::{EventHubConsumerClient, EventHubConsumerClientOptions},
+    producer::{EventHubProducerClient, EventHubProducerClientOptions, SendEventOptions},
+    BasicRetryPolicy, EventData, EventHubConnection, EventHubsRetryOptions,
+};
+
+use super::InMemoryEventHub;
+
+/// An in-memory Event Hub and a connection to it, from which the clients under test are created
+#[derive(Debug)]
+pub(crate) struct TestEventHub {
+    pub(crate) event_hub: InMemoryEventHub,
+    pub(crate) connection: EventHubConnection,
+}
+
+impl TestEventHub {
+    /// The name of the Event Hub
+    pub(crate) const NAME: &'static str = "test-hub";
+
+    pub(crate) fn new(partition_count: usize) -> Self {
+        let event_hub = InMemoryEventHub::new(Self::NAME, partition_count);
+        let connection = EventHubConnection::new_from_in_memory_event_hub(&event_hub);
+        Self {
+            event_hub,
+            connection,
+        }
+    }
+
+    /// Retry options with a short delay, so that the clients recover their links quickly
+    pub(crate) fn retry_options() -> EventHubsRetryOptions {
+        EventHubsRetryOptions {
+            delay: StdDuration::from_millis(10),
+            ..Default::default()
+        }
+    }
+
+    /// A producer that shares the connection
+    pub(crate) fn producer(&mut self) -> EventHubProducerClient<BasicRetryPolicy> {
+        self.producer_with_options(EventHubProducerClientOptions::default())
+    }
+
+    /// A producer that shares the connection, with the retry options of the fixture
+    pub(crate) fn producer_with_options(
+        &mut self,
+        options: EventHubProducerClientOptions,
+    ) -> EventHubProducerClient<BasicRetryPolicy> {
+        let options = EventHubProducerClientOptions {
+            retry_options: Self::retry_options(),
+            ..options
+        };
+        EventHubProducerClient::with_connection(&mut self.connection, options)
+    }
+
+    /// A consumer of the default consumer group that shares the connection
+    pub(crate) fn consumer(&mut self) -> EventHubConsumerClient<BasicRetryPolicy> {
+        let options = EventHubConsumerClientOptions {
+            retry_options: Self::retry_options(),
+            ..Default::default()
+        };
+        EventHubConsumerClient::with_connection(
+            EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+            &mut self.connection,
+            options,
+        )
+    }
+
+    /// Publishes the bodies to the partition in a single batch
+    pub(crate) async fn publish(&mut self, partition_id: &str, bodies: &[&str]) {
+        let mut producer = self.producer();
+        let events = bodies.iter().map(|body| EventData::from(*body));
+        let options = SendEventOptions::new().with_partition_id(partition_id);
+        producer.send_events(events, options).await.unwrap();
+        producer.close().await.unwrap();
+    }
+}
@@ -0,0 +1,268 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::{
+    amqp::amqp_producer::IdempotentPublishingState,
+    consumer::EventPosition,
+    core::{RecoverableTransport, TransportClient, TransportProducerFeatures},
+    event_hubs_properties::EventHubProperties,
+    event_hubs_retry_policy::EventHubsRetryPolicy,
+    producer::{PartitionPublishingOptions, PartitionPublishingProperties},
+    PartitionProperties,
+};
+
+use super::{
+    error::InMemoryTransportError, in_memory_consumer::InMemoryConsumer,
+    in_memory_event_hub::InMemoryEventHub, in_memory_producer::InMemoryProducer,
+};
+
+/// A transport client that talks to [`InMemoryEventHub`]s instead of the Event Hubs service
+#[derive(Debug)]
+pub(crate) struct InMemoryClient {
+    /// The Event Hubs of the namespace that the client can reach
+    event_hubs: Arc<Vec<InMemoryEventHub>>,
+    /// Shared among all the clones of the client, ie. closing any clone closes all of them
+    pub(crate) is_closed: Arc<AtomicBool>,
+    is_shared: bool,
+}
+
+impl InMemoryClient {
+    pub(crate) fn new(event_hubs: Vec<InMemoryEventHub>) -> Self {
+        Self {
+            event_hubs: Arc::new(event_hubs),
+            is_closed: Arc::new(AtomicBool::new(false)),
+            is_shared: false,
+        }
+    }
+
+    pub(crate) fn clone_as_shared(&mut self) -> Self {
+        self.is_shared = true;
+        Self {
+            event_hubs: self.event_hubs.clone(),
+            is_closed: self.is_closed.clone(),
+            is_shared: true,
+        }
+    }
+
+    fn ensure_not_closed(&self) -> Result<(), InMemoryTransportError> {
+        match self.is_closed() {
+            true => Err(InMemoryTransportError::ClientClosed),
+            false => Ok(()),
+        }
+    }
+
+    /// The client only serves the Event Hubs that it was created with
+    fn event_hub(&self, event_hub_name: &str) -> Result<&InMemoryEventHub, InMemoryTransportError> {
+        self.ensure_not_closed()?;
+        self.event_hubs
+            .iter()
+            .find(|event_hub| event_hub.event_hub_name() == event_hub_name)
+            .ok_or_else(|| InMemoryTransportError::EventHubNotFound(event_hub_name.to_string()))
+    }
+
+    fn dispose(&self) {
+        self.is_closed.store(true, Ordering::Relaxed);
+        // Wake up the consumers that are waiting for events
+        for event_hub in self.event_hubs.iter() {
+            event_hub.notify().notify_waiters();
+        }
+    }
+}
+
+impl TransportClient for InMemoryClient {
+    type Producer<RP>
+        = InMemoryProducer<RP>
+    where
+        RP: EventHubsRetryPolicy + Send;
+    type Consumer<RP>
+        = InMemoryConsumer<RP>
+    where
+        RP: EventHubsRetryPolicy + Send;
+
+    type RequestResponseError = InMemoryTransportError;
+    type OpenProducerError = InMemoryTransportError;
+    type RecoverProducerError = InMemoryTransportError;
+    type OpenConsumerError = InMemoryTransportError;
+    type RecoverConsumerError = InMemoryTransportError;
+    type DisposeError = InMemoryTransportError;
+
+    fn is_closed(&self) -> bool {
+        self.is_closed.load(Ordering::Relaxed)
+    }
+
+    async fn get_properties(
+        &mut self,
+        event_hub_name: &str,
+    ) -> Result<EventHubProperties, Self::RequestResponseError> {
+        Ok(self.event_hub(event_hub_name)?.properties())
+    }
+
+    async fn get_partition_properties(
+        &mut self,
+        event_hub_name: &str,
+        partition_id: &str,
+    ) -> Result<PartitionProperties, Self::RequestResponseError> {
+        self.event_hub(event_hub_name)?
+            .partition_properties(partition_id)
+    }
+
+    async fn create_producer<RP>(
+        &mut self,
+        event_hub_name: &str,
+        partition_id: Option<String>,
+        _producer_identifier: Option<String>,
+        requested_features: TransportProducerFeatures,
+        partition_options: PartitionPublishingOptions,
+        retry_policy: RP,
+    ) -> Result<Self::Producer<RP>, Self::OpenProducerError>
+    where
+        RP: EventHubsRetryPolicy + Send,
+    {
+        let event_hub = self.event_hub(event_hub_name)?;
+
+        let mut state = event_hub.lock();
+        if let Some(partition_id) = &partition_id {
+            state.partition(partition_id)?;
+        }
+
+        // The service assigns a producer group to idempotent producers that do not request one,
+        // and reports the last sequence number that was published by the producer group
+        let idempotent_state = match (&partition_id, requested_features) {
+            (Some(partition_id), TransportProducerFeatures::IdempotentPublishing) => {
+                let producer_group_id = match partition_options.producer_group_id {
+                    Some(producer_group_id) => producer_group_id,
+                    None => state.next_producer_group_id(),
+                };
+                let last_published_sequence_number = match partition_options
+                    .starting_sequence_number
+                {
+                    Some(sequence_number) => Some(sequence_number),
+                    None => state.last_producer_sequence_number(partition_id, producer_group_id)?,
+                };
+                Some(IdempotentPublishingState {
+                    producer_group_id: Some(producer_group_id),
+                    owner_level: Some(partition_options.owner_level.unwrap_or(0)),
+                    last_published_sequence_number,
+                })
+            }
+            _ => None,
+        };
+        let link_generation = state.link_generation;
+        drop(state);
+
+        let initialized_partition_properties = match idempotent_state {
+            Some(state) => state.to_partition_properties(),
+            None => PartitionPublishingProperties::default(),
+        };
+        Ok(InMemoryProducer {
+            event_hub: event_hub.clone(),
+            is_closed: Arc::downgrade(&self.is_closed),
+            partition_id,
+            link_generation,
+            initialized_partition_properties,
+            retry_policy,
+            idempotent_state,
+        })
+    }
+
+    async fn recover_producer<RP>(
+        &mut self,
+        producer: &mut Self::Producer<RP>,
+    ) -> Result<(), Self::RecoverProducerError>
+    where
+        RP: EventHubsRetryPolicy + Send,
+    {
+        log::debug!("Recovering in-memory producer");
+
+        self.ensure_not_closed()?;
+        producer.link_generation = producer.event_hub.lock().link_generation;
+        Ok(())
+    }
+
+    async fn create_consumer<RP>(
+        &mut self,
+        event_hub_name: &str,
+        _consumer_group: &str,
+        partition_id: &str,
+        _consumer_identifier: Option<String>,
+        event_position: &EventPosition,
+        retry_policy: RP,
+        track_last_enqueued_event_properties: bool,
+        _owner_level: Option<i64>,
+        _prefetch_count: Option<u32>,
+    ) -> Result<Self::Consumer<RP>, Self::OpenConsumerError>
+    where
+        RP: EventHubsRetryPolicy + Send,
+    {
+        let event_hub = self.event_hub(event_hub_name)?;
+
+        let state = event_hub.lock();
+        let next_index = state.partition(partition_id)?.index_of(event_position)?;
+        let link_generation = state.link_generation;
+        drop(state);
+
+        Ok(InMemoryConsumer {
+            event_hub: event_hub.clone(),
+            is_closed: Arc::downgrade(&self.is_closed),
+            partition_id: Arc::new(partition_id.to_string()),
+            next_index,
+            link_generation,
+            track_last_enqueued_event_properties,
+            current_event_position: None,
+            initial_event_position: event_position.clone(),
+            retry_policy,
+        })
+    }
+
+    async fn recover_consumer<RP>(
+        &mut self,
+        consumer: &mut Self::Consumer<RP>,
+    ) -> Result<(), Self::RecoverConsumerError>
+    where
+        RP: EventHubsRetryPolicy + Send,
+    {
+        log::debug!("Recovering in-memory consumer");
+
+        self.ensure_not_closed()?;
+
+        // Re-open the link from where the consumer left off, like the AMQP transport does
+        let event_position = consumer.resume_event_position();
+        let state = consumer.event_hub.lock();
+        consumer.next_index = state
+            .partition(&consumer.partition_id)?
+            .index_of(&event_position)?;
+        consumer.link_generation = state.link_generation;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<(), Self::DisposeError> {
+        self.dispose();
+        Ok(())
+    }
+
+    async fn close_if_owned(&mut self) -> Result<(), Self::DisposeError> {
+        if !self.is_shared || Arc::strong_count(&self.is_closed) == 1 {
+            self.dispose();
+        }
+        Ok(())
+    }
+
+    fn is_owned(&self) -> bool {
+        !self.is_shared
+    }
+
+    fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+}
+
+impl RecoverableTransport for InMemoryClient {
+    type RecoverError = InMemoryTransportError;
+
+    async fn recover(&mut self) -> Result<(), Self::RecoverError> {
+        // There is no connection to re-open, but a closed client cannot be recovered
+        self.ensure_not_closed()
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    collections::VecDeque,
+    sync::{atomic::AtomicBool, Arc, Weak},
+    time::Duration as StdDuration,
+};
+
+use crate::{
+    amqp::amqp_consumer::{single::resume_event_position, PartitionTaggedEvent},
+    consumer::EventPosition,
+    core::{RecoverableError, TransportClient},
+    event_hubs_retry_policy::EventHubsRetryPolicy,
+    util::{self, time::Instant},
+    ReceivedEventData,
+};
+
+use super::{
+    error::InMemoryTransportError, in_memory_client::InMemoryClient,
+    in_memory_event_hub::InMemoryEventHub, is_client_closed,
+};
+
+/// A consumer that reads the events of a partition of an [`InMemoryEventHub`]
+#[derive(Debug)]
+pub(crate) struct InMemoryConsumer<RP> {
+    pub(crate) event_hub: InMemoryEventHub,
+    pub(crate) is_closed: Weak<AtomicBool>,
+    pub(crate) partition_id: Arc<String>,
+    /// The index of the next event to deliver in the partition
+    pub(crate) next_index: usize,
+    /// The link is detached if this is behind the link generation of the Event Hub
+    pub(crate) link_generation: u64,
+    pub(crate) track_last_enqueued_event_properties: bool,
+    pub(crate) current_event_position: Option<EventPosition>,
+    pub(crate) initial_event_position: EventPosition,
+    pub(crate) retry_policy: RP,
+}
+
+impl<RP> InMemoryConsumer<RP> {
+    /// The position to resume receiving from when the link is re-opened.
+    pub(crate) fn resume_event_position(&self) -> EventPosition {
+        resume_event_position(
+            self.current_event_position.as_ref(),
+            &self.initial_event_position,
+        )
+    }
+
+    /// Returns the next event, or `None` if there is no new event in the partition
+    fn try_recv(&mut self) -> Result<Option<ReceivedEventData>, InMemoryTransportError> {
+        if is_client_closed(&self.is_closed) {
+            return Err(InMemoryTransportError::ClientClosed);
+        }
+
+        let state = self.event_hub.lock();
+        if state.link_generation != self.link_generation {
+            return Err(InMemoryTransportError::LinkDetached);
+        }
+        let partition = state.partition(&self.partition_id)?;
+        let mut event = match partition.events.get(self.next_index) {
+            Some(enqueued) => enqueued.event.clone(),
+            None => return Ok(None),
+        };
+        if self.track_last_enqueued_event_properties {
+            event.amqp_message.message.delivery_annotations =
+                partition.last_enqueued_event_annotations();
+        }
+        drop(state);
+
+        self.next_index += 1;
+        if let Some(offset) = event.offset() {
+            self.current_event_position = Some(EventPosition::from_offset(offset, false));
+        }
+        Ok(Some(event))
+    }
+
+    fn fill_buf(
+        &mut self,
+        buffer: &mut VecDeque<ReceivedEventData>,
+    ) -> Result<(), InMemoryTransportError> {
+        while buffer.len() < buffer.capacity() {
+            match self.try_recv()? {
+                Some(event) => buffer.push_back(event),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Waits for the retry delay and then recovers the consumers if the error is transient.
+/// Otherwise the error is returned.
+async fn recover_consumers<'c, RP>(
+    client: &mut InMemoryClient,
+    consumers: impl Iterator<Item = &'c mut InMemoryConsumer<RP>>,
+    err: InMemoryTransportError,
+    retry_delay: Option<StdDuration>,
+) -> Result<(), InMemoryTransportError>
+where
+    RP: EventHubsRetryPolicy + Send + 'c,
+{
+    log::debug!("Failed to receive event: {:?}", err);
+
+    if err.is_scope_disposed() {
+        return Err(err);
+    }
+    match retry_delay {
+        Some(retry_delay) => util::time::sleep(retry_delay).await,
+        None => return Err(err),
+    }
+
+    if err.should_try_recover() {
+        for consumer in consumers {
+            client.recover_consumer(consumer).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Receives until the buffer is full or until the wait time has elapsed
+pub(crate) async fn receive_event_batch<RP>(
+    client: &mut InMemoryClient,
+    consumer: &mut InMemoryConsumer<RP>,
+    buffer: &mut VecDeque<ReceivedEventData>,
+    max_wait_time: Option<StdDuration>,
+) -> Result<(), InMemoryTransportError>
+where
+    RP: EventHubsRetryPolicy + Send,
+{
+    let wait_time = max_wait_time.unwrap_or_else(|| consumer.retry_policy.calculate_try_timeout(0));
+    let deadline = Instant::now() + wait_time;
+    let mut failed_attempts = 0;
+
+    loop {
+        let event_hub = consumer.event_hub.clone();
+        let notified = event_hub.notify().notified();
+
+        let err = match consumer.fill_buf(buffer) {
+            Ok(_) => {
+                if buffer.len() >= buffer.capacity() {
+                    return Ok(());
+                }
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match util::time::timeout(remaining, notified).await {
+                    Ok(_) => continue,
+                    Err(_elapsed) => return Ok(()),
+                }
+            }
+            Err(err) => err,
+        };
+
+        failed_attempts += 1;
+        let retry_delay = consumer
+            .retry_policy
+            .calculate_retry_delay(&err, failed_attempts);
+        recover_consumers(client, std::iter::once(&mut *consumer), err, retry_delay).await?;
+    }
+}
+
+/// Consumers of multiple partitions, which are read in a round robin fashion
+#[derive(Debug)]
+pub(crate) struct MultipleInMemoryConsumers<RP> {
+    pub(crate) inner: Vec<InMemoryConsumer<RP>>,
+    next: usize,
+    retry_policy: RP,
+}
+
+impl<RP> MultipleInMemoryConsumers<RP> {
+    pub(crate) fn new(consumers: Vec<InMemoryConsumer<RP>>, retry_policy: RP) -> Self {
+        Self {
+            inner: consumers,
+            next: 0,
+            retry_policy,
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<Option<PartitionTaggedEvent>, InMemoryTransportError> {
+        let count = self.inner.len();
+        for i in 0..count {
+            let index = (self.next + i) % count;
+            let consumer = &mut self.inner[index];
+            if let Some(event) = consumer.try_recv()? {
+                // Start from the next partition so that no partition is starved
+                self.next = (index + 1) % count;
+                return Ok(Some((consumer.partition_id.clone(), event)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the next event together with the ID of its partition, or `None` if there is no
+/// consumer
+pub(crate) async fn receive_event_from_any<RP>(
+    client: &mut InMemoryClient,
+    consumers: &mut MultipleInMemoryConsumers<RP>,
+) -> Option<Result<PartitionTaggedEvent, InMemoryTransportError>>
+where
+    RP: EventHubsRetryPolicy + Send,
+{
+    let event_hub = consumers.inner.first()?.event_hub.clone();
+    let mut failed_attempts = 0;
+
+    loop {
+        // The notification must be registered before checking for new events, otherwise an event
+        // that is enqueued in between would be missed
+        let notified = event_hub.notify().notified();
+
+        let err = match consumers.try_recv() {
+            Ok(Some(event)) => return Some(Ok(event)),
+            Ok(None) => {
+                notified.await;
+                continue;
+            }
+            Err(err) => err,
+        };
+
+        failed_attempts += 1;
+        let retry_delay = consumers
+            .retry_policy
+            .calculate_retry_delay(&err, failed_attempts);
+        let result = recover_consumers(client, consumers.inner.iter_mut(), err, retry_delay).await;
+        if let Err(err) = result {
+            return Some(Err(err));
+        }
+    }
+}
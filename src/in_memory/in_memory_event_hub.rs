@@ -0,0 +1,591 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use fe2o3_amqp_types::{
+    messaging::{Annotations, Body, DeliveryAnnotations, Message},
+    primitives::{Timestamp, Value},
+};
+use serde_amqp::serialized_size;
+use time::OffsetDateTime;
+use tokio::sync::Notify;
+
+use crate::{
+    amqp::{amqp_message_converter::next_sequence_number, amqp_property},
+    constants::DEFAULT_OFFSET_DATE_TIME,
+    consumer::{error::OffsetIsEmpty, EventPosition, END_OF_STREAM_OFFSET},
+    event_hubs_properties::EventHubProperties,
+    util::time::now_utc,
+    PartitionProperties, ReceivedEventData,
+};
+
+use super::error::InMemoryTransportError;
+
+/// The fully qualified namespace reported by connections to an [`InMemoryEventHub`]
+pub(crate) const IN_MEMORY_NAMESPACE: &str = "in-memory.servicebus.windows.net";
+
+/// The maximum size of a message, which matches the limit of the standard tier of the service
+pub(crate) const DEFAULT_MAX_MESSAGE_SIZE_IN_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug)]
+pub(crate) struct EnqueuedEvent {
+    pub(crate) sequence_number: i64,
+    pub(crate) offset: i64,
+    /// In milliseconds since the unix epoch, which is the precision of an AMQP timestamp
+    pub(crate) enqueued_time: i64,
+    pub(crate) event: ReceivedEventData,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct PartitionLog {
+    pub(crate) events: Vec<EnqueuedEvent>,
+    next_offset: i64,
+    /// The last sequence number published by each producer group with idempotent publishing
+    producer_groups: HashMap<i64, i32>,
+}
+
+impl PartitionLog {
+    /// The index of the first event that is after the given position
+    pub(crate) fn index_of(
+        &self,
+        position: &EventPosition,
+    ) -> Result<usize, InMemoryTransportError> {
+        let index = match position {
+            EventPosition::Offset {
+                offset,
+                is_inclusive,
+            } => {
+                if offset.is_empty() {
+                    return Err(OffsetIsEmpty.into());
+                }
+                if offset == END_OF_STREAM_OFFSET {
+                    return Ok(self.events.len());
+                }
+                let offset: i64 = offset
+                    .parse()
+                    .map_err(|_| InMemoryTransportError::InvalidOffset(offset.clone()))?;
+                self.events.partition_point(|event| match is_inclusive {
+                    true => event.offset < offset,
+                    false => event.offset <= offset,
+                })
+            }
+            EventPosition::SequenceNumber {
+                sequence_number,
+                is_inclusive,
+            } => self.events.partition_point(|event| match is_inclusive {
+                true => event.sequence_number < *sequence_number,
+                false => event.sequence_number <= *sequence_number,
+            }),
+            // The service filters on the events that are enqueued after the given time
+            EventPosition::EnqueuedTime(enqueued_time) => {
+                let millis = (enqueued_time.unix_timestamp_nanos() / 1_000_000) as i64;
+                self.events
+                    .partition_point(|event| event.enqueued_time <= millis)
+            }
+        };
+        Ok(index)
+    }
+
+    /// The delivery annotations that describe the last event enqueued in the partition, which the
+    /// service adds to the events delivered to a consumer that tracks them
+    pub(crate) fn last_enqueued_event_annotations(&self) -> Option<DeliveryAnnotations> {
+        let last = self.events.last()?;
+        let mut annotations = Annotations::new();
+        annotations.insert(
+            amqp_property::PARTITION_LAST_ENQUEUED_SEQUENCE_NUMBER.into(),
+            Value::Long(last.sequence_number),
+        );
+        annotations.insert(
+            amqp_property::PARTITION_LAST_ENQUEUED_OFFSET.into(),
+            Value::String(last.offset.to_string()),
+        );
+        annotations.insert(
+            amqp_property::PARTITION_LAST_ENQUEUED_TIME_UTC.into(),
+            Value::Timestamp(Timestamp::from_milliseconds(last.enqueued_time)),
+        );
+        annotations.insert(
+            amqp_property::LAST_PARTITION_PROPERTIES_RETRIEVAL_TIME_UTC.into(),
+            Value::Timestamp(Timestamp::from(now_utc())),
+        );
+        Some(DeliveryAnnotations(annotations))
+    }
+
+    /// Checks the sequence numbers stamped by an idempotent producer. Returns false if the events
+    /// were already published and should be discarded.
+    fn accept_producer_sequence(
+        &mut self,
+        producer_group_id: i64,
+        first_sequence_number: i32,
+        count: usize,
+    ) -> Result<bool, InMemoryTransportError> {
+        let expected = self
+            .producer_groups
+            .get(&producer_group_id)
+            .map(|last| next_sequence_number(*last))
+            .unwrap_or(first_sequence_number);
+        if first_sequence_number < expected {
+            return Ok(false);
+        }
+        if first_sequence_number > expected {
+            return Err(InMemoryTransportError::SequenceOutOfOrder {
+                expected,
+                actual: first_sequence_number,
+            });
+        }
+
+        let mut last = first_sequence_number;
+        for _ in 1..count {
+            last = next_sequence_number(last);
+        }
+        self.producer_groups.insert(producer_group_id, last);
+        Ok(true)
+    }
+
+    fn append(&mut self, message: Message<Body<Value>>, partition_key: Option<&str>) {
+        let sequence_number = self.events.len() as i64;
+        let offset = self.next_offset;
+        let enqueued_time = (now_utc().unix_timestamp_nanos() / 1_000_000) as i64;
+        // Offsets grow by the size of the body, so that they are different from sequence numbers
+        self.next_offset += (body_size(&message.body) as i64).max(1);
+
+        let mut annotations = message.message_annotations.unwrap_or_default();
+        annotations.insert(
+            amqp_property::SEQUENCE_NUMBER.into(),
+            Value::Long(sequence_number),
+        );
+        annotations.insert(
+            amqp_property::OFFSET.into(),
+            Value::String(offset.to_string()),
+        );
+        annotations.insert(
+            amqp_property::ENQUEUED_TIME.into(),
+            Value::Timestamp(Timestamp::from_milliseconds(enqueued_time)),
+        );
+        if let Some(partition_key) = partition_key.filter(|key| !key.is_empty()) {
+            annotations.insert(amqp_property::PARTITION_KEY.into(), partition_key.into());
+        }
+
+        let raw_amqp_message = Message {
+            message_annotations: Some(annotations),
+            ..message
+        };
+        self.events.push(EnqueuedEvent {
+            sequence_number,
+            offset,
+            enqueued_time,
+            event: ReceivedEventData::from_raw_amqp_message(raw_amqp_message),
+        });
+    }
+}
+
+/// The size of the data in the body, or the encoded size of the other kinds of body
+fn body_size(body: &Body<Value>) -> usize {
+    match body {
+        Body::Data(batch) => batch.iter().map(|data| data.0.len()).sum(),
+        _ => serialized_size(body).unwrap_or_default(),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct State {
+    pub(crate) partitions: Vec<PartitionLog>,
+    /// Incremented every time the links are detached
+    pub(crate) link_generation: u64,
+    next_partition: usize,
+    next_producer_group_id: i64,
+}
+
+impl State {
+    pub(crate) fn partition(
+        &self,
+        partition_id: &str,
+    ) -> Result<&PartitionLog, InMemoryTransportError> {
+        partition_index(partition_id, self.partitions.len())
+            .map(|index| &self.partitions[index])
+            .ok_or_else(|| InMemoryTransportError::PartitionNotFound(partition_id.to_string()))
+    }
+
+    pub(crate) fn next_producer_group_id(&mut self) -> i64 {
+        self.next_producer_group_id += 1;
+        self.next_producer_group_id
+    }
+
+    /// The last sequence number published by a producer group to a partition
+    pub(crate) fn last_producer_sequence_number(
+        &self,
+        partition_id: &str,
+        producer_group_id: i64,
+    ) -> Result<Option<i32>, InMemoryTransportError> {
+        let partition = self.partition(partition_id)?;
+        Ok(partition.producer_groups.get(&producer_group_id).copied())
+    }
+
+    /// Events without a partition are assigned with the hash of the partition key, or in a round
+    /// robin fashion if there is no partition key
+    fn assign_partition(&mut self, partition_key: Option<&str>) -> usize {
+        let count = self.partitions.len();
+        match partition_key.filter(|key| !key.is_empty()) {
+            Some(key) => {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                (hasher.finish() % count as u64) as usize
+            }
+            None => {
+                let index = self.next_partition % count;
+                self.next_partition = (index + 1) % count;
+                index
+            }
+        }
+    }
+}
+
+fn partition_index(partition_id: &str, partition_count: usize) -> Option<usize> {
+    partition_id
+        .parse::<usize>()
+        .ok()
+        .filter(|index| *index < partition_count)
+}
+
+#[derive(Debug)]
+struct Inner {
+    event_hub_name: String,
+    created_on: OffsetDateTime,
+    max_message_size: u64,
+    state: Mutex<State>,
+    /// Wakes up the consumers when events are enqueued or the links are detached
+    notify: Notify,
+}
+
+/// An Event Hub that lives in memory, which can be used to run the producer and consumer clients
+/// in tests without an Event Hubs namespace.
+///
+/// Connect to the Event Hub with [`crate::EventHubConnection::new_from_in_memory_event_hub`], and
+/// then create the clients with the `with_connection` methods. Cloning the Event Hub returns a
+/// handle to the same state.
+///
+/// The Event Hub assigns sequence numbers, offsets and enqueued times to the events in each
+/// partition, and the consumers start reading from the requested [`EventPosition`]. Events that
+/// are published without a partition are assigned with the hash of the partition key, or in a
+/// round robin fashion if there is no partition key. Idempotent producers get a producer group and
+/// duplicated events are discarded. Owner levels of consumers and retention of events are not
+/// simulated.
+///
+/// # Example
+///
+/// ```rust
+/// use azeventhubs::{
+///     in_memory::InMemoryEventHub,
+///     producer::{EventHubProducerClient, EventHubProducerClientOptions, SendEventOptions},
+///     EventHubConnection,
+/// };
+///
+/// # async fn example() -> Result<(), azure_core::Error> {
+/// let event_hub = InMemoryEventHub::new("my-event-hub", 2);
+/// let mut connection = EventHubConnection::new_from_in_memory_event_hub(&event_hub);
+/// let mut producer =
+///     EventHubProducerClient::with_connection(&mut connection, EventHubProducerClientOptions::default());
+///
+/// let options = SendEventOptions::new().with_partition_id("0");
+/// producer.send_event("hello", options).await?;
+/// assert_eq!(event_hub.events("0").len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InMemoryEventHub {
+    inner: Arc<Inner>,
+}
+
+impl InMemoryEventHub {
+    /// Creates a new Event Hub with the given number of partitions. The partitions are identified
+    /// by their index, ie. `"0"`, `"1"`, etc.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `partition_count` is zero.
+    pub fn new(event_hub_name: impl Into<String>, partition_count: usize) -> Self {
+        assert!(
+            partition_count > 0,
+            "An Event Hub must have at least one partition"
+        );
+
+        let partitions = (0..partition_count)
+            .map(|_| PartitionLog::default())
+            .collect();
+        let state = State {
+            partitions,
+            link_generation: 0,
+            next_partition: 0,
+            next_producer_group_id: 0,
+        };
+        Self {
+            inner: Arc::new(Inner {
+                event_hub_name: event_hub_name.into(),
+                created_on: now_utc(),
+                max_message_size: DEFAULT_MAX_MESSAGE_SIZE_IN_BYTES,
+                state: Mutex::new(state),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// The name of the Event Hub
+    pub fn event_hub_name(&self) -> &str {
+        &self.inner.event_hub_name
+    }
+
+    /// The identifiers of the partitions
+    pub fn partition_ids(&self) -> Vec<String> {
+        (0..self.lock().partitions.len())
+            .map(|index| index.to_string())
+            .collect()
+    }
+
+    /// The events that are enqueued in a partition, in the order they were enqueued. An empty list
+    /// is returned if the partition does not exist.
+    pub fn events(&self, partition_id: &str) -> Vec<ReceivedEventData> {
+        self.lock()
+            .partition(partition_id)
+            .map(|partition| {
+                partition
+                    .events
+                    .iter()
+                    .map(|enqueued| enqueued.event.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Detaches the links of all the producers and consumers, as if the connection was dropped.
+    ///
+    /// The next operation of each producer and consumer fails with a transient error, and the
+    /// clients recover the links according to their retry policy.
+    pub fn detach_links(&self) {
+        self.lock().link_generation += 1;
+        self.inner.notify.notify_waiters();
+    }
+
+    pub(crate) fn max_message_size(&self) -> u64 {
+        self.inner.max_message_size
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, State> {
+        // The state is always left consistent, so it is safe to ignore poisoning
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub(crate) fn notify(&self) -> &Notify {
+        &self.inner.notify
+    }
+
+    pub(crate) fn properties(&self) -> EventHubProperties {
+        EventHubProperties {
+            name: self.inner.event_hub_name.clone(),
+            created_on: self.inner.created_on,
+            partition_ids: self.partition_ids(),
+        }
+    }
+
+    pub(crate) fn partition_properties(
+        &self,
+        partition_id: &str,
+    ) -> Result<PartitionProperties, InMemoryTransportError> {
+        let state = self.lock();
+        let partition = state.partition(partition_id)?;
+        let properties = match (partition.events.first(), partition.events.last()) {
+            (Some(first), Some(last)) => PartitionProperties {
+                event_hub_name: self.inner.event_hub_name.clone(),
+                id: partition_id.to_string(),
+                beginning_sequence_number: first.sequence_number,
+                last_enqueued_sequence_number: last.sequence_number,
+                last_enqueued_offset: last.offset,
+                last_enqueued_time: last.event.enqueued_time(),
+                is_empty: false,
+            },
+            _ => PartitionProperties {
+                event_hub_name: self.inner.event_hub_name.clone(),
+                id: partition_id.to_string(),
+                beginning_sequence_number: 0,
+                last_enqueued_sequence_number: -1,
+                last_enqueued_offset: -1,
+                last_enqueued_time: DEFAULT_OFFSET_DATE_TIME,
+                is_empty: true,
+            },
+        };
+        Ok(properties)
+    }
+
+    /// Enqueues the messages in the given partition, or in the partition assigned to the partition
+    /// key if `partition_id` is `None`.
+    ///
+    /// If the messages are stamped by an idempotent producer, `producer_sequence` carries the
+    /// producer group and the sequence number of the first message. Messages that were already
+    /// published are discarded.
+    pub(crate) fn enqueue(
+        &self,
+        partition_id: Option<&str>,
+        partition_key: Option<&str>,
+        messages: Vec<Message<Body<Value>>>,
+        producer_sequence: Option<(i64, i32)>,
+    ) -> Result<(), InMemoryTransportError> {
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let mut state = self.lock();
+        let index = match partition_id {
+            Some(partition_id) => partition_index(partition_id, state.partitions.len())
+                .ok_or_else(|| {
+                    InMemoryTransportError::PartitionNotFound(partition_id.to_string())
+                })?,
+            None => state.assign_partition(partition_key),
+        };
+        let partition = &mut state.partitions[index];
+
+        if let Some((producer_group_id, first_sequence_number)) = producer_sequence {
+            let count = messages.len();
+            if !partition.accept_producer_sequence(
+                producer_group_id,
+                first_sequence_number,
+                count,
+            )? {
+                log::debug!(
+                    "Discarding duplicated events from producer group {}",
+                    producer_group_id
+                );
+                return Ok(());
+            }
+        }
+
+        for message in messages {
+            partition.append(message, partition_key);
+        }
+        drop(state);
+
+        self.inner.notify.notify_waiters();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use crate::{consumer::EventPosition, EventData};
+
+    use super::InMemoryEventHub;
+
+    fn enqueue_events(event_hub: &InMemoryEventHub, partition_id: &str, bodies: &[&str]) {
+        let messages = bodies
+            .iter()
+            .map(|body| EventData::from(*body).amqp_message.message)
+            .collect();
+        event_hub
+            .enqueue(Some(partition_id), None, messages, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn events_are_assigned_increasing_positions() {
+        let event_hub = InMemoryEventHub::new("test", 2);
+        enqueue_events(&event_hub, "1", &["a", "bb", "ccc"]);
+
+        let events = event_hub.events("1");
+        let sequence_numbers: Vec<_> = events.iter().map(|e| e.sequence_number()).collect();
+        let offsets: Vec<_> = events.iter().map(|e| e.offset().unwrap()).collect();
+        assert_eq!(sequence_numbers, vec![0, 1, 2]);
+        assert_eq!(offsets, vec![0, 1, 3]);
+        assert!(event_hub.events("0").is_empty());
+
+        let properties = event_hub.partition_properties("1").unwrap();
+        assert_eq!(properties.last_enqueued_sequence_number(), 2);
+        assert_eq!(properties.last_enqueued_offset(), 3);
+        assert!(!properties.is_empty());
+        assert!(event_hub.partition_properties("0").unwrap().is_empty());
+        assert!(event_hub.partition_properties("2").is_err());
+    }
+
+    #[test]
+    fn event_positions_are_resolved() {
+        let event_hub = InMemoryEventHub::new("test", 1);
+        enqueue_events(&event_hub, "0", &["a", "bb", "ccc"]);
+
+        let state = event_hub.lock();
+        let partition = state.partition("0").unwrap();
+        let index_of = |position: EventPosition| partition.index_of(&position).unwrap();
+
+        assert_eq!(index_of(EventPosition::earliest()), 0);
+        assert_eq!(index_of(EventPosition::latest()), 3);
+        assert_eq!(index_of(EventPosition::from_offset(1, true)), 1);
+        assert_eq!(index_of(EventPosition::from_offset(1, false)), 2);
+        assert_eq!(index_of(EventPosition::from_offset(2, true)), 2);
+        assert_eq!(index_of(EventPosition::from_sequence_number(2, true)), 2);
+        assert_eq!(index_of(EventPosition::from_sequence_number(2, false)), 3);
+        assert_eq!(
+            index_of(EventPosition::from_enqueued_time(
+                datetime!(2000-01-01 0:00 UTC)
+            )),
+            0
+        );
+        assert_eq!(
+            index_of(EventPosition::from_enqueued_time(
+                datetime!(9999-01-01 0:00 UTC)
+            )),
+            3
+        );
+    }
+
+    #[test]
+    fn events_with_the_same_partition_key_go_to_the_same_partition() {
+        let event_hub = InMemoryEventHub::new("test", 4);
+        for _ in 0..3 {
+            let message = EventData::from("event").amqp_message.message;
+            event_hub
+                .enqueue(None, Some("key"), vec![message], None)
+                .unwrap();
+        }
+
+        let partitions: Vec<_> = event_hub
+            .partition_ids()
+            .iter()
+            .map(|id| event_hub.events(id))
+            .filter(|events| !events.is_empty())
+            .collect();
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(partitions[0].len(), 3);
+        assert_eq!(partitions[0][0].partition_key(), Some("key"));
+    }
+
+    #[test]
+    fn duplicated_idempotent_events_are_discarded() {
+        let event_hub = InMemoryEventHub::new("test", 1);
+        let messages = || {
+            vec![
+                EventData::from("a").amqp_message.message,
+                EventData::from("b").amqp_message.message,
+            ]
+        };
+
+        event_hub
+            .enqueue(Some("0"), None, messages(), Some((1, 0)))
+            .unwrap();
+        // A retried send carries the same sequence numbers
+        event_hub
+            .enqueue(Some("0"), None, messages(), Some((1, 0)))
+            .unwrap();
+        assert_eq!(event_hub.events("0").len(), 2);
+
+        let result = event_hub.enqueue(Some("0"), None, messages(), Some((1, 5)));
+        assert!(result.is_err());
+
+        event_hub
+            .enqueue(Some("0"), None, messages(), Some((1, 2)))
+            .unwrap();
+        assert_eq!(event_hub.events("0").len(), 4);
+    }
+}
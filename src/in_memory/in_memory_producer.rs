@@ -0,0 +1,204 @@
+use std::sync::{atomic::AtomicBool, Weak};
+
+use fe2o3_amqp_types::{
+    messaging::{Body, Message},
+    primitives::Value,
+};
+
+use crate::{
+    amqp::{
+        amqp_event_batch::AmqpEventBatch, amqp_message_converter::stamp_idempotent_annotations,
+        amqp_producer::IdempotentPublishingState, error::CreateBatchError,
+    },
+    core::{RecoverableError, TransportClient, TransportProducer},
+    event_hubs_retry_policy::EventHubsRetryPolicy,
+    producer::{CreateBatchOptions, PartitionPublishingProperties, SendEventOptions},
+    util, EventData,
+};
+
+use super::{
+    error::InMemoryTransportError, in_memory_client::InMemoryClient,
+    in_memory_event_hub::InMemoryEventHub, is_client_closed,
+};
+
+/// A producer that publishes to an [`InMemoryEventHub`]
+#[derive(Debug)]
+pub(crate) struct InMemoryProducer<RP> {
+    pub(crate) event_hub: InMemoryEventHub,
+    pub(crate) is_closed: Weak<AtomicBool>,
+    pub(crate) partition_id: Option<String>,
+    /// The link is detached if this is behind the link generation of the Event Hub
+    pub(crate) link_generation: u64,
+
+    /// The publishing properties assigned when the producer was created
+    pub(crate) initialized_partition_properties: PartitionPublishingProperties,
+    pub(crate) retry_policy: RP,
+
+    /// This is `Some(_)` only if idempotent publishing is enabled
+    pub(crate) idempotent_state: Option<IdempotentPublishingState>,
+}
+
+impl<RP> InMemoryProducer<RP> {
+    pub(crate) fn create_batch(
+        &self,
+        options: CreateBatchOptions,
+        reserve_idempotent_annotations: bool,
+    ) -> Result<AmqpEventBatch, CreateBatchError> {
+        AmqpEventBatch::with_link_max_message_size(
+            options,
+            self.event_hub.max_message_size(),
+            reserve_idempotent_annotations,
+        )
+    }
+
+    fn try_send(
+        &mut self,
+        mut messages: Vec<Message<Body<Value>>>,
+        partition_key: Option<&str>,
+    ) -> Result<(), InMemoryTransportError> {
+        if is_client_closed(&self.is_closed) {
+            return Err(InMemoryTransportError::ClientClosed);
+        }
+        if self.event_hub.lock().link_generation != self.link_generation {
+            return Err(InMemoryTransportError::LinkDetached);
+        }
+
+        let state = match self.idempotent_state {
+            Some(state) => state,
+            None => {
+                return self.event_hub.enqueue(
+                    self.partition_id.as_deref(),
+                    partition_key,
+                    messages,
+                    None,
+                )
+            }
+        };
+
+        // The same sequence numbers are stamped if the send is retried, so the Event Hub can
+        // discard the events that were already published
+        let first_sequence_number = state.next_sequence_number();
+        stamp_idempotent_annotations(
+            messages.iter_mut(),
+            state.producer_group_id,
+            state.owner_level,
+            first_sequence_number,
+        );
+        let count = messages.len();
+        let producer_sequence = state
+            .producer_group_id
+            .map(|producer_group_id| (producer_group_id, first_sequence_number));
+        self.event_hub.enqueue(
+            self.partition_id.as_deref(),
+            partition_key,
+            messages,
+            producer_sequence,
+        )?;
+
+        // Sequence numbers roll over to 0 after i32::MAX
+        let last_published_sequence_number =
+            ((first_sequence_number as i64 + count as i64 - 1) % (i32::MAX as i64 + 1)) as i32;
+        if let Some(state) = self.idempotent_state.as_mut() {
+            state.last_published_sequence_number = Some(last_published_sequence_number);
+        }
+        Ok(())
+    }
+}
+
+/// Retries the sends of an [`InMemoryProducer`] and recovers the producer when its link is
+/// detached
+pub(crate) struct RecoverableInMemoryProducer<'a, RP> {
+    producer: &'a mut InMemoryProducer<RP>,
+    client: &'a mut InMemoryClient,
+}
+
+impl<'a, RP> RecoverableInMemoryProducer<'a, RP>
+where
+    RP: EventHubsRetryPolicy + Send,
+{
+    pub(crate) fn new(
+        producer: &'a mut InMemoryProducer<RP>,
+        client: &'a mut InMemoryClient,
+    ) -> RecoverableInMemoryProducer<'a, RP> {
+        RecoverableInMemoryProducer { producer, client }
+    }
+
+    async fn send_messages(
+        &mut self,
+        messages: Vec<Message<Body<Value>>>,
+        partition_key: Option<String>,
+    ) -> Result<(), InMemoryTransportError> {
+        let mut failed_attempts = 0;
+        let mut should_try_recover = false;
+
+        loop {
+            if should_try_recover {
+                self.client.recover_producer(self.producer).await?;
+            }
+
+            let err = match self
+                .producer
+                .try_send(messages.clone(), partition_key.as_deref())
+            {
+                Ok(_) => return Ok(()),
+                Err(err) => err,
+            };
+
+            log::debug!("Failed to send events: {:?}", err);
+
+            if err.is_scope_disposed() {
+                return Err(err);
+            }
+            should_try_recover = err.should_try_recover();
+
+            failed_attempts += 1;
+            let retry_delay = self
+                .producer
+                .retry_policy
+                .calculate_retry_delay(&err, failed_attempts);
+
+            match retry_delay {
+                Some(retry_delay) => util::time::sleep(retry_delay).await,
+                None => return Err(err),
+            }
+        }
+    }
+}
+
+impl<'a, RP> TransportProducer for RecoverableInMemoryProducer<'a, RP>
+where
+    RP: EventHubsRetryPolicy + Send,
+{
+    type MessageBatch = AmqpEventBatch;
+
+    type SendError = InMemoryTransportError;
+    type CreateBatchError = CreateBatchError;
+
+    fn create_batch(
+        &self,
+        options: CreateBatchOptions,
+        reserve_idempotent_annotations: bool,
+    ) -> Result<Self::MessageBatch, Self::CreateBatchError> {
+        self.producer
+            .create_batch(options, reserve_idempotent_annotations)
+    }
+
+    async fn send(
+        &mut self,
+        events: impl ExactSizeIterator<Item = EventData> + Send,
+        options: SendEventOptions,
+    ) -> Result<(), Self::SendError> {
+        let messages = events.map(|event| event.amqp_message.message).collect();
+        self.send_messages(messages, options.into_partition_key())
+            .await
+    }
+
+    async fn send_batch(
+        &mut self,
+        batch: Self::MessageBatch,
+        options: SendEventOptions,
+    ) -> Result<(), Self::SendError> {
+        self.send_messages(batch.events, options.into_partition_key())
+            .await
+    }
+}
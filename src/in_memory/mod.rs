@@ -0,0 +1,143 @@
+//! An in-memory transport that simulates an Event Hub, which allows the producer and consumer
+//! clients to be tested without an Event Hubs namespace.
+//!
+//! This module is only available with the `in-memory-transport` feature.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Weak,
+};
+
+mod error;
+#[cfg(test)]
+pub(crate) mod fixture;
+pub(crate) mod in_memory_client;
+pub(crate) mod in_memory_consumer;
+pub(crate) mod in_memory_event_hub;
+pub(crate) mod in_memory_producer;
+
+pub use error::InMemoryTransportError;
+pub use in_memory_event_hub::InMemoryEventHub;
+
+pub(crate) use in_memory_event_hub::IN_MEMORY_NAMESPACE;
+
+/// Producers and consumers only keep a weak reference to the state of the client, so they are
+/// closed once all the clones of the client are dropped
+fn is_client_closed(is_closed: &Weak<AtomicBool>) -> bool {
+    is_closed
+        .upgrade()
+        .map(|is_closed| is_closed.load(Ordering::Relaxed))
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use futures_util::StreamExt;
+
+    use crate::{
+        consumer::{EventHubConsumerClient, EventPosition},
+        primitives::{PartitionReceiver, PartitionReceiverOptions},
+        producer::SendEventOptions,
+        ReceivedEventData,
+    };
+
+    use super::fixture::TestEventHub;
+
+    fn body(event: &ReceivedEventData) -> &[u8] {
+        event.body().unwrap()
+    }
+
+    #[tokio::test]
+    async fn events_sent_by_producer_are_read_by_consumer() {
+        let mut test_hub = TestEventHub::new(2);
+        let mut producer = test_hub.producer();
+        let mut consumer = test_hub.consumer();
+
+        assert_eq!(producer.get_partition_ids().await.unwrap(), ["0", "1"]);
+
+        let options = SendEventOptions::new().with_partition_id("1");
+        producer.send_event("hello", options.clone()).await.unwrap();
+        producer.send_event("world", options).await.unwrap();
+
+        let mut stream = consumer
+            .read_events_from_partition("1", EventPosition::earliest(), Default::default())
+            .await
+            .unwrap();
+        let first = stream.next().await.unwrap().unwrap();
+        let second = stream.next().await.unwrap().unwrap();
+        assert_eq!(body(&first), b"hello");
+        assert_eq!(body(&second), b"world");
+        assert_eq!(second.sequence_number(), first.sequence_number() + 1);
+        assert_eq!(test_hub.event_hub.events("1").len(), 2);
+        stream.close().await.unwrap();
+
+        producer.close().await.unwrap();
+        consumer.close().await.unwrap();
+        test_hub.connection.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn consumer_waits_for_events_from_all_partitions() {
+        let mut test_hub = TestEventHub::new(2);
+        let mut producer = test_hub.producer();
+        let mut consumer = test_hub.consumer();
+
+        let mut stream = consumer
+            .read_events(false, Default::default())
+            .await
+            .unwrap();
+
+        // The events are sent after the consumer has started waiting
+        let send = async move {
+            tokio::task::yield_now().await;
+            for partition_id in ["0", "1"] {
+                let options = SendEventOptions::new().with_partition_id(partition_id);
+                producer.send_event(partition_id, options).await.unwrap();
+            }
+            producer
+        };
+        let read = async {
+            let mut bodies = Vec::new();
+            for _ in 0..2 {
+                let event = stream.next().await.unwrap().unwrap();
+                let body = event.body().unwrap();
+                assert_eq!(event.partition_context().partition_id().as_bytes(), body);
+                bodies.push(body.to_vec());
+            }
+            bodies
+        };
+        let (producer, mut bodies) = tokio::join!(send, read);
+        bodies.sort();
+        assert_eq!(bodies, [b"0".to_vec(), b"1".to_vec()]);
+
+        stream.close().await.unwrap();
+        producer.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn partition_receiver_receives_batch() {
+        let mut test_hub = TestEventHub::new(1);
+        test_hub.publish("0", &["a", "b", "c"]).await;
+
+        let mut receiver = PartitionReceiver::with_conneciton(
+            EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+            "0",
+            EventPosition::from_sequence_number(0, false),
+            test_hub.connection,
+            PartitionReceiverOptions::default(),
+        )
+        .await
+        .unwrap();
+        let bodies: Vec<_> = receiver
+            .recv_batch(2, StdDuration::from_millis(10))
+            .await
+            .unwrap()
+            .map(|event| body(&event).to_vec())
+            .collect();
+        assert_eq!(bodies, [b"b".to_vec(), b"c".to_vec()]);
+
+        receiver.close().await.unwrap();
+    }
+}
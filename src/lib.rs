@@ -93,9 +93,9 @@
 //! | Event Hub Producer | Yes |
 //! | Event Hub Consumer | Yes |
 //! | Partition Receiver | Yes |
-//! | Event Hub Buffered Producer | Not yet |
-//! | Event Hub Processor | Not yet |
-//! | Checkpoint Store | Not yet |
+//! | Event Hub Buffered Producer | Yes |
+//! | Event Hub Processor | Yes |
+//! | Checkpoint Store | Yes |
 //!
 //! # TLS Support
 //!
@@ -118,6 +118,11 @@
 //! | `default` | Enables "native-tls" feature |
 //! | `rustls` | Enables the use of the `rustls` crate for TLS support |
 //! | `native-tls` | Enables the use of the `native-tls` crate for TLS support |
+//! | `blob-checkpoint-store` | Enables `BlobCheckpointStore`, a checkpoint store backed by Azure Blob Storage |
+//! | `in-memory-transport` | Enables `InMemoryEventHub`, an Event Hub that lives in memory for offline testing |
+//! | `amqp-emulator` | Enables `EventHubsEmulator`, a local AMQP server that serves an `InMemoryEventHub` to the clients |
+//! | `serde-payload` | Enables `EventData::from_json`, `EventData::from_serde` and `ReceivedEventData::deserialize` to encode typed payloads |
+//! | `schema-registry-avro` | Enables `SchemaRegistryAvroSerializer` and `AzureSchemaRegistryClient`, which encode events with Avro schemas from a schema registry |
 //!
 //! # WebAssembly Support
 //!
@@ -136,19 +141,24 @@ mod cfg;
 // TODO: support wasm32 target
 cfg_not_wasm32! {
     pub(crate) mod amqp;
+    pub(crate) mod amqp_annotated_message;
     pub(crate) mod constants;
     pub(crate) mod core;
     pub(crate) mod diagnostics;
     pub(crate) mod event_data;
     pub(crate) mod event_hubs_connection;
     pub(crate) mod event_hubs_connection_option;
+    pub(crate) mod event_hubs_connection_pool;
     pub(crate) mod event_hubs_connection_string_properties;
     pub(crate) mod event_hubs_properties;
+    pub(crate) mod event_hubs_proxy_options;
     pub(crate) mod event_hubs_retry_mode;
     pub(crate) mod event_hubs_retry_options;
     pub(crate) mod event_hubs_retry_policy;
+    pub(crate) mod event_hubs_tls_connector;
     pub(crate) mod event_hubs_transport_type;
     pub(crate) mod partition_properties;
+    pub(crate) mod transport_kind;
     pub(crate) mod util;
 
     pub mod authorization;
@@ -156,15 +166,33 @@ cfg_not_wasm32! {
     pub mod primitives;
     pub mod producer;
 
+    #[cfg(feature = "in-memory-transport")]
+    pub mod in_memory;
+
+    #[cfg(feature = "amqp-emulator")]
+    pub mod emulator;
+
+    #[cfg(feature = "schema-registry-avro")]
+    pub mod schema_registry;
+
+    #[cfg(feature = "serde-payload")]
+    pub(crate) mod event_payload;
+
+    pub use crate::amqp_annotated_message::*;
     pub use crate::core::BasicRetryPolicy;
     pub use crate::event_data::*;
+    #[cfg(feature = "serde-payload")]
+    pub use crate::event_payload::*;
     pub use crate::event_hubs_connection::*;
     pub use crate::event_hubs_connection_option::*;
+    pub use crate::event_hubs_connection_pool::*;
     pub use crate::event_hubs_connection_string_properties::*;
     pub use crate::event_hubs_properties::*;
+    pub use crate::event_hubs_proxy_options::*;
     pub use crate::event_hubs_retry_mode::*;
     pub use crate::event_hubs_retry_options::*;
     pub use crate::event_hubs_retry_policy::*;
+    pub use crate::event_hubs_tls_connector::*;
     pub use crate::event_hubs_transport_type::*;
     pub use crate::partition_properties::*;
 }
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use azure_core::{
+    error::ErrorKind,
+    prelude::{IfMatchCondition, Metadata},
+    StatusCode,
+};
+use azure_storage_blobs::prelude::{Blob, ContainerClient};
+use futures_util::StreamExt;
+
+use super::{
+    CheckpointStore, EventProcessorCheckpoint, EventProcessorPartitionOwnership,
+    PartitionOwnershipStore,
+};
+
+/// Metadata key for the identifier of the owner of a partition
+const OWNER_IDENTIFIER: &str = "ownerid";
+
+/// Metadata key for the offset of a checkpoint
+const OFFSET: &str = "offset";
+
+/// Metadata key for the sequence number of a checkpoint
+const SEQUENCE_NUMBER: &str = "sequencenumber";
+
+const OWNERSHIP_PREFIX: &str = "ownership";
+const CHECKPOINT_PREFIX: &str = "checkpoint";
+
+/// A [`CheckpointStore`] that persists the ownership and checkpoints in an Azure Blob Storage
+/// container.
+///
+/// The blobs follow the same layout as the checkpoint stores of the other Azure SDKs so that
+/// processors written with different languages can share the same container. Each record is an
+/// empty blob named `<namespace>/<event hub>/<consumer group>/{ownership,checkpoint}/<partition id>`,
+/// where the namespace, Event Hub name and consumer group are lowercased, and the state is kept in
+/// the blob metadata (`ownerid` for ownership, `offset` and `sequencenumber` for checkpoints).
+///
+/// The ETag of an ownership blob is used as [`EventProcessorPartitionOwnership::version`] so that
+/// claims are performed with optimistic concurrency.
+///
+/// The container must exist before the store is used.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use azure_storage::StorageCredentials;
+/// use azure_storage_blobs::prelude::ClientBuilder;
+///
+/// let credentials = StorageCredentials::access_key(account, access_key);
+/// let container_client = ClientBuilder::new(account, credentials).container_client("checkpoints");
+/// let store = BlobCheckpointStore::new(container_client);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BlobCheckpointStore {
+    container_client: ContainerClient,
+}
+
+impl BlobCheckpointStore {
+    /// Creates a new store that persists the records in the given container
+    pub fn new(container_client: ContainerClient) -> Self {
+        Self { container_client }
+    }
+
+    /// The client of the container that holds the records
+    pub fn container_client(&self) -> &ContainerClient {
+        &self.container_client
+    }
+
+    async fn list_blobs(&self, prefix: String) -> Result<Vec<Blob>, azure_core::Error> {
+        let mut stream = self
+            .container_client
+            .list_blobs()
+            .prefix(prefix)
+            .include_metadata(true)
+            .into_stream();
+        let mut blobs = Vec::new();
+        while let Some(page) = stream.next().await {
+            blobs.extend(page?.blobs.blobs().cloned());
+        }
+        Ok(blobs)
+    }
+
+    /// Claims a single ownership. Returns `Ok(None)` if the ownership has been modified by another
+    /// processor.
+    async fn claim(
+        &self,
+        mut ownership: EventProcessorPartitionOwnership,
+    ) -> Result<Option<EventProcessorPartitionOwnership>, azure_core::Error> {
+        let blob_name = blob_name(
+            &ownership.fully_qualified_namespace,
+            &ownership.event_hub_name,
+            &ownership.consumer_group,
+            OWNERSHIP_PREFIX,
+            &ownership.partition_id,
+        );
+        let blob_client = self.container_client.blob_client(blob_name);
+        let mut metadata = Metadata::new();
+        metadata.insert(OWNER_IDENTIFIER, ownership.owner_identifier.clone());
+
+        let result = match ownership.version.take() {
+            // The blob must not exist yet
+            None => blob_client
+                .put_block_blob(Vec::new())
+                .metadata(metadata)
+                .if_match(IfMatchCondition::NotMatch("*".to_string()))
+                .await
+                .map(|response| (response.etag, response.last_modified)),
+            Some(etag) => blob_client
+                .set_metadata()
+                .metadata(metadata)
+                .if_match(IfMatchCondition::Match(etag))
+                .await
+                .map(|response| (response.etag, response.date)),
+        };
+
+        match result {
+            Ok((etag, last_modified)) => {
+                ownership.version = Some(etag);
+                ownership.last_modified_time = Some(last_modified);
+                Ok(Some(ownership))
+            }
+            Err(err) if is_concurrency_conflict(&err) => {
+                log::debug!(
+                    "Ownership of partition {} was modified by another processor",
+                    ownership.partition_id
+                );
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl PartitionOwnershipStore for BlobCheckpointStore {
+    async fn list_ownership(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error> {
+        let prefix = blob_prefix(
+            fully_qualified_namespace,
+            event_hub_name,
+            consumer_group,
+            OWNERSHIP_PREFIX,
+        );
+        let blobs = self.list_blobs(prefix).await?;
+        Ok(blobs
+            .into_iter()
+            .map(|blob| {
+                let owner_identifier = blob
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| get_metadata(metadata, OWNER_IDENTIFIER))
+                    .unwrap_or_default()
+                    .to_string();
+                EventProcessorPartitionOwnership {
+                    fully_qualified_namespace: fully_qualified_namespace.to_string(),
+                    event_hub_name: event_hub_name.to_string(),
+                    consumer_group: consumer_group.to_string(),
+                    partition_id: partition_id_from_blob_name(&blob.name).to_string(),
+                    owner_identifier,
+                    last_modified_time: Some(blob.properties.last_modified),
+                    version: Some(blob.properties.etag.to_string()),
+                }
+            })
+            .collect())
+    }
+
+    async fn claim_ownership(
+        &self,
+        desired_ownership: Vec<EventProcessorPartitionOwnership>,
+    ) -> Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error> {
+        let mut claimed = Vec::with_capacity(desired_ownership.len());
+        for ownership in desired_ownership {
+            if let Some(ownership) = self.claim(ownership).await? {
+                claimed.push(ownership);
+            }
+        }
+        Ok(claimed)
+    }
+}
+
+impl CheckpointStore for BlobCheckpointStore {
+    async fn list_checkpoints(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<EventProcessorCheckpoint>, azure_core::Error> {
+        let prefix = blob_prefix(
+            fully_qualified_namespace,
+            event_hub_name,
+            consumer_group,
+            CHECKPOINT_PREFIX,
+        );
+        let blobs = self.list_blobs(prefix).await?;
+        Ok(blobs
+            .into_iter()
+            .filter_map(|blob| {
+                let metadata = blob.metadata.as_ref()?;
+                let offset = get_metadata(metadata, OFFSET).and_then(|s| s.parse().ok());
+                let sequence_number =
+                    get_metadata(metadata, SEQUENCE_NUMBER).and_then(|s| s.parse().ok());
+                Some(EventProcessorCheckpoint {
+                    fully_qualified_namespace: fully_qualified_namespace.to_string(),
+                    event_hub_name: event_hub_name.to_string(),
+                    consumer_group: consumer_group.to_string(),
+                    partition_id: partition_id_from_blob_name(&blob.name).to_string(),
+                    offset,
+                    sequence_number,
+                })
+            })
+            .collect())
+    }
+
+    async fn update_checkpoint(
+        &self,
+        checkpoint: EventProcessorCheckpoint,
+    ) -> Result<(), azure_core::Error> {
+        let blob_name = blob_name(
+            &checkpoint.fully_qualified_namespace,
+            &checkpoint.event_hub_name,
+            &checkpoint.consumer_group,
+            CHECKPOINT_PREFIX,
+            &checkpoint.partition_id,
+        );
+        let mut metadata = Metadata::new();
+        if let Some(offset) = checkpoint.offset {
+            metadata.insert(OFFSET, offset.to_string());
+        }
+        if let Some(sequence_number) = checkpoint.sequence_number {
+            metadata.insert(SEQUENCE_NUMBER, sequence_number.to_string());
+        }
+
+        self.container_client
+            .blob_client(blob_name)
+            .put_block_blob(Vec::new())
+            .metadata(metadata)
+            .await
+            .map(|_| ())
+    }
+}
+
+fn blob_prefix(
+    fully_qualified_namespace: &str,
+    event_hub_name: &str,
+    consumer_group: &str,
+    kind: &str,
+) -> String {
+    format!(
+        "{}/{}/{}/{}/",
+        fully_qualified_namespace.to_lowercase(),
+        event_hub_name.to_lowercase(),
+        consumer_group.to_lowercase(),
+        kind
+    )
+}
+
+fn blob_name(
+    fully_qualified_namespace: &str,
+    event_hub_name: &str,
+    consumer_group: &str,
+    kind: &str,
+    partition_id: &str,
+) -> String {
+    let mut name = blob_prefix(
+        fully_qualified_namespace,
+        event_hub_name,
+        consumer_group,
+        kind,
+    );
+    name.push_str(partition_id);
+    name
+}
+
+fn partition_id_from_blob_name(blob_name: &str) -> &str {
+    blob_name.rsplit('/').next().unwrap_or(blob_name)
+}
+
+/// Metadata keys are case-insensitive in the storage service
+fn get_metadata<'a>(metadata: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    metadata
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Returns true if the request failed because the blob was modified (or created) by someone else
+fn is_concurrency_conflict(error: &azure_core::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::HttpResponse { status, .. }
+            if matches!(
+                status,
+                StatusCode::PreconditionFailed | StatusCode::Conflict | StatusCode::NotFound
+            )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{blob_name, blob_prefix, get_metadata, partition_id_from_blob_name};
+
+    #[test]
+    fn blob_names_follow_the_layout_of_other_sdks() {
+        let prefix = blob_prefix(
+            "MyNamespace.servicebus.windows.net",
+            "MyHub",
+            "$Default",
+            "ownership",
+        );
+        assert_eq!(
+            prefix,
+            "mynamespace.servicebus.windows.net/myhub/$default/ownership/"
+        );
+
+        let name = blob_name(
+            "MyNamespace.servicebus.windows.net",
+            "MyHub",
+            "$Default",
+            "checkpoint",
+            "3",
+        );
+        assert_eq!(
+            name,
+            "mynamespace.servicebus.windows.net/myhub/$default/checkpoint/3"
+        );
+        assert_eq!(partition_id_from_blob_name(&name), "3");
+    }
+
+    #[test]
+    fn metadata_keys_are_case_insensitive() {
+        let mut metadata = HashMap::new();
+        metadata.insert("SequenceNumber".to_string(), "42".to_string());
+        assert_eq!(get_metadata(&metadata, "sequencenumber"), Some("42"));
+        assert_eq!(get_metadata(&metadata, "offset"), None);
+    }
+}
@@ -0,0 +1,62 @@
+use std::future::Future;
+
+use super::{EventProcessorCheckpoint, PartitionOwnershipStore};
+
+/// A storage for partition ownership and checkpoints, which allows event processors to coordinate
+/// the partition ownership and to resume processing from where it left off.
+///
+/// Both the ownership and the checkpoints are keyed by the fully qualified namespace, the Event Hub
+/// name, the consumer group and the partition identifier.
+pub trait CheckpointStore: PartitionOwnershipStore {
+    /// Requests a list of the checkpoints for all partitions of a given Event Hub and consumer group
+    /// pairing.
+    fn list_checkpoints(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> impl Future<Output = Result<Vec<EventProcessorCheckpoint>, azure_core::Error>> + Send;
+
+    /// Creates or updates the checkpoint of a partition.
+    fn update_checkpoint(
+        &self,
+        checkpoint: EventProcessorCheckpoint,
+    ) -> impl Future<Output = Result<(), azure_core::Error>> + Send;
+}
+
+/// Key used by the stores for both ownership and checkpoints. The namespace, Event Hub name and
+/// consumer group are case-insensitive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PartitionKey {
+    pub(crate) fully_qualified_namespace: String,
+    pub(crate) event_hub_name: String,
+    pub(crate) consumer_group: String,
+    pub(crate) partition_id: String,
+}
+
+impl PartitionKey {
+    pub(crate) fn new(
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+        partition_id: &str,
+    ) -> Self {
+        Self {
+            fully_qualified_namespace: fully_qualified_namespace.to_lowercase(),
+            event_hub_name: event_hub_name.to_lowercase(),
+            consumer_group: consumer_group.to_lowercase(),
+            partition_id: partition_id.to_string(),
+        }
+    }
+
+    pub(crate) fn matches(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> bool {
+        self.fully_qualified_namespace == fully_qualified_namespace.to_lowercase()
+            && self.event_hub_name == event_hub_name.to_lowercase()
+            && self.consumer_group == consumer_group.to_lowercase()
+    }
+}
@@ -0,0 +1,669 @@
+use std::{collections::HashMap, future::Future, marker::PhantomData, sync::Arc};
+
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    authorization::event_hub_token_credential::EventHubTokenCredential,
+    consumer::{EventPosition, EventStream, PartitionContext, PartitionEvent},
+    core::BasicRetryPolicy,
+    event_hubs_retry_policy::EventHubsRetryPolicy,
+    util::time::{now_utc, sleep, Instant},
+    EventHubConnection, EventHubsRetryOptions,
+};
+
+use super::{
+    partition_load_balancer::PartitionLoadBalancer, CheckpointStore, EventProcessorCheckpoint,
+    EventProcessorOptions, EventProcessorPartitionOwnership,
+};
+
+/// The owner level used by the processor when reading from a partition. Using an exclusive reader
+/// ensures that a processor that has lost the ownership of a partition stops reading from it once
+/// the new owner starts reading.
+const PROCESSOR_OWNER_LEVEL: i64 = 0;
+
+/// Outcome of processing a partition, which carries the identifier of the partition, the
+/// generation of the processing task and the position of the last event that was processed.
+type PartitionProcessingOutcome = (
+    Arc<String>,
+    u64,
+    Option<EventPosition>,
+    Result<(), azure_core::Error>,
+);
+
+/// Provides a base for creating a custom processor for consuming events across all partitions of a
+/// given Event Hub within a specific consumer group. The processor is capable of collaborating with
+/// other instances for the same Event Hub and consumer group pairing to share work by using a
+/// [`CheckpointStore`] as a means of coordination.
+///
+/// Each processor claims a fair share of the partitions, renews the ownership of the partitions
+/// that it is processing, and rebalances when other processors join or leave the consumer group.
+/// When a partition is claimed, reading starts from the checkpoint found in the store, if any.
+#[derive(Debug)]
+pub struct EventProcessor<S, RP> {
+    connection: EventHubConnection,
+    consumer_group: Arc<String>,
+    identifier: Arc<String>,
+    store: S,
+    options: EventProcessorOptions,
+    load_balancer: PartitionLoadBalancer,
+    retry_policy_marker: PhantomData<RP>,
+}
+
+/// A builder for an [`EventProcessor`].
+#[derive(Debug)]
+pub struct EventProcessorBuilder<S, RP> {
+    _marker: PhantomData<(S, RP)>,
+}
+
+impl<S> EventProcessor<S, BasicRetryPolicy>
+where
+    S: CheckpointStore,
+{
+    /// Creates a new [`EventProcessorBuilder`] with a custom retry policy.
+    pub fn with_policy<RP>() -> EventProcessorBuilder<S, RP>
+    where
+        RP: EventHubsRetryPolicy + From<EventHubsRetryOptions> + Send,
+    {
+        EventProcessorBuilder {
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a new [`EventProcessor`] from a connection string.
+    pub async fn new_from_connection_string(
+        consumer_group: impl Into<String>,
+        connection_string: impl Into<String>,
+        event_hub_name: impl Into<Option<String>>,
+        store: S,
+        options: EventProcessorOptions,
+    ) -> Result<Self, azure_core::Error> {
+        Self::with_policy()
+            .new_from_connection_string(
+                consumer_group,
+                connection_string,
+                event_hub_name,
+                store,
+                options,
+            )
+            .await
+    }
+
+    /// Creates a new [`EventProcessor`] from a namespace and a credential.
+    pub async fn new_from_credential(
+        consumer_group: impl Into<String>,
+        fully_qualified_namespace: impl Into<String>,
+        event_hub_name: impl Into<String>,
+        credential: impl Into<EventHubTokenCredential>,
+        store: S,
+        options: EventProcessorOptions,
+    ) -> Result<Self, azure_core::Error> {
+        Self::with_policy()
+            .new_from_credential(
+                consumer_group,
+                fully_qualified_namespace,
+                event_hub_name,
+                credential,
+                store,
+                options,
+            )
+            .await
+    }
+
+    /// Creates a new [`EventProcessor`] from an existing [`EventHubConnection`].
+    pub fn with_connection(
+        consumer_group: impl Into<String>,
+        connection: &mut EventHubConnection,
+        store: S,
+        options: EventProcessorOptions,
+    ) -> Self {
+        Self::with_policy().with_connection(consumer_group, connection, store, options)
+    }
+}
+
+impl<S, RP> EventProcessorBuilder<S, RP>
+where
+    S: CheckpointStore,
+    RP: EventHubsRetryPolicy + From<EventHubsRetryOptions> + Send,
+{
+    /// Creates a new [`EventProcessor`] from a connection string.
+    pub async fn new_from_connection_string(
+        self,
+        consumer_group: impl Into<String>,
+        connection_string: impl Into<String>,
+        event_hub_name: impl Into<Option<String>>,
+        store: S,
+        options: EventProcessorOptions,
+    ) -> Result<EventProcessor<S, RP>, azure_core::Error> {
+        let connection = EventHubConnection::new_from_connection_string(
+            connection_string.into(),
+            event_hub_name.into(),
+            options.connection_options.clone(),
+        )
+        .await?;
+        Ok(EventProcessor::new(
+            consumer_group,
+            connection,
+            store,
+            options,
+        ))
+    }
+
+    /// Creates a new [`EventProcessor`] from a namespace and a credential.
+    pub async fn new_from_credential(
+        self,
+        consumer_group: impl Into<String>,
+        fully_qualified_namespace: impl Into<String>,
+        event_hub_name: impl Into<String>,
+        credential: impl Into<EventHubTokenCredential>,
+        store: S,
+        options: EventProcessorOptions,
+    ) -> Result<EventProcessor<S, RP>, azure_core::Error> {
+        let connection = EventHubConnection::new_from_credential(
+            fully_qualified_namespace.into(),
+            event_hub_name.into(),
+            credential.into(),
+            options.connection_options.clone(),
+        )
+        .await?;
+        Ok(EventProcessor::new(
+            consumer_group,
+            connection,
+            store,
+            options,
+        ))
+    }
+
+    /// Creates a new [`EventProcessor`] from an existing [`EventHubConnection`].
+    pub fn with_connection(
+        self,
+        consumer_group: impl Into<String>,
+        connection: &mut EventHubConnection,
+        store: S,
+        options: EventProcessorOptions,
+    ) -> EventProcessor<S, RP> {
+        EventProcessor::new(consumer_group, connection.clone_as_shared(), store, options)
+    }
+}
+
+impl<S, RP> EventProcessor<S, RP> {
+    fn new(
+        consumer_group: impl Into<String>,
+        connection: EventHubConnection,
+        store: S,
+        options: EventProcessorOptions,
+    ) -> Self {
+        let identifier = options
+            .identifier
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let load_balancer = PartitionLoadBalancer::new(
+            identifier.clone(),
+            options.load_balancing_strategy,
+            options.partition_ownership_expiration_interval,
+        );
+        Self {
+            connection,
+            consumer_group: Arc::new(consumer_group.into()),
+            identifier: Arc::new(identifier),
+            store,
+            options,
+            load_balancer,
+            retry_policy_marker: PhantomData,
+        }
+    }
+
+    /// The unique identifier of the processor, which is used as the owner of the claimed partitions
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The name of the consumer group that the processor is associated with
+    pub fn consumer_group(&self) -> &str {
+        &self.consumer_group
+    }
+
+    /// The fully qualified Event Hubs namespace that the processor is associated with
+    pub fn fully_qualified_namespace(&self) -> &str {
+        self.connection.fully_qualified_namespace()
+    }
+
+    /// The name of the Event Hub that the processor is associated with
+    pub fn event_hub_name(&self) -> &str {
+        self.connection.event_hub_name()
+    }
+
+    /// Returns a reference to the store used for coordinating the partition ownership
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<S, RP> EventProcessor<S, RP>
+where
+    S: CheckpointStore,
+    RP: EventHubsRetryPolicy + From<EventHubsRetryOptions> + Send + Unpin + 'static,
+{
+    /// Runs the processor until `shutdown` completes.
+    ///
+    /// The processor periodically balances the partition ownership with the other processors
+    /// sharing the same store, and calls `handler` for each event read from the partitions that it
+    /// owns. Events from the same partition are handled in order, while events from different
+    /// partitions are handled concurrently.
+    ///
+    /// Errors encountered while balancing the ownership or reading from a partition are logged, and
+    /// the processor will attempt to resume processing in the next load balancing cycle. When
+    /// `shutdown` completes, the processor stops reading from all partitions and relinquishes the
+    /// ownership of them so that other processors may claim them right away.
+    ///
+    /// A handler that is still running when `shutdown` completes, or when the ownership of its
+    /// partition is lost, is dropped at its next `.await`. The event is then handled again by the
+    /// processor that reads the partition next, so the handler should checkpoint an event only
+    /// once it is done with it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let store = FileCheckpointStore::new("./checkpoints");
+    /// let mut processor = EventProcessor::new_from_connection_string(
+    ///     "$Default",
+    ///     connection_string,
+    ///     event_hub_name,
+    ///     store.clone(),
+    ///     EventProcessorOptions::default(),
+    /// )
+    /// .await?;
+    ///
+    /// let cancel = CancellationToken::new();
+    /// processor
+    ///     .run(
+    ///         |event| {
+    ///             let store = store.clone();
+    ///             async move {
+    ///                 log::info!("{}: {:?}", event.partition_context().partition_id(), event.body());
+    ///                 let checkpoint = EventProcessorCheckpoint::from(&event);
+    ///                 if let Err(err) = store.update_checkpoint(checkpoint).await {
+    ///                     log::error!("Failed to update checkpoint: {:?}", err);
+    ///                 }
+    ///             }
+    ///         },
+    ///         cancel.cancelled(),
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn run<H, Fut>(
+        &mut self,
+        handler: H,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), azure_core::Error>
+    where
+        H: Fn(PartitionEvent) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let mut shutdown = std::pin::pin!(shutdown);
+        let mut tasks = FuturesUnordered::new();
+        let mut processing: HashMap<Arc<String>, (u64, CancellationToken)> = HashMap::new();
+        let mut last_positions: HashMap<Arc<String>, EventPosition> = HashMap::new();
+        let mut owned: Vec<EventProcessorPartitionOwnership> = Vec::new();
+        let mut generation = 0u64;
+
+        // Waits on the future while continuing to process the partitions. Returns `None` if
+        // `shutdown` completes first.
+        macro_rules! drive {
+            ($fut:expr) => {{
+                let mut fut = std::pin::pin!($fut);
+                loop {
+                    tokio::select! {
+                        _ = &mut shutdown => break None,
+                        output = &mut fut => break Some(output),
+                        Some(outcome) = tasks.next(), if !tasks.is_empty() => {
+                            on_partition_stopped(&mut processing, &mut last_positions, outcome);
+                        }
+                    }
+                }
+            }};
+        }
+
+        loop {
+            let next_cycle = Instant::now() + self.options.load_balancing_update_interval;
+
+            let Some(cycle_result) = drive!(self.run_load_balancing_cycle()) else {
+                break;
+            };
+            match cycle_result {
+                Ok(claimed) => owned = claimed,
+                Err(err) => log::error!("Failed to balance partition ownership: {:?}", err),
+            }
+
+            // Stop processing partitions that are no longer owned
+            processing.retain(|partition_id, (_, cancel)| {
+                let is_owned = owned.iter().any(|o| o.partition_id == **partition_id);
+                if !is_owned {
+                    log::debug!("Ownership of partition {} lost", partition_id);
+                    cancel.cancel();
+                    last_positions.remove(partition_id);
+                }
+                is_owned
+            });
+
+            // Start processing newly owned partitions
+            let mut newly_owned: Vec<_> = owned
+                .iter()
+                .filter(|o| !processing.contains_key(&o.partition_id))
+                .map(|o| Arc::new(o.partition_id.clone()))
+                .collect();
+            let mut checkpoints = Vec::new();
+            if !newly_owned.is_empty() {
+                let list_checkpoints = self.store.list_checkpoints(
+                    self.connection.fully_qualified_namespace(),
+                    self.connection.event_hub_name(),
+                    &self.consumer_group,
+                );
+                match drive!(list_checkpoints) {
+                    Some(Ok(listed)) => checkpoints = listed,
+                    Some(Err(err)) => {
+                        // Do not risk reprocessing the partitions from the default position. They
+                        // will be started in the next cycle.
+                        log::error!("Failed to list checkpoints: {:?}", err);
+                        newly_owned.clear();
+                    }
+                    None => break,
+                }
+            }
+
+            for partition_id in newly_owned {
+                // Resume from where this processor left off if it has kept the ownership, and
+                // otherwise from the checkpoint
+                let starting_position = last_positions
+                    .get(&partition_id)
+                    .cloned()
+                    .or_else(|| {
+                        checkpoints
+                            .iter()
+                            .find(|c| c.partition_id == *partition_id)
+                            .and_then(EventProcessorCheckpoint::starting_position)
+                    })
+                    .unwrap_or_else(|| self.options.default_starting_position.clone());
+                let cancel = CancellationToken::new();
+                generation += 1;
+                processing.insert(partition_id.clone(), (generation, cancel.clone()));
+
+                log::debug!(
+                    "Start processing partition {} from {:?}",
+                    partition_id,
+                    starting_position
+                );
+                let connection = self.connection.clone_as_shared();
+                tasks.push(process_partition::<RP, _, _>(
+                    connection,
+                    self.consumer_group.clone(),
+                    partition_id,
+                    generation,
+                    starting_position,
+                    self.identifier.clone(),
+                    self.options.clone(),
+                    &handler,
+                    cancel,
+                ));
+            }
+
+            if drive!(sleep(next_cycle.saturating_duration_since(Instant::now()))).is_none() {
+                break;
+            }
+        }
+
+        // Stop processing all partitions and wait for them to finish
+        for (_, (_, cancel)) in processing.drain() {
+            cancel.cancel();
+        }
+        while let Some(outcome) = tasks.next().await {
+            if let (partition_id, _, _, Err(err)) = outcome {
+                log::error!("Error processing partition {}: {:?}", partition_id, err);
+            }
+        }
+
+        self.relinquish_ownership(owned).await
+    }
+
+    /// Closes the processor.
+    ///
+    /// The underlying connection will be closed if the processor is the last one using it.
+    pub async fn close(self) -> Result<(), azure_core::Error> {
+        self.connection.close_if_owned().await
+    }
+
+    /// Runs a single load balancing cycle, which renews the ownership of the partitions that are
+    /// currently owned by this processor and claims new partitions if needed. Returns the
+    /// ownership of all the partitions that are owned by this processor after the cycle.
+    async fn run_load_balancing_cycle(
+        &mut self,
+    ) -> Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error> {
+        let partition_ids = self
+            .connection
+            .get_partition_ids(RP::from(self.options.retry_options.clone()))
+            .await?;
+        let ownership = self
+            .store
+            .list_ownership(
+                self.connection.fully_qualified_namespace(),
+                self.connection.event_hub_name(),
+                &self.consumer_group,
+            )
+            .await?;
+
+        let now = now_utc();
+        let to_claim = self
+            .load_balancer
+            .find_partitions_to_claim(&partition_ids, &ownership, now);
+
+        let mut desired: Vec<EventProcessorPartitionOwnership> = ownership
+            .iter()
+            .filter(|o| o.owner_identifier == *self.identifier)
+            .filter(|o| partition_ids.contains(&o.partition_id))
+            .cloned()
+            .collect();
+        for partition_id in to_claim {
+            let claim = match ownership.iter().find(|o| o.partition_id == partition_id) {
+                Some(existing) => EventProcessorPartitionOwnership {
+                    owner_identifier: self.identifier.to_string(),
+                    ..existing.clone()
+                },
+                None => EventProcessorPartitionOwnership {
+                    fully_qualified_namespace: self.connection.fully_qualified_namespace().into(),
+                    event_hub_name: self.connection.event_hub_name().into(),
+                    consumer_group: self.consumer_group.to_string(),
+                    partition_id,
+                    owner_identifier: self.identifier.to_string(),
+                    last_modified_time: None,
+                    version: None,
+                },
+            };
+            desired.push(claim);
+        }
+
+        if desired.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        for ownership in desired.iter_mut() {
+            ownership.last_modified_time = Some(now);
+        }
+
+        let claimed = self.store.claim_ownership(desired).await?;
+        Ok(claimed
+            .into_iter()
+            .filter(|o| o.owner_identifier == *self.identifier)
+            .collect())
+    }
+
+    async fn relinquish_ownership(
+        &mut self,
+        owned: Vec<EventProcessorPartitionOwnership>,
+    ) -> Result<(), azure_core::Error> {
+        if owned.is_empty() {
+            return Ok(());
+        }
+
+        let relinquished = owned
+            .into_iter()
+            .map(|ownership| EventProcessorPartitionOwnership {
+                owner_identifier: String::new(),
+                ..ownership
+            })
+            .collect();
+        self.store.claim_ownership(relinquished).await.map(|_| ())
+    }
+}
+
+fn on_partition_stopped(
+    processing: &mut HashMap<Arc<String>, (u64, CancellationToken)>,
+    last_positions: &mut HashMap<Arc<String>, EventPosition>,
+    outcome: PartitionProcessingOutcome,
+) {
+    let (partition_id, generation, last_position, result) = outcome;
+    if let Err(err) = result {
+        log::error!("Error processing partition {}: {:?}", partition_id, err);
+    }
+
+    // The partition may have been lost or restarted since the task was cancelled
+    if matches!(processing.get(&partition_id), Some((g, _)) if *g == generation) {
+        processing.remove(&partition_id);
+        if let Some(position) = last_position {
+            last_positions.insert(partition_id, position);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_partition<RP, H, Fut>(
+    mut connection: EventHubConnection,
+    consumer_group: Arc<String>,
+    partition_id: Arc<String>,
+    generation: u64,
+    starting_position: EventPosition,
+    identifier: Arc<String>,
+    options: EventProcessorOptions,
+    handler: &H,
+    cancel: CancellationToken,
+) -> PartitionProcessingOutcome
+where
+    RP: EventHubsRetryPolicy + From<EventHubsRetryOptions> + Send + Unpin + 'static,
+    H: Fn(PartitionEvent) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut last_position = None;
+    let consumer = connection
+        .create_transport_consumer(
+            &consumer_group,
+            &partition_id,
+            Some(identifier.to_string()),
+            starting_position,
+            RP::from(options.retry_options.clone()),
+            options.track_last_enqueued_event_properties,
+            Some(PROCESSOR_OWNER_LEVEL),
+            Some(options.prefetch_count),
+        )
+        .await;
+    let consumer = match consumer {
+        Ok(consumer) => consumer,
+        Err(err) => return (partition_id, generation, last_position, Err(err)),
+    };
+
+    let (sender, receiver) = watch::channel(None);
+    let context = PartitionContext::new(
+        Arc::new(connection.fully_qualified_namespace().to_string()),
+        Arc::new(connection.event_hub_name().to_string()),
+        consumer_group,
+        partition_id.clone(),
+        receiver,
+    );
+
+    let mut stream = EventStream::with_consumer(&mut connection.inner, consumer);
+    let result = loop {
+        let event = tokio::select! {
+            _ = cancel.cancelled() => {
+                // Dropping the stream instead of closing it as closing would wait for the
+                // pending receive to complete
+                drop(stream);
+                return (partition_id, generation, last_position, Ok(()));
+            },
+            event = stream.next() => event,
+        };
+
+        match event {
+            Some(Ok(event)) => {
+                let offset = event.offset();
+                if options.track_last_enqueued_event_properties {
+                    sender.send_replace(Some(event.clone()));
+                }
+                let event = PartitionEvent::new(context.clone(), event.into_raw_amqp_message());
+
+                // The handler is dropped if the partition stops being processed while it runs, and
+                // the event is then read again from the last handled event
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        drop(stream);
+                        return (partition_id, generation, last_position, Ok(()));
+                    },
+                    _ = handler(event) => {},
+                }
+                if let Some(offset) = offset {
+                    last_position = Some(EventPosition::from_offset(offset, false));
+                }
+            }
+            Some(Err(err)) => break Err(err),
+            None => break Ok(()),
+        }
+    };
+
+    let result = match stream.close().await {
+        Ok(_) => result,
+        Err(err) => result.and(Err(err.into())),
+    };
+    (partition_id, generation, last_position, result)
+}
+
+#[cfg(all(test, feature = "in-memory-transport"))]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use tokio::sync::Notify;
+
+    use crate::{
+        consumer::EventHubConsumerClient,
+        in_memory::fixture::TestEventHub,
+        primitives::{EventProcessorOptions, InMemoryCheckpointStore},
+    };
+
+    use super::EventProcessor;
+
+    #[tokio::test]
+    async fn shutdown_does_not_wait_for_a_running_handler() {
+        let mut test_hub = TestEventHub::new(1);
+        test_hub.publish("0", &["a"]).await;
+        let options = EventProcessorOptions {
+            retry_options: TestEventHub::retry_options(),
+            ..Default::default()
+        };
+        let mut processor = EventProcessor::with_connection(
+            EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+            &mut test_hub.connection,
+            InMemoryCheckpointStore::new(),
+            options,
+        );
+
+        let handling = Notify::new();
+        let run = processor.run(
+            |_| async {
+                handling.notify_one();
+                std::future::pending::<()>().await
+            },
+            handling.notified(),
+        );
+        tokio::time::timeout(StdDuration::from_secs(5), run)
+            .await
+            .expect("the handler should be dropped on shutdown")
+            .unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::consumer::{EventPosition, PartitionEvent};
+
+/// Represents a checkpoint for a partition, which records the position of the last event that was
+/// processed so that processing can resume from that position.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EventProcessorCheckpoint {
+    /// The fully qualified Event Hubs namespace that the checkpoint is associated with.
+    pub fully_qualified_namespace: String,
+
+    /// The name of the specific Event Hub that the checkpoint is associated with, relative to the
+    /// Event Hubs namespace that contains it.
+    pub event_hub_name: String,
+
+    /// The name of the consumer group that the checkpoint is associated with.
+    pub consumer_group: String,
+
+    /// The identifier of the Event Hub partition that the checkpoint is associated with.
+    pub partition_id: String,
+
+    /// The offset of the last event that was processed.
+    pub offset: Option<i64>,
+
+    /// The sequence number of the last event that was processed.
+    pub sequence_number: Option<i64>,
+}
+
+impl EventProcessorCheckpoint {
+    /// The position to resume reading from, which is the event immediately after the one recorded
+    /// by the checkpoint.
+    ///
+    /// The offset takes precedence over the sequence number if both are present. Returns `None`
+    /// if the checkpoint records neither.
+    pub fn starting_position(&self) -> Option<EventPosition> {
+        match (self.offset, self.sequence_number) {
+            (Some(offset), _) => Some(EventPosition::from_offset(offset, false)),
+            (None, Some(sequence_number)) => {
+                Some(EventPosition::from_sequence_number(sequence_number, false))
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+impl From<&PartitionEvent> for EventProcessorCheckpoint {
+    fn from(event: &PartitionEvent) -> Self {
+        let context = event.partition_context();
+        let offset = match event.offset() {
+            i64::MIN => None,
+            offset => Some(offset),
+        };
+        let sequence_number = match event.sequence_number() {
+            i64::MIN => None,
+            sequence_number => Some(sequence_number),
+        };
+        Self {
+            fully_qualified_namespace: context.fully_qualified_namespace().to_string(),
+            event_hub_name: context.event_hub_name().to_string(),
+            consumer_group: context.consumer_group().to_string(),
+            partition_id: context.partition_id().to_string(),
+            offset,
+            sequence_number,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::consumer::EventPosition;
+
+    use super::EventProcessorCheckpoint;
+
+    fn checkpoint(offset: Option<i64>, sequence_number: Option<i64>) -> EventProcessorCheckpoint {
+        EventProcessorCheckpoint {
+            fully_qualified_namespace: "namespace".to_string(),
+            event_hub_name: "eventhub".to_string(),
+            consumer_group: "$Default".to_string(),
+            partition_id: "0".to_string(),
+            offset,
+            sequence_number,
+        }
+    }
+
+    #[test]
+    fn starting_position_prefers_offset() {
+        let position = checkpoint(Some(1024), Some(7)).starting_position();
+        assert_eq!(position, Some(EventPosition::from_offset(1024, false)));
+    }
+
+    #[test]
+    fn starting_position_falls_back_to_sequence_number() {
+        let position = checkpoint(None, Some(7)).starting_position();
+        assert_eq!(position, Some(EventPosition::from_sequence_number(7, false)));
+    }
+
+    #[test]
+    fn starting_position_is_none_without_offset_or_sequence_number() {
+        assert_eq!(checkpoint(None, None).starting_position(), None);
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration as StdDuration;
+
+use crate::{consumer::EventPosition, EventHubConnectionOptions, EventHubsRetryOptions};
+
+use super::partition_receiver_options::DEFAULT_PREFETCH_COUNT;
+
+/// The default interval at which the processor attempts to balance the partition ownership
+pub const DEFAULT_LOAD_BALANCING_UPDATE_INTERVAL: StdDuration = StdDuration::from_secs(10);
+
+/// The default interval after which a partition ownership that has not been renewed is considered
+/// to be expired and may be claimed by another processor
+pub const DEFAULT_PARTITION_OWNERSHIP_EXPIRATION_INTERVAL: StdDuration = StdDuration::from_secs(30);
+
+/// The strategy that an event processor will use when claiming ownership of partitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LoadBalancingStrategy {
+    /// An event processor will claim at most one partition per load balancing cycle, so that the
+    /// partitions are distributed gradually across all of the active processors.
+    #[default]
+    Balanced,
+
+    /// An event processor will claim as many partitions as needed to reach its fair share within
+    /// a single load balancing cycle.
+    Greedy,
+}
+
+/// The set of options that can be specified when creating a
+/// [`crate::primitives::EventProcessor`]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct EventProcessorOptions {
+    /// The set of options that can be specified when creating an [`crate::EventHubConnection`]
+    pub connection_options: EventHubConnectionOptions,
+
+    /// The set of options to govern retry behavior and try timeouts.
+    pub retry_options: EventHubsRetryOptions,
+
+    /// The identifier of the processor. If not specified, a UUID will be used.
+    pub identifier: Option<String>,
+
+    /// The strategy to use when claiming ownership of partitions
+    pub load_balancing_strategy: LoadBalancingStrategy,
+
+    /// The interval at which the processor attempts to balance the partition ownership and renew
+    /// the ownership of the partitions that it is processing.
+    pub load_balancing_update_interval: StdDuration,
+
+    /// The interval after which a partition ownership that has not been renewed is considered to
+    /// be expired and may be claimed by another processor. This should be longer than
+    /// `load_balancing_update_interval`.
+    pub partition_ownership_expiration_interval: StdDuration,
+
+    /// The position to start reading from when a partition is claimed and there is no other
+    /// information about where processing left off.
+    pub default_starting_position: EventPosition,
+
+    /// The number of events that will be eagerly requested from the Event Hubs service and queued
+    /// locally without regard to whether a read operation is currently active, intended to help
+    /// maximize throughput by allowing events to be read from from a local cache rather than
+    /// waiting on a service request.
+    pub prefetch_count: u32,
+
+    /// Indicates whether or not the processor should request information on the last enqueued
+    /// event on the partition associated with a given event, and track that information as events
+    /// are read.
+    pub track_last_enqueued_event_properties: bool,
+}
+
+impl Default for EventProcessorOptions {
+    fn default() -> Self {
+        Self {
+            connection_options: Default::default(),
+            retry_options: Default::default(),
+            identifier: None,
+            load_balancing_strategy: LoadBalancingStrategy::default(),
+            load_balancing_update_interval: DEFAULT_LOAD_BALANCING_UPDATE_INTERVAL,
+            partition_ownership_expiration_interval:
+                DEFAULT_PARTITION_OWNERSHIP_EXPIRATION_INTERVAL,
+            default_starting_position: EventPosition::earliest(),
+            prefetch_count: DEFAULT_PREFETCH_COUNT,
+            track_last_enqueued_event_properties: false,
+        }
+    }
+}
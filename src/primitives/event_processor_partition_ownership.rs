@@ -0,0 +1,75 @@
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// The set of information describing the ownership of a partition by an event processor, as
+/// persisted in a [`PartitionOwnershipStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EventProcessorPartitionOwnership {
+    /// The fully qualified Event Hubs namespace that the ownership is associated with.
+    pub fully_qualified_namespace: String,
+
+    /// The name of the specific Event Hub that the ownership is associated with, relative to the
+    /// Event Hubs namespace that contains it.
+    pub event_hub_name: String,
+
+    /// The name of the consumer group that the ownership is associated with.
+    pub consumer_group: String,
+
+    /// The identifier of the Event Hub partition that the ownership is associated with.
+    pub partition_id: String,
+
+    /// The identifier of the event processor that owns the partition. An empty identifier
+    /// indicates that the ownership has been relinquished.
+    pub owner_identifier: String,
+
+    /// The date and time, in UTC, that the ownership was last modified. This is populated by the
+    /// store when the ownership is claimed.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_modified_time: Option<OffsetDateTime>,
+
+    /// The entity tag needed to update the ownership. This is populated by the store and is used
+    /// for optimistic concurrency when claiming ownership.
+    pub version: Option<String>,
+}
+
+impl EventProcessorPartitionOwnership {
+    /// Returns `true` if the ownership is held by a processor and has been modified within the
+    /// `expiration` interval.
+    pub(crate) fn is_active(&self, now: OffsetDateTime, expiration: std::time::Duration) -> bool {
+        if self.owner_identifier.is_empty() {
+            return false;
+        }
+
+        match self.last_modified_time {
+            Some(last_modified_time) => last_modified_time + expiration > now,
+            None => false,
+        }
+    }
+}
+
+/// A storage for partition ownership that is shared by all the event processors in the same
+/// consumer group, allowing them to coordinate which processor reads from which partition.
+///
+/// Claiming ownership must be performed with optimistic concurrency. A claim should only succeed
+/// if the [`EventProcessorPartitionOwnership::version`] matches the version currently persisted
+/// in the store, or if there is no ownership persisted for the partition and the version is
+/// `None`.
+pub trait PartitionOwnershipStore {
+    /// Requests a list of the ownership assignments for partitions between each of the cooperating
+    /// event processor instances for a given Event Hub and consumer group pairing.
+    fn list_ownership(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> impl Future<Output = Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error>> + Send;
+
+    /// Attempts to claim ownership of the specified partitions for processing. Only the claims
+    /// that succeeded are returned, with the `last_modified_time` and `version` updated.
+    fn claim_ownership(
+        &self,
+        desired_ownership: Vec<EventProcessorPartitionOwnership>,
+    ) -> impl Future<Output = Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error>> + Send;
+}
@@ -0,0 +1,319 @@
+use std::{
+    io::ErrorKind as IoErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use azure_core::error::ErrorKind;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::Mutex;
+
+use crate::util::time::now_utc;
+
+use super::{
+    CheckpointStore, EventProcessorCheckpoint, EventProcessorPartitionOwnership,
+    PartitionOwnershipStore,
+};
+
+const OWNERSHIP_DIR: &str = "ownership";
+const CHECKPOINT_DIR: &str = "checkpoint";
+const FILE_EXTENSION: &str = "json";
+
+/// A [`CheckpointStore`] that persists the ownership and checkpoints as JSON files on the local
+/// file system.
+///
+/// Each record is stored in its own file under
+/// `<root>/<namespace>/<event hub>/<consumer group>/{ownership,checkpoint}/<partition id>.json`,
+/// where the namespace, Event Hub name and consumer group are lowercased. Files are flushed to disk
+/// and then replaced atomically so that a crash never leaves a partially written record behind.
+///
+/// Claims are serialized within the process. Processors in different processes may share the same
+/// root directory, but the version check and the write are not atomic across processes.
+#[derive(Debug, Clone)]
+pub struct FileCheckpointStore {
+    root: Arc<PathBuf>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl FileCheckpointStore {
+    /// Creates a new store rooted at the given directory. The directory is created when the first
+    /// record is written.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: Arc::new(root.into()),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// The root directory of the store
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn dir(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+        kind: &str,
+    ) -> PathBuf {
+        self.root
+            .join(fully_qualified_namespace.to_lowercase())
+            .join(event_hub_name.to_lowercase())
+            .join(consumer_group.to_lowercase())
+            .join(kind)
+    }
+
+    fn file(&self, dir: PathBuf, partition_id: &str) -> PathBuf {
+        dir.join(partition_id).with_extension(FILE_EXTENSION)
+    }
+}
+
+impl PartitionOwnershipStore for FileCheckpointStore {
+    async fn list_ownership(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error> {
+        let dir = self.dir(
+            fully_qualified_namespace,
+            event_hub_name,
+            consumer_group,
+            OWNERSHIP_DIR,
+        );
+        read_records(&dir).await
+    }
+
+    async fn claim_ownership(
+        &self,
+        desired_ownership: Vec<EventProcessorPartitionOwnership>,
+    ) -> Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error> {
+        let _guard = self.lock.lock().await;
+        let mut claimed = Vec::with_capacity(desired_ownership.len());
+        for mut ownership in desired_ownership {
+            let dir = self.dir(
+                &ownership.fully_qualified_namespace,
+                &ownership.event_hub_name,
+                &ownership.consumer_group,
+                OWNERSHIP_DIR,
+            );
+            let path = self.file(dir, &ownership.partition_id);
+            let current: Option<EventProcessorPartitionOwnership> = read_record(&path).await?;
+            let current_version = current.as_ref().and_then(|o| o.version.as_ref());
+            if current_version != ownership.version.as_ref() {
+                log::debug!(
+                    "Ownership of partition {} was modified by another processor",
+                    ownership.partition_id
+                );
+                continue;
+            }
+
+            ownership.last_modified_time = Some(now_utc());
+            ownership.version = Some(uuid::Uuid::new_v4().to_string());
+            write_record(&path, &ownership).await?;
+            claimed.push(ownership);
+        }
+        Ok(claimed)
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    async fn list_checkpoints(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<EventProcessorCheckpoint>, azure_core::Error> {
+        let dir = self.dir(
+            fully_qualified_namespace,
+            event_hub_name,
+            consumer_group,
+            CHECKPOINT_DIR,
+        );
+        read_records(&dir).await
+    }
+
+    async fn update_checkpoint(
+        &self,
+        checkpoint: EventProcessorCheckpoint,
+    ) -> Result<(), azure_core::Error> {
+        let dir = self.dir(
+            &checkpoint.fully_qualified_namespace,
+            &checkpoint.event_hub_name,
+            &checkpoint.consumer_group,
+            CHECKPOINT_DIR,
+        );
+        let path = self.file(dir, &checkpoint.partition_id);
+        write_record(&path, &checkpoint).await
+    }
+}
+
+fn io_error(error: std::io::Error) -> azure_core::Error {
+    azure_core::Error::new(ErrorKind::Io, error)
+}
+
+fn json_error(error: serde_json::Error) -> azure_core::Error {
+    azure_core::Error::new(ErrorKind::DataConversion, error)
+}
+
+async fn read_record<T>(path: &Path) -> Result<Option<T>, azure_core::Error>
+where
+    T: DeserializeOwned,
+{
+    match tokio::fs::read(path).await {
+        Ok(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(json_error),
+        Err(err) if err.kind() == IoErrorKind::NotFound => Ok(None),
+        Err(err) => Err(io_error(err)),
+    }
+}
+
+async fn read_records<T>(dir: &Path) -> Result<Vec<T>, azure_core::Error>
+where
+    T: DeserializeOwned,
+{
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == IoErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(io_error(err)),
+    };
+
+    let mut records = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(io_error)? {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(FILE_EXTENSION) {
+            continue;
+        }
+        // The file may have been removed since the directory was read
+        if let Some(record) = read_record(&path).await? {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+async fn write_record<T>(path: &Path, record: &T) -> Result<(), azure_core::Error>
+where
+    T: Serialize,
+{
+    let parent = path.parent();
+    if let Some(parent) = parent {
+        tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+    }
+
+    // Write to a temporary file first and then rename it so that readers never observe a partially
+    // written record. The contents are flushed to disk before the rename, otherwise a crash could
+    // leave the renamed file empty
+    let bytes = serde_json::to_vec_pretty(record).map_err(json_error)?;
+    let temp_path = path.with_extension(format!("{}.{}", FILE_EXTENSION, uuid::Uuid::new_v4()));
+    tokio::fs::write(&temp_path, bytes)
+        .await
+        .map_err(io_error)?;
+    let temp_file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(&temp_path)
+        .await
+        .map_err(io_error)?;
+    temp_file.sync_all().await.map_err(io_error)?;
+    drop(temp_file);
+    tokio::fs::rename(&temp_path, path).await.map_err(io_error)?;
+
+    // The rename itself is only durable once the directory entry is flushed
+    #[cfg(unix)]
+    if let Some(parent) = parent {
+        let dir = tokio::fs::File::open(parent).await.map_err(io_error)?;
+        dir.sync_all().await.map_err(io_error)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::{
+        CheckpointStore, EventProcessorCheckpoint, EventProcessorPartitionOwnership,
+        PartitionOwnershipStore,
+    };
+
+    use super::FileCheckpointStore;
+
+    struct TempDir(std::path::PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("azeventhubs-{}", uuid::Uuid::new_v4()));
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn ownership_and_checkpoints_persist_across_instances() {
+        let temp_dir = TempDir::new();
+        let store = FileCheckpointStore::new(&temp_dir.0);
+
+        let ownership = EventProcessorPartitionOwnership {
+            fully_qualified_namespace: "namespace.servicebus.windows.net".to_string(),
+            event_hub_name: "eventhub".to_string(),
+            consumer_group: "$Default".to_string(),
+            partition_id: "0".to_string(),
+            owner_identifier: "a".to_string(),
+            last_modified_time: None,
+            version: None,
+        };
+        let claimed = store
+            .claim_ownership(vec![ownership.clone()])
+            .await
+            .unwrap();
+        assert_eq!(claimed.len(), 1);
+
+        // A stale claim must fail
+        let mut stale = ownership.clone();
+        stale.owner_identifier = "b".to_string();
+        assert!(store.claim_ownership(vec![stale]).await.unwrap().is_empty());
+
+        let checkpoint = EventProcessorCheckpoint {
+            fully_qualified_namespace: "namespace.servicebus.windows.net".to_string(),
+            event_hub_name: "eventhub".to_string(),
+            consumer_group: "$Default".to_string(),
+            partition_id: "0".to_string(),
+            offset: Some(4096),
+            sequence_number: Some(42),
+        };
+        store.update_checkpoint(checkpoint.clone()).await.unwrap();
+
+        // A new store over the same directory sees the same state
+        let store = FileCheckpointStore::new(&temp_dir.0);
+        let listed = store
+            .list_ownership("namespace.servicebus.windows.net", "eventhub", "$Default")
+            .await
+            .unwrap();
+        assert_eq!(listed, claimed);
+
+        let listed = store
+            .list_checkpoints("namespace.servicebus.windows.net", "eventhub", "$Default")
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![checkpoint]);
+    }
+
+    #[tokio::test]
+    async fn listing_empty_store_returns_nothing() {
+        let temp_dir = TempDir::new();
+        let store = FileCheckpointStore::new(&temp_dir.0);
+        let ownership = store
+            .list_ownership("namespace", "eventhub", "$Default")
+            .await
+            .unwrap();
+        assert!(ownership.is_empty());
+        let checkpoints = store
+            .list_checkpoints("namespace", "eventhub", "$Default")
+            .await
+            .unwrap();
+        assert!(checkpoints.is_empty());
+    }
+}
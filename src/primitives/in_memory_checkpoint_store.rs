@@ -0,0 +1,209 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::util::time::now_utc;
+
+use super::{
+    checkpoint_store::PartitionKey, CheckpointStore, EventProcessorCheckpoint,
+    EventProcessorPartitionOwnership, PartitionOwnershipStore,
+};
+
+#[derive(Debug, Default)]
+struct Inner {
+    ownership: HashMap<PartitionKey, EventProcessorPartitionOwnership>,
+    checkpoints: HashMap<PartitionKey, EventProcessorCheckpoint>,
+}
+
+/// A [`CheckpointStore`] that keeps the ownership and checkpoints in memory.
+///
+/// The state is lost when the store is dropped, so this is mainly intended for testing or for
+/// coordinating multiple processors within the same process. Cloning the store returns a handle to
+/// the same state.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryCheckpointStore {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl InMemoryCheckpointStore {
+    /// Creates a new empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        // The state is always left consistent, so it is safe to ignore poisoning
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl PartitionOwnershipStore for InMemoryCheckpointStore {
+    async fn list_ownership(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error> {
+        let inner = self.lock();
+        Ok(inner
+            .ownership
+            .iter()
+            .filter(|(key, _)| {
+                key.matches(fully_qualified_namespace, event_hub_name, consumer_group)
+            })
+            .map(|(_, ownership)| ownership.clone())
+            .collect())
+    }
+
+    async fn claim_ownership(
+        &self,
+        desired_ownership: Vec<EventProcessorPartitionOwnership>,
+    ) -> Result<Vec<EventProcessorPartitionOwnership>, azure_core::Error> {
+        let mut inner = self.lock();
+        let mut claimed = Vec::with_capacity(desired_ownership.len());
+        for mut ownership in desired_ownership {
+            let key = PartitionKey::new(
+                &ownership.fully_qualified_namespace,
+                &ownership.event_hub_name,
+                &ownership.consumer_group,
+                &ownership.partition_id,
+            );
+            let current_version = inner.ownership.get(&key).and_then(|o| o.version.as_ref());
+            if current_version != ownership.version.as_ref() {
+                log::debug!(
+                    "Ownership of partition {} was modified by another processor",
+                    ownership.partition_id
+                );
+                continue;
+            }
+
+            ownership.last_modified_time = Some(now_utc());
+            ownership.version = Some(uuid::Uuid::new_v4().to_string());
+            inner.ownership.insert(key, ownership.clone());
+            claimed.push(ownership);
+        }
+        Ok(claimed)
+    }
+}
+
+impl CheckpointStore for InMemoryCheckpointStore {
+    async fn list_checkpoints(
+        &self,
+        fully_qualified_namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<EventProcessorCheckpoint>, azure_core::Error> {
+        let inner = self.lock();
+        Ok(inner
+            .checkpoints
+            .iter()
+            .filter(|(key, _)| {
+                key.matches(fully_qualified_namespace, event_hub_name, consumer_group)
+            })
+            .map(|(_, checkpoint)| checkpoint.clone())
+            .collect())
+    }
+
+    async fn update_checkpoint(
+        &self,
+        checkpoint: EventProcessorCheckpoint,
+    ) -> Result<(), azure_core::Error> {
+        let key = PartitionKey::new(
+            &checkpoint.fully_qualified_namespace,
+            &checkpoint.event_hub_name,
+            &checkpoint.consumer_group,
+            &checkpoint.partition_id,
+        );
+        self.lock().checkpoints.insert(key, checkpoint);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::{
+        CheckpointStore, EventProcessorCheckpoint, EventProcessorPartitionOwnership,
+        PartitionOwnershipStore,
+    };
+
+    use super::InMemoryCheckpointStore;
+
+    fn ownership(partition_id: &str, owner: &str) -> EventProcessorPartitionOwnership {
+        EventProcessorPartitionOwnership {
+            fully_qualified_namespace: "namespace".to_string(),
+            event_hub_name: "eventhub".to_string(),
+            consumer_group: "$Default".to_string(),
+            partition_id: partition_id.to_string(),
+            owner_identifier: owner.to_string(),
+            last_modified_time: None,
+            version: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn claim_ownership_with_stale_version_fails() {
+        let store = InMemoryCheckpointStore::new();
+
+        let claimed = store
+            .claim_ownership(vec![ownership("0", "a")])
+            .await
+            .unwrap();
+        assert_eq!(claimed.len(), 1);
+        assert!(claimed[0].version.is_some());
+        assert!(claimed[0].last_modified_time.is_some());
+
+        // Claiming without the current version must fail
+        let claimed_by_b = store
+            .claim_ownership(vec![ownership("0", "b")])
+            .await
+            .unwrap();
+        assert!(claimed_by_b.is_empty());
+
+        // Claiming with the current version succeeds
+        let mut steal = claimed[0].clone();
+        steal.owner_identifier = "b".to_string();
+        let claimed_by_b = store.claim_ownership(vec![steal]).await.unwrap();
+        assert_eq!(claimed_by_b.len(), 1);
+
+        // The version held by "a" is now stale
+        let renewed = store.claim_ownership(claimed).await.unwrap();
+        assert!(renewed.is_empty());
+
+        let listed = store
+            .list_ownership("NAMESPACE", "EventHub", "$default")
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].owner_identifier, "b");
+    }
+
+    #[tokio::test]
+    async fn update_checkpoint_overwrites_previous_checkpoint() {
+        let store = InMemoryCheckpointStore::new();
+        let mut checkpoint = EventProcessorCheckpoint {
+            fully_qualified_namespace: "namespace".to_string(),
+            event_hub_name: "eventhub".to_string(),
+            consumer_group: "$Default".to_string(),
+            partition_id: "0".to_string(),
+            offset: Some(0),
+            sequence_number: Some(0),
+        };
+        store.update_checkpoint(checkpoint.clone()).await.unwrap();
+        checkpoint.offset = Some(128);
+        checkpoint.sequence_number = Some(1);
+        store.update_checkpoint(checkpoint.clone()).await.unwrap();
+
+        let listed = store
+            .list_checkpoints("namespace", "eventhub", "$Default")
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![checkpoint]);
+
+        let other_group = store
+            .list_checkpoints("namespace", "eventhub", "other")
+            .await
+            .unwrap();
+        assert!(other_group.is_empty());
+    }
+}
@@ -2,17 +2,30 @@
 //!
 //! This module matches to the `Azure.Messaging.EventHubs.Primitives` namespace in the .NET SDK.
 
-// TODO: mod blob_checkpoint_store;
-// TODO: mod checkpoint_store;
-// TODO: mod event_processor;
-// TODO: mod event_processor_checkpoint;
-// TODO: mod event_processor_options;
 // TODO: mod event_processor_partition;
-// TODO: mod event_processor_partition_ownership;
 // TODO: mod pluggable_checkpoint_store_event_processor;
 
+#[cfg(feature = "blob-checkpoint-store")]
+mod blob_checkpoint_store;
+mod checkpoint_store;
+mod event_processor;
+mod event_processor_checkpoint;
+mod event_processor_options;
+mod event_processor_partition_ownership;
+mod file_checkpoint_store;
+mod in_memory_checkpoint_store;
+mod partition_load_balancer;
 mod partition_receiver;
 mod partition_receiver_options;
 
+#[cfg(feature = "blob-checkpoint-store")]
+pub use blob_checkpoint_store::*;
+pub use checkpoint_store::CheckpointStore;
+pub use event_processor::*;
+pub use event_processor_checkpoint::*;
+pub use event_processor_options::*;
+pub use event_processor_partition_ownership::*;
+pub use file_checkpoint_store::*;
+pub use in_memory_checkpoint_store::*;
 pub use partition_receiver::*;
 pub use partition_receiver_options::*;
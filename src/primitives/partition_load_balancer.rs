@@ -0,0 +1,264 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration as StdDuration,
+};
+
+use rand::seq::SliceRandom;
+use time::OffsetDateTime;
+
+use super::{EventProcessorPartitionOwnership, LoadBalancingStrategy};
+
+/// Determines which partitions an event processor should attempt to claim so that the partitions
+/// are evenly distributed among all of the active event processors in the same consumer group.
+#[derive(Debug, Clone)]
+pub(crate) struct PartitionLoadBalancer {
+    pub(crate) owner_identifier: String,
+    pub(crate) strategy: LoadBalancingStrategy,
+    pub(crate) ownership_expiration: StdDuration,
+}
+
+impl PartitionLoadBalancer {
+    pub(crate) fn new(
+        owner_identifier: String,
+        strategy: LoadBalancingStrategy,
+        ownership_expiration: StdDuration,
+    ) -> Self {
+        Self {
+            owner_identifier,
+            strategy,
+            ownership_expiration,
+        }
+    }
+
+    /// Finds the partitions that should be claimed during this load balancing cycle.
+    ///
+    /// Partitions that are unowned or whose ownership has expired are preferred. If there is no
+    /// such partition and the processor owns less than its fair share, a partition is stolen from
+    /// the processor that owns the most partitions.
+    pub(crate) fn find_partitions_to_claim(
+        &self,
+        partition_ids: &[String],
+        ownership: &[EventProcessorPartitionOwnership],
+        now: OffsetDateTime,
+    ) -> Vec<String> {
+        if partition_ids.is_empty() {
+            return Vec::new();
+        }
+
+        let known_partitions: HashSet<&str> = partition_ids.iter().map(|s| s.as_str()).collect();
+        let active_ownership: HashMap<&str, &str> = ownership
+            .iter()
+            .filter(|o| known_partitions.contains(o.partition_id.as_str()))
+            .filter(|o| o.is_active(now, self.ownership_expiration))
+            .map(|o| (o.partition_id.as_str(), o.owner_identifier.as_str()))
+            .collect();
+
+        let mut distribution: HashMap<&str, Vec<&str>> = HashMap::new();
+        distribution.insert(&self.owner_identifier, Vec::new());
+        for (partition_id, owner) in &active_ownership {
+            distribution.entry(owner).or_default().push(partition_id);
+        }
+
+        let owner_count = distribution.len();
+        let minimum_owned = partition_ids.len() / owner_count;
+        let maximum_owned = minimum_owned + 1;
+        let extra_partitions = partition_ids.len() % owner_count;
+        let owned = distribution[self.owner_identifier.as_str()].len();
+        let owners_above_minimum = distribution
+            .values()
+            .filter(|partitions| partitions.len() > minimum_owned)
+            .count();
+
+        // The processor should claim if it owns less than the minimum, or if it owns exactly the
+        // minimum and there are still extra partitions that are not yet held by any processor
+        let should_claim = owned < minimum_owned
+            || (owned == minimum_owned && owners_above_minimum < extra_partitions);
+        if !should_claim {
+            return Vec::new();
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut unclaimed: Vec<&str> = partition_ids
+            .iter()
+            .map(|s| s.as_str())
+            .filter(|id| !active_ownership.contains_key(id))
+            .collect();
+
+        if !unclaimed.is_empty() {
+            let claim_count = match self.strategy {
+                LoadBalancingStrategy::Balanced => 1,
+                LoadBalancingStrategy::Greedy => (minimum_owned - owned).max(1),
+            };
+            unclaimed.shuffle(&mut rng);
+            return unclaimed
+                .into_iter()
+                .take(claim_count)
+                .map(String::from)
+                .collect();
+        }
+
+        // Steal from the processors that own more than the maximum first. If there is no such
+        // processor and this one owns less than the minimum, steal from those that own exactly the
+        // maximum.
+        let steal_from = |threshold: usize| -> Vec<&str> {
+            distribution
+                .iter()
+                .filter(|(owner, _)| **owner != self.owner_identifier)
+                .filter(|(_, partitions)| partitions.len() >= threshold)
+                .flat_map(|(_, partitions)| partitions.iter().copied())
+                .collect()
+        };
+        let mut candidates = steal_from(maximum_owned + 1);
+        if candidates.is_empty() && owned < minimum_owned {
+            candidates = steal_from(maximum_owned);
+        }
+
+        candidates
+            .choose(&mut rng)
+            .map(|id| vec![id.to_string()])
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use time::OffsetDateTime;
+
+    use crate::primitives::{EventProcessorPartitionOwnership, LoadBalancingStrategy};
+
+    use super::PartitionLoadBalancer;
+
+    const EXPIRATION: StdDuration = StdDuration::from_secs(30);
+
+    fn partition_ids(count: usize) -> Vec<String> {
+        (0..count).map(|i| i.to_string()).collect()
+    }
+
+    fn ownership(
+        partition_id: &str,
+        owner: &str,
+        last_modified_time: OffsetDateTime,
+    ) -> EventProcessorPartitionOwnership {
+        EventProcessorPartitionOwnership {
+            fully_qualified_namespace: "namespace".to_string(),
+            event_hub_name: "eventhub".to_string(),
+            consumer_group: "$Default".to_string(),
+            partition_id: partition_id.to_string(),
+            owner_identifier: owner.to_string(),
+            last_modified_time: Some(last_modified_time),
+            version: None,
+        }
+    }
+
+    #[test]
+    fn balanced_claims_one_unowned_partition() {
+        let balancer =
+            PartitionLoadBalancer::new("a".into(), LoadBalancingStrategy::Balanced, EXPIRATION);
+        let now = OffsetDateTime::now_utc();
+        let claims = balancer.find_partitions_to_claim(&partition_ids(4), &[], now);
+        assert_eq!(claims.len(), 1);
+    }
+
+    #[test]
+    fn greedy_claims_fair_share_of_unowned_partitions() {
+        let balancer =
+            PartitionLoadBalancer::new("a".into(), LoadBalancingStrategy::Greedy, EXPIRATION);
+        let now = OffsetDateTime::now_utc();
+        let claims = balancer.find_partitions_to_claim(&partition_ids(4), &[], now);
+        assert_eq!(claims.len(), 4);
+
+        let owned = vec![ownership("0", "b", now), ownership("1", "b", now)];
+        let claims = balancer.find_partitions_to_claim(&partition_ids(4), &owned, now);
+        assert_eq!(claims.len(), 2);
+        assert!(claims.iter().all(|id| id == "2" || id == "3"));
+    }
+
+    #[test]
+    fn does_not_claim_when_balanced() {
+        let balancer =
+            PartitionLoadBalancer::new("a".into(), LoadBalancingStrategy::Balanced, EXPIRATION);
+        let now = OffsetDateTime::now_utc();
+        let owned = vec![
+            ownership("0", "a", now),
+            ownership("1", "a", now),
+            ownership("2", "b", now),
+            ownership("3", "b", now),
+        ];
+        let claims = balancer.find_partitions_to_claim(&partition_ids(4), &owned, now);
+        assert!(claims.is_empty());
+
+        // Odd number of partitions where the other processor holds the extra one
+        let owned = vec![
+            ownership("0", "a", now),
+            ownership("1", "b", now),
+            ownership("2", "b", now),
+        ];
+        let claims = balancer.find_partitions_to_claim(&partition_ids(3), &owned, now);
+        assert!(claims.is_empty());
+    }
+
+    #[test]
+    fn claims_extra_partition_when_unowned() {
+        let balancer =
+            PartitionLoadBalancer::new("a".into(), LoadBalancingStrategy::Balanced, EXPIRATION);
+        let now = OffsetDateTime::now_utc();
+        let owned = vec![ownership("0", "a", now), ownership("1", "b", now)];
+        let claims = balancer.find_partitions_to_claim(&partition_ids(3), &owned, now);
+        assert_eq!(claims, vec!["2".to_string()]);
+    }
+
+    #[test]
+    fn expired_ownership_is_treated_as_unowned() {
+        let balancer =
+            PartitionLoadBalancer::new("a".into(), LoadBalancingStrategy::Greedy, EXPIRATION);
+        let now = OffsetDateTime::now_utc();
+        let expired = now - StdDuration::from_secs(60);
+        let owned = vec![ownership("0", "b", expired), ownership("1", "b", now)];
+        let claims = balancer.find_partitions_to_claim(&partition_ids(2), &owned, now);
+        assert_eq!(claims, vec!["0".to_string()]);
+    }
+
+    #[test]
+    fn relinquished_ownership_is_treated_as_unowned() {
+        let balancer =
+            PartitionLoadBalancer::new("a".into(), LoadBalancingStrategy::Balanced, EXPIRATION);
+        let now = OffsetDateTime::now_utc();
+        let owned = vec![ownership("0", "", now), ownership("1", "b", now)];
+        let claims = balancer.find_partitions_to_claim(&partition_ids(2), &owned, now);
+        assert_eq!(claims, vec!["0".to_string()]);
+    }
+
+    #[test]
+    fn steals_from_overloaded_processor() {
+        let balancer =
+            PartitionLoadBalancer::new("a".into(), LoadBalancingStrategy::Balanced, EXPIRATION);
+        let now = OffsetDateTime::now_utc();
+        let owned: Vec<_> = partition_ids(4)
+            .iter()
+            .map(|id| ownership(id, "b", now))
+            .collect();
+        let claims = balancer.find_partitions_to_claim(&partition_ids(4), &owned, now);
+        assert_eq!(claims.len(), 1);
+    }
+
+    #[test]
+    fn steals_from_processor_with_maximum_when_below_minimum() {
+        let balancer =
+            PartitionLoadBalancer::new("a".into(), LoadBalancingStrategy::Balanced, EXPIRATION);
+        let now = OffsetDateTime::now_utc();
+        // 5 partitions across 3 processors: minimum is 1, maximum is 2
+        let owned = vec![
+            ownership("0", "b", now),
+            ownership("1", "b", now),
+            ownership("2", "c", now),
+            ownership("3", "c", now),
+            ownership("4", "c", now),
+        ];
+        let claims = balancer.find_partitions_to_claim(&partition_ids(5), &owned, now);
+        assert_eq!(claims.len(), 1);
+        // "c" owns more than the maximum, so it should be the one to lose a partition
+        assert!(["2", "3", "4"].contains(&claims[0].as_str()));
+    }
+}
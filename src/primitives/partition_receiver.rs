@@ -1,11 +1,11 @@
 use std::{collections::VecDeque, marker::PhantomData, time::Duration as StdDuration};
 
 use crate::{
-    amqp::amqp_consumer::{single::receive_event_batch, AmqpConsumer},
     authorization::event_hub_token_credential::EventHubTokenCredential,
-    consumer::EventPosition,
+    consumer::{EventPosition, LastEnqueuedEventProperties},
     core::BasicRetryPolicy,
     event_hubs_retry_policy::EventHubsRetryPolicy,
+    transport_kind::TransportConsumerKind,
     EventHubConnection, EventHubsRetryOptions, ReceivedEventData,
 };
 
@@ -17,8 +17,9 @@ use super::partition_receiver_options::PartitionReceiverOptions;
 #[derive(Debug)]
 pub struct PartitionReceiver<RP> {
     connection: EventHubConnection,
-    inner_consumer: AmqpConsumer<RP>,
+    inner_consumer: TransportConsumerKind<RP>,
     options: PartitionReceiverOptions,
+    last_enqueued_event_properties: LastEnqueuedEventProperties,
 }
 
 /// A builder for a [`PartitionReceiver`].
@@ -191,6 +192,7 @@ where
             connection,
             inner_consumer,
             options,
+            last_enqueued_event_properties: LastEnqueuedEventProperties::default(),
         })
     }
 }
@@ -209,18 +211,30 @@ where
         let mut buffer = VecDeque::with_capacity(max_event_count);
         let max_wait_time = max_wait_time.into();
         let max_wait_time = max_wait_time.map(|t| t.max(self.options.maximum_receive_wait_time));
-        receive_event_batch(
-            &mut self.connection.inner,
-            &mut self.inner_consumer,
-            &mut buffer,
-            max_wait_time,
-        )
-        .await?;
+        self.inner_consumer
+            .receive_event_batch(&mut self.connection.inner, &mut buffer, max_wait_time)
+            .await?;
+
+        let last_properties = buffer
+            .iter()
+            .rev()
+            .map(LastEnqueuedEventProperties::from_event)
+            .find(|properties| !properties.is_empty());
+        if let Some(properties) = last_properties {
+            self.last_enqueued_event_properties = properties;
+        }
         Ok(buffer.into_iter())
     }
 }
 
 impl<RP> PartitionReceiver<RP> {
+    /// A set of information about the last event enqueued in the partition, as observed from the
+    /// events received by this receiver. This is only available if the receiver was created with
+    /// [`PartitionReceiverOptions::track_last_enqueued_event_properties`] set.
+    pub fn read_last_enqueued_event_properties(&self) -> LastEnqueuedEventProperties {
+        self.last_enqueued_event_properties
+    }
+
     /// Closes the [`PartitionReceiver`].
     pub async fn close(self) -> Result<(), azure_core::Error> {
         self.inner_consumer.close().await?;
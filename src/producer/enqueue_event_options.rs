@@ -0,0 +1,49 @@
+use super::Partition;
+
+/// The set of options that can be specified when buffering events for publishing with the
+/// [`EventHubBufferedProducerClient`](super::EventHubBufferedProducerClient).
+#[derive(Debug, Clone, Default)]
+pub struct EnqueueEventOptions {
+    /// If specified, events be published according to this partitioning strategy. Otherwise, the
+    /// events are distributed among the available partitions in a round-robin manner.
+    pub partition: Option<Partition>,
+}
+
+impl EnqueueEventOptions {
+    /// Create a new instance of [`EnqueueEventOptions`] with default values
+    ///
+    /// # Default Value
+    ///
+    /// - `partition`: `None`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the `partition` field to `Some(Partition::Key(key))`
+    pub fn with_partition_key(mut self, partition_key: impl Into<String>) -> Self {
+        self.partition = Some(Partition::Key(partition_key.into()));
+        self
+    }
+
+    /// Set the `partition` field to `Some(Partition::Id(id))`
+    pub fn with_partition_id(mut self, partition_id: impl Into<String>) -> Self {
+        self.partition = Some(Partition::Id(partition_id.into()));
+        self
+    }
+
+    /// Get a reference to the partition key, if specified.
+    pub fn partition_key(&self) -> Option<&str> {
+        match &self.partition {
+            Some(Partition::Key(key)) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Get a reference to the partition id, if specified.
+    pub fn partition_id(&self) -> Option<&str> {
+        match &self.partition {
+            Some(Partition::Id(id)) => Some(id),
+            _ => None,
+        }
+    }
+}
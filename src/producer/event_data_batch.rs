@@ -1,4 +1,7 @@
-use fe2o3_amqp_types::messaging::{Data, Message};
+use fe2o3_amqp_types::{
+    messaging::{Body, Message},
+    primitives::Value,
+};
 
 use crate::{
     amqp::{amqp_event_batch::AmqpEventBatch, error::TryAddError},
@@ -43,7 +46,7 @@ impl EventDataBatch {
     }
 
     /// Iterate over the messages in the batch.
-    pub fn iter(&self) -> std::slice::Iter<'_, Message<Data>> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Message<Body<Value>>> {
         self.inner.iter()
     }
 
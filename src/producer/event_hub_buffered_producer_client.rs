@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use azure_core::error::ErrorKind;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+
+use crate::{
+    event_hubs_retry_policy::EventHubsRetryPolicy, util::time::Instant, AmqpAnnotatedMessage,
+    EventData, EventHubsRetryOptions,
+};
+
+use super::{
+    CreateBatchOptions, EnqueueEventOptions, EventDataBatch, EventHubBufferedProducerClientOptions,
+    EventHubProducerClient, Partition, SendEventOptions, TryAddError,
+};
+
+const BUFFERED_PRODUCER_COMMAND_QUEUE_SIZE: usize = 128;
+
+/// The set of information passed to the handler that is invoked when a batch of buffered events
+/// has been successfully published.
+#[derive(Debug)]
+pub struct SendEventBatchSucceededEventArgs {
+    /// The events that were published
+    pub events: Vec<EventData>,
+
+    /// The partition, or the partition key, that the events were published to
+    pub partition: Partition,
+}
+
+/// The set of information passed to the handler that is invoked when a batch of buffered events
+/// could not be published.
+#[derive(Debug)]
+pub struct SendEventBatchFailedEventArgs {
+    /// The events that could not be published
+    pub events: Vec<EventData>,
+
+    /// The partition, or the partition key, that the events were to be published to
+    pub partition: Partition,
+
+    /// The error that caused the failure
+    pub error: azure_core::Error,
+}
+
+enum Command {
+    Enqueue {
+        events: Vec<EventData>,
+        options: EnqueueEventOptions,
+        result_sender: oneshot::Sender<Result<usize, azure_core::Error>>,
+    },
+    Flush {
+        result_sender: oneshot::Sender<()>,
+    },
+}
+
+/// A client responsible for publishing instances of [`EventData`] to a specific Event Hub.
+/// Depending on the options specified when events are enqueued, they may be automatically
+/// assigned to a partition, grouped according to the specified partition key, or assigned a
+/// specifically requested partition.
+///
+/// The [`EventHubBufferedProducerClient`] does not publish immediately, instead using a deferred
+/// model where events are collected into a buffer so that they may be efficiently batched and
+/// published when the batch is full, when the
+/// [`maximum_wait_time`](EventHubBufferedProducerClientOptions::maximum_wait_time) has elapsed, or
+/// when the client is flushed or closed. The outcome of each batch is reported through the
+/// handlers given when the client is created.
+///
+/// Events enqueued without a partition are distributed among the partitions in a round-robin
+/// manner.
+///
+/// The events are buffered and published by a background task, so the client must be created
+/// within a tokio runtime. Events that are still buffered when the client is dropped are
+/// published in the background; use [`close`](Self::close) to wait for them to be published.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use azeventhubs::producer::{
+///     EnqueueEventOptions, EventHubBufferedProducerClient, EventHubBufferedProducerClientOptions,
+///     EventHubProducerClient, EventHubProducerClientOptions,
+/// };
+///
+/// # async fn example() -> Result<(), azure_core::Error> {
+/// let producer = EventHubProducerClient::new_from_connection_string(
+///     "<CONNECTION_STRING>",
+///     String::from("<EVENT_HUB_NAME>"),
+///     EventHubProducerClientOptions::default(),
+/// )
+/// .await?;
+///
+/// let client = EventHubBufferedProducerClient::new(
+///     producer,
+///     EventHubBufferedProducerClientOptions::default(),
+///     |args| println!("Published {} events", args.events.len()),
+///     |args| eprintln!("Failed to publish {} events: {}", args.events.len(), args.error),
+/// );
+///
+/// client.enqueue_event("Hello, world!", EnqueueEventOptions::default()).await?;
+/// client.close().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct EventHubBufferedProducerClient {
+    command_sender: mpsc::Sender<Command>,
+    join_handle: JoinHandle<Result<(), azure_core::Error>>,
+}
+
+impl std::fmt::Debug for EventHubBufferedProducerClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventHubBufferedProducerClient").finish()
+    }
+}
+
+impl EventHubBufferedProducerClient {
+    /// Creates a new buffered client that publishes the events with the given
+    /// [`EventHubProducerClient`].
+    ///
+    /// `on_send_succeeded` is invoked for every batch that has been published, and
+    /// `on_send_failed` for every batch that could not be published. The handlers are invoked on
+    /// the background task and should return quickly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn new<RP, S, F>(
+        producer: EventHubProducerClient<RP>,
+        options: EventHubBufferedProducerClientOptions,
+        on_send_succeeded: S,
+        on_send_failed: F,
+    ) -> Self
+    where
+        RP: EventHubsRetryPolicy + From<EventHubsRetryOptions> + Send + 'static,
+        S: FnMut(SendEventBatchSucceededEventArgs) + Send + 'static,
+        F: FnMut(SendEventBatchFailedEventArgs) + Send + 'static,
+    {
+        let (command_sender, commands) = mpsc::channel(BUFFERED_PRODUCER_COMMAND_QUEUE_SIZE);
+        let publisher = BufferedPublisher {
+            producer,
+            options,
+            on_send_succeeded,
+            on_send_failed,
+            buffers: HashMap::new(),
+            partition_ids: None,
+            next_partition_index: 0,
+        };
+        let join_handle = tokio::spawn(publisher.event_loop(commands));
+
+        Self {
+            command_sender,
+            join_handle,
+        }
+    }
+
+    /// Enqueues an event into the buffer to be published to the Event Hub.
+    ///
+    /// Returns the total number of events that are currently buffered and waiting to be published
+    /// across all partitions.
+    pub async fn enqueue_event(
+        &self,
+        event: impl Into<EventData>,
+        options: EnqueueEventOptions,
+    ) -> Result<usize, azure_core::Error> {
+        self.enqueue_events(std::iter::once(event.into()), options)
+            .await
+    }
+
+    /// Enqueues a set of events into the buffer to be published to the Event Hub.
+    ///
+    /// Returns the total number of events that are currently buffered and waiting to be published
+    /// across all partitions. If an error is returned, the events that precede the one that
+    /// failed remain enqueued.
+    pub async fn enqueue_events<E>(
+        &self,
+        events: E,
+        options: EnqueueEventOptions,
+    ) -> Result<usize, azure_core::Error>
+    where
+        E: IntoIterator<Item = EventData>,
+    {
+        let (result_sender, result) = oneshot::channel();
+        let command = Command::Enqueue {
+            events: events.into_iter().collect(),
+            options,
+            result_sender,
+        };
+        self.command_sender
+            .send(command)
+            .await
+            .map_err(|_| publisher_stopped())?;
+        result.await.map_err(|_| publisher_stopped())?
+    }
+
+    /// Attempts to publish all events in the buffer immediately. The outcome of each batch is
+    /// reported through the handlers, and this returns once all the buffered events have been
+    /// attempted.
+    pub async fn flush(&self) -> Result<(), azure_core::Error> {
+        let (result_sender, result) = oneshot::channel();
+        self.command_sender
+            .send(Command::Flush { result_sender })
+            .await
+            .map_err(|_| publisher_stopped())?;
+        result.await.map_err(|_| publisher_stopped())
+    }
+
+    /// Publishes all the events remaining in the buffer and closes the underlying
+    /// [`EventHubProducerClient`].
+    pub async fn close(self) -> Result<(), azure_core::Error> {
+        // The background task flushes and stops once all the senders have been dropped
+        drop(self.command_sender);
+        match self.join_handle.await {
+            Ok(result) => result,
+            Err(err) => Err(azure_core::Error::new(ErrorKind::Other, err)),
+        }
+    }
+}
+
+fn publisher_stopped() -> azure_core::Error {
+    azure_core::Error::message(
+        ErrorKind::Other,
+        "The background task of the buffered producer has stopped",
+    )
+}
+
+/// The events buffered for a single partition or partition key
+struct PartitionBuffer {
+    /// The events are only held by the batch, and are copied out of it when it is published
+    batch: EventDataBatch,
+    deadline: Option<Instant>,
+}
+
+struct BufferedPublisher<RP, S, F> {
+    producer: EventHubProducerClient<RP>,
+    options: EventHubBufferedProducerClientOptions,
+    on_send_succeeded: S,
+    on_send_failed: F,
+    buffers: HashMap<Partition, PartitionBuffer>,
+    partition_ids: Option<Vec<String>>,
+    next_partition_index: usize,
+}
+
+impl<RP, S, F> BufferedPublisher<RP, S, F>
+where
+    RP: EventHubsRetryPolicy + From<EventHubsRetryOptions> + Send + 'static,
+    S: FnMut(SendEventBatchSucceededEventArgs) + Send + 'static,
+    F: FnMut(SendEventBatchFailedEventArgs) + Send + 'static,
+{
+    async fn event_loop(
+        mut self,
+        mut commands: mpsc::Receiver<Command>,
+    ) -> Result<(), azure_core::Error> {
+        loop {
+            let deadline = self.buffers.values().filter_map(|b| b.deadline).min();
+            tokio::select! {
+                command = commands.recv() => match command {
+                    Some(Command::Enqueue { events, options, result_sender }) => {
+                        let result = self.enqueue(events, options).await;
+                        let _ = result_sender.send(result);
+                    }
+                    Some(Command::Flush { result_sender }) => {
+                        self.flush().await;
+                        let _ = result_sender.send(());
+                    }
+                    // All the handles have been dropped
+                    None => break,
+                },
+                _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    self.publish_expired().await;
+                }
+            }
+        }
+
+        self.flush().await;
+        self.producer.close().await
+    }
+
+    fn buffered_event_count(&self) -> usize {
+        self.buffers.values().map(|b| b.batch.len()).sum()
+    }
+
+    async fn next_partition_id(&mut self) -> Result<String, azure_core::Error> {
+        if self.partition_ids.is_none() {
+            self.partition_ids = Some(self.producer.get_partition_ids().await?);
+        }
+
+        let partition_ids = self.partition_ids.as_deref().unwrap_or_default();
+        if partition_ids.is_empty() {
+            return Err(azure_core::Error::message(
+                ErrorKind::Other,
+                "The Event Hub does not have any partition",
+            ));
+        }
+        let partition_id = partition_ids[self.next_partition_index % partition_ids.len()].clone();
+        self.next_partition_index = self.next_partition_index.wrapping_add(1);
+        Ok(partition_id)
+    }
+
+    async fn enqueue(
+        &mut self,
+        events: Vec<EventData>,
+        options: EnqueueEventOptions,
+    ) -> Result<usize, azure_core::Error> {
+        for event in events {
+            let partition = match &options.partition {
+                Some(partition) => partition.clone(),
+                None => Partition::Id(self.next_partition_id().await?),
+            };
+            self.enqueue_to(partition, event).await?;
+        }
+        Ok(self.buffered_event_count())
+    }
+
+    async fn enqueue_to(
+        &mut self,
+        partition: Partition,
+        mut event: EventData,
+    ) -> Result<(), azure_core::Error> {
+        loop {
+            if !self.buffers.contains_key(&partition) {
+                let batch_options = match &partition {
+                    Partition::Key(key) => CreateBatchOptions::new().with_partition_key(key),
+                    Partition::Id(_) => CreateBatchOptions::new(),
+                };
+                let batch = self.producer.create_batch(batch_options).await?;
+                let deadline = self
+                    .options
+                    .maximum_wait_time
+                    .map(|wait_time| Instant::now() + wait_time);
+                let buffer = PartitionBuffer { batch, deadline };
+                self.buffers.insert(partition.clone(), buffer);
+            }
+
+            // This is safe because the buffer is inserted above if it does not exist
+            let buffer = self.buffers.get_mut(&partition).unwrap();
+            match buffer.batch.try_add(event) {
+                Ok(()) => {
+                    if buffer.batch.len() >= self.options.maximum_event_buffer_length_per_partition
+                    {
+                        self.publish(&partition).await;
+                    }
+                    return Ok(());
+                }
+                // The batch is full, so send it and try again with a new batch
+                Err(TryAddError::BatchFull(returned)) if !buffer.batch.is_empty() => {
+                    event = returned;
+                    self.publish(&partition).await;
+                }
+                // The event does not fit in an empty batch or cannot be serialized
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    async fn publish_expired(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<_> = self
+            .buffers
+            .iter()
+            .filter(|(_, buffer)| buffer.deadline.is_some_and(|deadline| deadline <= now))
+            .map(|(partition, _)| partition.clone())
+            .collect();
+        for partition in expired {
+            self.publish(&partition).await;
+        }
+    }
+
+    async fn flush(&mut self) {
+        let partitions: Vec<_> = self.buffers.keys().cloned().collect();
+        for partition in partitions {
+            self.publish(&partition).await;
+        }
+    }
+
+    async fn publish(&mut self, partition: &Partition) {
+        let buffer = match self.buffers.remove(partition) {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        if buffer.batch.is_empty() {
+            return;
+        }
+
+        // The batch is consumed by the send, so the events that are reported to the handlers are
+        // copied out of it first
+        let events: Vec<EventData> = buffer
+            .batch
+            .iter()
+            .map(|message| EventData::from(AmqpAnnotatedMessage::from(message.clone())))
+            .collect();
+        let options = SendEventOptions {
+            partition: Some(partition.clone()),
+        };
+        match self.producer.send_batch(buffer.batch, options).await {
+            Ok(()) => (self.on_send_succeeded)(SendEventBatchSucceededEventArgs {
+                events,
+                partition: partition.clone(),
+            }),
+            Err(error) => {
+                log::error!("Failed to publish buffered events: {}", error);
+                (self.on_send_failed)(SendEventBatchFailedEventArgs {
+                    events,
+                    partition: partition.clone(),
+                    error,
+                })
+            }
+        }
+    }
+}
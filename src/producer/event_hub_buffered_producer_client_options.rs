@@ -0,0 +1,59 @@
+use std::time::Duration as StdDuration;
+
+/// The default amount of time to wait for a batch to be filled before it is sent
+pub const DEFAULT_MAXIMUM_WAIT_TIME: StdDuration = StdDuration::from_secs(1);
+
+/// The default maximum number of events that can be buffered for a single partition
+pub const DEFAULT_MAXIMUM_EVENT_BUFFER_LENGTH_PER_PARTITION: usize = 1500;
+
+/// The set of options that can be specified when creating an
+/// [`EventHubBufferedProducerClient`](super::EventHubBufferedProducerClient).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EventHubBufferedProducerClientOptions {
+    /// The amount of time to wait for a batch to be filled before it is sent, measured from the
+    /// time the first event of the batch was enqueued. If `None`, a batch is only sent when it is
+    /// full, when the buffer limit is reached, or when the client is flushed.
+    pub maximum_wait_time: Option<StdDuration>,
+
+    /// The maximum number of events that can be buffered for a single partition before they are
+    /// sent, even if the batch is not full yet.
+    pub maximum_event_buffer_length_per_partition: usize,
+}
+
+impl Default for EventHubBufferedProducerClientOptions {
+    fn default() -> Self {
+        Self {
+            maximum_wait_time: Some(DEFAULT_MAXIMUM_WAIT_TIME),
+            maximum_event_buffer_length_per_partition:
+                DEFAULT_MAXIMUM_EVENT_BUFFER_LENGTH_PER_PARTITION,
+        }
+    }
+}
+
+impl EventHubBufferedProducerClientOptions {
+    /// Create a new instance of [`EventHubBufferedProducerClientOptions`] with default values
+    ///
+    /// # Default Value
+    ///
+    /// - `maximum_wait_time`: `Some(DEFAULT_MAXIMUM_WAIT_TIME)`
+    /// - `maximum_event_buffer_length_per_partition`:
+    ///   `DEFAULT_MAXIMUM_EVENT_BUFFER_LENGTH_PER_PARTITION`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the `maximum_wait_time` field
+    pub fn with_maximum_wait_time(
+        mut self,
+        maximum_wait_time: impl Into<Option<StdDuration>>,
+    ) -> Self {
+        self.maximum_wait_time = maximum_wait_time.into();
+        self
+    }
+
+    /// Set the `maximum_event_buffer_length_per_partition` field
+    pub fn with_maximum_event_buffer_length_per_partition(mut self, length: usize) -> Self {
+        self.maximum_event_buffer_length_per_partition = length;
+        self
+    }
+}
@@ -1,20 +1,23 @@
 use std::{collections::HashMap, marker::PhantomData};
 
+use azure_core::error::ErrorKind;
+
 use crate::{
-    amqp::amqp_producer::{AmqpProducer, RecoverableAmqpProducer},
     authorization::{
         event_hub_token_credential::EventHubTokenCredential, AzureNamedKeyCredential,
         AzureSasCredential,
     },
-    core::{BasicRetryPolicy, TransportProducer},
+    core::{BasicRetryPolicy, TransportProducer, TransportProducerFeatures},
     event_hubs_properties::EventHubProperties,
     event_hubs_retry_policy::EventHubsRetryPolicy,
+    transport_kind::{RecoverableTransportProducer, TransportProducerKind},
     EventData, EventHubConnection, EventHubsRetryOptions, PartitionProperties,
 };
 
 use super::{
     create_batch_options::CreateBatchOptions, event_data_batch::EventDataBatch,
     event_hub_producer_client_options::EventHubProducerClientOptions,
+    partition_publishing_properties::PartitionPublishingProperties,
     send_event_options::SendEventOptions,
 };
 
@@ -33,8 +36,8 @@ pub struct EventHubProducerClient<RP> {
     connection: EventHubConnection,
     /// An abstracted Event Hub transport-specific producer that is associated with the
     /// Event Hub gateway rather than a specific partition; intended to perform delegated operations.
-    gateway_producer: Option<AmqpProducer<RP>>,
-    producer_pool: HashMap<String, AmqpProducer<RP>>,
+    gateway_producer: Option<TransportProducerKind<RP>>,
+    producer_pool: HashMap<String, TransportProducerKind<RP>>,
     options: EventHubProducerClientOptions,
     retry_policy_marker: PhantomData<RP>,
 }
@@ -264,14 +267,16 @@ where
 {
     async fn get_or_create_gateway_producer_mut(
         &mut self,
-    ) -> Result<RecoverableAmqpProducer<'_, RP>, azure_core::Error> {
+    ) -> Result<RecoverableTransportProducer<'_, RP>, azure_core::Error> {
         let producer_identifier = Some(
             self.options
                 .identifier
                 .clone()
                 .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
         );
-        let requested_features = self.options.create_features();
+        // The gateway does not support idempotent publishing, and it is only used to create batches
+        // and to send events that are not published directly to a partition
+        let requested_features = TransportProducerFeatures::None;
         let retry_policy = RP::from(self.options.retry_options.clone());
 
         if self.gateway_producer.is_none() {
@@ -294,13 +299,13 @@ where
 
         let producer = self.gateway_producer.as_mut().unwrap();
         let client = &mut self.connection.inner;
-        Ok(RecoverableAmqpProducer::new(producer, client))
+        Ok(RecoverableTransportProducer::new(producer, client))
     }
 
     async fn get_pooled_producer_mut(
         &mut self,
         partition_id: Option<&str>,
-    ) -> Result<RecoverableAmqpProducer<'_, RP>, azure_core::Error> {
+    ) -> Result<RecoverableTransportProducer<'_, RP>, azure_core::Error> {
         let producer_identifier = Some(
             self.options
                 .identifier
@@ -336,21 +341,33 @@ where
                 // This is safe because we just checked that the key exists.
                 let producer = self.producer_pool.get_mut(partition_id).unwrap();
                 let client = &mut self.connection.inner;
-                Ok(RecoverableAmqpProducer::new(producer, client))
+                Ok(RecoverableTransportProducer::new(producer, client))
             }
             None => self.get_or_create_gateway_producer_mut().await,
         }
     }
 
+    fn validate_send_options(&self, options: &SendEventOptions) -> Result<(), azure_core::Error> {
+        let has_partition_id = options.partition_id().is_some_and(|id| !id.is_empty());
+        if self.options.enable_idempotent_partitions && !has_partition_id {
+            return Err(azure_core::Error::message(
+                ErrorKind::Other,
+                "A partition identifier must be specified when idempotent publishing is enabled",
+            ));
+        }
+        Ok(())
+    }
+
     /// Creates a new [`EventDataBatch`] with the given options.
     pub async fn create_batch(
         &mut self,
         options: CreateBatchOptions,
     ) -> Result<EventDataBatch, azure_core::Error> {
+        let reserve_idempotent_annotations = self.options.enable_idempotent_partitions;
         let inner = self
             .get_or_create_gateway_producer_mut()
             .await?
-            .create_batch(options)?;
+            .create_batch(options, reserve_idempotent_annotations)?;
         Ok(EventDataBatch { inner })
     }
 
@@ -374,12 +391,10 @@ where
         E: IntoIterator<Item = EventData>,
         E::IntoIter: ExactSizeIterator + Send,
     {
+        self.validate_send_options(&options)?;
         let partition_id = options.partition_id();
         let mut producer = self.get_pooled_producer_mut(partition_id).await?;
-        producer
-            .send(events.into_iter(), options)
-            .await
-            .map_err(Into::into)
+        producer.send(events.into_iter(), options).await
     }
 
     /// Sends a batch of events to the Event Hub.
@@ -388,12 +403,10 @@ where
         batch: EventDataBatch,
         options: SendEventOptions,
     ) -> Result<(), azure_core::Error> {
+        self.validate_send_options(&options)?;
         let partition_id = options.partition_id();
         let mut producer = self.get_pooled_producer_mut(partition_id).await?;
-        producer
-            .send_batch(batch.inner, options)
-            .await
-            .map_err(Into::into)
+        producer.send_batch(batch.inner, options).await
     }
 
     /// Retrieves information about the Event Hub that the connection is associated with, including
@@ -424,6 +437,36 @@ where
             .await
     }
 
+    /// Retrieves the publishing properties that the Event Hubs service assigned to this client for
+    /// a specific partition, including the producer group, the owner level and the sequence number
+    /// of the last event published by the producer group.
+    ///
+    /// The producer for the partition is created if it does not exist yet. If idempotent publishing
+    /// is not enabled, no producer is created and empty properties are returned.
+    pub async fn get_partition_publishing_properties(
+        &mut self,
+        partition_id: &str,
+    ) -> Result<PartitionPublishingProperties, azure_core::Error> {
+        if partition_id.is_empty() {
+            return Err(azure_core::Error::message(
+                ErrorKind::Other,
+                "A partition identifier must be specified",
+            ));
+        }
+
+        if !self.options.enable_idempotent_partitions {
+            return Ok(PartitionPublishingProperties::default());
+        }
+
+        // Make sure that the producer for the partition has been attached
+        self.get_pooled_producer_mut(Some(partition_id)).await?;
+        Ok(self
+            .producer_pool
+            .get(partition_id)
+            .map(|producer| producer.initialized_partition_properties().clone())
+            .unwrap_or_default())
+    }
+
     /// Performs the task needed to clean up resources used by the [`EventHubProducerClient`],
     /// including ensuring that the client itself has been closed.
     ///
@@ -432,7 +475,7 @@ where
     pub async fn close(self) -> Result<(), azure_core::Error> {
         let mut result = Ok(());
         for (_, producer) in self.producer_pool {
-            let res = producer.close().await.map_err(Into::into);
+            let res = producer.close().await;
             result = result.and(res);
         }
 
@@ -440,3 +483,53 @@ where
         result.and(res)
     }
 }
+
+#[cfg(all(test, feature = "in-memory-transport"))]
+mod tests {
+    use crate::{
+        in_memory::fixture::TestEventHub,
+        producer::{EventHubProducerClientOptions, PartitionPublishingOptions, SendEventOptions},
+        EventData,
+    };
+
+    #[tokio::test]
+    async fn idempotent_producer_resumes_sequence_of_producer_group() {
+        let mut test_hub = TestEventHub::new(1);
+        let mut producer = test_hub.producer_with_options(EventHubProducerClientOptions {
+            enable_idempotent_partitions: true,
+            ..Default::default()
+        });
+
+        let properties = producer
+            .get_partition_publishing_properties("0")
+            .await
+            .unwrap();
+        assert!(properties.is_idempotent_publishing_enabled());
+        assert_eq!(properties.last_published_sequence_number(), None);
+        let producer_group_id = properties.producer_group_id().unwrap();
+
+        let options = SendEventOptions::new().with_partition_id("0");
+        let events = ["a", "b"].map(EventData::from);
+        producer.send_events(events, options.clone()).await.unwrap();
+        producer.send_event("c", options).await.unwrap();
+        producer.close().await.unwrap();
+
+        // The Event Hub reports the last sequence number published by the producer group
+        let partition_options = PartitionPublishingOptions {
+            producer_group_id: Some(producer_group_id),
+            ..Default::default()
+        };
+        let mut producer = test_hub.producer_with_options(EventHubProducerClientOptions {
+            enable_idempotent_partitions: true,
+            partition_options: [("0".to_string(), partition_options)].into(),
+            ..Default::default()
+        });
+        let properties = producer
+            .get_partition_publishing_properties("0")
+            .await
+            .unwrap();
+        assert_eq!(properties.producer_group_id(), Some(producer_group_id));
+        assert_eq!(properties.last_published_sequence_number(), Some(2));
+        assert_eq!(test_hub.event_hub.events("0").len(), 3);
+    }
+}
@@ -21,11 +21,23 @@ pub struct EventHubProducerClientOptions {
 
     /// The set of options that can be specified when publishing events to a specific partition.
     pub partition_options: HashMap<String, PartitionPublishingOptions>,
+
+    /// Indicates whether or not the producer should enable idempotent publishing to the Event Hub
+    /// partitions.
+    ///
+    /// If enabled, the producer will only be able to publish directly to partitions; it will not
+    /// be able to publish to the Event Hubs gateway for automatic partition routing nor using a
+    /// partition key. The service assigns a sequence number to each event, which allows it to
+    /// discard events that are published more than once when a send is retried.
+    pub enable_idempotent_partitions: bool,
 }
 
 impl EventHubProducerClientOptions {
     pub(crate) fn create_features(&self) -> TransportProducerFeatures {
-        TransportProducerFeatures::None
+        match self.enable_idempotent_partitions {
+            true => TransportProducerFeatures::IdempotentPublishing,
+            false => TransportProducerFeatures::None,
+        }
     }
 
     pub(crate) fn get_publishing_options_or_default_for_partition(
@@ -0,0 +1,38 @@
+/// The set of information about the state of publishing to a partition, as observed by the
+/// [`crate::producer::EventHubProducerClient`].
+///
+/// The values are the ones that the Event Hubs service assigned when the producer for the partition
+/// was attached. They are only populated when idempotent publishing is enabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PartitionPublishingProperties {
+    pub(crate) is_idempotent_publishing_enabled: bool,
+    pub(crate) producer_group_id: Option<i64>,
+    pub(crate) owner_level: Option<i16>,
+    pub(crate) last_published_sequence_number: Option<i32>,
+}
+
+impl PartitionPublishingProperties {
+    /// Indicates whether or not idempotent publishing is enabled for the producer.
+    pub fn is_idempotent_publishing_enabled(&self) -> bool {
+        self.is_idempotent_publishing_enabled
+    }
+
+    /// The identifier of the producer group that the producer is associated with when publishing
+    /// to the partition.
+    pub fn producer_group_id(&self) -> Option<i64> {
+        self.producer_group_id
+    }
+
+    /// The owner level that the producer asserted when publishing to the partition.
+    pub fn owner_level(&self) -> Option<i16> {
+        self.owner_level
+    }
+
+    /// The sequence number assigned to the event that was last published to the partition by the
+    /// producer group before the producer was attached.
+    ///
+    /// This is `None` if no events have been published by the producer group yet.
+    pub fn last_published_sequence_number(&self) -> Option<i32> {
+        self.last_published_sequence_number
+    }
+}
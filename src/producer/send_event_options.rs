@@ -1,5 +1,5 @@
 /// The partitioning strategy to use when publishing events to Event Hubs.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Partition {
     /// Allows a hashing key to be provided for the batch of events, which instructs Event Hubs to
     /// map the key to an automatically-assigned partition.
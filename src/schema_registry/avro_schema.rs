@@ -0,0 +1,583 @@
+//! A parsed Avro schema which encodes and decodes serde values with `apache-avro`.
+//!
+//! A payload is checked against the schema before it is decoded, so that a small payload cannot
+//! make the decoder allocate or recurse without bound.
+
+use std::collections::HashMap;
+
+use apache_avro::{
+    schema::{Name, Namespace, ResolvedSchema, SchemaKind},
+    types::Value,
+    Schema,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::error::AvroError;
+
+/// The most levels of nested schemas that a payload is decoded through
+const MAX_DEPTH: usize = 128;
+
+/// The most items of an array whose items are encoded in zero bytes, eg. an array of nulls
+const MAX_ZERO_SIZE_ITEMS: u64 = 1 << 16;
+
+/// A parsed Avro schema
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AvroSchema {
+    name: String,
+    schema: Schema,
+    names: HashMap<Name, Schema>,
+}
+
+impl AvroSchema {
+    /// Parses the JSON definition of a schema
+    pub(crate) fn parse(definition: &str) -> Result<Self, AvroError> {
+        let schema = Schema::parse_str(definition)
+            .map_err(|err| AvroError::InvalidSchema(err.to_string()))?;
+        let names = ResolvedSchema::try_from(&schema)
+            .map_err(|err| AvroError::InvalidSchema(err.to_string()))?
+            .get_names()
+            .iter()
+            .map(|(name, schema)| (name.clone(), (*schema).clone()))
+            .collect();
+        let name = match schema.name() {
+            Some(name) => name.fullname(None),
+            None => format!("{:?}", SchemaKind::from(&schema)).to_lowercase(),
+        };
+        Ok(Self {
+            name,
+            schema,
+            names,
+        })
+    }
+
+    /// The full name of the schema, or the name of the type if the schema is not a named type
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Encodes a value in the Avro binary encoding
+    pub(crate) fn encode<T>(&self, value: &T) -> Result<Vec<u8>, AvroError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = apache_avro::to_value(value)
+            .map_err(|err| AvroError::SchemaMismatch(err.to_string()))?;
+        // `apache-avro` truncates a long that is resolved to an int
+        check_int_range(&value, &self.schema, &self.names, &None, 0)?;
+        let value = value
+            .resolve(&self.schema)
+            .map_err(|err| AvroError::SchemaMismatch(err.to_string()))?;
+        apache_avro::to_avro_datum(&self.schema, value)
+            .map_err(|err| AvroError::SchemaMismatch(err.to_string()))
+    }
+
+    /// Decodes a value that was encoded with this schema. The value is resolved to the reader
+    /// schema if one is given. The whole payload must be consumed.
+    pub(crate) fn decode<T>(
+        &self,
+        bytes: &[u8],
+        reader_schema: Option<&AvroSchema>,
+    ) -> Result<T, AvroError>
+    where
+        T: DeserializeOwned,
+    {
+        PayloadCheck::new(&self.names, bytes).check(&self.schema)?;
+
+        let mut reader = bytes;
+        let value = apache_avro::from_avro_datum(
+            &self.schema,
+            &mut reader,
+            reader_schema.map(|schema| &schema.schema),
+        )
+        .map_err(|err| AvroError::MalformedPayload(err.to_string()))?;
+        apache_avro::from_value(&value).map_err(|err| AvroError::SchemaMismatch(err.to_string()))
+    }
+}
+
+/// Walks a payload with the schema without decoding it, which rejects the payloads that are
+/// truncated, have trailing bytes, declare more items or bytes than the payload holds, or are
+/// nested deeper than [`MAX_DEPTH`]
+struct PayloadCheck<'a> {
+    names: &'a HashMap<Name, Schema>,
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PayloadCheck<'a> {
+    fn new(names: &'a HashMap<Name, Schema>, bytes: &'a [u8]) -> Self {
+        Self {
+            names,
+            bytes,
+            position: 0,
+        }
+    }
+
+    fn check(mut self, schema: &Schema) -> Result<(), AvroError> {
+        self.check_value(schema, &None, 0)?;
+        match self.remaining() {
+            0 => Ok(()),
+            trailing => Err(malformed(format!("{} trailing bytes", trailing))),
+        }
+    }
+
+    fn check_value(
+        &mut self,
+        schema: &Schema,
+        namespace: &Namespace,
+        depth: usize,
+    ) -> Result<(), AvroError> {
+        if depth > MAX_DEPTH {
+            return Err(malformed(format!(
+                "the value is nested deeper than {} levels",
+                MAX_DEPTH
+            )));
+        }
+
+        match schema {
+            Schema::Null => {}
+            Schema::Boolean => {
+                self.read_exact(1)?;
+            }
+            Schema::Int
+            | Schema::Long
+            | Schema::Date
+            | Schema::TimeMillis
+            | Schema::TimeMicros
+            | Schema::TimestampMillis
+            | Schema::TimestampMicros
+            | Schema::TimestampNanos
+            | Schema::LocalTimestampMillis
+            | Schema::LocalTimestampMicros
+            | Schema::LocalTimestampNanos
+            | Schema::Enum(_) => {
+                self.read_long()?;
+            }
+            Schema::Float => {
+                self.read_exact(4)?;
+            }
+            Schema::Double => {
+                self.read_exact(8)?;
+            }
+            Schema::Duration => {
+                self.read_exact(12)?;
+            }
+            Schema::Bytes | Schema::String | Schema::BigDecimal | Schema::Uuid => {
+                self.read_bytes()?;
+            }
+            Schema::Fixed(fixed) => {
+                self.read_exact(fixed.size)?;
+            }
+            Schema::Decimal(decimal) => self.check_value(&decimal.inner, namespace, depth + 1)?,
+            Schema::Array(array) => {
+                let zero_size = self.is_zero_size(&array.items, namespace, depth);
+                self.check_blocks(zero_size, |check| {
+                    check.check_value(&array.items, namespace, depth + 1)
+                })?
+            }
+            Schema::Map(map) => self.check_blocks(false, |check| {
+                check.read_bytes()?;
+                check.check_value(&map.types, namespace, depth + 1)
+            })?,
+            Schema::Union(union) => {
+                let index = self.read_long()?;
+                let variant = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| union.variants().get(index))
+                    .ok_or_else(|| malformed(format!("no union variant at index {}", index)))?;
+                self.check_value(variant, namespace, depth + 1)?
+            }
+            Schema::Record(record) => {
+                let namespace = record.name.fully_qualified_name(namespace).namespace;
+                for field in &record.fields {
+                    self.check_value(&field.schema, &namespace, depth + 1)?;
+                }
+            }
+            Schema::Ref { name } => {
+                let (schema, namespace) = self.resolve(name, namespace)?;
+                self.check_value(schema, &namespace, depth + 1)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the blocks of an array or a map. The items of a block must fit in the rest of the
+    /// payload unless they are encoded in zero bytes, in which case the number of items is capped.
+    fn check_blocks(
+        &mut self,
+        zero_size: bool,
+        mut check_item: impl FnMut(&mut Self) -> Result<(), AvroError>,
+    ) -> Result<(), AvroError> {
+        let mut total = 0u64;
+        while let Some(count) = self.read_block_count()? {
+            total = total.saturating_add(count);
+            if zero_size && total > MAX_ZERO_SIZE_ITEMS {
+                return Err(malformed(format!(
+                    "more than {} items that are encoded in zero bytes",
+                    MAX_ZERO_SIZE_ITEMS
+                )));
+            }
+            if !zero_size && count > self.remaining() as u64 {
+                return Err(malformed(format!(
+                    "a block of {} items exceeds the remaining {} bytes",
+                    count,
+                    self.remaining()
+                )));
+            }
+            for _ in 0..count {
+                check_item(self)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a value of the schema is encoded in zero bytes
+    fn is_zero_size(&self, schema: &Schema, namespace: &Namespace, depth: usize) -> bool {
+        if depth > MAX_DEPTH {
+            return false;
+        }
+        match schema {
+            Schema::Null => true,
+            Schema::Fixed(fixed) => fixed.size == 0,
+            Schema::Decimal(decimal) => self.is_zero_size(&decimal.inner, namespace, depth + 1),
+            Schema::Record(record) => {
+                let namespace = record.name.fully_qualified_name(namespace).namespace;
+                record
+                    .fields
+                    .iter()
+                    .all(|field| self.is_zero_size(&field.schema, &namespace, depth + 1))
+            }
+            Schema::Ref { name } => match self.resolve(name, namespace) {
+                Ok((schema, namespace)) => self.is_zero_size(schema, &namespace, depth + 1),
+                Err(_) => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn resolve(
+        &self,
+        name: &Name,
+        namespace: &Namespace,
+    ) -> Result<(&'a Schema, Namespace), AvroError> {
+        let name = name.fully_qualified_name(namespace);
+        let schema = self
+            .names
+            .get(&name)
+            .ok_or_else(|| AvroError::InvalidSchema(format!("unknown type {}", name)))?;
+        Ok((schema, name.namespace))
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    fn read_exact(&mut self, len: usize) -> Result<&'a [u8], AvroError> {
+        if len > self.remaining() {
+            return Err(malformed("unexpected end of payload".to_string()));
+        }
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn read_long(&mut self) -> Result<i64, AvroError> {
+        let mut zigzag = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_exact(1)?[0];
+            zigzag |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64));
+            }
+        }
+        Err(malformed("variable-length integer is too long".to_string()))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], AvroError> {
+        let len = self.read_long()?;
+        let len =
+            usize::try_from(len).map_err(|_| malformed(format!("negative length {}", len)))?;
+        self.read_exact(len)
+    }
+
+    /// Returns the number of items in the next block of an array or map, or `None` after the
+    /// last block
+    fn read_block_count(&mut self) -> Result<Option<u64>, AvroError> {
+        match self.read_long()? {
+            0 => Ok(None),
+            count if count < 0 => {
+                // A negative count is followed by the size of the block in bytes
+                self.read_long()?;
+                Ok(Some(count.unsigned_abs()))
+            }
+            count => Ok(Some(count as u64)),
+        }
+    }
+}
+
+/// Checks that the longs of a value fit in the ints of the schema that they are resolved to
+fn check_int_range(
+    value: &Value,
+    schema: &Schema,
+    names: &HashMap<Name, Schema>,
+    namespace: &Namespace,
+    depth: usize,
+) -> Result<(), AvroError> {
+    if depth > MAX_DEPTH {
+        return Ok(());
+    }
+
+    match (schema, value) {
+        (Schema::Int | Schema::Date | Schema::TimeMillis, Value::Long(n))
+            if i32::try_from(*n).is_err() =>
+        {
+            return Err(AvroError::SchemaMismatch(format!(
+                "{} does not fit in {:?}",
+                n,
+                SchemaKind::from(schema)
+            )));
+        }
+        (Schema::Record(record), Value::Record(_) | Value::Map(_)) => {
+            let namespace = record.name.fully_qualified_name(namespace).namespace;
+            for field in &record.fields {
+                let field_value = match value {
+                    Value::Record(fields) => fields
+                        .iter()
+                        .find(|(name, _)| *name == field.name)
+                        .map(|(_, value)| value),
+                    Value::Map(entries) => entries.get(&field.name),
+                    _ => None,
+                };
+                if let Some(field_value) = field_value {
+                    check_int_range(field_value, &field.schema, names, &namespace, depth + 1)?;
+                }
+            }
+        }
+        (Schema::Array(array), Value::Array(items)) => {
+            for item in items {
+                check_int_range(item, &array.items, names, namespace, depth + 1)?;
+            }
+        }
+        (Schema::Map(map), Value::Map(entries)) => {
+            for entry in entries.values() {
+                check_int_range(entry, &map.types, names, namespace, depth + 1)?;
+            }
+        }
+        (Schema::Union(union), _) => {
+            let value = match value {
+                Value::Union(_, inner) => inner,
+                value => value,
+            };
+            // The same variant that the value is resolved to
+            if let Some((_, variant)) =
+                union.find_schema_with_known_schemata(value, Some(names), namespace)
+            {
+                check_int_range(value, variant, names, namespace, depth + 1)?;
+            }
+        }
+        (Schema::Ref { name }, _) => {
+            let name = name.fully_qualified_name(namespace);
+            if let Some(schema) = names.get(&name) {
+                check_int_range(value, schema, names, &name.namespace, depth + 1)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn malformed(message: String) -> AvroError {
+    AvroError::MalformedPayload(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::AvroSchema;
+    use crate::schema_registry::AvroError;
+
+    const READING_SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Reading",
+        "namespace": "com.example",
+        "fields": [
+            {"name": "sensor", "type": "string"},
+            {"name": "value", "type": "double"},
+            {"name": "count", "type": "long"},
+            {"name": "unit", "type": ["null", {"type": "enum", "name": "Unit", "symbols": ["C", "F"]}], "default": null},
+            {"name": "tags", "type": {"type": "map", "values": "int"}},
+            {"name": "samples", "type": {"type": "array", "items": "float"}},
+            {"name": "taken_at", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+            {"name": "next", "type": ["null", "Reading"]}
+        ]
+    }"#;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum Unit {
+        C,
+        F,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Reading {
+        sensor: String,
+        value: f64,
+        count: i64,
+        unit: Option<Unit>,
+        tags: std::collections::HashMap<String, i32>,
+        samples: Vec<f32>,
+        taken_at: i64,
+        next: Option<Box<Reading>>,
+    }
+
+    fn reading(sensor: &str, next: Option<Reading>) -> Reading {
+        Reading {
+            sensor: sensor.to_string(),
+            value: 21.5,
+            count: 3,
+            unit: Some(Unit::F),
+            tags: [("floor".to_string(), 2)].into_iter().collect(),
+            samples: vec![1.5, -2.0],
+            taken_at: 1_700_000_000_000,
+            next: next.map(Box::new),
+        }
+    }
+
+    #[test]
+    fn primitives_are_encoded_as_specified() {
+        let long = AvroSchema::parse(r#""long""#).unwrap();
+        assert_eq!(long.name(), "long");
+        assert_eq!(long.encode(&0i64).unwrap(), [0x00]);
+        assert_eq!(long.encode(&-1i64).unwrap(), [0x01]);
+        assert_eq!(long.encode(&64i64).unwrap(), [0x80, 0x01]);
+        assert_eq!(long.decode::<i64>(&[0x80, 0x01], None).unwrap(), 64);
+        let bytes = long.encode(&i64::MIN).unwrap();
+        assert_eq!(long.decode::<i64>(&bytes, None).unwrap(), i64::MIN);
+
+        let string = AvroSchema::parse(r#"{"type": "string"}"#).unwrap();
+        assert_eq!(string.encode("foo").unwrap(), [0x06, b'f', b'o', b'o']);
+    }
+
+    #[test]
+    fn record_round_trips() {
+        let schema = AvroSchema::parse(READING_SCHEMA).unwrap();
+        assert_eq!(schema.name(), "com.example.Reading");
+
+        let value = reading("a", Some(reading("b", None)));
+        let bytes = schema.encode(&value).unwrap();
+        assert_eq!(schema.decode::<Reading>(&bytes, None).unwrap(), value);
+    }
+
+    #[test]
+    fn string_is_encoded_in_the_string_branch_of_a_union() {
+        let schema = AvroSchema::parse(r#"["null", "bytes", "string"]"#).unwrap();
+        assert_eq!(schema.encode("a").unwrap(), [0x04, 0x02, b'a']);
+
+        // Without a string branch the string is encoded as bytes
+        let schema = AvroSchema::parse(r#"["null", "bytes"]"#).unwrap();
+        assert_eq!(schema.encode("a").unwrap(), [0x02, 0x02, b'a']);
+    }
+
+    #[test]
+    fn value_is_resolved_to_the_reader_schema() {
+        let writer = AvroSchema::parse(
+            r#"{"type": "record", "name": "Point", "fields": [{"name": "x", "type": "int"}]}"#,
+        )
+        .unwrap();
+        let reader = AvroSchema::parse(
+            r#"{"type": "record", "name": "Point", "fields": [
+                {"name": "x", "type": "long"},
+                {"name": "y", "type": "long", "default": 7}
+            ]}"#,
+        )
+        .unwrap();
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Point {
+            x: i64,
+            y: i64,
+        }
+
+        #[derive(Serialize)]
+        struct WrittenPoint {
+            x: i32,
+        }
+
+        let bytes = writer.encode(&WrittenPoint { x: 3 }).unwrap();
+        let point: Point = writer.decode(&bytes, Some(&reader)).unwrap();
+        assert_eq!(point, Point { x: 3, y: 7 });
+    }
+
+    #[test]
+    fn value_not_matching_schema_is_rejected() {
+        let schema = AvroSchema::parse(READING_SCHEMA).unwrap();
+        let err = schema
+            .encode(&serde_json::json!({"sensor": 1}))
+            .unwrap_err();
+        assert!(matches!(err, AvroError::SchemaMismatch(_)), "{}", err);
+
+        let int = AvroSchema::parse(r#""int""#).unwrap();
+        assert!(int.encode(&(i64::from(i32::MAX) + 1)).is_err());
+    }
+
+    #[test]
+    fn malformed_payload_is_rejected() {
+        let string = AvroSchema::parse(r#""string""#).unwrap();
+        for payload in [
+            &[0x06, b'f'][..],
+            &[0x02, b'f', b'g'],
+            &[0xfe, 0xff, 0xff, 0xff, 0x0f],
+        ] {
+            assert!(matches!(
+                string.decode::<String>(payload, None),
+                Err(AvroError::MalformedPayload(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn block_count_beyond_the_payload_is_rejected() {
+        // A block count near i64::MAX followed by a few bytes
+        let payload = [
+            0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 0x00,
+        ];
+
+        let longs = AvroSchema::parse(r#"{"type": "array", "items": "long"}"#).unwrap();
+        let result = longs.decode::<Vec<i64>>(&payload, None);
+        assert!(matches!(result, Err(AvroError::MalformedPayload(_))));
+
+        let nulls = AvroSchema::parse(r#"{"type": "array", "items": "null"}"#).unwrap();
+        let result = nulls.decode::<Vec<()>>(&payload, None);
+        assert!(matches!(result, Err(AvroError::MalformedPayload(_))));
+
+        let empty_records = AvroSchema::parse(
+            r#"{"type": "map", "values": {"type": "record", "name": "E", "fields": []}}"#,
+        )
+        .unwrap();
+        let result = empty_records.decode::<serde_json::Value>(&payload, None);
+        assert!(matches!(result, Err(AvroError::MalformedPayload(_))));
+
+        // Zero-size items within the cap are still accepted
+        let bytes = nulls.encode(&vec![(); 3]).unwrap();
+        assert_eq!(nulls.decode::<Vec<()>>(&bytes, None).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn deeply_nested_payload_is_rejected() {
+        let schema = AvroSchema::parse(
+            r#"{"type": "record", "name": "Node", "fields": [{"name": "next", "type": ["null", "Node"]}]}"#,
+        )
+        .unwrap();
+        let mut payload = vec![0x02; 100_000];
+        payload.push(0x00);
+        let result = schema.decode::<serde_json::Value>(&payload, None);
+        assert!(matches!(result, Err(AvroError::MalformedPayload(_))));
+    }
+
+    #[test]
+    fn invalid_schema_is_rejected() {
+        assert!(AvroSchema::parse(r#""Unknown""#).is_err());
+        assert!(AvroSchema::parse(r#"{"type": "record", "name": "A"}"#).is_err());
+        assert!(AvroSchema::parse("not json").is_err());
+    }
+}
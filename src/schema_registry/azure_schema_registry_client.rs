@@ -0,0 +1,351 @@
+use std::sync::Arc;
+
+use azure_core::{
+    auth::TokenCredential,
+    error::{ErrorKind, ResultExt},
+    headers::{self, HeaderName, Headers},
+    HttpClient, Method, Request, Url,
+};
+
+use super::{SchemaProperties, SchemaRegistryClient, SchemaRegistrySchema};
+
+/// The version of the Schema Registry REST API
+const API_VERSION: &str = "2022-10";
+
+/// The scope of the tokens that authorize the requests to the Schema Registry
+const SCHEMA_REGISTRY_SCOPE: &str = "https://eventhubs.azure.net/.default";
+
+/// The content type of the definition of an Avro schema
+const AVRO_SCHEMA_CONTENT_TYPE: &str = "application/json; serialization=Avro";
+
+const SCHEMA_ID: HeaderName = HeaderName::from_static("schema-id");
+const SCHEMA_GROUP_NAME: HeaderName = HeaderName::from_static("schema-group-name");
+const SCHEMA_NAME: HeaderName = HeaderName::from_static("schema-name");
+
+/// A [`SchemaRegistryClient`] of the Azure Schema Registry of an Event Hubs namespace.
+///
+/// The requests are authorized with the tokens of a [`TokenCredential`], eg.
+/// `azure_identity::DefaultAzureCredential`, and sent with the given [`HttpClient`], eg. the one
+/// returned by `azure_core::new_http_client()` when `azure_core` is built with `reqwest`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+///
+/// use azeventhubs::schema_registry::{
+///     AzureSchemaRegistryClient, SchemaRegistryAvroSerializer,
+///     SchemaRegistryAvroSerializerOptions,
+/// };
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let credential = azure_identity::create_credential()?;
+/// let client = AzureSchemaRegistryClient::new(
+///     "<namespace>.servicebus.windows.net",
+///     credential,
+///     azure_core::new_http_client(),
+/// )?;
+/// let serializer = SchemaRegistryAvroSerializer::new(
+///     client,
+///     "<schema-group>",
+///     SchemaRegistryAvroSerializerOptions::default(),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AzureSchemaRegistryClient {
+    endpoint: Url,
+    credential: Arc<dyn TokenCredential>,
+    http_client: Arc<dyn HttpClient>,
+}
+
+impl AzureSchemaRegistryClient {
+    /// Creates a client of the Schema Registry of the namespace, eg.
+    /// `<namespace>.servicebus.windows.net`
+    pub fn new(
+        fully_qualified_namespace: &str,
+        credential: Arc<dyn TokenCredential>,
+        http_client: Arc<dyn HttpClient>,
+    ) -> Result<Self, azure_core::Error> {
+        let endpoint = Url::parse(&format!("https://{}/", fully_qualified_namespace))
+            .with_context(ErrorKind::DataConversion, || {
+                format!("Invalid namespace {:?}", fully_qualified_namespace)
+            })?;
+        Ok(Self {
+            endpoint,
+            credential,
+            http_client,
+        })
+    }
+
+    /// The fully qualified name of the namespace
+    pub fn fully_qualified_namespace(&self) -> &str {
+        self.endpoint.host_str().unwrap_or_default()
+    }
+
+    fn url(&self, path_segments: &[&str]) -> Url {
+        let mut url = self.endpoint.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty().extend(path_segments);
+        }
+        url.query_pairs_mut()
+            .append_pair("api-version", API_VERSION);
+        url
+    }
+
+    /// Sends a request and returns the headers and the body of a successful response
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        definition: Option<&str>,
+    ) -> Result<(Headers, String), azure_core::Error> {
+        let token = self.credential.get_token(&[SCHEMA_REGISTRY_SCOPE]).await?;
+
+        let mut request = Request::new(url, method);
+        request.insert_header(
+            headers::AUTHORIZATION,
+            format!("Bearer {}", token.token.secret()),
+        );
+        if let Some(definition) = definition {
+            request.insert_header(headers::CONTENT_TYPE, AVRO_SCHEMA_CONTENT_TYPE);
+            request.set_body(definition.to_string());
+        }
+
+        let (status, headers, body) = self
+            .http_client
+            .execute_request(&request)
+            .await?
+            .deconstruct();
+        let body = body.collect().await?;
+        if !status.is_success() {
+            let kind = ErrorKind::http_response_from_parts(status, &headers, &body);
+            let message = String::from_utf8_lossy(&body).into_owned();
+            return Err(azure_core::Error::message(kind, message));
+        }
+
+        let body = String::from_utf8(body.to_vec())
+            .with_context(ErrorKind::DataConversion, || "The response is not UTF-8")?;
+        Ok((headers, body))
+    }
+}
+
+fn schema_properties(headers: &Headers) -> Result<SchemaProperties, azure_core::Error> {
+    Ok(SchemaProperties::new(
+        headers.get_str(&SCHEMA_ID)?,
+        headers.get_str(&SCHEMA_GROUP_NAME)?,
+        headers.get_str(&SCHEMA_NAME)?,
+    ))
+}
+
+impl SchemaRegistryClient for AzureSchemaRegistryClient {
+    async fn register_schema(
+        &self,
+        group_name: &str,
+        name: &str,
+        definition: &str,
+    ) -> Result<SchemaProperties, azure_core::Error> {
+        let url = self.url(&["$schemaGroups", group_name, "schemas", name]);
+        let (headers, _) = self.send(Method::Put, url, Some(definition)).await?;
+        schema_properties(&headers)
+    }
+
+    async fn get_schema_properties(
+        &self,
+        group_name: &str,
+        name: &str,
+        definition: &str,
+    ) -> Result<SchemaProperties, azure_core::Error> {
+        let get_id = format!("{}:get-id", name);
+        let url = self.url(&["$schemaGroups", group_name, "schemas", &get_id]);
+        let (headers, _) = self.send(Method::Post, url, Some(definition)).await?;
+        schema_properties(&headers)
+    }
+
+    async fn get_schema(&self, schema_id: &str) -> Result<SchemaRegistrySchema, azure_core::Error> {
+        let url = self.url(&["$schemaGroups", "$schemas", schema_id]);
+        let (headers, definition) = self.send(Method::Get, url, None).await?;
+        Ok(SchemaRegistrySchema::new(
+            schema_properties(&headers)?,
+            definition,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    };
+
+    use azure_core::{
+        auth::{AccessToken, TokenCredential},
+        headers::{self, Headers},
+        Body, HttpClient, Method, Request, Response, StatusCode,
+    };
+
+    use crate::schema_registry::SchemaRegistryClient;
+
+    use super::{AzureSchemaRegistryClient, SCHEMA_GROUP_NAME, SCHEMA_ID, SCHEMA_NAME};
+
+    #[derive(Debug)]
+    struct StaticTokenCredential;
+
+    impl TokenCredential for StaticTokenCredential {
+        fn get_token<'life0, 'life1, 'life2, 'async_trait>(
+            &'life0 self,
+            _scopes: &'life1 [&'life2 str],
+        ) -> Pin<Box<dyn Future<Output = azure_core::Result<AccessToken>> + Send + 'async_trait>>
+        where
+            Self: 'async_trait,
+            'life0: 'async_trait,
+            'life1: 'async_trait,
+            'life2: 'async_trait,
+        {
+            let expires_on = time::OffsetDateTime::now_utc() + time::Duration::hours(1);
+            Box::pin(async move { Ok(AccessToken::new("token".to_string(), expires_on)) })
+        }
+
+        fn clear_cache<'life0, 'async_trait>(
+            &'life0 self,
+        ) -> Pin<Box<dyn Future<Output = azure_core::Result<()>> + Send + 'async_trait>>
+        where
+            Self: 'async_trait,
+            'life0: 'async_trait,
+        {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    /// Records the requests and answers each with the same response
+    #[derive(Debug)]
+    struct MockHttpClient {
+        status: StatusCode,
+        body: &'static str,
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl MockHttpClient {
+        fn new(status: StatusCode, body: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                status,
+                body,
+                requests: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    impl HttpClient for MockHttpClient {
+        fn execute_request<'life0, 'life1, 'async_trait>(
+            &'life0 self,
+            request: &'life1 Request,
+        ) -> Pin<Box<dyn Future<Output = azure_core::Result<Response>> + Send + 'async_trait>>
+        where
+            Self: 'async_trait,
+            'life0: 'async_trait,
+            'life1: 'async_trait,
+        {
+            self.requests.lock().unwrap().push(request.clone());
+
+            let mut headers = Headers::new();
+            headers.insert(SCHEMA_ID, "schema-id");
+            headers.insert(SCHEMA_GROUP_NAME, "group");
+            headers.insert(SCHEMA_NAME, "example.Reading");
+            let body = bytes::Bytes::from_static(self.body.as_bytes());
+            let stream = futures_util::stream::once(async move { Ok(body) });
+            let response = Response::new(self.status, headers, Box::pin(stream));
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    fn client(http_client: Arc<MockHttpClient>) -> AzureSchemaRegistryClient {
+        AzureSchemaRegistryClient::new(
+            "namespace.servicebus.windows.net",
+            Arc::new(StaticTokenCredential),
+            http_client,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn register_schema_puts_the_definition() {
+        let http_client = MockHttpClient::new(StatusCode::NoContent, "");
+        let client = client(http_client.clone());
+
+        let properties = client
+            .register_schema("group", "example.Reading", r#"{"type":"string"}"#)
+            .await
+            .unwrap();
+        assert_eq!(properties.id(), "schema-id");
+        assert_eq!(properties.group_name(), "group");
+        assert_eq!(properties.name(), "example.Reading");
+
+        let requests = http_client.requests.lock().unwrap();
+        let request = &requests[0];
+        assert_eq!(request.method(), &Method::Put);
+        assert_eq!(
+            request.url().as_str(),
+            concat!(
+                "https://namespace.servicebus.windows.net",
+                "/$schemaGroups/group/schemas/example.Reading?api-version=2022-10"
+            )
+        );
+        assert_eq!(
+            request.headers().get_str(&headers::AUTHORIZATION).unwrap(),
+            "Bearer token"
+        );
+        assert_eq!(
+            request.headers().get_str(&headers::CONTENT_TYPE).unwrap(),
+            "application/json; serialization=Avro"
+        );
+        assert!(matches!(request.body(), Body::Bytes(body) if body == r#"{"type":"string"}"#));
+    }
+
+    #[tokio::test]
+    async fn get_schema_returns_the_definition() {
+        let http_client = MockHttpClient::new(StatusCode::Ok, r#"{"type":"string"}"#);
+        let client = client(http_client.clone());
+
+        let schema = client.get_schema("schema-id").await.unwrap();
+        assert_eq!(schema.properties().id(), "schema-id");
+        assert_eq!(schema.definition(), r#"{"type":"string"}"#);
+
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(requests[0].method(), &Method::Get);
+        assert_eq!(
+            requests[0].url().as_str(),
+            concat!(
+                "https://namespace.servicebus.windows.net",
+                "/$schemaGroups/$schemas/schema-id?api-version=2022-10"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn error_response_is_returned_as_error() {
+        let http_client = MockHttpClient::new(StatusCode::NotFound, "schema not found");
+        let client = client(http_client.clone());
+
+        let err = client
+            .get_schema_properties("group", "example.Reading", r#"{"type":"string"}"#)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            azure_core::error::ErrorKind::HttpResponse {
+                status: StatusCode::NotFound,
+                ..
+            }
+        ));
+
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(requests[0].method(), &Method::Post);
+        assert!(requests[0]
+            .url()
+            .path()
+            .ends_with("/schemas/example.Reading:get-id"));
+    }
+}
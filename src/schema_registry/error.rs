@@ -0,0 +1,39 @@
+//! Error types for the schema registry serializer
+
+use crate::amqp::error::RawAmqpMessageError;
+
+/// Error parsing an Avro schema or encoding and decoding a value with it
+#[derive(Debug, thiserror::Error)]
+pub enum AvroError {
+    /// The schema definition is not a valid Avro schema
+    #[error("Invalid Avro schema: {0}")]
+    InvalidSchema(String),
+
+    /// The value cannot be encoded with the schema
+    #[error("The value does not match the Avro schema: {0}")]
+    SchemaMismatch(String),
+
+    /// The payload is not a valid encoding of a value of the schema
+    #[error("Malformed Avro payload: {0}")]
+    MalformedPayload(String),
+}
+
+/// Error serializing or deserializing an event with the schema registry
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaRegistryError {
+    /// Error with the Avro schema or payload
+    #[error(transparent)]
+    Avro(#[from] AvroError),
+
+    /// The content type of the event is not `avro/binary+<schema-id>`
+    #[error("The content type {0:?} is not an Avro content type with a schema ID")]
+    InvalidContentType(Option<String>),
+
+    /// The body of the event is not a single data section
+    #[error(transparent)]
+    RawAmqpMessage(#[from] RawAmqpMessageError),
+
+    /// Error returned by the schema registry client
+    #[error(transparent)]
+    Registry(#[from] azure_core::Error),
+}
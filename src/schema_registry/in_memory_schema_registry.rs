@@ -0,0 +1,94 @@
+use std::sync::{Arc, Mutex};
+
+use azure_core::error::ErrorKind;
+
+use super::{SchemaProperties, SchemaRegistryClient, SchemaRegistrySchema};
+
+/// A [`SchemaRegistryClient`] that keeps the schemas in memory.
+///
+/// This is mainly intended for testing. Cloning the registry returns a handle to the same
+/// schemas.
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySchemaRegistry {
+    schemas: Arc<Mutex<Vec<SchemaRegistrySchema>>>,
+}
+
+impl InMemorySchemaRegistry {
+    /// Creates a new empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The schemas in the registry, in the order they were registered
+    pub fn schemas(&self) -> Vec<SchemaRegistrySchema> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<SchemaRegistrySchema>> {
+        // The state is always left consistent, so it is safe to ignore poisoning
+        self.schemas.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn find(
+        schemas: &[SchemaRegistrySchema],
+        group_name: &str,
+        name: &str,
+        definition: &str,
+    ) -> Option<SchemaProperties> {
+        schemas
+            .iter()
+            .find(|schema| {
+                schema.properties.group_name == group_name
+                    && schema.properties.name == name
+                    && schema.definition == definition
+            })
+            .map(|schema| schema.properties.clone())
+    }
+}
+
+impl SchemaRegistryClient for InMemorySchemaRegistry {
+    async fn register_schema(
+        &self,
+        group_name: &str,
+        name: &str,
+        definition: &str,
+    ) -> Result<SchemaProperties, azure_core::Error> {
+        let mut schemas = self.lock();
+        if let Some(properties) = Self::find(&schemas, group_name, name, definition) {
+            return Ok(properties);
+        }
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let properties = SchemaProperties::new(id, group_name, name);
+        schemas.push(SchemaRegistrySchema::new(properties.clone(), definition));
+        Ok(properties)
+    }
+
+    async fn get_schema_properties(
+        &self,
+        group_name: &str,
+        name: &str,
+        definition: &str,
+    ) -> Result<SchemaProperties, azure_core::Error> {
+        Self::find(&self.lock(), group_name, name, definition).ok_or_else(|| {
+            azure_core::Error::with_message(ErrorKind::Other, || {
+                format!(
+                    "The schema {} is not registered in the group {}",
+                    name, group_name
+                )
+            })
+        })
+    }
+
+    async fn get_schema(&self, schema_id: &str) -> Result<SchemaRegistrySchema, azure_core::Error> {
+        self.lock()
+            .iter()
+            .find(|schema| schema.properties.id == schema_id)
+            .cloned()
+            .ok_or_else(|| {
+                azure_core::Error::with_message(ErrorKind::Other, || {
+                    format!("The schema {} does not exist", schema_id)
+                })
+            })
+    }
+}
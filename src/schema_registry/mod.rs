@@ -0,0 +1,23 @@
+//! Serialization of events with the schemas of a schema registry, such as the Azure Schema
+//! Registry.
+//!
+//! [`SchemaRegistryAvroSerializer`] encodes the body of an event with an Avro schema and records
+//! the ID of the schema in the content type of the event. The schemas are resolved through a
+//! [`SchemaRegistryClient`]: [`AzureSchemaRegistryClient`] is a client of the Azure Schema Registry
+//! of an Event Hubs namespace, and [`InMemorySchemaRegistry`] keeps the schemas in memory for
+//! testing.
+//!
+//! This module is only available with the `schema-registry-avro` feature.
+
+mod avro_schema;
+mod azure_schema_registry_client;
+mod error;
+mod in_memory_schema_registry;
+mod schema_registry_avro_serializer;
+mod schema_registry_client;
+
+pub use azure_schema_registry_client::AzureSchemaRegistryClient;
+pub use error::{AvroError, SchemaRegistryError};
+pub use in_memory_schema_registry::InMemorySchemaRegistry;
+pub use schema_registry_avro_serializer::*;
+pub use schema_registry_client::*;
@@ -0,0 +1,399 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{EventData, ReceivedEventData};
+
+use super::{avro_schema::AvroSchema, SchemaRegistryClient, SchemaRegistryError};
+
+/// The prefix of the content type of an Avro encoded event, which is followed by the ID of the
+/// schema
+pub const AVRO_CONTENT_TYPE_PREFIX: &str = "avro/binary+";
+
+/// Options for [`SchemaRegistryAvroSerializer`]
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistryAvroSerializerOptions {
+    /// Whether a schema that is not in the registry is registered when a value is serialized
+    /// with it. Otherwise the schema must be registered beforehand. Defaults to `false`.
+    pub auto_register_schemas: bool,
+}
+
+#[derive(Debug, Default)]
+struct SchemaCache {
+    ids_by_definition: HashMap<String, String>,
+    schemas_by_id: HashMap<String, Arc<AvroSchema>>,
+    reader_schemas: HashMap<String, Arc<AvroSchema>>,
+}
+
+/// Serializes values into events whose body is encoded with an Avro schema from a schema
+/// registry, and deserializes such events back into values.
+///
+/// The body of the event is the Avro binary encoding of the value and the content type is
+/// `avro/binary+<schema-id>`, which is the format used by the Azure Schema Registry serializers
+/// of the other Azure SDKs. The schemas are cached after they are first resolved.
+///
+/// The values are encoded and decoded with `apache-avro`, which supports the logical types and
+/// resolves an event written with an older or newer schema to the reader schema given to
+/// [`deserialize_with_reader_schema`](Self::deserialize_with_reader_schema).
+///
+/// # Example
+///
+/// ```rust
+/// use azeventhubs::schema_registry::{
+///     InMemorySchemaRegistry, SchemaRegistryAvroSerializer, SchemaRegistryAvroSerializerOptions,
+/// };
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Reading {
+///     sensor: String,
+///     value: f64,
+/// }
+///
+/// const SCHEMA: &str = r#"{
+///     "type": "record",
+///     "name": "Reading",
+///     "namespace": "com.example",
+///     "fields": [
+///         {"name": "sensor", "type": "string"},
+///         {"name": "value", "type": "double"}
+///     ]
+/// }"#;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let options = SchemaRegistryAvroSerializerOptions {
+///     auto_register_schemas: true,
+/// };
+/// let serializer =
+///     SchemaRegistryAvroSerializer::new(InMemorySchemaRegistry::new(), "readings", options);
+/// let reading = Reading { sensor: "a".to_string(), value: 1.5 };
+/// let event = serializer.serialize(&reading, SCHEMA).await?;
+/// assert!(event.content_type().unwrap().starts_with("avro/binary+"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SchemaRegistryAvroSerializer<C> {
+    client: C,
+    group_name: String,
+    options: SchemaRegistryAvroSerializerOptions,
+    cache: Mutex<SchemaCache>,
+}
+
+impl<C> SchemaRegistryAvroSerializer<C>
+where
+    C: SchemaRegistryClient,
+{
+    /// Creates a serializer which resolves the schemas of the given schema group with the client
+    pub fn new(
+        client: C,
+        group_name: impl Into<String>,
+        options: SchemaRegistryAvroSerializerOptions,
+    ) -> Self {
+        Self {
+            client,
+            group_name: group_name.into(),
+            options,
+            cache: Mutex::new(SchemaCache::default()),
+        }
+    }
+
+    /// The schema group of the serializer
+    pub fn group_name(&self) -> &str {
+        &self.group_name
+    }
+
+    /// Creates an event whose body is the value encoded with the schema
+    pub async fn serialize<T>(
+        &self,
+        value: &T,
+        schema_definition: &str,
+    ) -> Result<EventData, SchemaRegistryError>
+    where
+        T: Serialize,
+    {
+        let (schema_id, schema) = self.schema_by_definition(schema_definition).await?;
+        let body = schema.encode(value)?;
+
+        let mut event = EventData::new(body);
+        event.set_content_type(format!("{}{}", AVRO_CONTENT_TYPE_PREFIX, schema_id));
+        Ok(event)
+    }
+
+    /// Decodes the body of an event with the schema that its content type refers to
+    pub async fn deserialize<T>(&self, event: &ReceivedEventData) -> Result<T, SchemaRegistryError>
+    where
+        T: DeserializeOwned,
+    {
+        let writer_schema = self.writer_schema(event).await?;
+        Ok(writer_schema.decode(event.body()?, None)?)
+    }
+
+    /// Decodes the body of an event with the schema that its content type refers to, and resolves
+    /// the value to the reader schema, eg. to add the fields that the writer schema does not have
+    /// with their defaults
+    pub async fn deserialize_with_reader_schema<T>(
+        &self,
+        event: &ReceivedEventData,
+        reader_schema_definition: &str,
+    ) -> Result<T, SchemaRegistryError>
+    where
+        T: DeserializeOwned,
+    {
+        let writer_schema = self.writer_schema(event).await?;
+        let reader_schema = self.reader_schema(reader_schema_definition)?;
+        Ok(writer_schema.decode(event.body()?, Some(&reader_schema))?)
+    }
+
+    async fn writer_schema(
+        &self,
+        event: &ReceivedEventData,
+    ) -> Result<Arc<AvroSchema>, SchemaRegistryError> {
+        let content_type = event.content_type();
+        let schema_id = content_type
+            .and_then(|content_type| content_type.strip_prefix(AVRO_CONTENT_TYPE_PREFIX))
+            .filter(|schema_id| !schema_id.is_empty())
+            .ok_or_else(|| {
+                SchemaRegistryError::InvalidContentType(content_type.map(String::from))
+            })?;
+        self.schema_by_id(schema_id).await
+    }
+
+    fn reader_schema(&self, definition: &str) -> Result<Arc<AvroSchema>, SchemaRegistryError> {
+        if let Some(schema) = self.lock_cache().reader_schemas.get(definition) {
+            return Ok(schema.clone());
+        }
+
+        let schema = Arc::new(AvroSchema::parse(definition)?);
+        self.lock_cache()
+            .reader_schemas
+            .insert(definition.to_string(), schema.clone());
+        Ok(schema)
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, SchemaCache> {
+        // The cache is always left consistent, so it is safe to ignore poisoning
+        self.cache.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    async fn schema_by_definition(
+        &self,
+        definition: &str,
+    ) -> Result<(String, Arc<AvroSchema>), SchemaRegistryError> {
+        {
+            let cache = self.lock_cache();
+            if let Some(schema_id) = cache.ids_by_definition.get(definition) {
+                let schema = cache.schemas_by_id[schema_id].clone();
+                return Ok((schema_id.clone(), schema));
+            }
+        }
+
+        let schema = Arc::new(AvroSchema::parse(definition)?);
+        let properties = match self.options.auto_register_schemas {
+            true => {
+                self.client
+                    .register_schema(&self.group_name, schema.name(), definition)
+                    .await?
+            }
+            false => {
+                self.client
+                    .get_schema_properties(&self.group_name, schema.name(), definition)
+                    .await?
+            }
+        };
+
+        let mut cache = self.lock_cache();
+        cache
+            .ids_by_definition
+            .insert(definition.to_string(), properties.id.clone());
+        cache
+            .schemas_by_id
+            .insert(properties.id.clone(), schema.clone());
+        Ok((properties.id, schema))
+    }
+
+    async fn schema_by_id(&self, schema_id: &str) -> Result<Arc<AvroSchema>, SchemaRegistryError> {
+        if let Some(schema) = self.lock_cache().schemas_by_id.get(schema_id) {
+            return Ok(schema.clone());
+        }
+
+        let registered = self.client.get_schema(schema_id).await?;
+        let schema = Arc::new(AvroSchema::parse(registered.definition())?);
+        self.lock_cache()
+            .schemas_by_id
+            .insert(schema_id.to_string(), schema.clone());
+        Ok(schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        schema_registry::{
+            InMemorySchemaRegistry, SchemaRegistryAvroSerializer,
+            SchemaRegistryAvroSerializerOptions, SchemaRegistryClient, SchemaRegistryError,
+        },
+        EventData, ReceivedEventData,
+    };
+
+    const SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Reading",
+        "namespace": "com.example",
+        "fields": [
+            {"name": "sensor", "type": "string"},
+            {"name": "value", "type": "double"},
+            {"name": "unit", "type": ["null", "string"], "default": null}
+        ]
+    }"#;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Reading {
+        sensor: String,
+        value: f64,
+        unit: Option<String>,
+    }
+
+    fn reading() -> Reading {
+        Reading {
+            sensor: "sensor-1".to_string(),
+            value: 21.5,
+            unit: Some("C".to_string()),
+        }
+    }
+
+    fn receive(event: EventData) -> ReceivedEventData {
+        ReceivedEventData::from_raw_amqp_message(event.amqp_message.into_raw_amqp_message())
+    }
+
+    fn serializer(
+        registry: &InMemorySchemaRegistry,
+        auto_register_schemas: bool,
+    ) -> SchemaRegistryAvroSerializer<InMemorySchemaRegistry> {
+        let options = SchemaRegistryAvroSerializerOptions {
+            auto_register_schemas,
+        };
+        SchemaRegistryAvroSerializer::new(registry.clone(), "readings", options)
+    }
+
+    #[tokio::test]
+    async fn serialized_event_round_trips_through_registry() {
+        let registry = InMemorySchemaRegistry::new();
+        let producer_serializer = serializer(&registry, true);
+
+        let event = producer_serializer
+            .serialize(&reading(), SCHEMA)
+            .await
+            .unwrap();
+        let schemas = registry.schemas();
+        assert_eq!(schemas.len(), 1);
+        let properties = schemas[0].properties();
+        assert_eq!(properties.group_name(), "readings");
+        assert_eq!(properties.name(), "com.example.Reading");
+        assert_eq!(
+            event.content_type(),
+            Some(format!("avro/binary+{}", properties.id()).as_str())
+        );
+
+        // A different serializer resolves the schema by the ID in the content type
+        let consumer_serializer = serializer(&registry, false);
+        let decoded: Reading = consumer_serializer
+            .deserialize(&receive(event))
+            .await
+            .unwrap();
+        assert_eq!(decoded, reading());
+
+        producer_serializer
+            .serialize(&reading(), SCHEMA)
+            .await
+            .unwrap();
+        assert_eq!(registry.schemas().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unregistered_schema_is_rejected_without_auto_registration() {
+        let registry = InMemorySchemaRegistry::new();
+        let result = serializer(&registry, false)
+            .serialize(&reading(), SCHEMA)
+            .await;
+        assert!(matches!(result, Err(SchemaRegistryError::Registry(_))));
+
+        registry
+            .register_schema("readings", "com.example.Reading", SCHEMA)
+            .await
+            .unwrap();
+        let event = serializer(&registry, false)
+            .serialize(&reading(), SCHEMA)
+            .await
+            .unwrap();
+        assert_eq!(
+            event.content_type(),
+            Some(format!("avro/binary+{}", registry.schemas()[0].properties().id()).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn event_without_avro_content_type_is_rejected() {
+        let registry = InMemorySchemaRegistry::new();
+        let mut event = EventData::new("{}");
+        event.set_content_type("application/json".to_string());
+
+        let result = serializer(&registry, false)
+            .deserialize::<Reading>(&receive(event))
+            .await;
+        assert!(matches!(
+            result,
+            Err(SchemaRegistryError::InvalidContentType(Some(content_type))) if content_type == "application/json"
+        ));
+
+        let mut event = EventData::new("");
+        event.set_content_type("avro/binary+unknown".to_string());
+        let result = serializer(&registry, false)
+            .deserialize::<Reading>(&receive(event))
+            .await;
+        assert!(matches!(result, Err(SchemaRegistryError::Registry(_))));
+    }
+
+    #[tokio::test]
+    async fn event_is_resolved_to_the_reader_schema() {
+        const READER_SCHEMA: &str = r#"{
+            "type": "record",
+            "name": "Reading",
+            "namespace": "com.example",
+            "fields": [
+                {"name": "sensor", "type": "string"},
+                {"name": "value", "type": "double"},
+                {"name": "unit", "type": ["null", "string"], "default": null},
+                {"name": "quality", "type": "int", "default": 100}
+            ]
+        }"#;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct ReadingWithQuality {
+            sensor: String,
+            quality: i32,
+        }
+
+        let registry = InMemorySchemaRegistry::new();
+        let event = serializer(&registry, true)
+            .serialize(&reading(), SCHEMA)
+            .await
+            .unwrap();
+
+        let decoded: ReadingWithQuality = serializer(&registry, false)
+            .deserialize_with_reader_schema(&receive(event), READER_SCHEMA)
+            .await
+            .unwrap();
+        assert_eq!(
+            decoded,
+            ReadingWithQuality {
+                sensor: "sensor-1".to_string(),
+                quality: 100,
+            }
+        );
+    }
+}
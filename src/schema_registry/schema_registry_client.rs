@@ -0,0 +1,97 @@
+use std::future::Future;
+
+/// The properties of a schema in the registry
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SchemaProperties {
+    pub(crate) id: String,
+    pub(crate) group_name: String,
+    pub(crate) name: String,
+}
+
+impl SchemaProperties {
+    /// Creates the properties of a schema
+    pub fn new(
+        id: impl Into<String>,
+        group_name: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            group_name: group_name.into(),
+            name: name.into(),
+        }
+    }
+
+    /// The ID that the registry assigned to the schema, which is what the content type of an
+    /// event refers to
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The name of the schema group
+    pub fn group_name(&self) -> &str {
+        &self.group_name
+    }
+
+    /// The name of the schema, which is the full name of the Avro type
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A schema in the registry
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SchemaRegistrySchema {
+    pub(crate) properties: SchemaProperties,
+    pub(crate) definition: String,
+}
+
+impl SchemaRegistrySchema {
+    /// Creates a schema from its properties and definition
+    pub fn new(properties: SchemaProperties, definition: impl Into<String>) -> Self {
+        Self {
+            properties,
+            definition: definition.into(),
+        }
+    }
+
+    /// The properties of the schema
+    pub fn properties(&self) -> &SchemaProperties {
+        &self.properties
+    }
+
+    /// The definition of the schema
+    pub fn definition(&self) -> &str {
+        &self.definition
+    }
+}
+
+/// A client of a schema registry, which resolves the schemas used by
+/// [`SchemaRegistryAvroSerializer`](super::SchemaRegistryAvroSerializer).
+///
+/// Schemas are grouped in schema groups and identified within a group by their name and
+/// definition. Each schema is assigned an ID that is unique across the registry.
+pub trait SchemaRegistryClient {
+    /// Registers a schema in the group and returns its properties. Registering a schema that is
+    /// already registered returns the properties of the existing schema.
+    fn register_schema(
+        &self,
+        group_name: &str,
+        name: &str,
+        definition: &str,
+    ) -> impl Future<Output = Result<SchemaProperties, azure_core::Error>> + Send;
+
+    /// Returns the properties of a schema that is registered in the group
+    fn get_schema_properties(
+        &self,
+        group_name: &str,
+        name: &str,
+        definition: &str,
+    ) -> impl Future<Output = Result<SchemaProperties, azure_core::Error>> + Send;
+
+    /// Returns the schema with the given ID
+    fn get_schema(
+        &self,
+        schema_id: &str,
+    ) -> impl Future<Output = Result<SchemaRegistrySchema, azure_core::Error>> + Send;
+}
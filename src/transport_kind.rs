@@ -0,0 +1,359 @@
+//! Dispatches the operations of the clients to the transport that the connection was created with.
+//!
+//! Producers and consumers are always used with the client that created them, so a producer or
+//! consumer of one transport is never paired with the client of another transport.
+
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+    time::Duration as StdDuration,
+};
+
+use crate::{
+    amqp::{
+        amqp_client::AmqpClient,
+        amqp_consumer::{single, AmqpConsumer},
+        amqp_event_batch::AmqpEventBatch,
+        amqp_producer::{AmqpProducer, RecoverableAmqpProducer},
+        error::{CreateBatchError, DisposeConsumerError},
+    },
+    core::{RecoverableTransport, TransportClient, TransportProducer},
+    event_hubs_retry_policy::EventHubsRetryPolicy,
+    producer::{CreateBatchOptions, PartitionPublishingProperties, SendEventOptions},
+    EventData, ReceivedEventData,
+};
+
+#[cfg(feature = "in-memory-transport")]
+use crate::in_memory::{
+    in_memory_client::InMemoryClient,
+    in_memory_consumer::{self, InMemoryConsumer},
+    in_memory_producer::{InMemoryProducer, RecoverableInMemoryProducer},
+};
+
+#[cfg(feature = "in-memory-transport")]
+const MISMATCHED_TRANSPORT: &str =
+    "producers and consumers are only used with the client that created them";
+
+// The in-memory variant is small, but it is only used for testing
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub(crate) enum TransportClientKind {
+    Amqp(AmqpClient),
+    #[cfg(feature = "in-memory-transport")]
+    InMemory(InMemoryClient),
+}
+
+impl TransportClientKind {
+    pub(crate) fn clone_as_shared(&mut self) -> Self {
+        match self {
+            Self::Amqp(client) => Self::Amqp(client.clone_as_shared()),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => Self::InMemory(client.clone_as_shared()),
+        }
+    }
+
+    pub(crate) fn as_amqp_mut(&mut self) -> &mut AmqpClient {
+        match self {
+            Self::Amqp(client) => client,
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(_) => unreachable!("{}", MISMATCHED_TRANSPORT),
+        }
+    }
+
+    #[cfg(feature = "in-memory-transport")]
+    pub(crate) fn as_in_memory_mut(&mut self) -> &mut InMemoryClient {
+        match self {
+            Self::InMemory(client) => client,
+            Self::Amqp(_) => unreachable!("{}", MISMATCHED_TRANSPORT),
+        }
+    }
+
+    /// The number of handles, including this one, that share the connection of the client
+    pub(crate) fn share_count(&self) -> usize {
+        match self {
+            Self::Amqp(client) => Arc::strong_count(&client.connection_scope.is_disposed),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => Arc::strong_count(&client.is_closed),
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        match self {
+            Self::Amqp(client) => client.is_closed(),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => client.is_closed(),
+        }
+    }
+
+    pub(crate) fn is_owned(&self) -> bool {
+        match self {
+            Self::Amqp(client) => client.is_owned(),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => client.is_owned(),
+        }
+    }
+
+    pub(crate) fn is_shared(&self) -> bool {
+        match self {
+            Self::Amqp(client) => client.is_shared(),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => client.is_shared(),
+        }
+    }
+
+    pub(crate) async fn close(&mut self) -> Result<(), azure_core::Error> {
+        match self {
+            Self::Amqp(client) => client.close().await.map_err(Into::into),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => client.close().await.map_err(Into::into),
+        }
+    }
+
+    pub(crate) async fn close_if_owned(&mut self) -> Result<(), azure_core::Error> {
+        match self {
+            Self::Amqp(client) => client.close_if_owned().await.map_err(Into::into),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => client.close_if_owned().await.map_err(Into::into),
+        }
+    }
+
+    pub(crate) async fn recover(&mut self) -> Result<(), azure_core::Error> {
+        match self {
+            Self::Amqp(client) => client.recover().await.map_err(Into::into),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => client.recover().await.map_err(Into::into),
+        }
+    }
+
+    pub(crate) async fn replace_connection(&mut self) -> Result<(), azure_core::Error> {
+        match self {
+            Self::Amqp(client) => client.replace_connection().await.map_err(Into::into),
+            // There is no connection to replace
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(client) => client.recover().await.map_err(Into::into),
+        }
+    }
+}
+
+/// The client that a stream receives with. It is either borrowed from the consumer client that
+/// created the stream, or a shared handle to the same connection that the stream owns.
+#[derive(Debug)]
+pub(crate) enum StreamClient<'a> {
+    Borrowed(&'a mut TransportClientKind),
+    Owned(Box<TransportClientKind>),
+}
+
+impl<'a> From<&'a mut TransportClientKind> for StreamClient<'a> {
+    fn from(client: &'a mut TransportClientKind) -> Self {
+        Self::Borrowed(client)
+    }
+}
+
+impl From<TransportClientKind> for StreamClient<'_> {
+    fn from(client: TransportClientKind) -> Self {
+        Self::Owned(Box::new(client))
+    }
+}
+
+impl Deref for StreamClient<'_> {
+    type Target = TransportClientKind;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Borrowed(client) => client,
+            Self::Owned(client) => client,
+        }
+    }
+}
+
+impl DerefMut for StreamClient<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Borrowed(client) => client,
+            Self::Owned(client) => client,
+        }
+    }
+}
+
+// The in-memory variant is small, but it is only used for testing
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub(crate) enum TransportProducerKind<RP> {
+    Amqp(AmqpProducer<RP>),
+    #[cfg(feature = "in-memory-transport")]
+    InMemory(InMemoryProducer<RP>),
+}
+
+impl<RP> TransportProducerKind<RP> {
+    /// The publishing properties that were assigned when the producer was created
+    pub(crate) fn initialized_partition_properties(&self) -> &PartitionPublishingProperties {
+        match self {
+            Self::Amqp(producer) => &producer.initialized_partition_properties,
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(producer) => &producer.initialized_partition_properties,
+        }
+    }
+
+    pub(crate) async fn close(self) -> Result<(), azure_core::Error> {
+        match self {
+            Self::Amqp(producer) => producer.close().await.map_err(Into::into),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(_) => Ok(()),
+        }
+    }
+}
+
+/// A producer that retries the sends and recovers the link with the client that created it
+pub(crate) enum RecoverableTransportProducer<'a, RP> {
+    Amqp(RecoverableAmqpProducer<'a, RP>),
+    #[cfg(feature = "in-memory-transport")]
+    InMemory(RecoverableInMemoryProducer<'a, RP>),
+}
+
+impl<'a, RP> RecoverableTransportProducer<'a, RP>
+where
+    RP: EventHubsRetryPolicy + Send,
+{
+    pub(crate) fn new(
+        producer: &'a mut TransportProducerKind<RP>,
+        client: &'a mut TransportClientKind,
+    ) -> Self {
+        match producer {
+            TransportProducerKind::Amqp(producer) => {
+                Self::Amqp(RecoverableAmqpProducer::new(producer, client.as_amqp_mut()))
+            }
+            #[cfg(feature = "in-memory-transport")]
+            TransportProducerKind::InMemory(producer) => Self::InMemory(
+                RecoverableInMemoryProducer::new(producer, client.as_in_memory_mut()),
+            ),
+        }
+    }
+}
+
+impl<'a, RP> TransportProducer for RecoverableTransportProducer<'a, RP>
+where
+    RP: EventHubsRetryPolicy + Send,
+{
+    type MessageBatch = AmqpEventBatch;
+
+    type SendError = azure_core::Error;
+    type CreateBatchError = CreateBatchError;
+
+    fn create_batch(
+        &self,
+        options: CreateBatchOptions,
+        reserve_idempotent_annotations: bool,
+    ) -> Result<Self::MessageBatch, Self::CreateBatchError> {
+        match self {
+            Self::Amqp(producer) => producer.create_batch(options, reserve_idempotent_annotations),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(producer) => {
+                producer.create_batch(options, reserve_idempotent_annotations)
+            }
+        }
+    }
+
+    async fn send(
+        &mut self,
+        events: impl ExactSizeIterator<Item = EventData> + Send,
+        options: SendEventOptions,
+    ) -> Result<(), Self::SendError> {
+        match self {
+            Self::Amqp(producer) => producer.send(events, options).await.map_err(Into::into),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(producer) => producer.send(events, options).await.map_err(Into::into),
+        }
+    }
+
+    async fn send_batch(
+        &mut self,
+        batch: Self::MessageBatch,
+        options: SendEventOptions,
+    ) -> Result<(), Self::SendError> {
+        match self {
+            Self::Amqp(producer) => producer
+                .send_batch(batch, options)
+                .await
+                .map_err(Into::into),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(producer) => producer
+                .send_batch(batch, options)
+                .await
+                .map_err(Into::into),
+        }
+    }
+}
+
+// The in-memory variant is small, but it is only used for testing
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub(crate) enum TransportConsumerKind<RP> {
+    Amqp(AmqpConsumer<RP>),
+    #[cfg(feature = "in-memory-transport")]
+    InMemory(InMemoryConsumer<RP>),
+}
+
+impl<RP> TransportConsumerKind<RP> {
+    pub(crate) fn into_amqp(self) -> AmqpConsumer<RP> {
+        match self {
+            Self::Amqp(consumer) => consumer,
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(_) => unreachable!("{}", MISMATCHED_TRANSPORT),
+        }
+    }
+
+    /// The ID of the partition that the consumer reads from
+    pub(crate) fn partition_id(&self) -> Arc<String> {
+        match self {
+            Self::Amqp(consumer) => Arc::new(consumer.initial_options.partition_id.clone()),
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(consumer) => consumer.partition_id.clone(),
+        }
+    }
+
+    #[cfg(feature = "in-memory-transport")]
+    pub(crate) fn into_in_memory(self) -> InMemoryConsumer<RP> {
+        match self {
+            Self::InMemory(consumer) => consumer,
+            Self::Amqp(_) => unreachable!("{}", MISMATCHED_TRANSPORT),
+        }
+    }
+
+    pub(crate) async fn close(self) -> Result<(), DisposeConsumerError> {
+        match self {
+            Self::Amqp(consumer) => consumer.close().await,
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(_) => Ok(()),
+        }
+    }
+}
+
+impl<RP> TransportConsumerKind<RP>
+where
+    RP: EventHubsRetryPolicy + Send,
+{
+    pub(crate) async fn receive_event_batch(
+        &mut self,
+        client: &mut TransportClientKind,
+        buffer: &mut VecDeque<ReceivedEventData>,
+        max_wait_time: Option<StdDuration>,
+    ) -> Result<(), azure_core::Error> {
+        match self {
+            Self::Amqp(consumer) => {
+                single::receive_event_batch(client.as_amqp_mut(), consumer, buffer, max_wait_time)
+                    .await
+                    .map_err(Into::into)
+            }
+            #[cfg(feature = "in-memory-transport")]
+            Self::InMemory(consumer) => in_memory_consumer::receive_event_batch(
+                client.as_in_memory_mut(),
+                consumer,
+                buffer,
+                max_wait_time,
+            )
+            .await
+            .map_err(Into::into),
+        }
+    }
+}
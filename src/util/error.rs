@@ -12,6 +12,10 @@ use fe2o3_amqp::{
     session::BeginError,
 };
 use fe2o3_amqp_management::error::Error as ManagementError;
+use fe2o3_amqp_types::{definitions, messaging::Outcome};
+use timer_kit::error::Elapsed;
+
+use crate::amqp::amqp_error::{is_server_busy_condition, is_transient_condition};
 
 impl IntoAzureCoreError for BeginError {
     fn into_azure_core_error(self) -> azure_core::Error {
@@ -296,4 +300,325 @@ impl ResumableLinkError for SenderResumeErrorKind {
             SenderResumeErrorKind::Timeout => true,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Classifies whether an operation that failed with the error may succeed if it is retried
+pub(crate) trait TransientError {
+    fn is_transient(&self) -> bool;
+
+    /// Returns true if the service reported that it is busy and the client should throttle its
+    /// requests
+    fn is_server_busy(&self) -> bool {
+        false
+    }
+}
+
+impl TransientError for definitions::Error {
+    fn is_transient(&self) -> bool {
+        is_transient_condition(&self.condition)
+    }
+
+    fn is_server_busy(&self) -> bool {
+        is_server_busy_condition(&self.condition)
+    }
+}
+
+impl TransientError for DetachError {
+    fn is_transient(&self) -> bool {
+        match self {
+            DetachError::RemoteDetachedWithError(err) => err.is_transient(),
+            DetachError::RemoteClosedWithError(err) => err.is_transient(),
+            DetachError::IllegalState
+            | DetachError::IllegalSessionState
+            | DetachError::ClosedByRemote
+            | DetachError::DetachedByRemote => true,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            DetachError::RemoteDetachedWithError(err) => err.is_server_busy(),
+            DetachError::RemoteClosedWithError(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for LinkStateError {
+    fn is_transient(&self) -> bool {
+        match self {
+            LinkStateError::RemoteDetachedWithError(err) => err.is_transient(),
+            LinkStateError::RemoteClosedWithError(err) => err.is_transient(),
+            LinkStateError::IllegalState
+            | LinkStateError::IllegalSessionState
+            | LinkStateError::RemoteDetached
+            | LinkStateError::RemoteClosed
+            | LinkStateError::ExpectImmediateDetach => true,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            LinkStateError::RemoteDetachedWithError(err) => err.is_server_busy(),
+            LinkStateError::RemoteClosedWithError(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for IllegalLinkStateError {
+    fn is_transient(&self) -> bool {
+        true
+    }
+}
+
+impl TransientError for SendError {
+    fn is_transient(&self) -> bool {
+        match self {
+            SendError::LinkStateError(err) => err.is_transient(),
+            SendError::Detached(err) => err.is_transient(),
+            SendError::NonTerminalDeliveryState => true,
+            SendError::IllegalDeliveryState => true,
+            SendError::MessageEncodeError => false,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            SendError::LinkStateError(err) => err.is_server_busy(),
+            SendError::Detached(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for RecvError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RecvError::LinkStateError(err) => err.is_transient(),
+            RecvError::MessageDecode(_) => false,
+            RecvError::TransferLimitExceeded
+            | RecvError::DeliveryIdIsNone
+            | RecvError::DeliveryTagIsNone
+            | RecvError::IllegalRcvSettleModeInTransfer
+            | RecvError::InconsistentFieldInMultiFrameDelivery
+            | RecvError::TransactionalAcquisitionIsNotImeplemented => true,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            RecvError::LinkStateError(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for SenderAttachError {
+    fn is_transient(&self) -> bool {
+        match self {
+            SenderAttachError::RemoteClosedWithError(err) => err.is_transient(),
+            SenderAttachError::IllegalSessionState
+            | SenderAttachError::IllegalState
+            | SenderAttachError::NonAttachFrameReceived
+            | SenderAttachError::ExpectImmediateDetach => true,
+            // The remaining errors indicate that the attach is rejected
+            _ => false,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            SenderAttachError::RemoteClosedWithError(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for ReceiverAttachError {
+    fn is_transient(&self) -> bool {
+        match self {
+            ReceiverAttachError::RemoteClosedWithError(err) => err.is_transient(),
+            ReceiverAttachError::IllegalSessionState
+            | ReceiverAttachError::IllegalState
+            | ReceiverAttachError::NonAttachFrameReceived
+            | ReceiverAttachError::ExpectImmediateDetach => true,
+            // The remaining errors indicate that the attach is rejected
+            _ => false,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            ReceiverAttachError::RemoteClosedWithError(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for SenderResumeErrorKind {
+    fn is_transient(&self) -> bool {
+        match self {
+            SenderResumeErrorKind::AttachError(err) => err.is_transient(),
+            SenderResumeErrorKind::SendError(err) => err.is_transient(),
+            SenderResumeErrorKind::DetachError(err) => err.is_transient(),
+            SenderResumeErrorKind::Timeout => true,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            SenderResumeErrorKind::AttachError(err) => err.is_server_busy(),
+            SenderResumeErrorKind::SendError(err) => err.is_server_busy(),
+            SenderResumeErrorKind::DetachError(err) => err.is_server_busy(),
+            SenderResumeErrorKind::Timeout => false,
+        }
+    }
+}
+
+impl TransientError for ReceiverResumeErrorKind {
+    fn is_transient(&self) -> bool {
+        match self {
+            ReceiverResumeErrorKind::AttachError(err) => err.is_transient(),
+            ReceiverResumeErrorKind::FlowError(err) => err.is_transient(),
+            ReceiverResumeErrorKind::DetachError(err) => err.is_transient(),
+            ReceiverResumeErrorKind::Timeout => true,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            ReceiverResumeErrorKind::AttachError(err) => err.is_server_busy(),
+            ReceiverResumeErrorKind::DetachError(err) => err.is_server_busy(),
+            ReceiverResumeErrorKind::FlowError(_) | ReceiverResumeErrorKind::Timeout => false,
+        }
+    }
+}
+
+impl TransientError for BeginError {
+    fn is_transient(&self) -> bool {
+        match self {
+            BeginError::RemoteEndedWithError(err) => err.is_transient(),
+            BeginError::IllegalState
+            | BeginError::IllegalConnectionState
+            | BeginError::RemoteEnded
+            | BeginError::LocalChannelMaxReached => true,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            BeginError::RemoteEndedWithError(err) => err.is_server_busy(),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for OpenError {
+    fn is_transient(&self) -> bool {
+        match self {
+            OpenError::RemoteClosedWithError(err) => err.is_transient(),
+            OpenError::Io(_)
+            | OpenError::TransportError(_)
+            | OpenError::IllegalState
+            | OpenError::RemoteClosed => true,
+            // Invalid configuration or rejected authentication
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for fe2o3_amqp::session::Error {
+    fn is_transient(&self) -> bool {
+        match self {
+            fe2o3_amqp::session::Error::RemoteEndedWithError(err) => err.is_transient(),
+            _ => true,
+        }
+    }
+}
+
+impl TransientError for fe2o3_amqp::connection::Error {
+    fn is_transient(&self) -> bool {
+        match self {
+            fe2o3_amqp::connection::Error::RemoteClosedWithError(err) => err.is_transient(),
+            fe2o3_amqp::connection::Error::TransportError(_)
+            | fe2o3_amqp::connection::Error::IllegalState
+            | fe2o3_amqp::connection::Error::RemoteClosed => true,
+            fe2o3_amqp::connection::Error::NotImplemented(_)
+            | fe2o3_amqp::connection::Error::NotFound(_)
+            | fe2o3_amqp::connection::Error::NotAllowed(_)
+            | fe2o3_amqp::connection::Error::JoinError(_) => false,
+        }
+    }
+}
+
+impl TransientError for fe2o3_amqp_ws::Error {
+    fn is_transient(&self) -> bool {
+        true
+    }
+}
+
+impl TransientError for ManagementError {
+    fn is_transient(&self) -> bool {
+        match self {
+            ManagementError::Send(err) => err.is_transient(),
+            ManagementError::Recv(err) => err.is_transient(),
+            ManagementError::Disposition(err) => err.is_transient(),
+            // The status codes follow the HTTP status codes
+            ManagementError::Status(err) => matches!(u16::from(err.code.0), 408 | 429 | 500..),
+            ManagementError::NotAccepted(Outcome::Rejected(rejected)) => rejected
+                .error
+                .as_ref()
+                .map(|err| err.is_transient())
+                .unwrap_or(false),
+            ManagementError::NotAccepted(_) => true,
+            ManagementError::CorrelationIdAndMessageIdAreNone
+            | ManagementError::StatusCodeNotFound
+            | ManagementError::DecodeError(_) => false,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        match self {
+            ManagementError::Send(err) => err.is_server_busy(),
+            ManagementError::Recv(err) => err.is_server_busy(),
+            // 503 Service Unavailable
+            ManagementError::Status(err) => u16::from(err.code.0) == 503,
+            ManagementError::NotAccepted(Outcome::Rejected(rejected)) => rejected
+                .error
+                .as_ref()
+                .map(|err| err.is_server_busy())
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+}
+
+impl TransientError for Elapsed {
+    fn is_transient(&self) -> bool {
+        true
+    }
+}
+
+impl TransientError for azure_core::Error {
+    fn is_transient(&self) -> bool {
+        use azure_core::error::ErrorKind;
+
+        match self.kind() {
+            ErrorKind::HttpResponse { status, .. } => {
+                matches!(u16::from(*status), 408 | 429 | 500..)
+            }
+            ErrorKind::Credential | ErrorKind::DataConversion => false,
+            _ => true,
+        }
+    }
+
+    fn is_server_busy(&self) -> bool {
+        use azure_core::error::ErrorKind;
+
+        matches!(
+            self.kind(),
+            ErrorKind::HttpResponse { status, .. } if u16::from(*status) == 503
+        )
+    }
+}
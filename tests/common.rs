@@ -1,3 +1,164 @@
+//! The Event Hubs namespace that the live tests run against.
+//!
+//! It is configured with the environment variables, which can be put in a `.env` file. With the
+//! `amqp-emulator` feature the tests run against an [`EventHubsEmulator`] instead, which is started
+//! once for each test binary, so that they can run without an Event Hubs namespace.
+
+#![allow(dead_code)]
+
+use azeventhubs::EventHubConnectionOptions;
+
+#[cfg(feature = "amqp-emulator")]
+use azeventhubs::{emulator::EventHubsEmulator, in_memory::InMemoryEventHub};
+
 pub fn setup_dotenv() {
     let _ = dotenv::from_filename(".env");
 }
+
+/// The name of the Event Hub that the emulator serves
+#[cfg(feature = "amqp-emulator")]
+const EMULATED_EVENT_HUB: &str = "live-tests";
+
+/// The number of partitions of the Event Hub that the emulator serves
+#[cfg(feature = "amqp-emulator")]
+const EMULATED_PARTITION_COUNT: usize = 4;
+
+/// The number of events that are published to each partition before the tests run, so that the
+/// consumers of the tests find more events than the tests publish themselves
+#[cfg(feature = "amqp-emulator")]
+const SEEDED_EVENTS_PER_PARTITION: usize = 32;
+
+/// Publishes [`SEEDED_EVENTS_PER_PARTITION`] events to each partition of the Event Hub
+#[cfg(feature = "amqp-emulator")]
+async fn seed_events(event_hub: &InMemoryEventHub) {
+    use azeventhubs::{
+        producer::{EventHubProducerClient, SendEventOptions},
+        EventHubConnection,
+    };
+
+    let mut connection = EventHubConnection::new_from_in_memory_event_hub(event_hub);
+    let mut producer = EventHubProducerClient::with_connection(&mut connection, Default::default());
+    for partition_id in event_hub.partition_ids() {
+        for i in 0..SEEDED_EVENTS_PER_PARTITION {
+            let event = format!("Event {} seeded in partition {}", i, partition_id);
+            let options = SendEventOptions::new().with_partition_id(&partition_id);
+            producer.send_event(event, options).await.unwrap();
+        }
+    }
+    producer.close().await.unwrap();
+    connection.close().await.unwrap();
+}
+
+/// The emulator that the tests of a test binary share.
+///
+/// Each test has its own runtime, so the emulator runs on a runtime of its own that outlives them.
+/// The Event Hub is seeded with events before the emulator starts.
+#[cfg(feature = "amqp-emulator")]
+fn emulator() -> &'static EventHubsEmulator {
+    static EMULATOR: std::sync::OnceLock<EventHubsEmulator> = std::sync::OnceLock::new();
+
+    EMULATOR.get_or_init(|| {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            let event_hub = InMemoryEventHub::new(EMULATED_EVENT_HUB, EMULATED_PARTITION_COUNT);
+            runtime.block_on(seed_events(&event_hub));
+            let emulator = runtime
+                .block_on(EventHubsEmulator::start(event_hub))
+                .unwrap();
+            sender.send(emulator).unwrap();
+            runtime.block_on(std::future::pending::<()>());
+        });
+        receiver.recv().unwrap()
+    })
+}
+
+#[cfg(not(feature = "amqp-emulator"))]
+fn env_var(key: &str) -> String {
+    setup_dotenv();
+    std::env::var(key).unwrap_or_else(|_| panic!("{} is not set", key))
+}
+
+/// A connection string of the namespace, without the name of the Event Hub
+pub fn connection_string() -> String {
+    #[cfg(feature = "amqp-emulator")]
+    {
+        let connection_string = emulator().connection_string();
+        match connection_string.split_once(";EntityPath=") {
+            Some((namespace, _)) => namespace.to_string(),
+            None => connection_string,
+        }
+    }
+    #[cfg(not(feature = "amqp-emulator"))]
+    {
+        env_var("EVENT_HUBS_CONNECTION_STRING")
+    }
+}
+
+/// A connection string that includes the name of the Event Hub
+pub fn connection_string_with_entity_path() -> String {
+    #[cfg(feature = "amqp-emulator")]
+    {
+        emulator().connection_string()
+    }
+    #[cfg(not(feature = "amqp-emulator"))]
+    {
+        env_var("EVENT_HUBS_CONNECTION_STRING_WITH_ENTITY_PATH")
+    }
+}
+
+/// The name of the Event Hub
+pub fn event_hub_name() -> String {
+    #[cfg(feature = "amqp-emulator")]
+    {
+        emulator().event_hub().event_hub_name().to_string()
+    }
+    #[cfg(not(feature = "amqp-emulator"))]
+    {
+        env_var("EVENT_HUB_NAME")
+    }
+}
+
+/// The fully qualified name of the namespace
+pub fn fully_qualified_namespace() -> String {
+    #[cfg(feature = "amqp-emulator")]
+    {
+        // The connection is made to the address of the emulator, whatever the namespace is
+        "localhost".to_string()
+    }
+    #[cfg(not(feature = "amqp-emulator"))]
+    {
+        format!("{}.servicebus.windows.net", env_var("EVENT_HUBS_NAMESPACE"))
+    }
+}
+
+/// The name and the key of a shared access policy of the namespace
+pub fn shared_access_key() -> (String, String) {
+    #[cfg(feature = "amqp-emulator")]
+    {
+        // The emulator does not validate the tokens
+        ("emulator".to_string(), "emulator".to_string())
+    }
+    #[cfg(not(feature = "amqp-emulator"))]
+    {
+        (
+            env_var("EVENT_HUBS_SHARED_ACCESS_KEY_NAME"),
+            env_var("EVENT_HUBS_SHARED_ACCESS_KEY"),
+        )
+    }
+}
+
+/// The options of the connections to the namespace
+pub fn connection_options() -> EventHubConnectionOptions {
+    #[cfg(feature = "amqp-emulator")]
+    {
+        emulator().connection_options()
+    }
+    #[cfg(not(feature = "amqp-emulator"))]
+    {
+        EventHubConnectionOptions::default()
+    }
+}
@@ -0,0 +1,97 @@
+#![cfg(all(test, feature = "test_e2e"))]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use azeventhubs::producer::{
+    EnqueueEventOptions, EventHubBufferedProducerClient, EventHubBufferedProducerClientOptions,
+    EventHubProducerClient, EventHubProducerClientOptions,
+};
+
+#[macro_use]
+mod cfg;
+
+mod common;
+
+cfg_not_wasm32! {
+    async fn create_buffered_producer(
+        options: EventHubBufferedProducerClientOptions,
+        published: Arc<AtomicUsize>,
+        failed: Arc<AtomicUsize>,
+    ) -> EventHubBufferedProducerClient {
+        let connection_string = common::connection_string_with_entity_path();
+        let producer_client = EventHubProducerClient::new_from_connection_string(
+            connection_string,
+            None,
+            EventHubProducerClientOptions {
+                connection_options: common::connection_options(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        EventHubBufferedProducerClient::new(
+            producer_client,
+            options,
+            move |args| {
+                published.fetch_add(args.events.len(), Ordering::SeqCst);
+            },
+            move |args| {
+                failed.fetch_add(args.events.len(), Ordering::SeqCst);
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn buffered_producer_publishes_all_events_on_close() {
+        common::setup_dotenv();
+
+        let published = Arc::new(AtomicUsize::new(0));
+        let failed = Arc::new(AtomicUsize::new(0));
+        let options = EventHubBufferedProducerClientOptions::new().with_maximum_wait_time(None);
+        let client = create_buffered_producer(options, published.clone(), failed.clone()).await;
+
+        let events = (0..30).map(|i| format!("buffered event {}", i).into());
+        let buffered = client
+            .enqueue_events(events, EnqueueEventOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(buffered, 30);
+
+        client
+            .enqueue_event("buffered event to partition 0", EnqueueEventOptions::new().with_partition_id("0"))
+            .await
+            .unwrap();
+        client
+            .enqueue_event("buffered event with key", EnqueueEventOptions::new().with_partition_key("key"))
+            .await
+            .unwrap();
+
+        client.close().await.unwrap();
+        assert_eq!(published.load(Ordering::SeqCst), 32);
+        assert_eq!(failed.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn buffered_producer_publishes_after_maximum_wait_time() {
+        common::setup_dotenv();
+
+        let published = Arc::new(AtomicUsize::new(0));
+        let failed = Arc::new(AtomicUsize::new(0));
+        let options = EventHubBufferedProducerClientOptions::new()
+            .with_maximum_wait_time(std::time::Duration::from_millis(500));
+        let client = create_buffered_producer(options, published.clone(), failed.clone()).await;
+
+        client
+            .enqueue_event("buffered event", EnqueueEventOptions::new().with_partition_id("0"))
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        assert_eq!(published.load(Ordering::SeqCst), 1);
+
+        client.close().await.unwrap();
+    }
+}
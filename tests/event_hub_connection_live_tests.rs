@@ -2,7 +2,7 @@
 
 use std::cfg;
 
-use azeventhubs::{EventHubConnection, EventHubConnectionOptions, EventHubsTransportType};
+use azeventhubs::{EventHubConnection, EventHubsTransportType};
 use azure_identity::TokenCredentialOptions;
 
 #[macro_use]
@@ -15,8 +15,8 @@ cfg_not_wasm32! {
     async fn connection_can_connect_to_event_hubs_using_full_connection_string_over_tcp() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING_WITH_ENTITY_PATH").unwrap();
-        let options = EventHubConnectionOptions::default();
+        let connection_string = common::connection_string_with_entity_path();
+        let options = common::connection_options();
         let connection = EventHubConnection::new_from_connection_string(connection_string, None, options)
             .await
             .unwrap();
@@ -24,13 +24,14 @@ cfg_not_wasm32! {
     }
 
     #[tokio::test]
+    #[cfg_attr(feature = "amqp-emulator", ignore = "the emulator only serves AmqpTcp")]
     async fn connection_can_connect_to_event_hubs_using_full_connection_string_and_event_hub_over_websockets(
     ) {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
-        let mut options = EventHubConnectionOptions::default();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
+        let mut options = common::connection_options();
         options.transport_type = EventHubsTransportType::AmqpWebSockets;
         let connection =
             EventHubConnection::new_from_connection_string(connection_string, event_hub_name, options)
@@ -44,9 +45,9 @@ cfg_not_wasm32! {
     {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
-        let options = EventHubConnectionOptions::default();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
+        let options = common::connection_options();
         let connection =
             EventHubConnection::new_from_connection_string(connection_string, event_hub_name, options)
                 .await
@@ -59,13 +60,11 @@ cfg_not_wasm32! {
         common::setup_dotenv();
         use azeventhubs::authorization::AzureNamedKeyCredential;
 
-        let options = EventHubConnectionOptions::default();
+        let options = common::connection_options();
 
-        let namespace = std::env::var("EVENT_HUBS_NAMESPACE").unwrap();
-        let fqn = format!("{}.servicebus.windows.net", namespace);
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
-        let key_name = std::env::var("EVENT_HUBS_SHARED_ACCESS_KEY_NAME").unwrap();
-        let key = std::env::var("EVENT_HUBS_SHARED_ACCESS_KEY").unwrap();
+        let fqn = common::fully_qualified_namespace();
+        let event_hub_name = common::event_hub_name();
+        let (key_name, key) = common::shared_access_key();
 
         let named_key_credential = AzureNamedKeyCredential::new(key_name, key);
 
@@ -79,16 +78,16 @@ cfg_not_wasm32! {
     }
 
     #[tokio::test]
+    #[cfg_attr(feature = "amqp-emulator", ignore = "the emulator cannot be reached with Entra ID")]
     async fn connection_can_connect_with_azure_identity_credential() {
         common::setup_dotenv();
 
         use azure_identity::DefaultAzureCredential;
 
-        let namespace = std::env::var("EVENT_HUBS_NAMESPACE").unwrap();
-        let fqn = format!("{}.servicebus.windows.net", namespace);
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
+        let fqn = common::fully_qualified_namespace();
+        let event_hub_name = common::event_hub_name();
 
-        let options = EventHubConnectionOptions::default();
+        let options = common::connection_options();
         let credential = DefaultAzureCredential::create(TokenCredentialOptions::default()).unwrap();
 
         let connection = EventHubConnection::new_from_credential(
@@ -24,8 +24,11 @@ cfg_not_wasm32! {
 
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING_WITH_ENTITY_PATH").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let connection_string = common::connection_string_with_entity_path();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, None, options)
                 .await
@@ -52,8 +55,8 @@ cfg_not_wasm32! {
 
         prepare_events_on_eventhubs(30, Some("0")).await;
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
         let consumer_group = EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME;
 
         let mut retry_options = EventHubsRetryOptions::default();
@@ -61,6 +64,7 @@ cfg_not_wasm32! {
         retry_options.try_timeout = std::time::Duration::from_secs(5);
         let mut options = EventHubConsumerClientOptions::default();
         options.retry_options = retry_options;
+        options.connection_options = common::connection_options();
 
         let mut consumer = EventHubConsumerClient::new_from_connection_string(
             consumer_group,
@@ -104,8 +108,8 @@ cfg_not_wasm32! {
 
         prepare_events_on_eventhubs(30, None).await;
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
         let consumer_group = EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME;
 
         let mut retry_options = EventHubsRetryOptions::default();
@@ -113,6 +117,7 @@ cfg_not_wasm32! {
         retry_options.try_timeout = std::time::Duration::from_secs(5);
         let mut options = EventHubConsumerClientOptions::default();
         options.retry_options = retry_options;
+        options.connection_options = common::connection_options();
 
         let mut consumer = EventHubConsumerClient::new_from_connection_string(
             consumer_group,
@@ -154,8 +159,8 @@ cfg_not_wasm32! {
 
         prepare_events_on_eventhubs(30, None).await;
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
         let consumer_group = EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME;
 
         let mut retry_options = EventHubsRetryOptions::default();
@@ -163,6 +168,7 @@ cfg_not_wasm32! {
         retry_options.try_timeout = std::time::Duration::from_secs(5);
         let mut options = EventHubConsumerClientOptions::default();
         options.retry_options = retry_options;
+        options.connection_options = common::connection_options();
 
         let handle = tokio::spawn(async move {
             let mut consumer = EventHubConsumerClient::new_from_connection_string(
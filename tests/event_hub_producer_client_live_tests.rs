@@ -4,7 +4,7 @@ use azeventhubs::{
     producer::{
         CreateBatchOptions, EventHubProducerClient, EventHubProducerClientOptions, SendEventOptions,
     },
-    EventHubConnection, EventHubConnectionOptions,
+    EventHubConnection,
 };
 use azure_identity::TokenCredentialOptions;
 
@@ -18,8 +18,11 @@ cfg_not_wasm32! {
     async fn producer_client_can_connect_to_event_hubs_using_full_connection_string_over_tcp() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING_WITH_ENTITY_PATH").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let connection_string = common::connection_string_with_entity_path();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, None, options)
                 .await
@@ -33,12 +36,13 @@ cfg_not_wasm32! {
 
         common::setup_dotenv();
 
-        let namespace = std::env::var("EVENT_HUBS_NAMESPACE").unwrap();
-        let fqn = format!("{}.servicebus.windows.net", namespace);
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
-        let key_name = std::env::var("EVENT_HUBS_SHARED_ACCESS_KEY_NAME").unwrap();
-        let key = std::env::var("EVENT_HUBS_SHARED_ACCESS_KEY").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let fqn = common::fully_qualified_namespace();
+        let event_hub_name = common::event_hub_name();
+        let (key_name, key) = common::shared_access_key();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let named_key_credential = AzureNamedKeyCredential::new(key_name, key);
 
         let mut producer_client = EventHubProducerClient::new_from_named_key_credential(
@@ -56,15 +60,18 @@ cfg_not_wasm32! {
     }
 
     #[tokio::test]
+    #[cfg_attr(feature = "amqp-emulator", ignore = "the emulator cannot be reached with Entra ID")]
     async fn producer_client_can_connect_using_azure_identity_credential() {
         use azure_identity::DefaultAzureCredential;
 
         common::setup_dotenv();
 
-        let namespace = std::env::var("EVENT_HUBS_NAMESPACE").unwrap();
-        let fqn = format!("{}.servicebus.windows.net", namespace);
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let fqn = common::fully_qualified_namespace();
+        let event_hub_name = common::event_hub_name();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let default_credential = DefaultAzureCredential::create(TokenCredentialOptions::default()).unwrap();
 
         let mut producer_client = EventHubProducerClient::new_from_credential(
@@ -85,8 +92,8 @@ cfg_not_wasm32! {
     async fn close_producer_client_does_not_close_shared_connection() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING_WITH_ENTITY_PATH").unwrap();
-        let connection_options = EventHubConnectionOptions::default();
+        let connection_string = common::connection_string_with_entity_path();
+        let connection_options = common::connection_options();
         let mut connection =
             EventHubConnection::new_from_connection_string(connection_string, None, connection_options)
                 .await
@@ -108,8 +115,11 @@ cfg_not_wasm32! {
     async fn producer_client_can_send_an_event_to_a_partition() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING_WITH_ENTITY_PATH").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let connection_string = common::connection_string_with_entity_path();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, None, options)
                 .await
@@ -126,8 +136,11 @@ cfg_not_wasm32! {
     async fn producer_client_can_send_without_specifying_partition_id() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING_WITH_ENTITY_PATH").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let connection_string = common::connection_string_with_entity_path();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, None, options)
                 .await
@@ -148,8 +161,11 @@ cfg_not_wasm32! {
 
         // env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace")).init();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING_WITH_ENTITY_PATH").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let connection_string = common::connection_string_with_entity_path();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, None, options)
                 .await
@@ -174,9 +190,12 @@ cfg_not_wasm32! {
     async fn producer_client_can_get_event_hub_properties() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, event_hub_name, options)
                 .await
@@ -191,9 +210,12 @@ cfg_not_wasm32! {
     async fn producer_client_can_get_partition_properties() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, event_hub_name, options)
                 .await
@@ -203,4 +225,65 @@ cfg_not_wasm32! {
 
         producer_client.close().await.unwrap();
     }
+
+    #[tokio::test]
+    #[cfg_attr(feature = "amqp-emulator", ignore = "idempotent publishing is not emulated")]
+    async fn idempotent_producer_client_can_send_batches_to_a_partition() {
+        common::setup_dotenv();
+
+        let connection_string = common::connection_string_with_entity_path();
+        let options = EventHubProducerClientOptions {
+            enable_idempotent_partitions: true,
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
+        let mut producer_client =
+            EventHubProducerClient::new_from_connection_string(connection_string, None, options)
+                .await
+                .unwrap();
+
+        let options = SendEventOptions::new().with_partition_id("0");
+        producer_client
+            .send_events(vec!["idempotent 1".into(), "idempotent 2".into()], options.clone())
+            .await
+            .unwrap();
+
+        let mut batch = producer_client.create_batch(CreateBatchOptions::default()).await.unwrap();
+        batch.try_add("idempotent 3").unwrap();
+        producer_client.send_batch(batch, options).await.unwrap();
+
+        // Publishing through the gateway is not allowed
+        let result = producer_client
+            .send_event("idempotent 4", SendEventOptions::default())
+            .await;
+        assert!(result.is_err());
+
+        producer_client.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(feature = "amqp-emulator", ignore = "idempotent publishing is not emulated")]
+    async fn producer_client_can_get_partition_publishing_properties() {
+        common::setup_dotenv();
+
+        let connection_string = common::connection_string_with_entity_path();
+        let options = EventHubProducerClientOptions {
+            enable_idempotent_partitions: true,
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
+        let mut producer_client =
+            EventHubProducerClient::new_from_connection_string(connection_string, None, options)
+                .await
+                .unwrap();
+
+        let properties = producer_client
+            .get_partition_publishing_properties("0")
+            .await
+            .unwrap();
+        assert!(properties.is_idempotent_publishing_enabled());
+        assert!(properties.producer_group_id().is_some());
+
+        producer_client.close().await.unwrap();
+    }
 }
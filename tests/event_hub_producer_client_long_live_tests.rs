@@ -19,10 +19,13 @@ cfg_not_wasm32! {
 
         // env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace")).init();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
         let partition_id = "0";
-        let options = EventHubProducerClientOptions::default();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, event_hub_name, options)
                 .await
@@ -53,10 +56,13 @@ cfg_not_wasm32! {
     async fn producer_client_can_recover_and_send_after_sleeping_for_40_mins() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
         let partition_id = "0";
-        let options = EventHubProducerClientOptions::default();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, event_hub_name, options)
                 .await
@@ -90,9 +96,12 @@ cfg_not_wasm32! {
     async fn producer_client_can_recover_and_get_properties_after_idling_40_mins() {
         common::setup_dotenv();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
-        let options = EventHubProducerClientOptions::default();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
+        let options = EventHubProducerClientOptions {
+            connection_options: common::connection_options(),
+            ..Default::default()
+        };
         let mut producer_client =
             EventHubProducerClient::new_from_connection_string(connection_string, event_hub_name, options)
                 .await
@@ -0,0 +1,732 @@
+#![cfg(all(test, not(target_arch = "wasm32"), feature = "amqp-emulator"))]
+
+//! These tests run the clients against an [`EventHubsEmulator`] over a real AMQP connection.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration as StdDuration,
+};
+
+use azeventhubs::{
+    authorization::{
+        EventHubTokenCredential, EventHubTokenProvider, EventHubTokenType, GetTokenFuture,
+    },
+    consumer::{
+        EventHubConsumerClient, EventHubConsumerClientOptions, EventPosition, ReadEventOptions,
+    },
+    emulator::EventHubsEmulator,
+    in_memory::InMemoryEventHub,
+    primitives::{PartitionReceiver, PartitionReceiverOptions},
+    producer::{
+        CreateBatchOptions, EventHubProducerClient, EventHubProducerClientOptions, SendEventOptions,
+    },
+    AmqpAnnotatedMessage, BasicRetryPolicy, EventData, EventHubConnection, EventHubConnectionPool,
+    EventHubConnectionPoolOptions, EventHubsProxyOptions, EventHubsRetryOptions, ReceivedEventData,
+};
+use azure_core::auth::AccessToken;
+use fe2o3_amqp_types::{messaging::annotations::AnnotationKey, primitives::Value};
+use futures_util::StreamExt;
+
+const EVENT_HUB: &str = "emulated-hub";
+
+async fn start_emulator(partition_count: usize) -> EventHubsEmulator {
+    EventHubsEmulator::start(InMemoryEventHub::new(EVENT_HUB, partition_count))
+        .await
+        .unwrap()
+}
+
+async fn create_producer(emulator: &EventHubsEmulator) -> EventHubProducerClient<BasicRetryPolicy> {
+    let options = EventHubProducerClientOptions {
+        connection_options: emulator.connection_options(),
+        ..Default::default()
+    };
+    EventHubProducerClient::new_from_connection_string(emulator.connection_string(), None, options)
+        .await
+        .unwrap()
+}
+
+async fn create_consumer(emulator: &EventHubsEmulator) -> EventHubConsumerClient<BasicRetryPolicy> {
+    let options = EventHubConsumerClientOptions {
+        connection_options: emulator.connection_options(),
+        ..Default::default()
+    };
+    EventHubConsumerClient::new_from_connection_string(
+        EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+        emulator.connection_string(),
+        None,
+        options,
+    )
+    .await
+    .unwrap()
+}
+
+fn body(event: &ReceivedEventData) -> &[u8] {
+    event.body().unwrap()
+}
+
+#[tokio::test]
+async fn get_event_hub_and_partition_properties() {
+    let emulator = start_emulator(2).await;
+    let mut producer = create_producer(&emulator).await;
+
+    let properties = producer.get_event_hub_properties().await.unwrap();
+    assert_eq!(properties.name(), EVENT_HUB);
+    assert_eq!(properties.partition_ids(), ["0", "1"]);
+
+    producer
+        .send_event("hello", SendEventOptions::new().with_partition_id("1"))
+        .await
+        .unwrap();
+    let properties = producer.get_partition_properties("1").await.unwrap();
+    assert_eq!(properties.id(), "1");
+    assert!(!properties.is_empty());
+    assert_eq!(properties.last_enqueued_sequence_number(), 0);
+
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn events_sent_by_producer_are_enqueued() {
+    let emulator = start_emulator(1).await;
+    let mut producer = create_producer(&emulator).await;
+
+    let options = SendEventOptions::new().with_partition_id("0");
+    producer.send_event("a", options.clone()).await.unwrap();
+    let events = ["b", "c"].map(EventData::from);
+    producer.send_events(events, options.clone()).await.unwrap();
+
+    let mut batch = producer
+        .create_batch(CreateBatchOptions::default())
+        .await
+        .unwrap();
+    batch.try_add("d").unwrap();
+    batch.try_add("e").unwrap();
+    producer.send_batch(batch, options).await.unwrap();
+
+    let bodies: Vec<_> = emulator
+        .event_hub()
+        .events("0")
+        .iter()
+        .map(|event| body(event).to_vec())
+        .collect();
+    assert_eq!(bodies, [b"a", b"b", b"c", b"d", b"e"].map(|b| b.to_vec()));
+
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn events_are_read_from_partition() {
+    let emulator = start_emulator(2).await;
+    let mut producer = create_producer(&emulator).await;
+    let mut consumer = create_consumer(&emulator).await;
+
+    let options = SendEventOptions::new().with_partition_id("1");
+    producer.send_event("first", options.clone()).await.unwrap();
+
+    let mut stream = consumer
+        .read_events_from_partition("1", EventPosition::earliest(), ReadEventOptions::default())
+        .await
+        .unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&first), b"first");
+
+    // The consumer link waits for the events that are sent after it is opened
+    producer.send_event("second", options).await.unwrap();
+    let second = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&second), b"second");
+    assert_eq!(second.sequence_number(), first.sequence_number() + 1);
+
+    stream.close().await.unwrap();
+    consumer.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn consumer_resumes_from_last_received_event_after_link_is_detached() {
+    let emulator = start_emulator(1).await;
+    let mut producer = create_producer(&emulator).await;
+    let mut consumer = create_consumer(&emulator).await;
+
+    let options = SendEventOptions::new().with_partition_id("0");
+    let events = ["a", "b"].map(EventData::from);
+    producer.send_events(events, options.clone()).await.unwrap();
+
+    let mut stream = consumer
+        .read_events_from_partition("0", EventPosition::earliest(), ReadEventOptions::default())
+        .await
+        .unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&first), b"a");
+    let second = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&second), b"b");
+
+    // The consumer link is detached, and recovered by the next read
+    emulator.event_hub().detach_links();
+    producer.send_event("c", options).await.unwrap();
+
+    // Neither the first event nor the last received one is read again
+    let third = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&third), b"c");
+    assert_eq!(third.sequence_number(), second.sequence_number() + 1);
+
+    stream.close().await.unwrap();
+    consumer.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn event_batches_are_read_from_partitions() {
+    let emulator = start_emulator(2).await;
+    let mut producer = create_producer(&emulator).await;
+    let mut consumer = create_consumer(&emulator).await;
+
+    for partition_id in ["0", "1"] {
+        let options = SendEventOptions::new().with_partition_id(partition_id);
+        let events = ["a", "b", "c"].map(EventData::from);
+        producer.send_events(events, options).await.unwrap();
+    }
+
+    let max_wait_time = StdDuration::from_millis(200);
+    let mut stream = consumer
+        .read_event_batches_from_partition(
+            "0",
+            EventPosition::earliest(),
+            2,
+            max_wait_time,
+            ReadEventOptions::default(),
+        )
+        .await
+        .unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    let second = stream.next().await.unwrap().unwrap();
+    let bodies: Vec<_> = first.iter().chain(&second).map(body).collect();
+    assert_eq!(first.len(), 2);
+    assert_eq!(bodies, [b"a", b"b", b"c"]);
+    stream.close().await.unwrap();
+
+    let mut stream = consumer
+        .read_event_batches(true, 10, max_wait_time, ReadEventOptions::default())
+        .await
+        .unwrap();
+    let mut received = 0;
+    while received < 6 {
+        let batch = stream.next().await.unwrap().unwrap();
+        assert!(batch.len() <= 10);
+        received += batch.len();
+    }
+    assert_eq!(received, 6);
+    stream.close().await.unwrap();
+
+    consumer.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn events_read_from_all_partitions_carry_partition_context() {
+    let emulator = start_emulator(3).await;
+    let mut producer = create_producer(&emulator).await;
+    let mut consumer = create_consumer(&emulator).await;
+
+    // Partition "0" is much busier than the others
+    let options = SendEventOptions::new().with_partition_id("0");
+    let events = std::iter::repeat("0").take(10).map(EventData::from);
+    producer.send_events(events, options).await.unwrap();
+    for partition_id in ["1", "2"] {
+        let options = SendEventOptions::new().with_partition_id(partition_id);
+        producer.send_event(partition_id, options).await.unwrap();
+    }
+
+    let mut stream = consumer
+        .read_events(true, ReadEventOptions::default())
+        .await
+        .unwrap();
+    let mut counts = std::collections::BTreeMap::new();
+    for _ in 0..12 {
+        let event = stream.next().await.unwrap().unwrap();
+        let context = event.partition_context();
+        assert_eq!(context.event_hub_name(), EVENT_HUB);
+        assert_eq!(context.partition_id().as_bytes(), event.body().unwrap());
+        *counts
+            .entry(context.partition_id().to_string())
+            .or_insert(0) += 1;
+    }
+    let counts: Vec<_> = counts.into_iter().collect();
+    assert_eq!(
+        counts,
+        [
+            ("0".to_string(), 10),
+            ("1".to_string(), 1),
+            ("2".to_string(), 1)
+        ]
+    );
+    stream.close().await.unwrap();
+
+    consumer.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn owned_streams_are_read_concurrently_in_spawned_tasks() {
+    let emulator = start_emulator(2).await;
+    let mut producer = create_producer(&emulator).await;
+    let mut consumer = create_consumer(&emulator).await;
+
+    for partition_id in ["0", "1"] {
+        let options = SendEventOptions::new().with_partition_id(partition_id);
+        let events = ["a", "b"].map(|body| EventData::from(format!("{partition_id}{body}")));
+        producer.send_events(events, options).await.unwrap();
+    }
+
+    let mut handles = Vec::new();
+    for partition_id in ["0", "1"] {
+        let mut stream = consumer
+            .read_owned_events_from_partition(
+                partition_id,
+                EventPosition::earliest(),
+                ReadEventOptions::default(),
+            )
+            .await
+            .unwrap();
+        handles.push(tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for _ in 0..2 {
+                let event = stream.next().await.unwrap().unwrap();
+                bodies.push(String::from_utf8(body(&event).to_vec()).unwrap());
+            }
+            stream.close().await.unwrap();
+            bodies
+        }));
+    }
+
+    let mut bodies = Vec::new();
+    for handle in handles {
+        bodies.push(handle.await.unwrap());
+    }
+    assert_eq!(bodies, [["0a", "0b"], ["1a", "1b"]]);
+
+    consumer.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn value_and_sequence_bodies_are_received() {
+    let emulator = start_emulator(1).await;
+    let mut producer = create_producer(&emulator).await;
+    let mut consumer = create_consumer(&emulator).await;
+
+    let mut value_message = AmqpAnnotatedMessage::from_value(Value::Long(42));
+    value_message
+        .message_annotations_mut()
+        .get_or_insert(Default::default())
+        .insert("x-opt-custom".into(), Value::from("annotated"));
+    let sequence_message =
+        AmqpAnnotatedMessage::from_sequence(vec![Value::from("a"), Value::Bool(true)]);
+    let events = [value_message, sequence_message].map(EventData::from);
+    producer
+        .send_events(events, SendEventOptions::new().with_partition_id("0"))
+        .await
+        .unwrap();
+
+    let mut stream = consumer
+        .read_events_from_partition("0", EventPosition::earliest(), ReadEventOptions::default())
+        .await
+        .unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert!(first.body().is_err());
+    let message = first.amqp_message();
+    assert_eq!(message.body().try_as_value().unwrap(), &Value::Long(42));
+    let annotation = message
+        .message_annotations()
+        .and_then(|annotations| annotations.get(&"x-opt-custom" as &dyn AnnotationKey));
+    assert_eq!(annotation, Some(&Value::from("annotated")));
+
+    let second = stream.next().await.unwrap().unwrap();
+    let sequences: Vec<_> = second
+        .amqp_message()
+        .body()
+        .try_as_sequence()
+        .unwrap()
+        .collect();
+    assert_eq!(sequences, [&vec![Value::from("a"), Value::Bool(true)]]);
+
+    stream.close().await.unwrap();
+    consumer.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn last_enqueued_event_properties_are_read_from_delivery_annotations() {
+    let emulator = start_emulator(1).await;
+    let mut producer = create_producer(&emulator).await;
+    let mut consumer = create_consumer(&emulator).await;
+    let events = ["a", "b"].map(EventData::from);
+    producer
+        .send_events(events, SendEventOptions::new().with_partition_id("0"))
+        .await
+        .unwrap();
+
+    let mut stream = consumer
+        .read_events_from_partition("0", EventPosition::earliest(), ReadEventOptions::default())
+        .await
+        .unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&first), b"a");
+    let properties = stream.read_last_enqueued_event_properties("0");
+    assert_eq!(properties.sequence_number(), Some(1));
+    assert_eq!(properties.offset(), Some(1));
+    assert!(properties.enqueued_time().is_some());
+    assert!(properties.last_received_time().is_some());
+
+    stream.close().await.unwrap();
+    consumer.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn partition_receiver_starts_from_event_position() {
+    let emulator = start_emulator(1).await;
+    let mut producer = create_producer(&emulator).await;
+    let events = ["a", "b", "c"].map(EventData::from);
+    producer
+        .send_events(events, SendEventOptions::new().with_partition_id("0"))
+        .await
+        .unwrap();
+
+    let options = PartitionReceiverOptions {
+        connection_options: emulator.connection_options(),
+        ..Default::default()
+    };
+    let mut receiver = PartitionReceiver::new_from_connection_string(
+        EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+        "0",
+        EventPosition::from_sequence_number(0, false),
+        emulator.connection_string(),
+        None,
+        options,
+    )
+    .await
+    .unwrap();
+    let bodies: Vec<_> = receiver
+        .recv_batch(2, StdDuration::from_secs(5))
+        .await
+        .unwrap()
+        .map(|event| body(&event).to_vec())
+        .collect();
+    assert_eq!(bodies, [b"b".to_vec(), b"c".to_vec()]);
+
+    receiver.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn unknown_event_hub_is_rejected() {
+    let emulator = start_emulator(1).await;
+    let options = EventHubProducerClientOptions {
+        connection_options: emulator.connection_options(),
+        ..Default::default()
+    };
+    let connection_string = emulator
+        .connection_string()
+        .replace(EVENT_HUB, "unknown-hub");
+    let mut producer =
+        EventHubProducerClient::new_from_connection_string(connection_string, None, options)
+            .await
+            .unwrap();
+
+    assert!(producer.get_event_hub_properties().await.is_err());
+}
+
+#[tokio::test]
+async fn proxy_is_rejected_with_amqp_tcp_transport() {
+    let emulator = start_emulator(1).await;
+    let mut connection_options = emulator.connection_options();
+    connection_options.proxy = Some(EventHubsProxyOptions::new(
+        url::Url::parse("http://127.0.0.1:3128").unwrap(),
+    ));
+    let options = EventHubProducerClientOptions {
+        connection_options,
+        ..Default::default()
+    };
+
+    let result = EventHubProducerClient::<BasicRetryPolicy>::new_from_connection_string(
+        emulator.connection_string(),
+        None,
+        options,
+    )
+    .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn events_are_sent_and_read_over_tuned_connection() {
+    let emulator = start_emulator(1).await;
+    let mut connection_options = emulator.connection_options();
+    connection_options.send_buffer_size_in_bytes = Some(32 * 1024);
+    connection_options.receive_buffer_size_in_bytes = Some(32 * 1024);
+    // An event that is larger than a frame is split across several transfer frames
+    connection_options.max_frame_size = 1024;
+    connection_options.channel_max = 16;
+    connection_options.session_incoming_window = 16;
+    connection_options.session_outgoing_window = 16;
+
+    let producer_options = EventHubProducerClientOptions {
+        connection_options: connection_options.clone(),
+        ..Default::default()
+    };
+    let mut producer = EventHubProducerClient::new_from_connection_string(
+        emulator.connection_string(),
+        None,
+        producer_options,
+    )
+    .await
+    .unwrap();
+    let consumer_options = EventHubConsumerClientOptions {
+        connection_options,
+        ..Default::default()
+    };
+    let mut consumer = EventHubConsumerClient::<BasicRetryPolicy>::new_from_connection_string(
+        EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+        emulator.connection_string(),
+        None,
+        consumer_options,
+    )
+    .await
+    .unwrap();
+
+    let large_body = vec![7u8; 10 * 1024];
+    let options = SendEventOptions::new().with_partition_id("0");
+    producer
+        .send_event(large_body.clone(), options)
+        .await
+        .unwrap();
+
+    let mut stream = consumer
+        .read_events_from_partition("0", EventPosition::earliest(), ReadEventOptions::default())
+        .await
+        .unwrap();
+    let event = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&event), &large_body[..]);
+
+    stream.close().await.unwrap();
+    consumer.close().await.unwrap();
+    producer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn clients_from_pool_share_connection() {
+    let emulator = start_emulator(1).await;
+    let options = EventHubConnectionPoolOptions {
+        connection_options: emulator.connection_options(),
+        ..Default::default()
+    };
+    let mut pool =
+        EventHubConnectionPool::new_from_connection_string(emulator.connection_string(), options)
+            .unwrap();
+
+    let mut producer = pool
+        .create_producer_client(EVENT_HUB, Default::default())
+        .await
+        .unwrap();
+    let mut consumer = pool
+        .create_consumer_client(
+            EVENT_HUB,
+            EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(pool.connection_count(), 1);
+    assert_eq!(pool.client_counts(EVENT_HUB), [2]);
+
+    let options = SendEventOptions::new().with_partition_id("0");
+    producer.send_event("pooled", options).await.unwrap();
+    let mut stream = consumer
+        .read_events_from_partition("0", EventPosition::earliest(), ReadEventOptions::default())
+        .await
+        .unwrap();
+    let event = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&event), b"pooled");
+    stream.close().await.unwrap();
+
+    // Closing a client leaves the shared connection open for the others
+    consumer.close().await.unwrap();
+    producer
+        .send_event(
+            "after close",
+            SendEventOptions::new().with_partition_id("0"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(pool.check_health().await, 0);
+
+    producer.close().await.unwrap();
+    pool.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn clients_recover_links_on_replaced_pool_connection() {
+    let emulator = start_emulator(1).await;
+    let options = EventHubConnectionPoolOptions {
+        connection_options: emulator.connection_options(),
+        // A request over the closed connection waits for the try timeout
+        retry_options: EventHubsRetryOptions {
+            try_timeout: StdDuration::from_secs(2),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut pool =
+        EventHubConnectionPool::new_from_connection_string(emulator.connection_string(), options)
+            .unwrap();
+    let mut producer = pool
+        .create_producer_client(EVENT_HUB, Default::default())
+        .await
+        .unwrap();
+    let options = SendEventOptions::new().with_partition_id("0");
+    producer
+        .send_event("before", options.clone())
+        .await
+        .unwrap();
+
+    // Restarting the emulator closes the connection of the pool
+    let local_addr = emulator.local_addr();
+    let event_hub = emulator.event_hub().clone();
+    emulator.shutdown();
+    // The listener is closed once the aborted accept loop is dropped
+    let mut restarted = None;
+    for _ in 0..50 {
+        match EventHubsEmulator::bind(local_addr, event_hub.clone()).await {
+            Ok(emulator) => {
+                restarted = Some(emulator);
+                break;
+            }
+            Err(_) => tokio::time::sleep(StdDuration::from_millis(20)).await,
+        }
+    }
+    let _emulator = restarted.expect("the emulator is restarted on the same address");
+
+    // The connection is replaced rather than dropped because the producer still uses it
+    assert_eq!(pool.check_health().await, 0);
+    assert_eq!(pool.client_counts(EVENT_HUB), [1]);
+    producer.send_event("after", options).await.unwrap();
+    let bodies: Vec<_> = event_hub
+        .events("0")
+        .iter()
+        .map(|e| body(e).to_vec())
+        .collect();
+    assert_eq!(bodies, [b"before".to_vec(), b"after".to_vec()]);
+
+    producer.close().await.unwrap();
+    pool.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn clients_of_several_event_hubs_share_connection() {
+    const OTHER_EVENT_HUB: &str = "other-hub";
+    let emulator = EventHubsEmulator::start_with_event_hubs([
+        InMemoryEventHub::new(EVENT_HUB, 1),
+        InMemoryEventHub::new(OTHER_EVENT_HUB, 2),
+    ])
+    .await
+    .unwrap();
+    let mut connection = EventHubConnection::new_from_connection_string(
+        emulator.connection_string(),
+        None,
+        emulator.connection_options(),
+    )
+    .await
+    .unwrap();
+
+    let mut producer = EventHubProducerClient::with_connection(&mut connection, Default::default());
+    let mut other_connection = connection.clone_for_event_hub(OTHER_EVENT_HUB);
+    let mut other_producer =
+        EventHubProducerClient::with_connection(&mut other_connection, Default::default());
+    let mut other_consumer = EventHubConsumerClient::with_connection(
+        EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME,
+        &mut other_connection,
+        Default::default(),
+    );
+
+    // The management requests are made for the Event Hub of each client
+    assert_eq!(producer.get_partition_ids().await.unwrap(), ["0"]);
+    assert_eq!(
+        other_producer.get_partition_ids().await.unwrap(),
+        ["0", "1"]
+    );
+
+    let options = SendEventOptions::new().with_partition_id("0");
+    producer.send_event("first", options.clone()).await.unwrap();
+    let options = SendEventOptions::new().with_partition_id("1");
+    other_producer.send_event("second", options).await.unwrap();
+    assert_eq!(emulator.event_hubs()[0].events("0").len(), 1);
+    assert_eq!(emulator.event_hubs()[1].events("1").len(), 1);
+
+    let mut stream = other_consumer
+        .read_events_from_partition("1", EventPosition::earliest(), ReadEventOptions::default())
+        .await
+        .unwrap();
+    let event = stream.next().await.unwrap().unwrap();
+    assert_eq!(body(&event), b"second");
+    stream.close().await.unwrap();
+
+    other_consumer.close().await.unwrap();
+    other_producer.close().await.unwrap();
+    producer.close().await.unwrap();
+    other_connection.close_if_owned().await.unwrap();
+    assert!(!connection.is_closed());
+    connection.close().await.unwrap();
+}
+
+/// Records the resources that tokens are requested for
+#[derive(Debug, Default)]
+struct RecordingTokenProvider {
+    resources: Arc<Mutex<Vec<String>>>,
+}
+
+impl EventHubTokenProvider for RecordingTokenProvider {
+    fn token_type(&self) -> EventHubTokenType {
+        EventHubTokenType::JsonWebToken
+    }
+
+    fn get_token<'a>(&'a self, resource: &'a str) -> GetTokenFuture<'a> {
+        self.resources.lock().unwrap().push(resource.to_string());
+        let expires_on = time::OffsetDateTime::now_utc() + time::Duration::hours(1);
+        Box::pin(async move { Ok(AccessToken::new("token", expires_on)) })
+    }
+
+    fn token_expiration_buffer(&self) -> Option<StdDuration> {
+        Some(StdDuration::from_secs(60))
+    }
+}
+
+#[tokio::test]
+async fn custom_token_provider_authorizes_links() {
+    let emulator = start_emulator(1).await;
+    let provider = RecordingTokenProvider::default();
+    let resources = provider.resources.clone();
+    let options = EventHubProducerClientOptions {
+        connection_options: emulator.connection_options(),
+        ..Default::default()
+    };
+    let credential = EventHubTokenCredential::from_provider(provider);
+    let mut producer =
+        EventHubProducerClient::new_from_credential("localhost", EVENT_HUB, credential, options)
+            .await
+            .unwrap();
+
+    let options = SendEventOptions::new().with_partition_id("0");
+    producer.send_event("a", options.clone()).await.unwrap();
+    producer.send_event("b", options).await.unwrap();
+    assert_eq!(emulator.event_hub().events("0").len(), 2);
+
+    // The token of the partition link is requested once and cached afterwards
+    let resources = resources.lock().unwrap().clone();
+    let partition_resources: Vec<_> = resources
+        .iter()
+        .filter(|resource| resource.ends_with("/Partitions/0"))
+        .collect();
+    assert_eq!(partition_resources.len(), 1);
+    assert!(partition_resources[0].contains(EVENT_HUB));
+
+    producer.close().await.unwrap();
+}
@@ -2,7 +2,7 @@
 
 use azeventhubs::{
     consumer::{EventHubConsumerClient, EventPosition},
-    primitives::PartitionReceiver,
+    primitives::{PartitionReceiver, PartitionReceiverOptions},
 };
 
 #[macro_use]
@@ -17,8 +17,8 @@ cfg_not_wasm32! {
 
         // env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
 
-        let connection_string = std::env::var("EVENT_HUBS_CONNECTION_STRING").unwrap();
-        let event_hub_name = std::env::var("EVENT_HUB_NAME").unwrap();
+        let connection_string = common::connection_string();
+        let event_hub_name = common::event_hub_name();
         let consumer_group = EventHubConsumerClient::DEFAULT_CONSUMER_GROUP_NAME;
         let partition_id = "0";
 
@@ -28,7 +28,10 @@ cfg_not_wasm32! {
             EventPosition::earliest(),
             connection_string,
             event_hub_name,
-            Default::default(),
+            PartitionReceiverOptions {
+                connection_options: common::connection_options(),
+                ..Default::default()
+            },
         )
         .await
         .unwrap();